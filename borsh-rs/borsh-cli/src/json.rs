@@ -0,0 +1,346 @@
+//! Conversion between schema-driven values and JSON.
+//!
+//! * structs with named fields are objects, all other structs, tuples, arrays and sequences are
+//!   arrays, so maps come out as arrays of `[key, value]` pairs;
+//! * `Option` is `null` or the inner value, other enums are the variant name for variants without
+//!   fields and `{"Variant": fields}` otherwise;
+//! * 128-bit integers are strings, since most JSON tooling cannot represent them as numbers.
+//!   Strings are also accepted for the other integer types.
+use oasis_borsh::schema::value::Value;
+use oasis_borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use serde_json::{Map, Number, Value as Json};
+
+/// A conversion error at the given path.
+fn error(path: &str, msg: String) -> String {
+    format!("{}: {}", path, msg)
+}
+
+fn definition<'a>(
+    container: &'a BorshSchemaContainer,
+    declaration: &str,
+    path: &str,
+) -> Result<&'a Definition, String> {
+    container.definitions.get(declaration).ok_or_else(|| {
+        error(
+            path,
+            format!("schema has no definition for {}", declaration),
+        )
+    })
+}
+
+fn is_option(variants: &[(String, String)]) -> bool {
+    variants.len() == 2 && variants[0].0 == "None" && variants[1].0 == "Some"
+}
+
+/// Whether values of `declaration` carry no data, like `()` and fieldless enum variants.
+fn is_unit(container: &BorshSchemaContainer, declaration: &str) -> bool {
+    match container.definitions.get(declaration) {
+        Some(Definition::Struct {
+            fields: Fields::Empty,
+        }) => true,
+        _ => declaration == "nil",
+    }
+}
+
+/// The value of a type for which [`is_unit`] holds.
+fn unit(declaration: &str) -> Value {
+    if declaration == "nil" {
+        Value::Nil
+    } else {
+        Value::Struct(Vec::new())
+    }
+}
+
+/// Convert `value` of type `declaration` to JSON.
+pub fn to_json(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    value: &Value,
+    path: &str,
+) -> Result<Json, String> {
+    Ok(match value {
+        Value::Nil => Json::Null,
+        Value::Bool(v) => Json::Bool(*v),
+        Value::U8(v) => Json::from(*v),
+        Value::U16(v) => Json::from(*v),
+        Value::U32(v) => Json::from(*v),
+        Value::U64(v) => Json::from(*v),
+        Value::I8(v) => Json::from(*v),
+        Value::I16(v) => Json::from(*v),
+        Value::I32(v) => Json::from(*v),
+        Value::I64(v) => Json::from(*v),
+        Value::U128(v) => Json::String(v.to_string()),
+        Value::I128(v) => Json::String(v.to_string()),
+        Value::F32(v) => float(*v as f64, path)?,
+        Value::F64(v) => float(*v, path)?,
+        Value::String(v) => Json::String(v.clone()),
+        Value::Array(values) | Value::Sequence(values) => {
+            let elements = match definition(container, declaration, path)? {
+                Definition::Array { elements, .. }
+                | Definition::Sequence { elements }
+                | Definition::Set { elements }
+                | Definition::Map { entries: elements } => elements,
+                Definition::Prefixed { sequence, .. } => {
+                    return to_json(container, sequence, value, path)
+                }
+                _ => return Err(error(path, format!("{} is not a sequence", declaration))),
+            };
+            Json::Array(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, v)| to_json(container, elements, v, &format!("{}[{}]", path, idx)))
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        Value::Tuple(values) => {
+            let elements = match definition(container, declaration, path)? {
+                Definition::Tuple { elements } => elements,
+                _ => return Err(error(path, format!("{} is not a tuple", declaration))),
+            };
+            Json::Array(
+                elements
+                    .iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(idx, (e, v))| to_json(container, e, v, &format!("{}.{}", path, idx)))
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        Value::Struct(values) => match definition(container, declaration, path)? {
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => {
+                    let mut object = Map::new();
+                    for ((name, field), v) in fields.iter().zip(values) {
+                        let json = to_json(container, field, v, &format!("{}.{}", path, name))?;
+                        object.insert(name.clone(), json);
+                    }
+                    Json::Object(object)
+                }
+                Fields::UnnamedFields(fields) => Json::Array(
+                    fields
+                        .iter()
+                        .zip(values)
+                        .enumerate()
+                        .map(|(idx, (f, v))| to_json(container, f, v, &format!("{}.{}", path, idx)))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Fields::Empty => Json::Array(Vec::new()),
+            },
+            _ => return Err(error(path, format!("{} is not a struct", declaration))),
+        },
+        Value::Enum { variant, value } => {
+            let variants = match definition(container, declaration, path)? {
+                Definition::Enum { variants } => variants,
+                _ => return Err(error(path, format!("{} is not an enum", declaration))),
+            };
+            let (name, variant_declaration) = variants
+                .get(*variant as usize)
+                .ok_or_else(|| error(path, format!("unexpected variant index {}", variant)))?;
+            let path = format!("{}::{}", path, name);
+            if is_option(variants) {
+                return match variant {
+                    0 => Ok(Json::Null),
+                    _ => to_json(container, variant_declaration, value, &path),
+                };
+            }
+            if is_unit(container, variant_declaration) {
+                Json::String(name.clone())
+            } else {
+                let mut object = Map::new();
+                let json = to_json(container, variant_declaration, value, &path)?;
+                object.insert(name.clone(), json);
+                Json::Object(object)
+            }
+        }
+    })
+}
+
+fn float(v: f64, path: &str) -> Result<Json, String> {
+    Number::from_f64(v)
+        .map(Json::Number)
+        .ok_or_else(|| error(path, format!("{} cannot be represented in JSON", v)))
+}
+
+fn mismatch(path: &str, declaration: &str, json: &Json) -> String {
+    error(path, format!("expected {}, found {}", declaration, json))
+}
+
+macro_rules! integer {
+    ($json: expr, $path: expr, $declaration: expr, $type: ty) => {{
+        let parsed = match $json {
+            Json::Number(n) => n.to_string().parse::<$type>().ok(),
+            Json::String(s) => s.parse::<$type>().ok(),
+            _ => None,
+        };
+        parsed.ok_or_else(|| mismatch($path, $declaration, $json))?
+    }};
+}
+
+fn elements(
+    container: &BorshSchemaContainer,
+    declarations: &[&String],
+    json: &Json,
+    path: &str,
+    declaration: &str,
+) -> Result<Vec<Value>, String> {
+    match json {
+        Json::Array(items) if items.len() == declarations.len() => declarations
+            .iter()
+            .zip(items)
+            .enumerate()
+            .map(|(idx, (d, item))| from_json(container, d, item, &format!("{}.{}", path, idx)))
+            .collect(),
+        _ => Err(mismatch(path, declaration, json)),
+    }
+}
+
+/// Convert `json` to a value of type `declaration`.
+pub fn from_json(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    json: &Json,
+    path: &str,
+) -> Result<Value, String> {
+    Ok(match declaration {
+        "nil" => match json {
+            Json::Null => Value::Nil,
+            _ => return Err(mismatch(path, declaration, json)),
+        },
+        "bool" => match json {
+            Json::Bool(v) => Value::Bool(*v),
+            _ => return Err(mismatch(path, declaration, json)),
+        },
+        "u8" => Value::U8(integer!(json, path, declaration, u8)),
+        "u16" => Value::U16(integer!(json, path, declaration, u16)),
+        "u32" => Value::U32(integer!(json, path, declaration, u32)),
+        "u64" => Value::U64(integer!(json, path, declaration, u64)),
+        "u128" => Value::U128(integer!(json, path, declaration, u128)),
+        "i8" => Value::I8(integer!(json, path, declaration, i8)),
+        "i16" => Value::I16(integer!(json, path, declaration, i16)),
+        "i32" => Value::I32(integer!(json, path, declaration, i32)),
+        "i64" => Value::I64(integer!(json, path, declaration, i64)),
+        "i128" => Value::I128(integer!(json, path, declaration, i128)),
+        "f32" | "f64" => {
+            let v = json
+                .as_f64()
+                .ok_or_else(|| mismatch(path, declaration, json))?;
+            if declaration == "f32" {
+                Value::F32(v as f32)
+            } else {
+                Value::F64(v)
+            }
+        }
+        "string" => match json {
+            Json::String(v) => Value::String(v.clone()),
+            _ => return Err(mismatch(path, declaration, json)),
+        },
+        _ => match definition(container, declaration, path)? {
+            Definition::Array { length, elements } => {
+                let declarations = vec![elements; *length as usize];
+                Value::Array(self::elements(
+                    container,
+                    &declarations,
+                    json,
+                    path,
+                    declaration,
+                )?)
+            }
+            Definition::Sequence { elements }
+            | Definition::Set { elements }
+            | Definition::Map { entries: elements } => match json {
+                Json::Array(items) => Value::Sequence(
+                    items
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, item)| {
+                            from_json(container, elements, item, &format!("{}[{}]", path, idx))
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+                _ => return Err(mismatch(path, declaration, json)),
+            },
+            Definition::Prefixed { sequence, .. } => from_json(container, sequence, json, path)?,
+            Definition::Tuple { elements } => {
+                let declarations: Vec<_> = elements.iter().collect();
+                Value::Tuple(self::elements(
+                    container,
+                    &declarations,
+                    json,
+                    path,
+                    declaration,
+                )?)
+            }
+            Definition::Struct { fields } => match (fields, json) {
+                (Fields::NamedFields(fields), Json::Object(object)) => {
+                    if let Some(unknown) =
+                        object.keys().find(|k| !fields.iter().any(|(n, _)| n == *k))
+                    {
+                        return Err(error(path, format!("unknown field {}", unknown)));
+                    }
+                    Value::Struct(
+                        fields
+                            .iter()
+                            .map(|(name, field)| {
+                                let path = format!("{}.{}", path, name);
+                                let item = object
+                                    .get(name)
+                                    .ok_or_else(|| error(&path, "missing field".to_string()))?;
+                                from_json(container, field, item, &path)
+                            })
+                            .collect::<Result<_, _>>()?,
+                    )
+                }
+                (Fields::UnnamedFields(fields), _) => {
+                    let declarations: Vec<_> = fields.iter().collect();
+                    Value::Struct(elements(container, &declarations, json, path, declaration)?)
+                }
+                (Fields::Empty, Json::Array(items)) if items.is_empty() => {
+                    Value::Struct(Vec::new())
+                }
+                _ => return Err(mismatch(path, declaration, json)),
+            },
+            Definition::Enum { variants } => {
+                if is_option(variants) {
+                    return Ok(match json {
+                        Json::Null => Value::Enum {
+                            variant: 0,
+                            value: Box::new(Value::Nil),
+                        },
+                        _ => {
+                            let path = format!("{}::Some", path);
+                            let value = from_json(container, &variants[1].1, json, &path)?;
+                            Value::Enum {
+                                variant: 1,
+                                value: Box::new(value),
+                            }
+                        }
+                    });
+                }
+                let (name, inner) = match json {
+                    Json::String(name) => (name, None),
+                    Json::Object(object) if object.len() == 1 => {
+                        let (name, inner) = object.iter().next().unwrap();
+                        (name, Some(inner))
+                    }
+                    _ => return Err(mismatch(path, declaration, json)),
+                };
+                let variant = variants
+                    .iter()
+                    .position(|(n, _)| n == name)
+                    .ok_or_else(|| error(path, format!("unknown variant {}", name)))?;
+                let variant_declaration = &variants[variant].1;
+                let path = format!("{}::{}", path, name);
+                let value = match inner {
+                    Some(inner) => from_json(container, variant_declaration, inner, &path)?,
+                    None if is_unit(container, variant_declaration) => unit(variant_declaration),
+                    None => return Err(error(&path, "variant has fields".to_string())),
+                };
+                Value::Enum {
+                    variant: variant as u8,
+                    value: Box::new(value),
+                }
+            }
+        },
+    })
+}
@@ -0,0 +1,197 @@
+//! `borsh` command line tool.
+//!
+//! Every subcommand is driven by a schema file: the JSON serialization of a
+//! `BorshSchemaContainer`, as produced by `serde_json::to_string(&T::schema_container())` with the
+//! `serde` feature of `oasis-borsh` enabled.
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+use oasis_borsh::schema::BorshSchemaContainer;
+use oasis_borsh::schema::{proto, value};
+
+mod json;
+
+const USAGE: &str = "\
+Usage:
+    borsh validate --schema <schema.json> <payload.bin>
+    borsh to-json --schema <schema.json> [--output <value.json>] <payload.bin>
+    borsh from-json --schema <schema.json> [--output <payload.bin>] <value.json>
+    borsh to-proto [--package <name>] [--output <types.proto>] <schema.json>
+
+Use `-` as the input path to read from standard input. Output goes to standard output unless
+`--output` is given.";
+
+/// Outcome of a failed command, mapped to the process exit code.
+enum Failure {
+    /// The input was read but is not acceptable, e.g. a payload that does not validate.
+    Rejected(String),
+    /// The command could not run: bad arguments, unreadable files, malformed schema.
+    Usage(String),
+}
+
+impl Failure {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Rejected(_) => 1,
+            Failure::Usage(_) => 2,
+        }
+    }
+}
+
+/// Arguments shared by all subcommands.
+struct Args {
+    schema: Option<String>,
+    input: String,
+    output: Option<String>,
+    package: Option<String>,
+}
+
+impl Args {
+    fn schema(&self) -> Result<&str, Failure> {
+        self.schema
+            .as_deref()
+            .ok_or_else(|| Failure::Usage("Missing --schema".to_string()))
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Args, Failure> {
+    let mut schema = None;
+    let mut input = None;
+    let mut output = None;
+    let mut package = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => match args.next() {
+                Some(path) => schema = Some(path.clone()),
+                None => return Err(Failure::Usage("--schema requires a path".to_string())),
+            },
+            "--output" | "-o" => match args.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err(Failure::Usage("--output requires a path".to_string())),
+            },
+            "--package" => match args.next() {
+                Some(name) => package = Some(name.clone()),
+                None => return Err(Failure::Usage("--package requires a name".to_string())),
+            },
+            _ if arg.starts_with("--") => {
+                return Err(Failure::Usage(format!("Unknown option {}", arg)))
+            }
+            _ if input.is_none() => input = Some(arg.clone()),
+            _ => return Err(Failure::Usage(format!("Unexpected argument {}", arg))),
+        }
+    }
+    Ok(Args {
+        schema,
+        input: input.ok_or_else(|| Failure::Usage("Missing input path".to_string()))?,
+        output,
+        package,
+    })
+}
+
+fn read_input(path: &str) -> Result<Vec<u8>, Failure> {
+    let result = if path == "-" {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf).map(|_| buf)
+    } else {
+        fs::read(path)
+    };
+    result.map_err(|err| Failure::Usage(format!("Cannot read {}: {}", path, err)))
+}
+
+fn write_output(path: Option<&str>, data: &[u8]) -> Result<(), Failure> {
+    let result = match path {
+        Some(path) => fs::write(path, data),
+        None => io::stdout().write_all(data),
+    };
+    result.map_err(|err| Failure::Usage(format!("Cannot write output: {}", err)))
+}
+
+fn read_schema(path: &str) -> Result<BorshSchemaContainer, Failure> {
+    let json = read_input(path)?;
+    serde_json::from_slice(&json)
+        .map_err(|err| Failure::Usage(format!("Invalid schema {}: {}", path, err)))
+}
+
+fn validate(args: &Args) -> Result<(), Failure> {
+    let container = read_schema(args.schema()?)?;
+    let payload = read_input(&args.input)?;
+    match value::validate(&container, &payload) {
+        Ok(_) => {
+            println!("{}: valid {}", args.input, container.declaration);
+            Ok(())
+        }
+        Err(invalid) => Err(Failure::Rejected(format!("{}: {}", args.input, invalid))),
+    }
+}
+
+fn to_json(args: &Args) -> Result<(), Failure> {
+    let container = read_schema(args.schema()?)?;
+    let payload = read_input(&args.input)?;
+    let value = value::validate(&container, &payload)
+        .map_err(|invalid| Failure::Rejected(format!("{}: {}", args.input, invalid)))?;
+    let json = json::to_json(
+        &container,
+        &container.declaration,
+        &value,
+        &container.declaration,
+    )
+    .map_err(Failure::Rejected)?;
+    let mut out = serde_json::to_vec_pretty(&json).expect("Serializing JSON values never fails");
+    out.push(b'\n');
+    write_output(args.output.as_deref(), &out)
+}
+
+fn from_json(args: &Args) -> Result<(), Failure> {
+    let container = read_schema(args.schema()?)?;
+    let input = read_input(&args.input)?;
+    let json: serde_json::Value = serde_json::from_slice(&input)
+        .map_err(|err| Failure::Rejected(format!("{}: invalid JSON: {}", args.input, err)))?;
+    let value = json::from_json(
+        &container,
+        &container.declaration,
+        &json,
+        &container.declaration,
+    )
+    .map_err(Failure::Rejected)?;
+    let payload = value::encode(&container, &value)
+        .map_err(|err| Failure::Rejected(format!("{}: {}", args.input, err)))?;
+    write_output(args.output.as_deref(), &payload)
+}
+
+fn to_proto(args: &Args) -> Result<(), Failure> {
+    let container = read_schema(&args.input)?;
+    let proto = proto::to_proto(&container, args.package.as_deref())
+        .map_err(|err| Failure::Rejected(format!("{}: {}", args.input, err)))?;
+    write_output(args.output.as_deref(), proto.as_bytes())
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Err(Failure::Usage("Missing subcommand".to_string())),
+    };
+    match command {
+        "validate" => validate(&parse_args(rest)?),
+        "to-json" => to_json(&parse_args(rest)?),
+        "from-json" => from_json(&parse_args(rest)?),
+        "to-proto" => to_proto(&parse_args(rest)?),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(Failure::Usage(format!("Unknown subcommand {}", command))),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(failure) = run(&args) {
+        match &failure {
+            Failure::Rejected(msg) => eprintln!("{}", msg),
+            Failure::Usage(msg) => eprintln!("error: {}\n\n{}", msg, USAGE),
+        }
+        process::exit(failure.exit_code());
+    }
+}
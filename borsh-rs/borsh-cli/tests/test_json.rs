@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Account {
+    owner: String,
+    balance: u128,
+    frozen: Option<bool>,
+    limits: BTreeMap<String, u64>,
+    status: Status,
+    history: Vec<Status>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Status {
+    Active,
+    Closed { at: u64 },
+}
+
+fn account() -> Account {
+    let mut limits = BTreeMap::new();
+    limits.insert("daily".to_string(), 100);
+    Account {
+        owner: "alice".to_string(),
+        balance: u128::MAX,
+        frozen: None,
+        limits,
+        status: Status::Active,
+        history: vec![Status::Closed { at: 5 }],
+    }
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("borsh-cli-json-{}", name));
+    fs::create_dir_all(&dir).unwrap();
+    let schema = serde_json::to_vec(&Account::schema_container()).unwrap();
+    fs::write(dir.join("schema.json"), schema).unwrap();
+    dir
+}
+
+fn borsh(dir: &Path, command: &str, input: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_borsh"))
+        .arg(command)
+        .arg("--schema")
+        .arg(dir.join("schema.json"))
+        .arg(dir.join(input))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_to_json() {
+    let dir = dir("to");
+    fs::write(dir.join("payload.bin"), account().try_to_vec().unwrap()).unwrap();
+    let output = borsh(&dir, "to-json", "payload.bin");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "owner": "alice",
+            "balance": u128::MAX.to_string(),
+            "frozen": null,
+            "limits": [["daily", 100]],
+            "status": "Active",
+            "history": [{"Closed": {"at": 5}}],
+        })
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let dir = dir("round-trip");
+    fs::write(dir.join("payload.bin"), account().try_to_vec().unwrap()).unwrap();
+    let json = borsh(&dir, "to-json", "payload.bin");
+    fs::write(dir.join("value.json"), &json.stdout).unwrap();
+    let output = borsh(&dir, "from-json", "value.json");
+    assert!(output.status.success());
+    assert_eq!(Account::try_from_slice(&output.stdout).unwrap(), account());
+}
+
+#[test]
+fn test_from_json_errors() {
+    let dir = dir("errors");
+    let mut json = serde_json::json!({
+        "owner": "bob",
+        "balance": 1,
+        "frozen": true,
+        "limits": [],
+        "status": "Frozen",
+        "history": [],
+    });
+    fs::write(dir.join("value.json"), json.to_string()).unwrap();
+    let output = borsh(&dir, "from-json", "value.json");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Account.status: unknown variant Frozen"));
+
+    json["status"] = serde_json::json!({"Closed": {"at": -1}});
+    fs::write(dir.join("value.json"), json.to_string()).unwrap();
+    let output = borsh(&dir, "from-json", "value.json");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Account.status::Closed.at: expected u64, found -1"));
+}
@@ -0,0 +1,30 @@
+use std::fs;
+use std::process::Command;
+
+use oasis_borsh::BorshSchema;
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Transfer {
+    from: String,
+    amount: u64,
+}
+
+#[test]
+fn test_to_proto() {
+    let dir = std::env::temp_dir().join("borsh-cli-proto");
+    fs::create_dir_all(&dir).unwrap();
+    let schema = serde_json::to_vec(&Transfer::schema_container()).unwrap();
+    fs::write(dir.join("schema.json"), schema).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_borsh"))
+        .args(["to-proto", "--package", "payments"])
+        .arg(dir.join("schema.json"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "// Generated from the borsh schema of Transfer.\nsyntax = \"proto3\";\n\n\
+         package payments;\n\nmessage Transfer {\n  string from = 1;\n  uint64 amount = 2;\n}\n"
+    );
+}
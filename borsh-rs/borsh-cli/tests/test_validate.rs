@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use oasis_borsh::{BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshSchema)]
+struct Event {
+    id: u64,
+    kind: Kind,
+    tags: Vec<String>,
+}
+
+#[derive(BorshSerialize, BorshSchema)]
+#[allow(dead_code)]
+enum Kind {
+    Created,
+    Flagged(bool),
+}
+
+fn event() -> Event {
+    Event {
+        id: 7,
+        kind: Kind::Flagged(true),
+        tags: vec!["a".to_string(), "b".to_string()],
+    }
+}
+
+/// Write the schema of `Event` and `payload` to a fresh directory and run `borsh validate`.
+fn validate(name: &str, payload: &[u8]) -> Output {
+    let dir = std::env::temp_dir().join(format!("borsh-cli-validate-{}", name));
+    fs::create_dir_all(&dir).unwrap();
+    let schema = dir.join("schema.json");
+    fs::write(
+        &schema,
+        serde_json::to_vec(&Event::schema_container()).unwrap(),
+    )
+    .unwrap();
+    let payload_path: PathBuf = dir.join("payload.bin");
+    fs::write(&payload_path, payload).unwrap();
+    Command::new(env!("CARGO_BIN_EXE_borsh"))
+        .arg("validate")
+        .arg("--schema")
+        .arg(&schema)
+        .arg(&payload_path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_valid_payload() {
+    let output = validate("valid", &event().try_to_vec().unwrap());
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("valid Event"));
+}
+
+#[test]
+fn test_invalid_bool() {
+    let mut payload = event().try_to_vec().unwrap();
+    payload[9] = 2;
+    let output = validate("bool", &payload);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Event.kind::Flagged.0 at byte 9: Invalid bool value: 2"));
+}
+
+#[test]
+fn test_truncated_sequence() {
+    let payload = event().try_to_vec().unwrap();
+    let output = validate("truncated", &payload[..payload.len() - 1]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Event.tags[1]"));
+}
+
+#[test]
+fn test_trailing_bytes() {
+    let mut payload = event().try_to_vec().unwrap();
+    payload.push(0);
+    let output = validate("trailing", &payload);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not all bytes read"));
+}
+
+#[test]
+fn test_usage_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_borsh"))
+        .arg("validate")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
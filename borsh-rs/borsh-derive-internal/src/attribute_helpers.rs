@@ -1,11 +1,77 @@
+//! Parsing of the borsh attributes, and the code generated per field that depends on them.
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
 use syn::spanned::Spanned;
-use syn::{Attribute, Error, Meta, NestedMeta, Path};
-use quote::ToTokens;
+use syn::{
+    Attribute, Error, Field, Fields, Generics, Ident, ItemEnum, ItemStruct, Lit, Meta, NestedMeta,
+    Path, Type,
+};
 
+/// Whether the field or variant is marked `#[borsh_skip]`.
 pub fn contains_skip(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path.is_ident("borsh_skip"))
+}
+
+/// The expression a skipped field is initialized with on decode: `Default::default()`, or a call
+/// to the function given by `#[borsh_skip(default = "path")]`.
+pub fn skip_default(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("borsh_skip")) {
+        match attr.parse_meta()? {
+            Meta::Path(_) => {}
+            Meta::List(meta_list) => {
+                let default_fn = match meta_list.nested.iter().next() {
+                    Some(NestedMeta::Meta(Meta::NameValue(name_value)))
+                        if meta_list.nested.len() == 1 && name_value.path.is_ident("default") =>
+                    {
+                        match &name_value.lit {
+                            Lit::Str(lit) => lit.parse::<Path>()?,
+                            lit => {
+                                return Err(Error::new(
+                                    lit.span(),
+                                    "default must be a string naming a function",
+                                ))
+                            }
+                        }
+                    }
+                    _ => {
+                        return Err(Error::new(
+                            meta_list.span(),
+                            "expected #[borsh_skip(default = \"path\")]",
+                        ))
+                    }
+                };
+                return Ok(quote! { #default_fn() });
+            }
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "expected #[borsh_skip] or #[borsh_skip(default = \"path\")]",
+                ))
+            }
+        }
+    }
+    Ok(quote! { Default::default() })
+}
+
+/// Whether the item is marked `#[borsh_delegate]`.
+///
+/// The impls derived for a generic item normally require every type parameter to implement the
+/// derived trait. With `#[borsh_delegate]` they instead require it of the types of the encoded
+/// fields that involve a type parameter, so that they exist exactly when the impls of the fields
+/// do. This lets a wrapper such as `Signed<T, Scheme>` be encoded whenever its payload can, even
+/// if `Scheme` only appears in a skipped `PhantomData` field. It applies to `BorshSerialize` and
+/// `BorshDeserialize`.
+pub fn contains_delegate(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("borsh_delegate"))
+}
+
+/// Whether the enum is marked `#[borsh_wire_tag]`.
+pub fn contains_wire_tag(attrs: &[Attribute]) -> bool {
     for attr in attrs.iter() {
         if let Ok(Meta::Path(path)) = attr.parse_meta() {
-            if path.to_token_stream().to_string().as_str() == "borsh_skip" {
+            if path.to_token_stream().to_string().as_str() == "borsh_wire_tag" {
                 return true;
             }
         }
@@ -13,6 +79,96 @@ pub fn contains_skip(attrs: &[Attribute]) -> bool {
     false
 }
 
+/// Whether `#[borsh_memcpy]` asks for the struct to be encoded by copying its bytes, checking that
+/// the struct is `#[repr(C)]` and its fields are encoded as they are laid out in memory. Whether
+/// the layout has padding can only be checked by the compiler, see `memcpy_assertions`.
+pub fn memcpy_layout(input: &ItemStruct) -> syn::Result<bool> {
+    let attr = match input
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("borsh_memcpy"))
+    {
+        Some(attr) => attr,
+        None => return Ok(false),
+    };
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        if let Meta::List(meta_list) = attr.parse_meta()? {
+            for nested in meta_list.nested.iter() {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("C") => repr_c = true,
+                    NestedMeta::Meta(meta) if meta.path().is_ident("packed") => {
+                        return Err(Error::new(
+                            nested.span(),
+                            "borsh_memcpy does not support packed structs",
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if !repr_c {
+        return Err(Error::new(attr.span(), "borsh_memcpy requires #[repr(C)]"));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "borsh_memcpy does not support generic structs",
+        ));
+    }
+    for field in input.fields.iter() {
+        let options = field_options(&field.attrs)?;
+        if contains_skip(&field.attrs) || options.order.is_some() || options.len.is_some() {
+            return Err(Error::new(
+                field.span(),
+                "fields of a borsh_memcpy struct are encoded as laid out and take no attributes",
+            ));
+        }
+    }
+    Ok(true)
+}
+
+/// Compile-time checks that a `#[borsh_memcpy]` struct has no padding and that every field can be
+/// copied as bytes, along with the `Memcpy` impl those checks make sound.
+pub fn memcpy_assertions(input: &ItemStruct, cratename: &Path) -> TokenStream {
+    let name = &input.ident;
+    let field_types = input
+        .fields
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+    let accessors = input
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let idx = syn::Index::from(idx);
+                quote! { #idx }
+            }
+        })
+        .collect::<Vec<_>>();
+    quote! {
+        const _: () = assert!(
+            core::mem::size_of::<#name>() == 0 #(+ core::mem::size_of::<#field_types>())*,
+            "borsh_memcpy struct has padding",
+        );
+
+        unsafe impl #cratename::memcpy::Memcpy for #name {
+            fn has_nan(&self) -> bool {
+                false #(|| #cratename::memcpy::Memcpy::has_nan(&self.#accessors))*
+            }
+
+            fn canonicalize_nans(&mut self) {
+                #(#cratename::memcpy::Memcpy::canonicalize_nans(&mut self.#accessors);)*
+            }
+        }
+    }
+}
+
+/// The method named by `#[borsh_init(method)]` on a struct or enum.
 pub fn contains_initialize_with(attrs: &[Attribute]) -> syn::Result<Option<Path>> {
     for attr in attrs.iter() {
         if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
@@ -32,3 +188,719 @@ pub fn contains_initialize_with(attrs: &[Attribute]) -> syn::Result<Option<Path>
     }
     Ok(None)
 }
+
+/// The type named by `#[borsh_header(Name)]` on a struct.
+pub fn contains_header(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("borsh_header")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    if let Meta::List(meta_list) = attr.parse_meta()? {
+        if let (1, Some(NestedMeta::Meta(Meta::Path(path)))) =
+            (meta_list.nested.len(), meta_list.nested.iter().next())
+        {
+            if let Some(ident) = path.get_ident() {
+                return Ok(Some(ident.clone()));
+            }
+        }
+    }
+    Err(Error::new(
+        attr.span(),
+        "expected #[borsh_header(Name)] naming the header type",
+    ))
+}
+
+/// The seed type named by `#[borsh_seed(Type)]` on a struct or enum.
+pub fn contains_seed(attrs: &[Attribute]) -> syn::Result<Option<Type>> {
+    match attrs.iter().find(|attr| attr.path.is_ident("borsh_seed")) {
+        Some(attr) => attr.parse_args().map(Some).map_err(|err| {
+            Error::new(
+                err.span(),
+                "expected #[borsh_seed(Type)] naming the seed type",
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+/// The context type named by `#[borsh_ctx(Type)]` on a struct or enum.
+pub fn contains_ctx(attrs: &[Attribute]) -> syn::Result<Option<Type>> {
+    match attrs.iter().find(|attr| attr.path.is_ident("borsh_ctx")) {
+        Some(attr) => attr.parse_args().map(Some).map_err(|err| {
+            Error::new(
+                err.span(),
+                "expected #[borsh_ctx(Type)] naming the context type",
+            )
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Options given in `#[borsh(...)]` on a struct or enum.
+#[derive(Default)]
+struct ContainerOptions {
+    /// `cfg(predicate)`: the borsh impls are only generated if all the predicates hold.
+    cfg: Vec<NestedMeta>,
+    /// `embed_schema`: the derived `BorshSchema` also emits the schema as a constant.
+    embed_schema: bool,
+}
+
+fn container_options(attrs: &[Attribute]) -> syn::Result<ContainerOptions> {
+    let mut options = ContainerOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("borsh")) {
+        let meta_list = match attr.parse_meta()? {
+            Meta::List(meta_list) => meta_list,
+            meta => return Err(Error::new(meta.span(), "expected #[borsh(...)]")),
+        };
+        for nested in meta_list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::List(list))
+                    if list.path.is_ident("cfg") && list.nested.len() == 1 =>
+                {
+                    options.cfg.extend(list.nested.iter().cloned())
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("embed_schema") => {
+                    options.embed_schema = true
+                }
+                _ => {
+                    return Err(Error::new(
+                        nested.span(),
+                        "expected cfg(predicate) or embed_schema in a borsh attribute on a type",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// The predicates of `#[borsh(cfg(...))]` on a struct or enum, under which its borsh impls are
+/// generated. Several of them must all hold.
+pub fn container_cfg(attrs: &[Attribute]) -> syn::Result<Vec<NestedMeta>> {
+    Ok(container_options(attrs)?.cfg)
+}
+
+/// Whether the struct or enum is marked `#[borsh(embed_schema)]`, asking the derived
+/// `BorshSchema` for a `BORSH_SCHEMA` constant describing the type in text, see
+/// [`embedded_schema`].
+pub fn contains_embed_schema(attrs: &[Attribute]) -> syn::Result<bool> {
+    Ok(container_options(attrs)?.embed_schema)
+}
+
+/// An inherent impl of the type named `name` holding `idl`, the text describing its layout, as
+/// `BORSH_SCHEMA`, so that a binary can expose its wire contracts at runtime.
+pub fn embedded_schema(name: &Ident, generics: &Generics, idl: &str) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let doc = format!(
+        "The layout of `{}` on the wire, as written by `#[borsh(embed_schema)]`.",
+        name
+    );
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #doc]
+            pub const BORSH_SCHEMA: &'static str = #idl;
+        }
+    }
+}
+
+/// The header of the text describing a struct or enum, like `struct Pair<K, V>`.
+pub fn idl_declaration(keyword: &str, name: &Ident, generics: &Generics) -> String {
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        format!("{} {}", keyword, name)
+    } else {
+        format!("{} {}<{}>", keyword, name, params.join(", "))
+    }
+}
+
+/// The text describing serialized `fields`, in wire order, like `{ a: u8, b: Vec<u8> }` or
+/// `(u8, Vec<u8>)`, or nothing for unit fields.
+pub fn idl_fields(fields: &Fields) -> syn::Result<String> {
+    let mut entries = Vec::new();
+    for (_, field) in wire_order(fields)? {
+        if contains_skip(&field.attrs) {
+            continue;
+        }
+        let ty = idl_type(&field.ty.to_token_stream().to_string());
+        let ty = match length_prefix(field)? {
+            Some(len) => format!("Prefixed<{}, {}>", ty, len),
+            None => ty,
+        };
+        entries.push(match &field.ident {
+            Some(ident) => format!("{}: {}", ident, ty),
+            None => ty,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) if entries.is_empty() => " {}".to_string(),
+        Fields::Named(_) => format!(" {{ {} }}", entries.join(", ")),
+        Fields::Unnamed(_) => format!("({})", entries.join(", ")),
+        Fields::Unit => String::new(),
+    })
+}
+
+/// `ty`, as spelled by its tokens, with the spacing of rustfmt.
+fn idl_type(ty: &str) -> String {
+    let mut ty = ty.to_string();
+    for (from, to) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        (" ;", ";"),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+    ] {
+        ty = ty.replace(from, to);
+    }
+    ty
+}
+
+/// Put every item of `impls`, generated for the type with `attrs`, under the predicates of its
+/// `#[borsh(cfg(...))]` attributes, so that the type can offer borsh support behind a feature.
+pub fn apply_cfg(attrs: &[Attribute], impls: TokenStream) -> syn::Result<TokenStream> {
+    let predicates = container_cfg(attrs)?;
+    if predicates.is_empty() {
+        return Ok(impls);
+    }
+    let mut file = syn::parse2::<syn::File>(impls)?;
+    for item in file.items.iter_mut() {
+        let attr: Attribute = syn::parse_quote!(#[cfg(all(#(#predicates),*))]);
+        match item {
+            syn::Item::Impl(item) => item.attrs.push(attr),
+            syn::Item::Const(item) => item.attrs.push(attr),
+            syn::Item::Fn(item) => item.attrs.push(attr),
+            syn::Item::Struct(item) => item.attrs.push(attr),
+            syn::Item::Enum(item) => item.attrs.push(attr),
+            item => return Err(Error::new(item.span(), "unexpected item in a borsh impl")),
+        }
+    }
+    Ok(file.into_token_stream())
+}
+
+/// The field named by `#[borsh_extension(field)]` on a struct, which keeps the bytes of the
+/// extension region that none of its `#[borsh(extension)]` fields account for.
+///
+/// Fields marked `#[borsh(extension)]` are encoded in wire order in a length-prefixed region after
+/// the other fields, see `oasis_borsh::extension`. Only structs with named fields have a region,
+/// and a struct with fields marked `#[borsh(extension)]` needs one.
+pub fn extension_field(input: &ItemStruct) -> syn::Result<Option<Ident>> {
+    let attr = match input
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("borsh_extension"))
+    {
+        Some(attr) => attr,
+        None => {
+            for field in input.fields.iter() {
+                if is_extension_field(field)? {
+                    return Err(Error::new(
+                        field.span(),
+                        "borsh(extension) fields need #[borsh_extension(field)] on the struct",
+                    ));
+                }
+            }
+            return Ok(None);
+        }
+    };
+    let ident: Ident = attr.parse_args().map_err(|err| {
+        Error::new(
+            err.span(),
+            "expected #[borsh_extension(field)] naming the field that keeps unknown extensions",
+        )
+    })?;
+    if input
+        .attrs
+        .iter()
+        .any(|attr| attr.path.is_ident("borsh_memcpy"))
+    {
+        return Err(Error::new(
+            attr.span(),
+            "borsh_extension cannot be combined with borsh_memcpy",
+        ));
+    }
+    let field = match &input.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .find(|field| field.ident.as_ref() == Some(&ident)),
+        _ => {
+            return Err(Error::new(
+                attr.span(),
+                "borsh_extension is only supported on structs with named fields",
+            ))
+        }
+    };
+    match field {
+        Some(field) if contains_skip(&field.attrs) || is_extension_field(field)? => {
+            Err(Error::new(
+                ident.span(),
+                "the field keeping unknown extensions cannot be skipped or an extension itself",
+            ))
+        }
+        Some(_) => Ok(Some(ident)),
+        None => Err(Error::new(ident.span(), "no such field")),
+    }
+}
+
+/// Whether `field` is marked `#[borsh(extension)]`.
+pub fn is_extension_field(field: &Field) -> syn::Result<bool> {
+    let extension = field_options(&field.attrs)?.extension;
+    if extension && contains_skip(&field.attrs) {
+        return Err(Error::new(
+            field.span(),
+            "a field with borsh(extension) cannot be skipped",
+        ));
+    }
+    Ok(extension)
+}
+
+/// Fails if the struct has an extension region, which the derive named `derive` cannot encode.
+pub fn reject_extension(input: &ItemStruct, derive: &str) -> syn::Result<()> {
+    if extension_field(input)?.is_some() {
+        return Err(Error::new(
+            input.ident.span(),
+            format!("{} does not support borsh_extension", derive),
+        ));
+    }
+    Ok(())
+}
+
+/// Options given in `#[borsh(...)]` on a field.
+#[derive(Default)]
+struct FieldOptions {
+    /// `order = N`: position of the field on the wire.
+    order: Option<u32>,
+    /// `len = "u8"`: integer type of the field's length prefix.
+    len: Option<Ident>,
+    /// `header`: the field is part of the header type of the struct.
+    header: bool,
+    /// `seed`: the field is decoded with the seed by a derived `BorshDeserializeSeed`.
+    seed: bool,
+    /// `ctx`: the field is encoded with the context by a derived `BorshSerializeCtx`.
+    ctx: bool,
+    /// `extension`: the field is encoded in the extension region of the struct.
+    extension: bool,
+}
+
+fn field_options(attrs: &[Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("borsh") {
+            continue;
+        }
+        let meta_list = match attr.parse_meta()? {
+            Meta::List(meta_list) => meta_list,
+            meta => return Err(Error::new(meta.span(), "expected #[borsh(...)]")),
+        };
+        for nested in meta_list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("order") =>
+                {
+                    match &name_value.lit {
+                        Lit::Int(lit) => options.order = Some(lit.base10_parse::<u32>()?),
+                        lit => return Err(Error::new(lit.span(), "order must be an integer")),
+                    }
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("len") =>
+                {
+                    match &name_value.lit {
+                        Lit::Str(lit) if ["u8", "u16", "u32", "u64"].contains(&&*lit.value()) => {
+                            options.len = Some(Ident::new(&lit.value(), lit.span()))
+                        }
+                        lit => {
+                            return Err(Error::new(
+                                lit.span(),
+                                "len must be one of \"u8\", \"u16\", \"u32\" and \"u64\"",
+                            ))
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("header") => {
+                    options.header = true
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("seed") => options.seed = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("ctx") => options.ctx = true,
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("extension") => {
+                    options.extension = true
+                }
+                _ => return Err(Error::new(nested.span(), "unknown borsh attribute")),
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn field_order(attrs: &[Attribute]) -> syn::Result<Option<u32>> {
+    Ok(field_options(attrs)?.order)
+}
+
+/// The legacy tags given by `#[borsh(alias_tag = N)]` on each variant, which decode to the variant
+/// as well as its own tag. Variants are still encoded with their own tag only.
+///
+/// An alias can be neither the tag of a variant nor an alias of another one, so every tag decodes
+/// to a single variant.
+pub fn alias_tags(input: &ItemEnum) -> syn::Result<Vec<Vec<u8>>> {
+    let mut all_aliases = Vec::new();
+    for variant in input.variants.iter() {
+        let mut aliases = Vec::new();
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("borsh"))
+        {
+            let meta_list = match attr.parse_meta()? {
+                Meta::List(meta_list) => meta_list,
+                meta => return Err(Error::new(meta.span(), "expected #[borsh(...)]")),
+            };
+            for nested in meta_list.nested.iter() {
+                let lit = match nested {
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("alias_tag") =>
+                    {
+                        &name_value.lit
+                    }
+                    _ => return Err(Error::new(nested.span(), "unknown borsh attribute")),
+                };
+                let tag = match lit {
+                    Lit::Int(lit) => lit.base10_parse::<u8>()?,
+                    lit => return Err(Error::new(lit.span(), "alias_tag must be an integer")),
+                };
+                if (tag as usize) < input.variants.len() {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!("alias_tag {} is the tag of a variant", tag),
+                    ));
+                }
+                if all_aliases
+                    .iter()
+                    .flatten()
+                    .chain(&aliases)
+                    .any(|&other| other == tag)
+                {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!("alias_tag {} is already used", tag),
+                    ));
+                }
+                aliases.push(tag);
+            }
+        }
+        all_aliases.push(aliases);
+    }
+    Ok(all_aliases)
+}
+
+/// The integer type given by `#[borsh(len = "...")]`, if any.
+fn length_prefix(field: &Field) -> syn::Result<Option<Ident>> {
+    let len = field_options(&field.attrs)?.len;
+    if len.is_some() && contains_skip(&field.attrs) {
+        return Err(Error::new(
+            field.span(),
+            "a field with borsh_skip cannot have a length prefix",
+        ));
+    }
+    Ok(len)
+}
+
+/// The trait the type of `field` needs to implement for the field to be serialized.
+pub fn field_ser_bound(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(_) => quote! { #cratename::prefix::SerializePrefixed },
+        None => quote! { #cratename::ser::BorshSerialize },
+    })
+}
+
+/// The trait the type of `field` needs to implement for the field to be deserialized.
+pub fn field_de_bound(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(_) => quote! { #cratename::prefix::DeserializePrefixed },
+        None => quote! { #cratename::de::BorshDeserialize },
+    })
+}
+
+/// The statement serializing `value`, a reference to `field`, into `writer`.
+pub fn serialize_field(
+    field: &Field,
+    value: TokenStream,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! {
+            #cratename::prefix::serialize::<#len, _, _>(#value, writer)?;
+        },
+        None => quote! {
+            #cratename::BorshSerialize::serialize(#value, writer)?;
+        },
+    })
+}
+
+/// The expression computing the serialized size of `value`, a reference to `field`.
+pub fn serialized_size_field(
+    field: &Field,
+    value: TokenStream,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! {
+            #cratename::prefix::serialized_size::<#len, _>(#value)
+        },
+        None => quote! {
+            #cratename::BorshSerialize::serialized_size(#value)
+        },
+    })
+}
+
+/// The expression estimating the serialized size of `value`, a reference to `field`.
+pub fn size_hint_field(
+    field: &Field,
+    value: TokenStream,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! {
+            #cratename::prefix::size_hint::<#len, _>(#value)
+        },
+        None => quote! {
+            #cratename::BorshSerialize::size_hint(#value)
+        },
+    })
+}
+
+/// The expression deserializing `field` from `reader`.
+pub fn deserialize_field(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    let read = read_field(field, cratename)?;
+    Ok(quote! { #read? })
+}
+
+/// Like [`deserialize_field`], without propagating the error: the expression is the `Result`.
+pub fn read_field(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! {
+            #cratename::prefix::deserialize::<#len, _, _>(reader)
+        },
+        None => quote! {
+            #cratename::BorshDeserialize::deserialize(reader)
+        },
+    })
+}
+
+/// Like [`deserialize_field`], for the body of `deserialize_slice`, where `reader` is a
+/// `&mut &[u8]`.
+pub fn deserialize_field_slice(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! {
+            #cratename::prefix::deserialize_slice::<#len, _>(reader)?
+        },
+        None => quote! {
+            #cratename::BorshDeserialize::deserialize_slice(reader)?
+        },
+    })
+}
+
+/// The expression deserializing `field` of the type named `ty` in a derived decoder, where
+/// `reader` is a `&mut R`, or a `&mut &[u8]` when `slice` is set.
+///
+/// Errors the field raises get `segment`, e.g. `.x` or `::Variant.0`, prepended to their path, so
+/// they name the field they occurred in. Reads from a slice also record the bytes the field takes
+/// in the decode metrics of `Self`, under `segment` without its leading separator.
+pub fn deserialize_field_in(
+    field: &Field,
+    ty: &str,
+    segment: &str,
+    slice: bool,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    let read = match (length_prefix(field)?, slice) {
+        (Some(len), false) => quote! { #cratename::prefix::deserialize::<#len, _, _>(reader) },
+        (Some(len), true) => quote! { #cratename::prefix::deserialize_slice::<#len, _>(reader) },
+        (None, false) => quote! { #cratename::BorshDeserialize::deserialize(reader) },
+        (None, true) => quote! { #cratename::BorshDeserialize::deserialize_slice(reader) },
+    };
+    let read = if slice {
+        let label = segment.trim_start_matches("::").trim_start_matches('.');
+        quote! { #cratename::de::metrics::field::<Self, _>(#label, reader, |reader| #read) }
+    } else {
+        read
+    };
+    Ok(quote! {
+        #cratename::de::in_field(#read, #ty, #segment)?
+    })
+}
+
+/// A statement decoding `field` from `reader`, a `&mut R`, into `place`, the expression naming the
+/// field in the value being overwritten. Length-prefixed fields are decoded afresh and assigned.
+pub fn deserialize_field_in_place(
+    field: &Field,
+    place: &TokenStream,
+    ty: &str,
+    segment: &str,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    Ok(if length_prefix(field)?.is_some() {
+        let deserialize = deserialize_field_in(field, ty, segment, false, cratename)?;
+        quote! { #place = #deserialize; }
+    } else {
+        quote! {
+            #cratename::de::in_field(
+                #cratename::BorshDeserialize::deserialize_in_place(reader, &mut #place),
+                #ty,
+                #segment,
+            )?;
+        }
+    })
+}
+
+/// Whether `field` is marked `#[borsh(seed)]`.
+pub fn is_seeded(field: &Field) -> syn::Result<bool> {
+    let seed = field_options(&field.attrs)?.seed;
+    if seed && (contains_skip(&field.attrs) || length_prefix(field)?.is_some()) {
+        return Err(Error::new(
+            field.span(),
+            "a field with borsh(seed) cannot be skipped or have a length prefix",
+        ));
+    }
+    Ok(seed)
+}
+
+/// Like [`deserialize_field_in`] from a reader, for the body of `deserialize_seed`, where `seed`
+/// is a `&mut #seed`. Fields marked `#[borsh(seed)]` are decoded with it.
+pub fn deserialize_field_seeded(
+    field: &Field,
+    seed: &TokenStream,
+    ty: &str,
+    segment: &str,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    if !is_seeded(field)? {
+        return deserialize_field_in(field, ty, segment, false, cratename);
+    }
+    let field_type = &field.ty;
+    Ok(quote! {
+        #cratename::de::in_field(
+            <#field_type as #cratename::de::BorshDeserializeSeed<#seed>>::deserialize_seed(
+                seed, reader,
+            ),
+            #ty,
+            #segment,
+        )?
+    })
+}
+
+/// Whether `field` is marked `#[borsh(ctx)]`.
+pub fn is_ctx_field(field: &Field) -> syn::Result<bool> {
+    let ctx = field_options(&field.attrs)?.ctx;
+    if ctx && (contains_skip(&field.attrs) || length_prefix(field)?.is_some()) {
+        return Err(Error::new(
+            field.span(),
+            "a field with borsh(ctx) cannot be skipped or have a length prefix",
+        ));
+    }
+    Ok(ctx)
+}
+
+/// Like [`serialize_field`], for the body of `serialize_with_ctx`, where `ctx` is a `&mut #ctx`.
+/// Fields marked `#[borsh(ctx)]` are serialized with it.
+pub fn serialize_field_with_ctx(
+    field: &Field,
+    value: TokenStream,
+    ctx: &TokenStream,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    if !is_ctx_field(field)? {
+        return serialize_field(field, value, cratename);
+    }
+    let field_type = &field.ty;
+    Ok(quote! {
+        <#field_type as #cratename::ser::BorshSerializeCtx<#ctx>>::serialize_with_ctx(
+            #value, writer, ctx,
+        )?;
+    })
+}
+
+/// Whether `field` is marked `#[borsh(header)]`.
+pub fn is_header_field(field: &Field) -> syn::Result<bool> {
+    Ok(field_options(&field.attrs)?.header)
+}
+
+/// The expression giving the number of bytes `field` encodes to, for a derived `BorshFixedSize`.
+pub fn fixed_size_field(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    if length_prefix(field)?.is_some() || is_extension_field(field)? {
+        return Err(Error::new(
+            field.span(),
+            "length-prefixed and extension fields have no fixed size",
+        ));
+    }
+    let field_type = &field.ty;
+    Ok(quote! { <#field_type as #cratename::fixed_size::BorshFixedSize>::SIZE })
+}
+
+/// The type whose schema describes `field` on the wire.
+pub fn schema_type(field: &Field, cratename: &Path) -> syn::Result<TokenStream> {
+    let field_type = &field.ty;
+    Ok(match length_prefix(field)? {
+        Some(len) => quote! { #cratename::schema::Prefixed<#field_type, #len> },
+        None => quote! { #field_type },
+    })
+}
+
+/// Fields paired with their declaration index, in the order they appear on the wire.
+///
+/// Without `#[borsh(order = N)]` this is the declaration order. Once any field has an order, every
+/// serialized field needs a distinct one and fields are sorted by it; skipped fields cannot have one.
+pub fn wire_order(fields: &Fields) -> syn::Result<Vec<(usize, &Field)>> {
+    let mut ordered = Vec::new();
+    let mut any_order = false;
+    for (idx, field) in fields.iter().enumerate() {
+        let order = field_order(&field.attrs)?;
+        if order.is_some() && contains_skip(&field.attrs) {
+            return Err(Error::new(
+                field.span(),
+                "a field with borsh_skip cannot have an order",
+            ));
+        }
+        any_order |= order.is_some();
+        ordered.push((order, idx, field));
+    }
+    if any_order {
+        for (order, _, field) in &ordered {
+            if order.is_none() && !contains_skip(&field.attrs) {
+                return Err(Error::new(
+                    field.span(),
+                    "every serialized field needs #[borsh(order = N)] once one field has it",
+                ));
+            }
+        }
+        // Skipped fields have no order and are sorted first; they are not serialized anyway.
+        ordered.sort_by_key(|(order, _, _)| *order);
+        for pair in ordered.windows(2) {
+            if pair[0].0.is_some() && pair[0].0 == pair[1].0 {
+                return Err(Error::new(pair[1].2.span(), "duplicate borsh order"));
+            }
+        }
+    }
+    Ok(ordered
+        .into_iter()
+        .map(|(_, idx, field)| (idx, field))
+        .collect())
+}
+
+/// Whether any field uses `#[borsh(order = N)]`.
+pub fn has_field_order(fields: &Fields) -> syn::Result<bool> {
+    for field in fields.iter() {
+        if field_order(&field.attrs)?.is_some() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
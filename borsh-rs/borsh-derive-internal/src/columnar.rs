@@ -0,0 +1,125 @@
+use crate::attribute_helpers::{
+    contains_skip, read_field, reject_extension, serialize_field, skip_default, wire_order,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{Error, Fields, Index, ItemStruct, Path};
+
+pub fn struct_columnar(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    reject_extension(input, "BorshColumnar")?;
+    let name = &input.ident;
+    let ty = name.to_string();
+    let mut write_columns = TokenStream::new();
+    let mut read_columns = TokenStream::new();
+    // The columns zipped together, and the pattern binding one row of them.
+    let mut zipped: Option<(TokenStream, TokenStream)> = None;
+    for (field_idx, field) in wire_order(&input.fields)? {
+        if contains_skip(&field.attrs) {
+            continue;
+        }
+        let member = match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(field_idx);
+                quote! { #index }
+            }
+        };
+        let serialize = serialize_field(field, quote! { &row.#member }, cratename)?;
+        write_columns.extend(quote! {
+            for row in rows {
+                #serialize
+            }
+        });
+
+        let column = format_ident!("column_{}", field_idx);
+        let field_ty = &field.ty;
+        let segment = match &field.ident {
+            Some(ident) => format!(".{}", ident),
+            None => format!(".{}", field_idx),
+        };
+        let read = read_field(field, cratename)?;
+        read_columns.extend(quote! {
+            let #column: #cratename::maybestd::vec::Vec<#field_ty> = #cratename::de::in_field(
+                #cratename::columnar::read_column(len, reader, |reader| #read),
+                #ty,
+                #segment,
+            )?;
+        });
+        zipped = Some(match zipped {
+            None => (quote! { #column.into_iter() }, quote! { #column }),
+            Some((iter, pattern)) => (
+                quote! { #iter.zip(#column) },
+                quote! { (#pattern, #column) },
+            ),
+        });
+    }
+    let (rows, pattern) = zipped.ok_or_else(|| {
+        Error::new(
+            input.span(),
+            "BorshColumnar needs at least one field that is not skipped",
+        )
+    })?;
+
+    let mut row = TokenStream::new();
+    for (field_idx, field) in input.fields.iter().enumerate() {
+        let value = if contains_skip(&field.attrs) {
+            skip_default(&field.attrs)?
+        } else {
+            let column = format_ident!("column_{}", field_idx);
+            quote! { #column }
+        };
+        row.extend(match &field.ident {
+            Some(ident) => quote! { #ident: #value, },
+            None => quote! { #value, },
+        });
+    }
+    let row = match &input.fields {
+        Fields::Named(_) => quote! { #name { #row } },
+        _ => quote! { #name ( #row ) },
+    };
+
+    let mut generics = crate::util::add_ser_constraints(input.generics.clone(), cratename);
+    generics = crate::util::add_de_constraints(generics, cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #cratename::columnar::BorshColumnar for #name #ty_generics #where_clause {
+            fn serialize_columns<W: #cratename::maybestd::io::Write>(rows: &[Self], writer: &mut W) -> core::result::Result<(), #cratename::maybestd::io::Error> {
+                #write_columns
+                Ok(())
+            }
+
+            fn deserialize_columns<R: #cratename::maybestd::io::Read>(len: usize, reader: &mut R) -> core::result::Result<#cratename::maybestd::vec::Vec<Self>, #cratename::maybestd::io::Error> {
+                #read_columns
+                #cratename::columnar::collect_rows(#rows.map(|#pattern| #row))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cratename() -> Path {
+        syn::parse_quote!(oasis_borsh)
+    }
+
+    #[test]
+    fn columnar_errors() {
+        let all_skipped: ItemStruct = syn::parse2(quote! {
+            struct A {
+                #[borsh_skip]
+                x: u8,
+            }
+        })
+        .unwrap();
+        assert!(struct_columnar(&all_skipped, &cratename()).is_err());
+
+        let unit: ItemStruct = syn::parse2(quote! {
+            struct A;
+        })
+        .unwrap();
+        assert!(struct_columnar(&unit, &cratename()).is_err());
+    }
+}
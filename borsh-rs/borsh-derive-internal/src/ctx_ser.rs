@@ -0,0 +1,133 @@
+use crate::attribute_helpers::{
+    contains_skip, reject_extension, serialize_field_with_ctx, wire_order,
+};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Fields, Generics, Ident, Index, ItemEnum, ItemStruct, Path};
+
+/// The impl serializing the item named `name` with `body`, which has `writer` and `ctx` in scope.
+fn ctx_impl(
+    name: &Ident,
+    item_generics: &Generics,
+    generics: &Generics,
+    ctx: &TokenStream,
+    body: TokenStream,
+    cratename: &Path,
+) -> TokenStream {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = item_generics.split_for_impl();
+    quote! {
+        impl #impl_generics #cratename::ser::BorshSerializeCtx<#ctx> for #name #ty_generics #where_clause {
+            fn serialize_with_ctx<W: #cratename::maybestd::io::Write>(&self, writer: &mut W, ctx: &mut #ctx) -> core::result::Result<(), #cratename::maybestd::io::Error> {
+                #body
+                Ok(())
+            }
+        }
+    }
+}
+
+pub fn struct_ctx_ser(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    reject_extension(input, "BorshSerializeCtx")?;
+    let (generics, ctx) = crate::util::ctx_generics(
+        &input.attrs,
+        &input.generics,
+        input.fields.iter(),
+        cratename,
+    )?;
+    let mut body = TokenStream::new();
+    for (field_idx, field) in wire_order(&input.fields)? {
+        if contains_skip(&field.attrs) {
+            continue;
+        }
+        let value = match &field.ident {
+            Some(ident) => quote! { &self.#ident },
+            None => {
+                let index = Index::from(field_idx);
+                quote! { &self.#index }
+            }
+        };
+        body.extend(serialize_field_with_ctx(field, value, &ctx, cratename)?);
+    }
+    Ok(ctx_impl(
+        &input.ident,
+        &input.generics,
+        &generics,
+        &ctx,
+        body,
+        cratename,
+    ))
+}
+
+pub fn enum_ctx_ser(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    let (generics, ctx) = crate::util::ctx_generics(
+        &input.attrs,
+        &input.generics,
+        input
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter()),
+        cratename,
+    )?;
+    let name = &input.ident;
+    let mut arms = TokenStream::new();
+    for (variant_idx, variant) in input.variants.iter().enumerate() {
+        let variant_idx = variant_idx as u8;
+        let variant_ident = &variant.ident;
+        let binding = |field_idx: usize| Ident::new(&format!("id{}", field_idx), Span::call_site());
+        let pattern = match &variant.fields {
+            Fields::Named(fields) => {
+                let names = fields
+                    .named
+                    .iter()
+                    .filter(|field| !contains_skip(&field.attrs))
+                    .map(|field| field.ident.as_ref().unwrap());
+                quote! { { #(#names,)* .. } }
+            }
+            Fields::Unnamed(fields) => {
+                let bindings = fields.unnamed.iter().enumerate().map(|(field_idx, field)| {
+                    if contains_skip(&field.attrs) {
+                        quote! { _ }
+                    } else {
+                        let binding = binding(field_idx);
+                        quote! { #binding }
+                    }
+                });
+                quote! { ( #(#bindings,)* ) }
+            }
+            Fields::Unit => TokenStream::new(),
+        };
+        let mut variant_body = TokenStream::new();
+        for (field_idx, field) in wire_order(&variant.fields)? {
+            if contains_skip(&field.attrs) {
+                continue;
+            }
+            let value = match &field.ident {
+                Some(ident) => quote! { #ident },
+                None => {
+                    let binding = binding(field_idx);
+                    quote! { #binding }
+                }
+            };
+            variant_body.extend(serialize_field_with_ctx(field, value, &ctx, cratename)?);
+        }
+        arms.extend(quote! {
+            #name::#variant_ident #pattern => {
+                writer.write_all(&[#variant_idx])?;
+                #variant_body
+            }
+        });
+    }
+    let body = quote! {
+        match self {
+            #arms
+        }
+    };
+    Ok(ctx_impl(
+        name,
+        &input.generics,
+        &generics,
+        &ctx,
+        body,
+        cratename,
+    ))
+}
@@ -0,0 +1,155 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::fold::{self, Fold};
+use syn::{
+    parse_quote, FnArg, GenericParam, Ident, ImplItem, ImplItemMethod, Item, ItemImpl, Pat, Path,
+    Type, TypeParamBound,
+};
+
+/// Replaces the type parameter of a method with the trait object type standing in for it.
+struct Substitute<'a> {
+    param: &'a Ident,
+    with: &'a Type,
+}
+
+impl Fold for Substitute<'_> {
+    fn fold_type(&mut self, ty: Type) -> Type {
+        match &ty {
+            Type::Path(path) if path.qself.is_none() && path.path.is_ident(self.param) => {
+                self.with.clone()
+            }
+            _ => fold::fold_type(self, ty),
+        }
+    }
+}
+
+/// The type parameter of `method` and the `Read` or `Write` trait bounding it, if that is the
+/// only generic parameter it has.
+fn io_param(method: &ImplItemMethod) -> Option<(&Ident, &Path)> {
+    let generics = &method.sig.generics;
+    if generics.params.len() != 1 || generics.where_clause.is_some() {
+        return None;
+    }
+    let param = match generics.params.first()? {
+        GenericParam::Type(param) => param,
+        _ => return None,
+    };
+    match (param.bounds.len(), param.bounds.first()?) {
+        (1, TypeParamBound::Trait(bound)) => {
+            let name = &bound.path.segments.last()?.ident;
+            if name == "Read" || name == "Write" {
+                Some((&param.ident, &bound.path))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Split `method` into a non-generic helper taking the reader or writer as a trait object, and
+/// a shim forwarding to it, which is all that is left to instantiate per reader or writer type.
+fn split(method: &mut ImplItemMethod) -> Option<ImplItemMethod> {
+    let (param, bound) = io_param(method)?;
+    let (param, bound) = (param.clone(), bound.clone());
+    let object: Type = parse_quote!(&mut dyn #bound);
+    let mut args = Vec::new();
+    let mut forwarded = Vec::new();
+    for arg in method.sig.inputs.iter() {
+        match arg {
+            FnArg::Receiver(_) => forwarded.push(quote! { self }),
+            FnArg::Typed(arg) => {
+                let ident = match &*arg.pat {
+                    Pat::Ident(pat) => &pat.ident,
+                    _ => return None,
+                };
+                let is_io = match &*arg.ty {
+                    Type::Reference(ty) => match &*ty.elem {
+                        Type::Path(ty) => ty.qself.is_none() && ty.path.is_ident(&param),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                if is_io {
+                    args.push(quote! { let mut #ident: #object = #ident; });
+                    forwarded.push(quote! { &mut #ident });
+                } else {
+                    forwarded.push(quote! { #ident });
+                }
+            }
+        }
+    }
+    if args.len() != 1 {
+        return None;
+    }
+
+    let mut substitute = Substitute {
+        param: &param,
+        with: &object,
+    };
+    let mut helper = substitute.fold_impl_item_method(method.clone());
+    helper.sig.generics = Default::default();
+    helper.sig.ident = format_ident!("__borsh_{}_dyn", method.sig.ident, span = Span::call_site());
+    helper.attrs = vec![parse_quote!(#[doc(hidden)])];
+    helper.vis = syn::Visibility::Inherited;
+
+    let helper_ident = &helper.sig.ident;
+    method.block = parse_quote!({
+        #(#args)*
+        Self::#helper_ident(#(#forwarded),*)
+    });
+    Some(helper)
+}
+
+fn is_plain_trait_impl(item: &ItemImpl) -> bool {
+    match &item.trait_ {
+        Some((_, path, _)) => path
+            .segments
+            .iter()
+            .all(|segment| segment.arguments.is_empty()),
+        None => false,
+    }
+}
+
+/// Rewrite the borsh impls generated for a type so that each method generic over a reader or a
+/// writer only converts it to `&mut dyn Read` or `&mut dyn Write` and calls a non-generic method
+/// doing the work, so the code decoding or encoding the type is compiled once rather than once
+/// per reader or writer type. This trades a virtual call per read or write for smaller binaries,
+/// which matters most on targets like WASM.
+///
+/// Impls are left as they are if their methods do not fit that pattern.
+pub fn dyn_io(impls: TokenStream) -> syn::Result<TokenStream> {
+    let mut file = syn::parse2::<syn::File>(impls)?;
+    let mut inherent = Vec::new();
+    for item in file.items.iter_mut() {
+        // The helpers go in an inherent impl, which cannot have the parameters that only
+        // appear in the arguments of the trait, like the seed type of a generic seeded impl.
+        let item = match item {
+            Item::Impl(item) if is_plain_trait_impl(item) => item,
+            _ => continue,
+        };
+        let mut helpers = Vec::new();
+        for impl_item in item.items.iter_mut() {
+            if let ImplItem::Method(method) = impl_item {
+                if let Some(helper) = split(method) {
+                    helpers.push(ImplItem::Method(helper));
+                }
+            }
+        }
+        if !helpers.is_empty() {
+            inherent.push(Item::Impl(ItemImpl {
+                attrs: Vec::new(),
+                defaultness: None,
+                unsafety: None,
+                impl_token: Default::default(),
+                generics: item.generics.clone(),
+                trait_: None,
+                self_ty: item.self_ty.clone(),
+                brace_token: Default::default(),
+                items: helpers,
+            }));
+        }
+    }
+    file.items.extend(inherent);
+    Ok(file.into_token_stream())
+}
@@ -1,39 +1,67 @@
-use crate::attribute_helpers::{contains_initialize_with, contains_skip};
+use crate::attribute_helpers::{
+    alias_tags, contains_initialize_with, contains_skip, contains_wire_tag, deserialize_field_in,
+    has_field_order, skip_default, wire_order,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Fields, ItemEnum};
+use syn::{Fields, Index, ItemEnum, Path};
 
-pub fn enum_de(input: &ItemEnum) -> syn::Result<TokenStream> {
+/// The match arms reading each variant from `reader`, a `&mut R` for `deserialize` and a
+/// `&mut &[u8]` for `deserialize_slice`.
+fn variant_arms(input: &ItemEnum, cratename: &Path, slice: bool) -> syn::Result<TokenStream> {
     let name = &input.ident;
-    let init_method = contains_initialize_with(&input.attrs)?;
+    let ty = name.to_string();
+    let read_field =
+        |field, segment: String| deserialize_field_in(field, &ty, &segment, slice, cratename);
+    let alias_tags = alias_tags(input)?;
     let mut variant_arms = TokenStream::new();
-    for (variant_idx, variant) in input.variants.iter().enumerate() {
+    for ((variant_idx, variant), aliases) in input.variants.iter().enumerate().zip(alias_tags) {
         let variant_idx = variant_idx as u8;
         let variant_ident = &variant.ident;
         let mut variant_header = TokenStream::new();
         match &variant.fields {
-            Fields::Named(fields) => {
-                for field in &fields.named {
+            // Fields are listed in wire order, which is the order they are evaluated in.
+            Fields::Named(_) => {
+                for (_, field) in wire_order(&variant.fields)? {
                     let field_name = field.ident.as_ref().unwrap();
                     if contains_skip(&field.attrs) {
+                        let default = skip_default(&field.attrs)?;
                         variant_header.extend(quote! {
-                            #field_name: Default::default(),
+                            #field_name: #default,
                         });
                     } else {
+                        let segment = format!("::{}.{}", variant_ident, field_name);
+                        let deserialize = read_field(field, segment)?;
                         variant_header.extend(quote! {
-                            #field_name: oasis_borsh::BorshDeserialize::deserialize(reader)?,
+                            #field_name: #deserialize,
                         });
                     }
                 }
                 variant_header = quote! { { #variant_header }};
             }
+            Fields::Unnamed(_) if has_field_order(&variant.fields)? => {
+                for (field_idx, field) in wire_order(&variant.fields)? {
+                    let field_idx = Index::from(field_idx);
+                    if contains_skip(&field.attrs) {
+                        let default = skip_default(&field.attrs)?;
+                        variant_header.extend(quote! { #field_idx: #default, });
+                    } else {
+                        let segment = format!("::{}.{}", variant_ident, field_idx.index);
+                        let deserialize = read_field(field, segment)?;
+                        variant_header.extend(quote! { #field_idx: #deserialize, });
+                    }
+                }
+                variant_header = quote! { { #variant_header }};
+            }
             Fields::Unnamed(fields) => {
-                for field in fields.unnamed.iter() {
+                for (field_idx, field) in fields.unnamed.iter().enumerate() {
                     if contains_skip(&field.attrs) {
-                        variant_header.extend(quote! { Default::default(), });
+                        let default = skip_default(&field.attrs)?;
+                        variant_header.extend(quote! { #default, });
                     } else {
-                        variant_header
-                            .extend(quote! { oasis_borsh::BorshDeserialize::deserialize(reader)?, });
+                        let segment = format!("::{}.{}", variant_ident, field_idx);
+                        let deserialize = read_field(field, segment)?;
+                        variant_header.extend(quote! { #deserialize, });
                     }
                 }
                 variant_header = quote! { ( #variant_header )};
@@ -41,52 +69,158 @@ pub fn enum_de(input: &ItemEnum) -> syn::Result<TokenStream> {
             Fields::Unit => {}
         }
         variant_arms.extend(quote! {
-            #variant_idx => #name::#variant_ident #variant_header ,
+            #variant_idx #(| #aliases)* => #name::#variant_ident #variant_header ,
+        });
+    }
+    Ok(variant_arms)
+}
+
+/// With `#[borsh_wire_tag]`, an associated function reading the tag at the start of an encoding,
+/// resolving aliases to the tag of their variant.
+fn peek_wire_tag(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    if !contains_wire_tag(&input.attrs) {
+        return Ok(TokenStream::new());
+    }
+    let name = &input.ident;
+    let mut tag_arms = TokenStream::new();
+    for (variant_idx, aliases) in alias_tags(input)?.into_iter().enumerate() {
+        let variant_idx = variant_idx as u8;
+        tag_arms.extend(quote! {
+            #variant_idx #(| #aliases)* => Ok(#variant_idx),
         });
     }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The tag of the variant encoded at the start of `v`, read without decoding the
+            /// rest. Legacy tags are resolved to the tag of their variant.
+            pub fn peek_wire_tag(v: &[u8]) -> core::result::Result<u8, #cratename::maybestd::io::Error> {
+                match #cratename::de::peek_tag(v)? {
+                    #tag_arms
+                    variant_idx => Err(#cratename::Error::InvalidTag(variant_idx).into()),
+                }
+            }
+        }
+    })
+}
+
+pub fn enum_de(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let peek_wire_tag = peek_wire_tag(input, cratename)?;
+    let init_method = contains_initialize_with(&input.attrs)?;
+    let variant_arms_slice = variant_arms(input, cratename, true)?;
+    let variant_arms = variant_arms(input, cratename, false)?;
     let variant_idx = quote! {
-        let mut variant_idx = [0u8; std::mem::size_of::<u8>()];
-        reader.read_exact(&mut variant_idx)?;
-        let variant_idx = u8::from_le_bytes(variant_idx);
+        let variant_idx = #cratename::de::read_tag(reader)?;
+    };
+    // The slice is put back at the tag if it names no variant, so the error points at the tag.
+    let variant_idx_slice = quote! {
+        let tag_start = *reader;
+        let variant_idx = #cratename::de::read_tag_slice(reader)?;
     };
 
-    let generics = crate::util::add_de_constraints(input.generics.clone());
+    let generics = crate::util::de_generics(
+        &input.attrs,
+        &input.generics,
+        input
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter()),
+        cratename,
+    )?;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    if let Some(method_ident) = init_method {
-        Ok(quote! {
-            impl #impl_generics oasis_borsh::de::BorshDeserialize for #name #ty_generics #where_clause {
-                fn deserialize<R: std::io::Read>(reader: &mut R) -> std::result::Result<Self, std::io::Error> {
-                    #variant_idx
-                    let mut return_value = match variant_idx {
-                        #variant_arms
-                        _ =>
-                        return Err(std::io::Error::new(
-                                   std::io::ErrorKind::InvalidInput,
-                                   format!("Unexpected variant index: {:?}", variant_idx),
-                                  )),
-                    };
-                    return_value.#method_ident();
-                    Ok(return_value)
-                }
+    // With `#[borsh_init]`, the decoded value is initialized before it is returned.
+    let (binding, init) = match init_method {
+        Some(method_ident) => (
+            quote! { let mut return_value },
+            quote! { return_value.#method_ident(); },
+        ),
+        None => (quote! { let return_value }, TokenStream::new()),
+    };
+
+    Ok(quote! {
+        #peek_wire_tag
+
+        impl #impl_generics #cratename::de::BorshDeserialize for #name #ty_generics #where_clause {
+            fn deserialize<R: #cratename::maybestd::io::Read>(reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                let _depth = #cratename::de::enter()?;
+                #variant_idx
+                #binding = match variant_idx {
+                    #variant_arms
+                    _ => return Err(#cratename::Error::InvalidTag(variant_idx).into()),
+                };
+                #init
+                Ok(return_value)
+            }
+
+            fn deserialize_slice(reader: &mut &[u8]) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                let _depth = #cratename::de::enter()?;
+                #variant_idx_slice
+                #binding = match variant_idx {
+                    #variant_arms_slice
+                    _ => {
+                        *reader = tag_start;
+                        return Err(#cratename::Error::InvalidTag(variant_idx).into());
+                    }
+                };
+                #init
+                Ok(return_value)
+            }
+        }
+
+        impl #impl_generics #cratename::de::BorshDeserializeVariant for #name #ty_generics #where_clause {
+            fn deserialize_variant<R: #cratename::maybestd::io::Read>(variant_idx: u8, reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                let _depth = #cratename::de::enter()?;
+                #binding = match variant_idx {
+                    #variant_arms
+                    _ => return Err(#cratename::Error::InvalidTag(variant_idx).into()),
+                };
+                #init
+                Ok(return_value)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cratename() -> Path {
+        syn::parse_quote!(oasis_borsh)
+    }
+
+    #[test]
+    fn alias_tag_errors() {
+        let variant_tag: ItemEnum = syn::parse2(quote! {
+            enum A {
+                B,
+                #[borsh(alias_tag = 0)]
+                C,
             }
         })
-    } else {
-        Ok(quote! {
-            impl #impl_generics oasis_borsh::de::BorshDeserialize for #name #ty_generics #where_clause {
-                fn deserialize<R: std::io::Read>(reader: &mut R) -> std::result::Result<Self, std::io::Error> {
-                    #variant_idx
-                    let return_value = match variant_idx {
-                        #variant_arms
-                        _ =>
-                        return Err(std::io::Error::new(
-                                   std::io::ErrorKind::InvalidInput,
-                                   format!("Unexpected variant index: {:?}", variant_idx),
-                                  )),
-                    };
-                    Ok(return_value)
-                }
+        .unwrap();
+        assert!(enum_de(&variant_tag, &cratename()).is_err());
+
+        let duplicate: ItemEnum = syn::parse2(quote! {
+            enum A {
+                #[borsh(alias_tag = 2)]
+                B,
+                #[borsh(alias_tag = 2)]
+                C,
+            }
+        })
+        .unwrap();
+        assert!(enum_de(&duplicate, &cratename()).is_err());
+
+        let unknown: ItemEnum = syn::parse2(quote! {
+            enum A {
+                #[borsh(order = 2)]
+                B,
             }
         })
+        .unwrap();
+        assert!(enum_de(&unknown, &cratename()).is_err());
     }
 }
@@ -0,0 +1,22 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ItemEnum, Path};
+
+/// Enums are replaced wholesale whenever their encoding changes.
+pub fn enum_diff(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let generics = crate::util::add_diff_constraints(input.generics.clone(), cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #cratename::diff::BorshDiff for #name #ty_generics #where_clause {
+            fn diff_into(old: &Self, new: &Self, out: &mut #cratename::maybestd::vec::Vec<u8>) -> core::result::Result<bool, #cratename::maybestd::io::Error> {
+                #cratename::diff::replace_diff_into(old, new, out)
+            }
+
+            fn apply_diff<R: #cratename::maybestd::io::Read>(&mut self, reader: &mut R) -> core::result::Result<(), #cratename::maybestd::io::Error> {
+                #cratename::diff::replace_apply_diff(self, reader)
+            }
+        }
+    })
+}
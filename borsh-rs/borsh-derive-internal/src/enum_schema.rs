@@ -0,0 +1,117 @@
+use crate::attribute_helpers::{
+    contains_embed_schema, contains_skip, embedded_schema, idl_declaration, idl_fields,
+    schema_type, wire_order,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Fields, ItemEnum, Path};
+
+pub fn enum_schema(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let generics = crate::util::add_schema_constraints(input.generics.clone(), cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let declaration = crate::util::schema_declaration(&name_str, &input.generics, cratename);
+
+    // Every variant is described by an anonymous struct named after the enum and the variant.
+    let mut variants = TokenStream::new();
+    let mut variant_definitions = TokenStream::new();
+    for variant in &input.variants {
+        let variant_name = variant.ident.to_string();
+        let variant_declaration = crate::util::schema_declaration(
+            &format!("{}{}", name_str, variant_name),
+            &input.generics,
+            cratename,
+        );
+        let mut field_types = Vec::new();
+        let fields = match &variant.fields {
+            Fields::Named(_) => {
+                let mut entries = TokenStream::new();
+                for (_, field) in wire_order(&variant.fields)? {
+                    if contains_skip(&field.attrs) {
+                        continue;
+                    }
+                    let field_name = field.ident.as_ref().unwrap().to_string();
+                    let field_type = schema_type(field, cratename)?;
+                    entries.extend(quote! {
+                        (#field_name.to_string(), <#field_type as #cratename::BorshSchema>::declaration()),
+                    });
+                    field_types.push(field_type);
+                }
+                quote! { #cratename::schema::Fields::NamedFields(vec![#entries]) }
+            }
+            Fields::Unnamed(_) => {
+                let mut entries = TokenStream::new();
+                for (_, field) in wire_order(&variant.fields)? {
+                    if contains_skip(&field.attrs) {
+                        continue;
+                    }
+                    let field_type = schema_type(field, cratename)?;
+                    entries.extend(quote! {
+                        <#field_type as #cratename::BorshSchema>::declaration(),
+                    });
+                    field_types.push(field_type);
+                }
+                quote! { #cratename::schema::Fields::UnnamedFields(vec![#entries]) }
+            }
+            Fields::Unit => quote! { #cratename::schema::Fields::Empty },
+        };
+        variants.extend(quote! {
+            (#variant_name.to_string(), { #variant_declaration }),
+        });
+        variant_definitions.extend(quote! {
+            let fields = #fields;
+            let definition = #cratename::schema::Definition::Struct { fields };
+            if <Self as #cratename::BorshSchema>::add_definition(
+                { #variant_declaration },
+                definition,
+                definitions,
+            ) {
+                #(<#field_types as #cratename::BorshSchema>::add_definitions_recursively(definitions);)*
+            }
+        });
+    }
+
+    let embedded = if contains_embed_schema(&input.attrs)? {
+        let mut variants = Vec::new();
+        for variant in &input.variants {
+            variants.push(format!("{}{}", variant.ident, idl_fields(&variant.fields)?));
+        }
+        let declaration = idl_declaration("enum", name, &input.generics);
+        let idl = if variants.is_empty() {
+            format!("{} {{}}", declaration)
+        } else {
+            format!("{} {{ {} }}", declaration, variants.join(", "))
+        };
+        embedded_schema(name, &input.generics, &idl)
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        #embedded
+
+        impl #impl_generics #cratename::BorshSchema for #name #ty_generics #where_clause {
+            fn declaration() -> #cratename::schema::Declaration {
+                #declaration
+            }
+
+            fn add_definitions_recursively(
+                definitions: &mut #cratename::maybestd::collections::HashMap<
+                    #cratename::schema::Declaration,
+                    #cratename::schema::Definition
+                >,
+            ) {
+                let variants = vec![#variants];
+                let definition = #cratename::schema::Definition::Enum { variants };
+                if <Self as #cratename::BorshSchema>::add_definition(
+                    <Self as #cratename::BorshSchema>::declaration(),
+                    definition,
+                    definitions,
+                ) {
+                    #variant_definitions
+                }
+            }
+        }
+    })
+}
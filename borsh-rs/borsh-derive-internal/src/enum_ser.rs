@@ -1,29 +1,45 @@
-use crate::attribute_helpers::contains_skip;
+use crate::attribute_helpers::{
+    contains_skip, contains_wire_tag, serialize_field, serialized_size_field, size_hint_field,
+    wire_order,
+};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::{Fields, Ident, ItemEnum};
+use syn::{Fields, Ident, ItemEnum, Path};
 
-pub fn enum_ser(input: &ItemEnum) -> syn::Result<TokenStream> {
+pub fn enum_ser(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
     let name = &input.ident;
     let mut body = TokenStream::new();
+    let mut sizes = TokenStream::new();
+    let mut hints = TokenStream::new();
+    let mut wire_tags = TokenStream::new();
     for (variant_idx, variant) in input.variants.iter().enumerate() {
         let variant_idx = variant_idx as u8;
         let variant_ident = &variant.ident;
         let mut variant_header = TokenStream::new();
         let mut variant_body = TokenStream::new();
+        let mut variant_size = quote! { 1 };
+        let mut variant_hint = quote! { 1usize };
         match &variant.fields {
             Fields::Named(fields) => {
                 for field in &fields.named {
                     let field_name = field.ident.as_ref().unwrap();
                     if contains_skip(&field.attrs) {
-                        variant_header.extend(quote! { _#field_name, });
-                        continue;
+                        variant_header.extend(quote! { #field_name: _, });
                     } else {
                         variant_header.extend(quote! { #field_name, });
                     }
-                    variant_body.extend(quote! {
-                         oasis_borsh::BorshSerialize::serialize(#field_name, writer)?;
-                    })
+                }
+                for (_, field) in wire_order(&variant.fields)? {
+                    if contains_skip(&field.attrs) {
+                        continue;
+                    }
+                    let field_name = field.ident.as_ref().unwrap();
+                    let value = quote! { #field_name };
+                    variant_body.extend(serialize_field(field, value.clone(), cratename)?);
+                    let field_size = serialized_size_field(field, value.clone(), cratename)?;
+                    variant_size.extend(quote! { + #field_size });
+                    let field_hint = size_hint_field(field, value, cratename)?;
+                    variant_hint.extend(quote! { .saturating_add(#field_hint) });
                 }
                 variant_header = quote! { { #variant_header }};
             }
@@ -34,40 +50,96 @@ pub fn enum_ser(input: &ItemEnum) -> syn::Result<TokenStream> {
                         let field_ident =
                             Ident::new(format!("_id{}", field_idx).as_str(), Span::call_site());
                         variant_header.extend(quote! { #field_ident, });
-                        continue;
                     } else {
                         let field_ident =
                             Ident::new(format!("id{}", field_idx).as_str(), Span::call_site());
                         variant_header.extend(quote! { #field_ident, });
-                        variant_body.extend(quote! {
-                            oasis_borsh::BorshSerialize::serialize(#field_ident, writer)?;
-                        })
                     }
                 }
+                for (field_idx, field) in wire_order(&variant.fields)? {
+                    if contains_skip(&field.attrs) {
+                        continue;
+                    }
+                    let field_ident =
+                        Ident::new(format!("id{}", field_idx).as_str(), Span::call_site());
+                    let value = quote! { #field_ident };
+                    variant_body.extend(serialize_field(field, value.clone(), cratename)?);
+                    let field_size = serialized_size_field(field, value.clone(), cratename)?;
+                    variant_size.extend(quote! { + #field_size });
+                    let field_hint = size_hint_field(field, value, cratename)?;
+                    variant_hint.extend(quote! { .saturating_add(#field_hint) });
+                }
                 variant_header = quote! { ( #variant_header )};
             }
             Fields::Unit => {}
         }
+        wire_tags.extend(quote! {
+            #name::#variant_ident { .. } => #variant_idx,
+        });
         body.extend(quote!(
             #name::#variant_ident #variant_header => {
                 let variant_idx: u8 = #variant_idx;
                 writer.write_all(&variant_idx.to_le_bytes())?;
                 #variant_body
             }
-        ))
+        ));
+        sizes.extend(quote!(
+            #name::#variant_ident #variant_header => #variant_size,
+        ));
+        hints.extend(quote!(
+            #name::#variant_ident #variant_header => #variant_hint,
+        ));
     }
 
-    let generics = crate::util::add_ser_constraints(input.generics.clone());
+    let generics = crate::util::ser_generics(
+        &input.attrs,
+        &input.generics,
+        input
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter()),
+        cratename,
+    )?;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
+    let wire_tag = if contains_wire_tag(&input.attrs) {
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// The variant index written in front of this value when it is serialized.
+                pub fn wire_tag(&self) -> u8 {
+                    match self {
+                        #wire_tags
+                    }
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     Ok(quote! {
-        impl #impl_generics oasis_borsh::ser::BorshSerialize for #name #ty_generics #where_clause {
-            fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), std::io::Error> {
+        #wire_tag
+
+        impl #impl_generics #cratename::ser::BorshSerialize for #name #ty_generics #where_clause {
+            fn serialize<W: #cratename::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), #cratename::maybestd::io::Error> {
                 match self {
                     #body
                 }
                 Ok(())
             }
+
+            fn serialized_size(&self) -> usize {
+                match self {
+                    #sizes
+                }
+            }
+
+            fn size_hint(&self) -> usize {
+                match self {
+                    #hints
+                }
+            }
         }
     })
 }
@@ -0,0 +1,82 @@
+use crate::attribute_helpers::{contains_skip, extension_field, fixed_size_field};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Error, Fields, Generics, Ident, ItemEnum, ItemStruct, Path};
+
+/// The expression summing the sizes of the encoded `fields`.
+fn fields_size(fields: &Fields, cratename: &Path) -> syn::Result<TokenStream> {
+    let mut size = quote! { 0 };
+    for field in fields.iter().filter(|field| !contains_skip(&field.attrs)) {
+        let field_size = fixed_size_field(field, cratename)?;
+        size.extend(quote! { + #field_size });
+    }
+    Ok(size)
+}
+
+fn fixed_size_impl(
+    name: &Ident,
+    generics: &Generics,
+    size: TokenStream,
+    cratename: &Path,
+) -> TokenStream {
+    let generics = crate::util::add_fixed_size_constraints(generics.clone(), cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #cratename::fixed_size::BorshFixedSize for #name #ty_generics #where_clause {
+            const SIZE: usize = #size;
+        }
+    }
+}
+
+pub fn struct_fixed_size(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    if extension_field(input)?.is_some() {
+        return Err(Error::new(
+            input.ident.span(),
+            "structs with an extension region have no fixed size",
+        ));
+    }
+    let size = fields_size(&input.fields, cratename)?;
+    Ok(fixed_size_impl(
+        &input.ident,
+        &input.generics,
+        size,
+        cratename,
+    ))
+}
+
+/// The size of an enum is its tag plus the size of its variants, which have to agree. Whether they
+/// do is only known once the sizes of generic fields are, so it is checked when `SIZE` is used.
+pub fn enum_fixed_size(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    if input.variants.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "enums without variants have no encoding",
+        ));
+    }
+    let sizes = input
+        .variants
+        .iter()
+        .map(|variant| fields_size(&variant.fields, cratename))
+        .collect::<syn::Result<Vec<_>>>()?;
+    let size = quote! {
+        {
+            let sizes = [#(#sizes),*];
+            let mut idx = 1;
+            while idx < sizes.len() {
+                assert!(
+                    sizes[idx] == sizes[0],
+                    "the variants of a BorshFixedSize enum must encode to the same size",
+                );
+                idx += 1;
+            }
+            1 + sizes[0]
+        }
+    };
+    Ok(fixed_size_impl(
+        &input.ident,
+        &input.generics,
+        size,
+        cratename,
+    ))
+}
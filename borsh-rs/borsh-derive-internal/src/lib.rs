@@ -1,17 +1,71 @@
+//! Code generation behind the borsh derive macros, for use in other procedural macros.
+//!
+//! Each of [`struct_ser`], [`struct_ctx_ser`], [`struct_de`], [`struct_seed_de`],
+//! [`struct_schema`], [`struct_diff`], [`struct_fixed_size`], [`struct_columnar`], [`enum_ser`],
+//! [`enum_ctx_ser`], [`enum_de`], [`enum_seed_de`], [`enum_schema`], [`enum_diff`] and
+//! [`enum_fixed_size`] takes a parsed item and returns the impl the corresponding derive would
+//! emit, or the error it would report. The generated code names the borsh crate through
+//! `cratename`, the path it is reachable at from the derived item: the derives pass `oasis_borsh`,
+//! while a framework that re-exports borsh passes its own path, so that its users need not depend
+//! on borsh directly:
+//!
+//! ```ignore
+//! #[proc_macro_derive(Message, attributes(borsh, borsh_skip))]
+//! pub fn message(input: TokenStream) -> TokenStream {
+//!     let input = syn::parse_macro_input!(input as syn::ItemStruct);
+//!     let cratename: syn::Path = syn::parse_quote!(my_framework::borsh);
+//!     let ser = struct_ser(&input, &cratename).unwrap_or_else(|err| err.to_compile_error());
+//!     let de = struct_de(&input, &cratename).unwrap_or_else(|err| err.to_compile_error());
+//!     quote::quote!(#ser #de).into()
+//! }
+//! ```
+//!
+//! A macro wrapping these has to declare the attributes the items may carry, as above; see
+//! [`attribute_helpers`] for what each of them means.
+//!
+//! [`dyn_io`] rewrites the output of any of them to be compiled once rather than per reader and
+//! writer type, which the derives do with the `dyn-io` feature of `oasis-borsh`.
+//!
+//! Generated code uses `core` paths and takes `io` and the allocating types from
+//! `cratename::maybestd`, so it compiles in `no_std` crates when borsh is built without its
+//! `std` feature.
+//!
+//! The signatures of the functions exported here follow semantic versioning: they only change in
+//! a new major version. The code they generate may change in any release, in step with the
+//! `oasis-borsh` version it is meant for.
 #![recursion_limit = "128"]
 
-mod attribute_helpers;
+pub mod attribute_helpers;
+mod columnar;
+mod ctx_ser;
+mod dyn_io;
 mod enum_de;
+mod enum_diff;
+mod enum_schema;
 mod enum_ser;
+mod fixed_size;
+mod seed_de;
 mod struct_de;
+mod struct_diff;
+mod struct_header;
+mod struct_schema;
 mod struct_ser;
 mod union_de;
 mod union_ser;
 mod util;
 
+pub use columnar::struct_columnar;
+pub use ctx_ser::{enum_ctx_ser, struct_ctx_ser};
+pub use dyn_io::dyn_io;
 pub use enum_de::enum_de;
+pub use enum_diff::enum_diff;
+pub use enum_schema::enum_schema;
 pub use enum_ser::enum_ser;
+pub use fixed_size::{enum_fixed_size, struct_fixed_size};
+pub use seed_de::{enum_seed_de, struct_seed_de};
 pub use struct_de::struct_de;
+pub use struct_diff::struct_diff;
+pub use struct_schema::struct_schema;
 pub use struct_ser::struct_ser;
 pub use union_de::union_de;
 pub use union_ser::union_ser;
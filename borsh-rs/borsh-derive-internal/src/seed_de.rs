@@ -0,0 +1,130 @@
+use crate::attribute_helpers::{
+    alias_tags, contains_initialize_with, contains_skip, deserialize_field_seeded,
+    reject_extension, skip_default, wire_order,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Fields, Generics, Index, ItemEnum, ItemStruct, Path};
+
+/// The expression building `path` from `fields` read in wire order, naming failing fields
+/// `segment_prefix` followed by the field, as in `::Variant.field`.
+fn fields_value(
+    path: TokenStream,
+    fields: &Fields,
+    ty: &str,
+    segment_prefix: &str,
+    seed: &TokenStream,
+    cratename: &Path,
+) -> syn::Result<TokenStream> {
+    let mut body = TokenStream::new();
+    for (field_idx, field) in wire_order(fields)? {
+        let (member, name) = match &field.ident {
+            Some(ident) => (quote! { #ident }, ident.to_string()),
+            None => {
+                let index = Index::from(field_idx);
+                (quote! { #index }, field_idx.to_string())
+            }
+        };
+        let value = if contains_skip(&field.attrs) {
+            skip_default(&field.attrs)?
+        } else {
+            let segment = format!("{}.{}", segment_prefix, name);
+            deserialize_field_seeded(field, seed, ty, &segment, cratename)?
+        };
+        body.extend(quote! { #member: #value, });
+    }
+    Ok(quote! { #path { #body } })
+}
+
+/// The impl decoding the item named `name` with `body`, which has `seed` and `reader` in scope.
+fn seed_impl(
+    name: &syn::Ident,
+    item_generics: &Generics,
+    generics: &Generics,
+    seed: &TokenStream,
+    init_method: Option<Path>,
+    body: TokenStream,
+    cratename: &Path,
+) -> TokenStream {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = item_generics.split_for_impl();
+    let init = init_method.map(|method_ident| quote! { return_value.#method_ident(); });
+    quote! {
+        impl #impl_generics #cratename::de::BorshDeserializeSeed<#seed> for #name #ty_generics #where_clause {
+            fn deserialize_seed<R: #cratename::maybestd::io::Read>(seed: &mut #seed, reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                let _depth = #cratename::de::enter()?;
+                #[allow(unused_mut)]
+                let mut return_value = #body;
+                #init
+                Ok(return_value)
+            }
+        }
+    }
+}
+
+pub fn struct_seed_de(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    reject_extension(input, "BorshDeserializeSeed")?;
+    let (generics, seed) = crate::util::seed_generics(
+        &input.attrs,
+        &input.generics,
+        input.fields.iter(),
+        cratename,
+    )?;
+    let ty = input.ident.to_string();
+    let body = fields_value(quote! { Self }, &input.fields, &ty, "", &seed, cratename)?;
+    Ok(seed_impl(
+        &input.ident,
+        &input.generics,
+        &generics,
+        &seed,
+        contains_initialize_with(&input.attrs)?,
+        body,
+        cratename,
+    ))
+}
+
+pub fn enum_seed_de(input: &ItemEnum, cratename: &Path) -> syn::Result<TokenStream> {
+    let (generics, seed) = crate::util::seed_generics(
+        &input.attrs,
+        &input.generics,
+        input
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter()),
+        cratename,
+    )?;
+    let name = &input.ident;
+    let ty = name.to_string();
+    let alias_tags = alias_tags(input)?;
+    let mut variant_arms = TokenStream::new();
+    for ((variant_idx, variant), aliases) in input.variants.iter().enumerate().zip(alias_tags) {
+        let variant_idx = variant_idx as u8;
+        let variant_ident = &variant.ident;
+        let value = fields_value(
+            quote! { #name::#variant_ident },
+            &variant.fields,
+            &ty,
+            &format!("::{}", variant_ident),
+            &seed,
+            cratename,
+        )?;
+        variant_arms.extend(quote! {
+            #variant_idx #(| #aliases)* => #value,
+        });
+    }
+    let body = quote! {
+        match #cratename::de::read_tag(reader)? {
+            #variant_arms
+            variant_idx => return Err(#cratename::Error::InvalidTag(variant_idx).into()),
+        }
+    };
+    Ok(seed_impl(
+        name,
+        &input.generics,
+        &generics,
+        &seed,
+        contains_initialize_with(&input.attrs)?,
+        body,
+        cratename,
+    ))
+}
@@ -1,36 +1,119 @@
-use crate::attribute_helpers::{contains_initialize_with, contains_skip};
+use crate::attribute_helpers::{
+    contains_initialize_with, contains_skip, deserialize_field_in, deserialize_field_in_place,
+    extension_field, has_field_order, is_extension_field, memcpy_layout, skip_default, wire_order,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Fields, ItemStruct};
+use syn::{Fields, Index, ItemStruct, Path};
 
-pub fn struct_de(input: &ItemStruct) -> syn::Result<TokenStream> {
-    let name = &input.ident;
-    let init_method = contains_initialize_with(&input.attrs)?;
+/// The expression reading a struct from `reader`, a `&mut R` for `deserialize` and a
+/// `&mut &[u8]` for `deserialize_slice`.
+fn return_value(input: &ItemStruct, cratename: &Path, slice: bool) -> syn::Result<TokenStream> {
+    let ty = input.ident.to_string();
+    let read_field = |field, name: String| {
+        deserialize_field_in(field, &ty, &format!(".{}", name), slice, cratename)
+    };
     let return_value = match &input.fields {
-        Fields::Named(fields) => {
+        // Struct expressions evaluate their fields in the order written, so listing the fields
+        // in wire order reads them in wire order.
+        Fields::Named(_) => {
+            let extension = extension_field(input)?;
             let mut body = TokenStream::new();
-            for field in &fields.named {
+            let mut region = TokenStream::new();
+            for (_, field) in wire_order(&input.fields)? {
                 let field_name = field.ident.as_ref().unwrap();
                 let delta = if contains_skip(&field.attrs) {
+                    let default = skip_default(&field.attrs)?;
+                    quote! {
+                        #field_name: #default,
+                    }
+                } else if extension.as_ref() == Some(field_name) {
+                    quote! {
+                        #field_name: Default::default(),
+                    }
+                } else if is_extension_field(field)? {
+                    // Regions written by older versions of the type end before their newer
+                    // fields, which keep their defaults.
+                    let deserialize = deserialize_field_in(
+                        field,
+                        &ty,
+                        &format!(".{}", field_name),
+                        true,
+                        cratename,
+                    )?;
+                    region.extend(quote! {
+                        if !reader.is_empty() {
+                            value.#field_name = #deserialize;
+                        }
+                    });
                     quote! {
                         #field_name: Default::default(),
                     }
                 } else {
+                    let deserialize = read_field(field, field_name.to_string())?;
                     quote! {
-                        #field_name: oasis_borsh::BorshDeserialize::deserialize(reader)?,
+                        #field_name: #deserialize,
                     }
                 };
                 body.extend(delta);
             }
+            match extension {
+                Some(unknown) => {
+                    let region = if region.is_empty() {
+                        region
+                    } else {
+                        quote! {
+                            {
+                                let reader = &mut region;
+                                #region
+                            }
+                        }
+                    };
+                    quote! {
+                        {
+                            let mut value = Self { #body };
+                            let region = #cratename::extension::read_region(reader)?;
+                            let mut region: &[u8] = &region;
+                            #region
+                            value.#unknown = #cratename::extension::unknown(region);
+                            value
+                        }
+                    }
+                }
+                None => quote! {
+                    Self { #body }
+                },
+            }
+        }
+        Fields::Unnamed(_) if has_field_order(&input.fields)? => {
+            let mut body = TokenStream::new();
+            for (field_idx, field) in wire_order(&input.fields)? {
+                let field_idx = Index::from(field_idx);
+                if contains_skip(&field.attrs) {
+                    let default = skip_default(&field.attrs)?;
+                    body.extend(quote! { #field_idx: #default, });
+                } else {
+                    let deserialize = read_field(field, field_idx.index.to_string())?;
+                    body.extend(quote! { #field_idx: #deserialize, });
+                }
+            }
             quote! {
                 Self { #body }
             }
         }
         Fields::Unnamed(fields) => {
             let mut body = TokenStream::new();
-            for _ in 0..fields.unnamed.len() {
-                let delta = quote! {
-                    oasis_borsh::BorshDeserialize::deserialize(reader)?,
+            for (field_idx, field) in fields.unnamed.iter().enumerate() {
+                let delta = if contains_skip(&field.attrs) {
+                    let default = skip_default(&field.attrs)?;
+                    quote! {
+                        #default,
+                    }
+                } else {
+                    let deserialize = read_field(field, field_idx.to_string())?;
+                    quote! {
+                        #deserialize,
+                    }
                 };
                 body.extend(delta);
             }
@@ -45,26 +128,123 @@ pub fn struct_de(input: &ItemStruct) -> syn::Result<TokenStream> {
         }
     };
 
-    let generics = crate::util::add_de_constraints(input.generics.clone());
+    Ok(if memcpy_layout(input)? {
+        let reader_type = if slice {
+            quote! { &[u8] }
+        } else {
+            quote! { R }
+        };
+        quote! {
+            if cfg!(target_endian = "little") {
+                #cratename::memcpy::deserialize::<Self, #reader_type>(reader)?
+            } else {
+                #return_value
+            }
+        }
+    } else {
+        return_value
+    })
+}
+
+/// The statements decoding a struct from `reader` into `place`, field by field in wire order.
+/// Skipped fields are reset to their default, as `deserialize` would leave them.
+fn in_place_body(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    let ty = input.ident.to_string();
+    let mut body = TokenStream::new();
+    for (field_idx, field) in wire_order(&input.fields)? {
+        let (member, name) = match &field.ident {
+            Some(ident) => (quote! { #ident }, ident.to_string()),
+            None => {
+                let index = Index::from(field_idx);
+                (quote! { #index }, field_idx.to_string())
+            }
+        };
+        let place = quote! { place.#member };
+        if contains_skip(&field.attrs) {
+            let default = skip_default(&field.attrs)?;
+            body.extend(quote! { #place = #default; });
+        } else {
+            let segment = format!(".{}", name);
+            body.extend(deserialize_field_in_place(
+                field, &place, &ty, &segment, cratename,
+            )?);
+        }
+    }
+    Ok(body)
+}
+
+pub fn struct_de(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let init_method = contains_initialize_with(&input.attrs)?;
+    let return_value_slice = return_value(input, cratename, true)?;
+    let return_value = return_value(input, cratename, false)?;
+    // Types decoded by copying their memory, or with an extension region, are overwritten whole,
+    // which the default does.
+    let in_place = if memcpy_layout(input)? || extension_field(input)?.is_some() {
+        TokenStream::new()
+    } else {
+        let body = in_place_body(input, cratename)?;
+        let init = init_method
+            .as_ref()
+            .map(|method_ident| quote! { place.#method_ident(); });
+        quote! {
+            fn deserialize_in_place<R: #cratename::maybestd::io::Read>(reader: &mut R, place: &mut Self) -> core::result::Result<(), #cratename::maybestd::io::Error> {
+                let _depth = #cratename::de::enter()?;
+                #body
+                #init
+                Ok(())
+            }
+        }
+    };
+
+    let header = crate::struct_header::struct_header(input, cratename)?;
+    let generics = crate::util::de_generics(
+        &input.attrs,
+        &input.generics,
+        input.fields.iter(),
+        cratename,
+    )?;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     if let Some(method_ident) = init_method {
         Ok(quote! {
-            impl #impl_generics oasis_borsh::de::BorshDeserialize for #name #ty_generics #where_clause {
-                fn deserialize<R: std::io::Read>(reader: &mut R) -> std::result::Result<Self, std::io::Error> {
+            impl #impl_generics #cratename::de::BorshDeserialize for #name #ty_generics #where_clause {
+                fn deserialize<R: #cratename::maybestd::io::Read>(reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                    let _depth = #cratename::de::enter()?;
                     let mut return_value = #return_value;
                     return_value.#method_ident();
                     Ok(return_value)
                 }
+
+                fn deserialize_slice(reader: &mut &[u8]) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                    let _depth = #cratename::de::enter()?;
+                    let mut return_value = #return_value_slice;
+                    return_value.#method_ident();
+                    Ok(return_value)
+                }
+
+                #in_place
             }
+
+            #header
         })
     } else {
         Ok(quote! {
-            impl #impl_generics oasis_borsh::de::BorshDeserialize for #name #ty_generics #where_clause {
-                fn deserialize<R: std::io::Read>(reader: &mut R) -> std::result::Result<Self, std::io::Error> {
+            impl #impl_generics #cratename::de::BorshDeserialize for #name #ty_generics #where_clause {
+                fn deserialize<R: #cratename::maybestd::io::Read>(reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                    let _depth = #cratename::de::enter()?;
                     Ok(#return_value)
                 }
+
+                fn deserialize_slice(reader: &mut &[u8]) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                    let _depth = #cratename::de::enter()?;
+                    Ok(#return_value_slice)
+                }
+
+                #in_place
             }
+
+            #header
         })
     }
 }
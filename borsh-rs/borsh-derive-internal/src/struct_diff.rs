@@ -0,0 +1,75 @@
+use crate::attribute_helpers::{contains_skip, wire_order};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Index, ItemStruct, Path};
+
+pub fn struct_diff(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    // Bits are assigned in wire order, so deltas keep their layout when fields are reordered.
+    let accessors: Vec<TokenStream> = wire_order(&input.fields)?
+        .into_iter()
+        .filter(|(_, field)| !contains_skip(&field.attrs))
+        .map(|(field_idx, field)| match &field.ident {
+            Some(field_name) => quote! { #field_name },
+            None => {
+                let field_idx = Index {
+                    index: field_idx as u32,
+                    span: Span::call_site(),
+                };
+                quote! { #field_idx }
+            }
+        })
+        .collect();
+
+    let bitmap_len = accessors.len().div_ceil(8);
+    let mut diff_body = TokenStream::new();
+    let mut apply_body = TokenStream::new();
+    let mut known_bits = vec![0u8; bitmap_len];
+    for (bit, accessor) in accessors.iter().enumerate() {
+        let byte = bit / 8;
+        let mask = 1u8 << (bit % 8);
+        known_bits[byte] |= mask;
+        diff_body.extend(quote! {
+            if #cratename::diff::BorshDiff::diff_into(&old.#accessor, &new.#accessor, &mut fields)? {
+                bitmap[#byte] |= #mask;
+            }
+        });
+        apply_body.extend(quote! {
+            if bitmap[#byte] & #mask != 0 {
+                #cratename::diff::BorshDiff::apply_diff(&mut self.#accessor, reader)?;
+            }
+        });
+    }
+
+    let generics = crate::util::add_diff_constraints(input.generics.clone(), cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #cratename::diff::BorshDiff for #name #ty_generics #where_clause {
+            fn diff_into(old: &Self, new: &Self, out: &mut #cratename::maybestd::vec::Vec<u8>) -> core::result::Result<bool, #cratename::maybestd::io::Error> {
+                let mut bitmap = [0u8; #bitmap_len];
+                let mut fields = #cratename::maybestd::vec::Vec::new();
+                #diff_body
+                if bitmap.iter().all(|byte| *byte == 0) {
+                    return Ok(false);
+                }
+                out.extend_from_slice(&bitmap);
+                out.extend_from_slice(&fields);
+                Ok(true)
+            }
+
+            fn apply_diff<R: #cratename::maybestd::io::Read>(&mut self, reader: &mut R) -> core::result::Result<(), #cratename::maybestd::io::Error> {
+                let mut bitmap = [0u8; #bitmap_len];
+                reader.read_exact(&mut bitmap)?;
+                let known_bits: [u8; #bitmap_len] = [#(#known_bits),*];
+                if bitmap.iter().all(|byte| *byte == 0)
+                    || bitmap.iter().zip(known_bits.iter()).any(|(byte, known)| byte & !known != 0)
+                {
+                    return Err(#cratename::diff::invalid_bitmap());
+                }
+                #apply_body
+                Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,135 @@
+use crate::attribute_helpers::{
+    contains_header, contains_skip, deserialize_field, is_header_field, wire_order,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Error, Fields, ItemStruct, Path};
+
+/// The header type requested with `#[borsh_header(Name)]`, if any: a struct holding the leading
+/// fields marked `#[borsh(header)]`, which decodes from the start of an encoded `input`.
+pub fn struct_header(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    let header_name = match contains_header(&input.attrs)? {
+        Some(header_name) => header_name,
+        None => return Ok(TokenStream::new()),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "borsh_header does not support generic structs",
+        ));
+    }
+
+    // The header fields have to come first on the wire, or decoding them would need the rest.
+    let mut header_fields = Vec::new();
+    let mut in_header = true;
+    for (_, field) in wire_order(&input.fields)? {
+        if contains_skip(&field.attrs) {
+            if is_header_field(field)? {
+                return Err(Error::new(
+                    field.span(),
+                    "a field with borsh_skip cannot be part of the header",
+                ));
+            }
+            continue;
+        }
+        match (is_header_field(field)?, in_header) {
+            (true, true) => header_fields.push(field),
+            (true, false) => {
+                return Err(Error::new(
+                    field.span(),
+                    "header fields must precede all other fields on the wire",
+                ))
+            }
+            (false, _) => in_header = false,
+        }
+    }
+    if header_fields.is_empty() {
+        return Err(Error::new(
+            header_name.span(),
+            "borsh_header needs at least one field marked #[borsh(header)]",
+        ));
+    }
+
+    let vis = &input.vis;
+    let doc = format!(
+        " The leading fields of [`{}`], decoded without the rest of the payload.",
+        input.ident
+    );
+    let mut declarations = TokenStream::new();
+    let mut body = TokenStream::new();
+    let (declaration, return_value) = match &input.fields {
+        Fields::Named(_) => {
+            for field in header_fields {
+                let field_vis = &field.vis;
+                let field_name = field.ident.as_ref().unwrap();
+                let field_type = &field.ty;
+                let deserialize = deserialize_field(field, cratename)?;
+                declarations.extend(quote! { #field_vis #field_name: #field_type, });
+                body.extend(quote! { #field_name: #deserialize, });
+            }
+            (
+                quote! { #vis struct #header_name { #declarations } },
+                quote! { Self { #body } },
+            )
+        }
+        _ => {
+            for field in header_fields {
+                let field_vis = &field.vis;
+                let field_type = &field.ty;
+                let deserialize = deserialize_field(field, cratename)?;
+                declarations.extend(quote! { #field_vis #field_type, });
+                body.extend(quote! { #deserialize, });
+            }
+            (
+                quote! { #vis struct #header_name ( #declarations ); },
+                quote! { Self( #body ) },
+            )
+        }
+    };
+
+    Ok(quote! {
+        #[doc = #doc]
+        #declaration
+
+        impl #cratename::de::BorshDeserialize for #header_name {
+            fn deserialize<R: #cratename::maybestd::io::Read>(reader: &mut R) -> core::result::Result<Self, #cratename::maybestd::io::Error> {
+                Ok(#return_value)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cratename() -> Path {
+        syn::parse_quote!(oasis_borsh)
+    }
+
+    #[test]
+    fn header_errors() {
+        let interleaved: ItemStruct = syn::parse2(quote! {
+            #[borsh_header(AHeader)]
+            struct A {
+                #[borsh(header)]
+                x: u64,
+                y: u64,
+                #[borsh(header)]
+                z: u64,
+            }
+        })
+        .unwrap();
+        assert!(struct_header(&interleaved, &cratename()).is_err());
+
+        let empty: ItemStruct = syn::parse2(quote! {
+            #[borsh_header(AHeader)]
+            struct A {
+                x: u64,
+            }
+        })
+        .unwrap();
+        assert!(struct_header(&empty, &cratename()).is_err());
+    }
+}
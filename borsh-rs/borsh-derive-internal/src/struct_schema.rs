@@ -0,0 +1,94 @@
+use crate::attribute_helpers::{
+    contains_embed_schema, contains_skip, embedded_schema, idl_declaration, idl_fields,
+    reject_extension, schema_type, wire_order,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Fields, ItemStruct, Path};
+
+pub fn struct_schema(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
+    reject_extension(input, "BorshSchema")?;
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let generics = crate::util::add_schema_constraints(input.generics.clone(), cratename);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let declaration = crate::util::schema_declaration(&name_str, &input.generics, cratename);
+
+    let mut field_types = Vec::new();
+    let fields = match &input.fields {
+        Fields::Named(_) => {
+            let mut entries = TokenStream::new();
+            for (_, field) in wire_order(&input.fields)? {
+                if contains_skip(&field.attrs) {
+                    continue;
+                }
+                let field_name = field.ident.as_ref().unwrap().to_string();
+                let field_type = schema_type(field, cratename)?;
+                entries.extend(quote! {
+                    (#field_name.to_string(), <#field_type as #cratename::BorshSchema>::declaration()),
+                });
+                field_types.push(field_type);
+            }
+            quote! {
+                #cratename::schema::Fields::NamedFields(vec![#entries])
+            }
+        }
+        Fields::Unnamed(_) => {
+            let mut entries = TokenStream::new();
+            for (_, field) in wire_order(&input.fields)? {
+                if contains_skip(&field.attrs) {
+                    continue;
+                }
+                let field_type = schema_type(field, cratename)?;
+                entries.extend(quote! {
+                    <#field_type as #cratename::BorshSchema>::declaration(),
+                });
+                field_types.push(field_type);
+            }
+            quote! {
+                #cratename::schema::Fields::UnnamedFields(vec![#entries])
+            }
+        }
+        Fields::Unit => quote! {
+            #cratename::schema::Fields::Empty
+        },
+    };
+
+    let embedded = if contains_embed_schema(&input.attrs)? {
+        let idl = format!(
+            "{}{}",
+            idl_declaration("struct", name, &input.generics),
+            idl_fields(&input.fields)?
+        );
+        embedded_schema(name, &input.generics, &idl)
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        #embedded
+
+        impl #impl_generics #cratename::BorshSchema for #name #ty_generics #where_clause {
+            fn declaration() -> #cratename::schema::Declaration {
+                #declaration
+            }
+
+            fn add_definitions_recursively(
+                definitions: &mut #cratename::maybestd::collections::HashMap<
+                    #cratename::schema::Declaration,
+                    #cratename::schema::Definition
+                >,
+            ) {
+                let fields = #fields;
+                let definition = #cratename::schema::Definition::Struct { fields };
+                if <Self as #cratename::BorshSchema>::add_definition(
+                    <Self as #cratename::BorshSchema>::declaration(),
+                    definition,
+                    definitions,
+                ) {
+                    #(<#field_types as #cratename::BorshSchema>::add_definitions_recursively(definitions);)*
+                }
+            }
+        }
+    })
+}
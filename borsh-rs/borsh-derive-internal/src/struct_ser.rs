@@ -1,49 +1,123 @@
-use crate::attribute_helpers::contains_skip;
+use crate::attribute_helpers::{
+    contains_skip, extension_field, is_extension_field, memcpy_assertions, memcpy_layout,
+    serialize_field, serialized_size_field, size_hint_field, wire_order,
+};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::{Fields, Index, ItemStruct};
+use syn::{Fields, Index, ItemStruct, Path};
 
-pub fn struct_ser(input: &ItemStruct) -> syn::Result<TokenStream> {
+pub fn struct_ser(input: &ItemStruct, cratename: &Path) -> syn::Result<TokenStream> {
     let name = &input.ident;
     let mut body = TokenStream::new();
+    let mut size = quote! { 0 };
+    let mut hint = quote! { 0usize };
+    // Checked up front, as it also rejects `#[borsh_extension]` on tuple structs.
+    let extension = extension_field(input)?;
     match &input.fields {
-        Fields::Named(fields) => {
-            for field in &fields.named {
-                if contains_skip(&field.attrs) {
+        Fields::Named(_) => {
+            let mut region = TokenStream::new();
+            for (_, field) in wire_order(&input.fields)? {
+                let field_name = field.ident.as_ref().unwrap();
+                if contains_skip(&field.attrs) || extension.as_ref() == Some(field_name) {
                     continue;
                 }
-                let field_name = field.ident.as_ref().unwrap();
-                let delta = quote! {
-                    oasis_borsh::BorshSerialize::serialize(&self.#field_name, writer)?;
+                let value = quote! { &self.#field_name };
+                let serialize = serialize_field(field, value.clone(), cratename)?;
+                if is_extension_field(field)? {
+                    region.extend(serialize);
+                } else {
+                    body.extend(serialize);
+                }
+                let field_size = serialized_size_field(field, value.clone(), cratename)?;
+                size.extend(quote! { + #field_size });
+                let field_hint = size_hint_field(field, value, cratename)?;
+                hint.extend(quote! { .saturating_add(#field_hint) });
+            }
+            // The extension fields are encoded into a buffer first, as the region starts with its
+            // length, and the unknown bytes kept from decoding follow them.
+            if let Some(unknown) = extension {
+                let known = if region.is_empty() {
+                    quote! { &[] }
+                } else {
+                    body.extend(quote! {
+                        let mut region = #cratename::maybestd::vec::Vec::new();
+                        {
+                            let writer = &mut region;
+                            #region
+                        }
+                    });
+                    quote! { &region }
                 };
-                body.extend(delta);
+                let unknown = quote! { &self.#unknown };
+                body.extend(quote! {
+                    #cratename::extension::write_region(writer, #known, #unknown)?;
+                });
+                size.extend(quote! { + #cratename::BorshSerialize::serialized_size(#unknown) });
+                hint.extend(quote! {
+                    .saturating_add(#cratename::BorshSerialize::size_hint(#unknown))
+                });
             }
         }
-        Fields::Unnamed(fields) => {
-            for field_idx in 0..fields.unnamed.len() {
+        Fields::Unnamed(_) => {
+            for (field_idx, field) in wire_order(&input.fields)? {
+                if contains_skip(&field.attrs) {
+                    continue;
+                }
                 let field_idx = Index {
                     index: field_idx as u32,
                     span: Span::call_site(),
                 };
-                let delta = quote! {
-                    oasis_borsh::BorshSerialize::serialize(&self.#field_idx, writer)?;
-                };
-                body.extend(delta);
+                let value = quote! { &self.#field_idx };
+                body.extend(serialize_field(field, value.clone(), cratename)?);
+                let field_size = serialized_size_field(field, value.clone(), cratename)?;
+                size.extend(quote! { + #field_size });
+                let field_hint = size_hint_field(field, value, cratename)?;
+                hint.extend(quote! { .saturating_add(#field_hint) });
             }
         }
         Fields::Unit => {}
     }
 
-    let generics = crate::util::add_ser_constraints(input.generics.clone());
+    // On little-endian targets a `#[borsh_memcpy]` struct's bytes are its encoding; elsewhere the
+    // fields are written one by one as usual.
+    let memcpy = memcpy_layout(input)?;
+    let (body, assertions) = if memcpy {
+        let body = quote! {
+            if cfg!(target_endian = "little") {
+                return #cratename::memcpy::serialize(self, writer);
+            }
+            #body
+        };
+        (body, memcpy_assertions(input, cratename))
+    } else {
+        (body, TokenStream::new())
+    };
+
+    let generics = crate::util::ser_generics(
+        &input.attrs,
+        &input.generics,
+        input.fields.iter(),
+        cratename,
+    )?;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     Ok(quote! {
-        impl #impl_generics oasis_borsh::ser::BorshSerialize for #name #ty_generics #where_clause {
-            fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), std::io::Error> {
+        impl #impl_generics #cratename::ser::BorshSerialize for #name #ty_generics #where_clause {
+            fn serialize<W: #cratename::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), #cratename::maybestd::io::Error> {
                 #body
                 Ok(())
             }
+
+            fn serialized_size(&self) -> usize {
+                #size
+            }
+
+            fn size_hint(&self) -> usize {
+                #hint
+            }
         }
+
+        #assertions
     })
 }
 
@@ -53,6 +127,10 @@ pub fn struct_ser(input: &ItemStruct) -> syn::Result<TokenStream> {
 mod tests {
     use super::*;
 
+    fn cratename() -> Path {
+        syn::parse_quote!(oasis_borsh)
+    }
+
     fn assert_eq(expected: TokenStream, actual: TokenStream) {
         assert_eq!(expected.to_string(), actual.to_string())
     }
@@ -66,14 +144,22 @@ mod tests {
             }
         }).unwrap();
 
-        let actual = struct_ser(&item_struct).unwrap();
+        let actual = struct_ser(&item_struct, &cratename()).unwrap();
         let expected = quote!{
             impl oasis_borsh::ser::BorshSerialize for A {
-                fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), std::io::Error> {
+                fn serialize<W: oasis_borsh::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), oasis_borsh::maybestd::io::Error> {
                     oasis_borsh::BorshSerialize::serialize(&self.x, writer)?;
                     oasis_borsh::BorshSerialize::serialize(&self.y, writer)?;
                     Ok(())
                 }
+
+                fn serialized_size(&self) -> usize {
+                    0 + oasis_borsh::BorshSerialize::serialized_size(&self.x) + oasis_borsh::BorshSerialize::serialized_size(&self.y)
+                }
+
+                fn size_hint(&self) -> usize {
+                    0usize.saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.x)).saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.y))
+                }
             }
         };
         assert_eq(expected, actual);
@@ -88,16 +174,213 @@ mod tests {
             }
         }).unwrap();
 
-        let actual = struct_ser(&item_struct).unwrap();
+        let actual = struct_ser(&item_struct, &cratename()).unwrap();
         let expected = quote!{
             impl<K: oasis_borsh::ser::BorshSerialize, V: oasis_borsh::ser::BorshSerialize> oasis_borsh::ser::BorshSerialize for A<K, V> {
-                fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::result::Result<(), std::io::Error> {
+                fn serialize<W: oasis_borsh::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), oasis_borsh::maybestd::io::Error> {
                     oasis_borsh::BorshSerialize::serialize(&self.x, writer)?;
                     oasis_borsh::BorshSerialize::serialize(&self.y, writer)?;
                     Ok(())
                 }
+
+                fn serialized_size(&self) -> usize {
+                    0 + oasis_borsh::BorshSerialize::serialized_size(&self.x) + oasis_borsh::BorshSerialize::serialized_size(&self.y)
+                }
+
+                fn size_hint(&self) -> usize {
+                    0usize.saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.x)).saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.y))
+                }
+            }
+        };
+        assert_eq(expected, actual);
+    }
+
+    #[test]
+    fn field_order() {
+        let item_struct: ItemStruct = syn::parse2(quote!{
+            struct A {
+                #[borsh(order = 1)]
+                x: u64,
+                #[borsh(order = 0)]
+                y: String,
+            }
+        }).unwrap();
+
+        let actual = struct_ser(&item_struct, &cratename()).unwrap();
+        let expected = quote!{
+            impl oasis_borsh::ser::BorshSerialize for A {
+                fn serialize<W: oasis_borsh::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), oasis_borsh::maybestd::io::Error> {
+                    oasis_borsh::BorshSerialize::serialize(&self.y, writer)?;
+                    oasis_borsh::BorshSerialize::serialize(&self.x, writer)?;
+                    Ok(())
+                }
+
+                fn serialized_size(&self) -> usize {
+                    0 + oasis_borsh::BorshSerialize::serialized_size(&self.y) + oasis_borsh::BorshSerialize::serialized_size(&self.x)
+                }
+
+                fn size_hint(&self) -> usize {
+                    0usize.saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.y)).saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.x))
+                }
+            }
+        };
+        assert_eq(expected, actual);
+    }
+
+    #[test]
+    fn field_order_errors() {
+        let duplicate: ItemStruct = syn::parse2(quote!{
+            struct A {
+                #[borsh(order = 0)]
+                x: u64,
+                #[borsh(order = 0)]
+                y: String,
+            }
+        }).unwrap();
+        assert!(struct_ser(&duplicate, &cratename()).is_err());
+
+        let partial: ItemStruct = syn::parse2(quote!{
+            struct A {
+                #[borsh(order = 0)]
+                x: u64,
+                y: String,
+            }
+        }).unwrap();
+        assert!(struct_ser(&partial, &cratename()).is_err());
+    }
+
+    #[test]
+    fn length_prefix() {
+        let item_struct: ItemStruct = syn::parse2(quote!{
+            struct A {
+                #[borsh(len = "u8")]
+                x: Vec<u64>,
+                y: String,
+            }
+        }).unwrap();
+
+        let actual = struct_ser(&item_struct, &cratename()).unwrap();
+        let expected = quote!{
+            impl oasis_borsh::ser::BorshSerialize for A {
+                fn serialize<W: oasis_borsh::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), oasis_borsh::maybestd::io::Error> {
+                    oasis_borsh::prefix::serialize::<u8, _, _>(&self.x, writer)?;
+                    oasis_borsh::BorshSerialize::serialize(&self.y, writer)?;
+                    Ok(())
+                }
+
+                fn serialized_size(&self) -> usize {
+                    0 + oasis_borsh::prefix::serialized_size::<u8, _>(&self.x) + oasis_borsh::BorshSerialize::serialized_size(&self.y)
+                }
+
+                fn size_hint(&self) -> usize {
+                    0usize.saturating_add(oasis_borsh::prefix::size_hint::<u8, _>(&self.x)).saturating_add(oasis_borsh::BorshSerialize::size_hint(&self.y))
+                }
+            }
+        };
+        assert_eq(expected, actual);
+
+        let invalid: ItemStruct = syn::parse2(quote!{
+            struct A {
+                #[borsh(len = "u24")]
+                x: Vec<u64>,
+            }
+        }).unwrap();
+        assert!(struct_ser(&invalid, &cratename()).is_err());
+    }
+
+    #[test]
+    fn custom_crate_path() {
+        let item_struct: ItemStruct = syn::parse2(quote!{
+            struct A<T> {
+                #[borsh(len = "u16")]
+                x: Vec<T>,
+            }
+        }).unwrap();
+
+        let actual = struct_ser(&item_struct, &syn::parse_quote!(framework::borsh)).unwrap();
+        let expected = quote!{
+            impl<T: framework::borsh::ser::BorshSerialize> framework::borsh::ser::BorshSerialize for A<T> {
+                fn serialize<W: framework::borsh::maybestd::io::Write>(&self, writer: &mut W) -> core::result::Result<(), framework::borsh::maybestd::io::Error> {
+                    framework::borsh::prefix::serialize::<u16, _, _>(&self.x, writer)?;
+                    Ok(())
+                }
+
+                fn serialized_size(&self) -> usize {
+                    0 + framework::borsh::prefix::serialized_size::<u16, _>(&self.x)
+                }
+
+                fn size_hint(&self) -> usize {
+                    0usize.saturating_add(framework::borsh::prefix::size_hint::<u16, _>(&self.x))
+                }
             }
         };
         assert_eq(expected, actual);
     }
+
+    #[test]
+    fn memcpy_errors() {
+        let not_repr_c: ItemStruct = syn::parse2(quote!{
+            #[borsh_memcpy]
+            struct A {
+                x: u64,
+            }
+        }).unwrap();
+        assert!(struct_ser(&not_repr_c, &cratename()).is_err());
+
+        let packed: ItemStruct = syn::parse2(quote!{
+            #[borsh_memcpy]
+            #[repr(C, packed)]
+            struct A {
+                x: u64,
+            }
+        }).unwrap();
+        assert!(struct_ser(&packed, &cratename()).is_err());
+
+        let skipped: ItemStruct = syn::parse2(quote!{
+            #[borsh_memcpy]
+            #[repr(C)]
+            struct A {
+                x: u64,
+                #[borsh_skip]
+                y: u64,
+            }
+        }).unwrap();
+        assert!(struct_ser(&skipped, &cratename()).is_err());
+    }
+
+    #[test]
+    fn extension_errors() {
+        let missing: ItemStruct = syn::parse2(quote!{
+            struct A {
+                x: u64,
+                #[borsh(extension)]
+                y: u64,
+            }
+        }).unwrap();
+        assert!(struct_ser(&missing, &cratename()).is_err());
+
+        let unknown_field: ItemStruct = syn::parse2(quote!{
+            #[borsh_extension(rest)]
+            struct A {
+                x: u64,
+            }
+        }).unwrap();
+        assert!(struct_ser(&unknown_field, &cratename()).is_err());
+
+        let extension_itself: ItemStruct = syn::parse2(quote!{
+            #[borsh_extension(rest)]
+            struct A {
+                x: u64,
+                #[borsh(extension)]
+                rest: Extension,
+            }
+        }).unwrap();
+        assert!(struct_ser(&extension_itself, &cratename()).is_err());
+
+        let tuple: ItemStruct = syn::parse2(quote!{
+            #[borsh_extension(rest)]
+            struct A(u64, Extension);
+        }).unwrap();
+        assert!(struct_ser(&tuple, &cratename()).is_err());
+    }
 }
@@ -1,6 +1,6 @@
 use proc_macro2::TokenStream;
-use syn::ItemUnion;
+use syn::{ItemUnion, Path};
 
-pub fn union_ser(_input: &ItemUnion) -> syn::Result<TokenStream> {
+pub fn union_ser(_input: &ItemUnion, _cratename: &Path) -> syn::Result<TokenStream> {
     unimplemented!()
 }
@@ -1,15 +1,194 @@
-use syn::{Generics, parse_quote};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::{parse_quote, Attribute, Field, Generics, Ident, Path};
 
-pub fn add_ser_constraints(mut generics: Generics) -> Generics {
+use crate::attribute_helpers::{
+    contains_ctx, contains_delegate, contains_seed, contains_skip, field_de_bound, field_ser_bound,
+    is_ctx_field, is_seeded,
+};
+
+pub fn add_ser_constraints(mut generics: Generics, cratename: &Path) -> Generics {
+    for type_param in generics.type_params_mut() {
+        type_param
+            .bounds
+            .push(parse_quote!(#cratename::ser::BorshSerialize));
+    }
+    generics
+}
+
+pub fn add_de_constraints(mut generics: Generics, cratename: &Path) -> Generics {
+    for type_param in generics.type_params_mut() {
+        type_param
+            .bounds
+            .push(parse_quote!(#cratename::de::BorshDeserialize));
+    }
+    generics
+}
+
+/// Generics of the `BorshSerialize` impl of an item with the given attributes and fields.
+pub fn ser_generics<'a>(
+    attrs: &[Attribute],
+    generics: &Generics,
+    fields: impl Iterator<Item = &'a Field>,
+    cratename: &Path,
+) -> syn::Result<Generics> {
+    if contains_delegate(attrs) {
+        add_field_constraints(generics.clone(), fields, |field| {
+            field_ser_bound(field, cratename)
+        })
+    } else {
+        Ok(add_ser_constraints(generics.clone(), cratename))
+    }
+}
+
+/// Generics of the `BorshDeserialize` impl of an item with the given attributes and fields.
+pub fn de_generics<'a>(
+    attrs: &[Attribute],
+    generics: &Generics,
+    fields: impl Iterator<Item = &'a Field>,
+    cratename: &Path,
+) -> syn::Result<Generics> {
+    if contains_delegate(attrs) {
+        add_field_constraints(generics.clone(), fields, |field| {
+            field_de_bound(field, cratename)
+        })
+    } else {
+        Ok(add_de_constraints(generics.clone(), cratename))
+    }
+}
+
+/// Generics of the `BorshDeserializeSeed` impl of an item with the given attributes and fields,
+/// with the seed type it is implemented for: the one named by `#[borsh_seed(Type)]`, or else a
+/// new type parameter, so that types which only pass the seed on accept any seed.
+///
+/// The new parameter is not one of the item's, so type generics come from the item's generics.
+pub fn seed_generics<'a>(
+    attrs: &[Attribute],
+    generics: &Generics,
+    fields: impl Iterator<Item = &'a Field>,
+    cratename: &Path,
+) -> syn::Result<(Generics, TokenStream)> {
+    let mut generics = generics.clone();
+    let seed = match contains_seed(attrs)? {
+        Some(seed) => quote!(#seed),
+        None => add_state_param(&mut generics, quote!(__BorshSeed)),
+    };
+    let generics = add_field_constraints(generics, fields, |field| {
+        if is_seeded(field)? {
+            Ok(quote!(#cratename::de::BorshDeserializeSeed<#seed>))
+        } else {
+            field_de_bound(field, cratename)
+        }
+    })?;
+    Ok((generics, seed))
+}
+
+/// Generics of the `BorshSerializeCtx` impl of an item with the given attributes and fields, with
+/// the context type it is implemented for, chosen like the seed in [`seed_generics`] but named by
+/// `#[borsh_ctx(Type)]`.
+pub fn ctx_generics<'a>(
+    attrs: &[Attribute],
+    generics: &Generics,
+    fields: impl Iterator<Item = &'a Field>,
+    cratename: &Path,
+) -> syn::Result<(Generics, TokenStream)> {
+    let mut generics = generics.clone();
+    let ctx = match contains_ctx(attrs)? {
+        Some(ctx) => quote!(#ctx),
+        None => add_state_param(&mut generics, quote!(__BorshCtx)),
+    };
+    let generics = add_field_constraints(generics, fields, |field| {
+        if is_ctx_field(field)? {
+            Ok(quote!(#cratename::ser::BorshSerializeCtx<#ctx>))
+        } else {
+            field_ser_bound(field, cratename)
+        }
+    })?;
+    Ok((generics, ctx))
+}
+
+/// Add the unsized type parameter `param` to `generics`, returning it.
+fn add_state_param(generics: &mut Generics, param: TokenStream) -> TokenStream {
+    generics.params.push(parse_quote!(#param: ?Sized));
+    param
+}
+
+/// Bounds the type of each encoded field that mentions a type parameter, in itself or in its
+/// bound, by the trait `bound` returns for it, instead of bounding the type parameters themselves.
+fn add_field_constraints<'a>(
+    mut generics: Generics,
+    fields: impl Iterator<Item = &'a Field>,
+    bound: impl Fn(&Field) -> syn::Result<TokenStream>,
+) -> syn::Result<Generics> {
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let mut bounded = Vec::new();
+    for field in fields.filter(|field| !contains_skip(&field.attrs)) {
+        let ty = &field.ty;
+        let bound = bound(field)?;
+        let key = quote!(#ty: #bound).to_string();
+        if mentions_any(quote!(#ty: #bound), &params) && !bounded.contains(&key) {
+            bounded.push(key);
+            generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#ty: #bound));
+        }
+    }
+    Ok(generics)
+}
+
+/// Whether `tokens` contain any of the identifiers `idents`.
+fn mentions_any(tokens: TokenStream, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+pub fn add_schema_constraints(mut generics: Generics, cratename: &Path) -> Generics {
+    for type_param in generics.type_params_mut() {
+        type_param
+            .bounds
+            .push(parse_quote!(#cratename::BorshSchema));
+    }
+    generics
+}
+
+/// Expression producing the schema declaration of a type named `name` with the given generics,
+/// e.g. `A<u64, string>`.
+pub fn schema_declaration(name: &str, generics: &Generics, cratename: &Path) -> TokenStream {
+    let params = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        quote! { #name.to_string() }
+    } else {
+        quote! {
+            let params = vec![#(<#params as #cratename::BorshSchema>::declaration()),*];
+            format!("{}<{}>", #name, params.join(", "))
+        }
+    }
+}
+
+pub fn add_diff_constraints(mut generics: Generics, cratename: &Path) -> Generics {
     for type_param in generics.type_params_mut() {
-        type_param.bounds.push(parse_quote!(oasis_borsh::ser::BorshSerialize));
+        type_param
+            .bounds
+            .push(parse_quote!(#cratename::diff::BorshDiff));
     }
     generics
 }
 
-pub fn add_de_constraints(mut generics: Generics) -> Generics {
+pub fn add_fixed_size_constraints(mut generics: Generics, cratename: &Path) -> Generics {
     for type_param in generics.type_params_mut() {
-        type_param.bounds.push(parse_quote!(oasis_borsh::de::BorshDeserialize));
+        type_param
+            .bounds
+            .push(parse_quote!(#cratename::fixed_size::BorshFixedSize));
     }
     generics
 }
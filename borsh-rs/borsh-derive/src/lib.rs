@@ -2,43 +2,196 @@ extern crate proc_macro;
 
 use oasis_borsh_derive_internal::*;
 use proc_macro::TokenStream;
-use quote::{quote, format_ident};
-use syn::{parse_macro_input, Ident, ItemEnum, ItemStruct, ItemUnion, LitInt, Token};
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Ident, ItemEnum, ItemStruct, ItemUnion, LitInt, Path, Token,
+};
 
-#[proc_macro_derive(BorshSerialize, attributes(borsh_skip))]
+/// Path the generated code names the borsh crate by.
+fn cratename() -> Path {
+    parse_quote!(oasis_borsh)
+}
+
+/// The impls generated for `input`, rewritten to use `dyn` readers and writers with the `dyn-io`
+/// feature, and under its `#[borsh(cfg(...))]` if it has one; or the error.
+fn expand(input: &TokenStream, res: syn::Result<proc_macro2::TokenStream>) -> TokenStream {
+    let res = res.and_then(|res| {
+        let res = if cfg!(feature = "dyn-io") {
+            dyn_io(res)?
+        } else {
+            res
+        };
+        let input = syn::parse::<syn::DeriveInput>(input.clone())?;
+        attribute_helpers::apply_cfg(&input.attrs, res)
+    });
+    TokenStream::from(match res {
+        Ok(res) => res,
+        Err(err) => err.to_compile_error(),
+    })
+}
+
+#[proc_macro_derive(
+    BorshSerialize,
+    attributes(
+        borsh,
+        borsh_skip,
+        borsh_wire_tag,
+        borsh_memcpy,
+        borsh_delegate,
+        borsh_extension
+    )
+)]
 pub fn borsh_serialize(input: TokenStream) -> TokenStream {
     let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
-        struct_ser(&input)
+        struct_ser(&input, &cratename())
     } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
-        enum_ser(&input)
+        enum_ser(&input, &cratename())
     } else if let Ok(input) = syn::parse::<ItemUnion>(input.clone()) {
-        union_ser(&input)
+        union_ser(&input, &cratename())
     } else {
         // Derive macros can only be defined on structs, enums, and unions.
         unreachable!()
     };
-    TokenStream::from(match res {
-        Ok(res) => res,
-        Err(err) => err.to_compile_error(),
-    })
+    expand(&input, res)
+}
+
+#[proc_macro_derive(BorshSerializeCtx, attributes(borsh, borsh_skip, borsh_ctx))]
+pub fn borsh_serialize_ctx(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_ctx_ser(&input, &cratename())
+    } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
+        enum_ctx_ser(&input, &cratename())
+    } else if syn::parse::<ItemUnion>(input.clone()).is_ok() {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh serialization with a context does not support unions.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
 }
 
-#[proc_macro_derive(BorshDeserialize, attributes(borsh_skip, borsh_init))]
+#[proc_macro_derive(
+    BorshDeserialize,
+    attributes(
+        borsh,
+        borsh_skip,
+        borsh_init,
+        borsh_header,
+        borsh_memcpy,
+        borsh_delegate,
+        borsh_wire_tag,
+        borsh_extension
+    )
+)]
 pub fn borsh_deserialize(input: TokenStream) -> TokenStream {
     let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
-        struct_de(&input)
+        struct_de(&input, &cratename())
     } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
-        enum_de(&input)
+        enum_de(&input, &cratename())
     } else if let Ok(input) = syn::parse::<ItemUnion>(input.clone()) {
-        union_de(&input)
+        union_de(&input, &cratename())
     } else {
         // Derive macros can only be defined on structs, enums, and unions.
         unreachable!()
     };
-    TokenStream::from(match res {
-        Ok(res) => res,
-        Err(err) => err.to_compile_error(),
-    })
+    expand(&input, res)
+}
+
+#[proc_macro_derive(
+    BorshDeserializeSeed,
+    attributes(borsh, borsh_skip, borsh_init, borsh_seed)
+)]
+pub fn borsh_deserialize_seed(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_seed_de(&input, &cratename())
+    } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
+        enum_seed_de(&input, &cratename())
+    } else if syn::parse::<ItemUnion>(input.clone()).is_ok() {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh seeded deserialization does not support unions.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
+}
+
+#[proc_macro_derive(BorshSchema, attributes(borsh, borsh_skip))]
+pub fn borsh_schema(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_schema(&input, &cratename())
+    } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
+        enum_schema(&input, &cratename())
+    } else if syn::parse::<ItemUnion>(input.clone()).is_ok() {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh schema does not support unions yet.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
+}
+
+#[proc_macro_derive(BorshDiff, attributes(borsh, borsh_skip))]
+pub fn borsh_diff(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_diff(&input, &cratename())
+    } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
+        enum_diff(&input, &cratename())
+    } else if syn::parse::<ItemUnion>(input.clone()).is_ok() {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh diff does not support unions yet.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
+}
+
+#[proc_macro_derive(BorshFixedSize, attributes(borsh, borsh_skip))]
+pub fn borsh_fixed_size(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_fixed_size(&input, &cratename())
+    } else if let Ok(input) = syn::parse::<ItemEnum>(input.clone()) {
+        enum_fixed_size(&input, &cratename())
+    } else if syn::parse::<ItemUnion>(input.clone()).is_ok() {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh fixed size does not support unions.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
+}
+
+#[proc_macro_derive(BorshColumnar, attributes(borsh, borsh_skip))]
+pub fn borsh_columnar(input: TokenStream) -> TokenStream {
+    let res = if let Ok(input) = syn::parse::<ItemStruct>(input.clone()) {
+        struct_columnar(&input, &cratename())
+    } else if syn::parse::<ItemEnum>(input.clone()).is_ok()
+        || syn::parse::<ItemUnion>(input.clone()).is_ok()
+    {
+        Err(syn::Error::new(
+            Span::call_site(),
+            "Borsh columnar encoding only supports structs.",
+        ))
+    } else {
+        // Derive macros can only be defined on structs, enums, and unions.
+        unreachable!()
+    };
+    expand(&input, res)
 }
 
 struct SeqMacroSpec {
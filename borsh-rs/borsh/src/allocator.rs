@@ -0,0 +1,128 @@
+//! Vectors and boxes in custom allocators, with the nightly `allocator_api`.
+//!
+//! With the `allocator-api` feature, `Vec<T, A>` and `Box<T, A>` serialize like `Vec<T>` and
+//! `Box<T>` for any allocator `A`, and [`BorshDeserializeIn`] decodes them into an allocator the
+//! caller passes in, such as an arena or a bump allocator that the whole decoded message is freed
+//! with at once:
+//!
+//! ```
+//! #![feature(allocator_api)]
+//! use std::alloc::Global;
+//!
+//! use oasis_borsh::allocator::BorshDeserializeIn;
+//! use oasis_borsh::BorshSerialize;
+//!
+//! let bytes = vec![1u64, 2, 3].try_to_vec().unwrap();
+//! let decoded = Vec::<u64, Global>::try_from_slice_in(&bytes, &Global).unwrap();
+//! assert_eq!(decoded, [1, 2, 3]);
+//! ```
+//!
+//! Elements are decoded with [`BorshDeserialize`], so only the vector's own buffer, or the
+//! boxed value, is allocated in the allocator passed in. Allocation failures are reported as
+//! [`crate::Error::AllocationFailed`] rather than aborting, as for vectors in the global
+//! allocator.
+use core::alloc::Allocator;
+use core::mem::size_of;
+
+use crate::de::{check_cancelled, check_len, hint, in_element, read_len, LenKind};
+use crate::maybestd::boxed::Box;
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A data-structure that can be deserialized into memory from an allocator of type `A`.
+pub trait BorshDeserializeIn<A: Allocator>: Sized {
+    /// Deserialize a value from `reader`, allocating it with `alloc`.
+    fn deserialize_in<R: Read>(reader: &mut R, alloc: &A) -> Result<Self, Error>;
+
+    /// Deserialize a value from all of `v`, allocating it with `alloc`, like
+    /// [`try_from_slice`](BorshDeserialize::try_from_slice).
+    fn try_from_slice_in(v: &[u8], alloc: &A) -> Result<Self, Error> {
+        let mut buf = v;
+        let result = Self::deserialize_in(&mut buf, alloc)?;
+        if !buf.is_empty() {
+            return Err(crate::Error::TrailingBytes.into());
+        }
+        Ok(result)
+    }
+}
+
+impl<T: BorshSerialize, A: Allocator> BorshSerialize for Vec<T, A> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.as_slice().serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.as_slice().serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        self.as_slice().size_hint()
+    }
+}
+
+impl<T: BorshSerialize + ?Sized, A: Allocator> BorshSerialize for Box<T, A> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (**self).serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+}
+
+impl<T: BorshDeserialize, A: Allocator + Clone> BorshDeserializeIn<A> for Vec<T, A> {
+    fn deserialize_in<R: Read>(reader: &mut R, alloc: &A) -> Result<Self, Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        let mut result = Vec::new_in(alloc.clone());
+        if size_of::<T>() == 0 {
+            // As for `Vec<T>`: nothing is allocated, but each element is still decoded.
+            hint::exact::<T>(len);
+            for i in 0..len as usize {
+                check_cancelled(i)?;
+                result.push(in_element(T::deserialize(reader), i)?);
+            }
+            return Ok(result);
+        }
+        try_reserve(&mut result, hint::cautious::<T>(len))?;
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let element = in_element(T::deserialize(reader), i)?;
+            if result.len() == result.capacity() {
+                try_reserve(&mut result, 1)?;
+            }
+            result.push(element);
+        }
+        Ok(result)
+    }
+}
+
+impl<T: BorshDeserialize, A: Allocator + Clone> BorshDeserializeIn<A> for Box<T, A> {
+    fn deserialize_in<R: Read>(reader: &mut R, alloc: &A) -> Result<Self, Error> {
+        let value = T::deserialize(reader)?;
+        Box::try_new_in(value, alloc.clone()).map_err(|_| {
+            crate::Error::AllocationFailed {
+                bytes: size_of::<T>(),
+            }
+            .into()
+        })
+    }
+}
+
+impl<T: BorshDeserialize, A: Allocator + Clone> BorshDeserializeIn<A> for Box<[T], A> {
+    fn deserialize_in<R: Read>(reader: &mut R, alloc: &A) -> Result<Self, Error> {
+        Ok(Vec::<T, A>::deserialize_in(reader, alloc)?.into_boxed_slice())
+    }
+}
+
+/// `vec.try_reserve(additional)`, failing with [`crate::Error::AllocationFailed`].
+fn try_reserve<T, A: Allocator>(vec: &mut Vec<T, A>, additional: usize) -> Result<(), Error> {
+    hint::charge_growth::<T>(vec.len(), vec.capacity(), additional)?;
+    vec.try_reserve(additional)
+        .map_err(|err| hint::allocation_failed::<T>(err, additional))
+}
@@ -0,0 +1,210 @@
+//! Floats with exactly one encoding per value, for fields of hashed or signed payloads.
+//!
+//! Borsh already rejects NaN, but two more cases make equal numbers encode differently or make
+//! results differ between platforms: `-0.0` equals `0.0` yet has its own bits, and subnormal
+//! numbers are flushed to zero by some hardware and compilers. A [`CanonicalF32`] or
+//! [`CanonicalF64`] holds neither, so its encoding is a function of its value:
+//!
+//! * [`new`](CanonicalF64::new) rejects NaN, `-0.0` and subnormals;
+//! * [`normalized`](CanonicalF64::normalized) turns `-0.0` and subnormals into `0.0` and only
+//!   rejects NaN.
+//!
+//! Decoding rejects `-0.0` and subnormals with `ErrorKind::InvalidData`, and NaN as for any float,
+//! rather than normalizing them, so that no two payloads decode to the same value. Both types are
+//! encoded like the float they wrap, and, having no NaN, are `Eq`, `Ord` and `Hash`.
+//!
+//! ```
+//! use oasis_borsh::canonical_float::CanonicalF64;
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! let zero = CanonicalF64::normalized(-0.0).unwrap();
+//! assert_eq!(zero.try_to_vec().unwrap(), 0.0f64.try_to_vec().unwrap());
+//! assert!(CanonicalF64::new(-0.0).is_err());
+//!
+//! let negative_zero = (-0.0f64).try_to_vec().unwrap();
+//! assert!(CanonicalF64::try_from_slice(&negative_zero).is_err());
+//! ```
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use crate::de::or_rewind;
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::fixed_size::BorshFixedSize;
+use crate::maybestd::io::{Error, ErrorKind, Read, Write};
+use crate::maybestd::string::ToString;
+use crate::maybestd::vec::Vec;
+#[cfg(feature = "std")]
+use crate::schema::{Declaration, Definition};
+#[cfg(feature = "std")]
+use crate::BorshSchema;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Why a float is not canonical.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NonCanonicalFloat {
+    NaN,
+    NegativeZero,
+    Subnormal,
+}
+
+impl fmt::Display for NonCanonicalFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            NonCanonicalFloat::NaN => "float is NaN",
+            NonCanonicalFloat::NegativeZero => "float is negative zero",
+            NonCanonicalFloat::Subnormal => "float is subnormal",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NonCanonicalFloat {}
+
+macro_rules! impl_canonical {
+    ($name: ident, $type: ident) => {
+        #[doc = concat!("An `", stringify!($type), "` that is not NaN, `-0.0` or subnormal.")]
+        #[derive(Clone, Copy, Default, PartialEq)]
+        #[repr(transparent)]
+        pub struct $name($type);
+
+        impl $name {
+            /// `value`, if it is canonical.
+            pub fn new(value: $type) -> Result<Self, NonCanonicalFloat> {
+                if value.is_nan() {
+                    Err(NonCanonicalFloat::NaN)
+                } else if value == 0.0 && value.is_sign_negative() {
+                    Err(NonCanonicalFloat::NegativeZero)
+                } else if value.is_subnormal() {
+                    Err(NonCanonicalFloat::Subnormal)
+                } else {
+                    Ok($name(value))
+                }
+            }
+
+            /// `value` with `-0.0` and subnormals replaced by `0.0`. Fails only for NaN.
+            pub fn normalized(value: $type) -> Result<Self, NonCanonicalFloat> {
+                if value.is_nan() {
+                    Err(NonCanonicalFloat::NaN)
+                } else if value == 0.0 || value.is_subnormal() {
+                    Ok($name(0.0))
+                } else {
+                    Ok($name(value))
+                }
+            }
+
+            pub fn get(self) -> $type {
+                self.0
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.total_cmp(&other.0)
+            }
+        }
+
+        /// Canonical floats are equal exactly when their bits are.
+        impl Hash for $name {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.to_bits().hash(state);
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl TryFrom<$type> for $name {
+            type Error = NonCanonicalFloat;
+
+            fn try_from(value: $type) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for $type {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl BorshSerialize for $name {
+            fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+                self.0.serialize(writer)
+            }
+
+            fn serialized_size(&self) -> usize {
+                core::mem::size_of::<$type>()
+            }
+
+            fn size_hint(&self) -> usize {
+                core::mem::size_of::<$type>()
+            }
+        }
+
+        impl BorshDeserialize for $name {
+            fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+                $name::new($type::deserialize(reader)?).map_err(invalid)
+            }
+
+            fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+                let start = *buf;
+                let value = $type::deserialize_slice(buf)?;
+                or_rewind($name::new(value).map_err(invalid), buf, start)
+            }
+        }
+
+        /// Described as the float it wraps, which it is on the wire.
+        #[cfg(feature = "std")]
+        impl BorshSchema for $name {
+            fn add_definitions_recursively(
+                definitions: &mut std::collections::HashMap<Declaration, Definition>,
+            ) {
+                $type::add_definitions_recursively(definitions);
+            }
+
+            fn declaration() -> Declaration {
+                $type::declaration()
+            }
+        }
+
+        impl BorshFixedSize for $name {
+            const SIZE: usize = core::mem::size_of::<$type>();
+        }
+
+        impl BorshDiff for $name {
+            fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+                replace_diff_into(old, new, out)
+            }
+
+            fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+                replace_apply_diff(self, reader)
+            }
+        }
+    };
+}
+
+impl_canonical!(CanonicalF32, f32);
+impl_canonical!(CanonicalF64, f64);
+
+fn invalid(err: NonCanonicalFloat) -> Error {
+    Error::new(ErrorKind::InvalidData, err.to_string())
+}
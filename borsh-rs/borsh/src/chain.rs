@@ -0,0 +1,212 @@
+//! Serializing into a chain of buffers.
+//!
+//! [`to_chain`] produces the encoding of a value as a list of [`Bytes`] segments instead of one
+//! contiguous `Vec<u8>`. Small writes are gathered into segments of up to [`SEGMENT_SIZE`] bytes,
+//! and [`Bytes`] fields of at least [`SHARE_THRESHOLD`] bytes are not copied at all: the output
+//! references them, so wrapping a multi-megabyte payload costs a reference count increment.
+//!
+//! `Bytes` fields are encoded like `Vec<u8>`, so the chain concatenates to exactly the output of
+//! `try_to_vec`.
+//!
+//! [`write_vectored`] hands the chain of a value to a writer with `Write::write_vectored`, so that
+//! sockets and files that gather writes send the large fields without them ever being copied
+//! into one contiguous buffer.
+//!
+//! Sharing depends on the type of the writer, so with the `dyn-io` feature, under which derived
+//! impls pass on a `&mut dyn Write`, the fields of derived types are copied like small ones.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, IoSlice, Read, Write};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::schema::{BorshSchema, Declaration, Definition};
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Largest segment produced from small writes.
+pub const SEGMENT_SIZE: usize = 64 * 1024;
+/// `Bytes` fields at least this long are referenced rather than copied.
+pub const SHARE_THRESHOLD: usize = 4096;
+
+struct Chain {
+    /// Address of the `ChainSink` writing into this chain.
+    sink: usize,
+    segments: Vec<Bytes>,
+    current: BytesMut,
+}
+
+impl Chain {
+    fn cut(&mut self) {
+        if !self.current.is_empty() {
+            self.segments.push(self.current.split().freeze());
+        }
+    }
+}
+
+thread_local! {
+    /// Chains being built on this thread, innermost last.
+    static CHAINS: RefCell<Vec<Chain>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The writer handed to `serialize` by [`to_chain`]. Its data lives in `CHAINS`, keyed by address.
+struct ChainSink {
+    /// Keeps the sink from being zero-sized, so its address is unique.
+    _address: u8,
+}
+
+fn with_chain<R>(sink: usize, f: impl FnOnce(&mut Chain) -> R) -> Option<R> {
+    CHAINS.with(|chains| {
+        let mut chains = chains.borrow_mut();
+        chains
+            .iter_mut()
+            .rev()
+            .find(|chain| chain.sink == sink)
+            .map(f)
+    })
+}
+
+impl Write for ChainSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let sink = self as *const ChainSink as usize;
+        with_chain(sink, |chain| {
+            let mut rest = buf;
+            while !rest.is_empty() {
+                let room = SEGMENT_SIZE - chain.current.len();
+                let len = room.min(rest.len());
+                chain.current.extend_from_slice(&rest[..len]);
+                rest = &rest[len..];
+                if chain.current.len() == SEGMENT_SIZE {
+                    chain.cut();
+                }
+            }
+        })
+        .expect("ChainSink is only used inside to_chain");
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Append `bytes` to the chain by reference if `writer` is the sink of a chain being built.
+/// Returns `false` if the caller has to write the bytes itself.
+pub(crate) fn try_share<W: ?Sized>(writer: &W, bytes: &Bytes) -> bool {
+    if bytes.len() < SHARE_THRESHOLD
+        || std::any::type_name::<W>() != std::any::type_name::<ChainSink>()
+    {
+        return false;
+    }
+    let sink = writer as *const W as *const () as usize;
+    with_chain(sink, |chain| {
+        chain.cut();
+        chain.segments.push(bytes.clone());
+    })
+    .is_some()
+}
+
+/// Serialize `value` into a chain of buffers.
+pub fn to_chain<T: BorshSerialize + ?Sized>(value: &T) -> Result<Vec<Bytes>, Error> {
+    /// Removes the chain again if `serialize` returns early or panics.
+    struct Pop(usize);
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            CHAINS.with(|chains| chains.borrow_mut().retain(|chain| chain.sink != self.0));
+        }
+    }
+
+    let mut sink = ChainSink { _address: 0 };
+    let address = &sink as *const ChainSink as usize;
+    CHAINS.with(|chains| {
+        chains.borrow_mut().push(Chain {
+            sink: address,
+            segments: Vec::new(),
+            current: BytesMut::new(),
+        })
+    });
+    let _pop = Pop(address);
+    value.serialize(&mut sink)?;
+    Ok(with_chain(address, |chain| {
+        chain.cut();
+        std::mem::take(&mut chain.segments)
+    })
+    .expect("Chain was pushed above"))
+}
+
+/// Serialize `value` into `writer` as a chain, see [`to_chain`], written with vectored writes.
+pub fn write_vectored<W, T>(writer: &mut W, value: &T) -> Result<(), Error>
+where
+    W: Write + ?Sized,
+    T: BorshSerialize + ?Sized,
+{
+    write_chain(writer, &to_chain(value)?)
+}
+
+/// Write all of `chain` into `writer` with vectored writes, retrying those that write part of it.
+pub fn write_chain<W: Write + ?Sized>(writer: &mut W, chain: &[Bytes]) -> Result<(), Error> {
+    let mut slices = chain
+        .iter()
+        .map(|segment| IoSlice::new(segment))
+        .collect::<Vec<_>>();
+    let mut slices = &mut slices[..];
+    while !slices.is_empty() {
+        match writer.write_vectored(slices) {
+            Ok(0) => {
+                return Err(Error::new(
+                    ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
+            Ok(written) => IoSlice::advance_slices(&mut slices, written),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+impl BorshSerialize for Bytes {
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        if self.len() > u32::MAX as usize {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Bytes field is too long",
+            ));
+        }
+        (self.len() as u32).serialize(writer)?;
+        if !try_share(&*writer, self) {
+            writer.write_all(self)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for Bytes {
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Bytes::from(<Vec<u8>>::deserialize(reader)?))
+    }
+}
+
+impl BorshSchema for Bytes {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <Vec<u8>>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <Vec<u8>>::declaration()
+    }
+}
+
+impl BorshDiff for Bytes {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
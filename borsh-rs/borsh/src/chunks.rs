@@ -0,0 +1,69 @@
+//! Decoding from non-contiguous buffers.
+//!
+//! Network stacks often hold a message in several pieces, such as the two halves of a ring buffer
+//! or the leaves of a rope. [`ChunkReader`] reads across such pieces in order, so the message can
+//! be decoded without first coalescing it into one `Vec`.
+use crate::maybestd::io::{Error, Read};
+
+use crate::BorshDeserialize;
+
+/// A reader over a sequence of byte slices, read one after the other.
+pub struct ChunkReader<'a, I> {
+    chunks: I,
+    current: &'a [u8],
+}
+
+impl<'a, I> ChunkReader<'a, I>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    pub fn new<C: IntoIterator<IntoIter = I>>(chunks: C) -> Self {
+        ChunkReader {
+            chunks: chunks.into_iter(),
+            current: &[],
+        }
+    }
+
+    /// Whether all chunks have been read completely.
+    pub fn is_exhausted(&mut self) -> bool {
+        self.fill().is_empty()
+    }
+
+    /// The unread part of the current chunk, advancing past empty chunks.
+    fn fill(&mut self) -> &'a [u8] {
+        while self.current.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.current = chunk,
+                None => break,
+            }
+        }
+        self.current
+    }
+}
+
+impl<'a, I> Read for ChunkReader<'a, I>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let current = self.fill();
+        let len = current.len().min(buf.len());
+        buf[..len].copy_from_slice(&current[..len]);
+        self.current = &current[len..];
+        Ok(len)
+    }
+}
+
+/// Deserialize a value whose encoding is split across `chunks`. All bytes must be consumed.
+pub fn from_chunks<'a, T, C>(chunks: C) -> Result<T, Error>
+where
+    T: BorshDeserialize,
+    C: IntoIterator<Item = &'a [u8]>,
+{
+    let mut reader = ChunkReader::new(chunks);
+    let result = T::deserialize(&mut reader)?;
+    if !reader.is_exhausted() {
+        return Err(crate::Error::TrailingBytes.into());
+    }
+    Ok(result)
+}
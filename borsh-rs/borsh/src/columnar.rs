@@ -0,0 +1,137 @@
+//! Sequences of structs encoded column by column, with the values of each field contiguous.
+//!
+//! A `Vec<T>` encodes its rows one after the other, so the values of one field are spread across
+//! the encoding, between those of the other fields. [`Columns<T>`] encodes the same rows as a
+//! struct of arrays: the `u32` number of rows, then the first field of every row, then the second
+//! field of every row, and so on. Batches of similar records, like telemetry samples, compress
+//! much better this way, and columns of fixed-size fields can be processed in bulk straight from
+//! the encoding.
+//!
+//! The row type derives [`BorshColumnar`], and in memory a `Columns<T>` is a `Vec<T>`:
+//!
+//! ```
+//! use oasis_borsh::columnar::Columns;
+//! use oasis_borsh::{BorshColumnar, BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshColumnar, Clone, PartialEq, Debug)]
+//! struct Sample {
+//!     sensor: u8,
+//!     value: i16,
+//! }
+//!
+//! let mut batch = Columns::new();
+//! batch.push(Sample { sensor: 1, value: -1 });
+//! batch.push(Sample { sensor: 2, value: 300 });
+//!
+//! let bytes = batch.try_to_vec().unwrap();
+//! assert_eq!(bytes, [2, 0, 0, 0, 1, 2, 0xff, 0xff, 0x2c, 0x01]);
+//! let decoded = Columns::<Sample>::try_from_slice(&bytes).unwrap();
+//! assert_eq!(decoded[1].value, 300);
+//! ```
+//!
+//! Only structs can derive `BorshColumnar`. Their fields are encoded as they would be in the
+//! struct, so `#[borsh_skip]`, `#[borsh(len = "...")]` and `#[borsh(order = N)]` apply; the
+//! columns are in wire order.
+use core::iter::FromIterator;
+use core::ops::{Deref, DerefMut};
+
+use crate::de::{check_cancelled, check_len, hint, in_element, read_len, LenKind};
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A struct whose sequences can be encoded column by column, see the module documentation.
+pub trait BorshColumnar: Sized {
+    /// Serialize the fields of `rows` one column at a time, without the number of rows.
+    fn serialize_columns<W: Write>(rows: &[Self], writer: &mut W) -> Result<(), Error>;
+
+    /// Deserialize `len` rows from their columns.
+    fn deserialize_columns<R: Read>(len: usize, reader: &mut R) -> Result<Vec<Self>, Error>;
+}
+
+/// Read a column of `len` values with `read`. Called by derived decoders.
+pub fn read_column<T, R, F>(len: usize, reader: &mut R, mut read: F) -> Result<Vec<T>, Error>
+where
+    R: Read,
+    F: FnMut(&mut R) -> Result<T, Error>,
+{
+    let mut column = hint::try_with_capacity(capacity::<T>(len))?;
+    for i in 0..len {
+        check_cancelled(i)?;
+        hint::try_push(&mut column, in_element(read(reader), i)?)?;
+    }
+    Ok(column)
+}
+
+/// Collect the rows assembled from decoded columns. Called by derived decoders.
+pub fn collect_rows<T, I: ExactSizeIterator<Item = T>>(rows: I) -> Result<Vec<T>, Error> {
+    let mut result = hint::try_with_capacity(rows.len())?;
+    result.extend(rows);
+    Ok(result)
+}
+
+/// Capacity to reserve for a column of `len` values, bounded like every other decode path.
+fn capacity<T>(len: usize) -> usize {
+    hint::cautious::<T>(len as u32)
+}
+
+/// A `Vec<T>` encoded column by column, see the module documentation.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Columns<T>(pub Vec<T>);
+
+impl<T> Columns<T> {
+    pub fn new() -> Self {
+        Columns(Vec::new())
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for Columns<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Columns<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for Columns<T> {
+    fn from(rows: Vec<T>) -> Self {
+        Columns(rows)
+    }
+}
+
+impl<T> From<Columns<T>> for Vec<T> {
+    fn from(columns: Columns<T>) -> Self {
+        columns.0
+    }
+}
+
+impl<T> FromIterator<T> for Columns<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Columns(iter.into_iter().collect())
+    }
+}
+
+impl<T: BorshColumnar> BorshSerialize for Columns<T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (self.0.len() as u32).serialize(writer)?;
+        T::serialize_columns(&self.0, writer)
+    }
+}
+
+impl<T: BorshColumnar> BorshDeserialize for Columns<T> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        T::deserialize_columns(len as usize, reader).map(Columns)
+    }
+}
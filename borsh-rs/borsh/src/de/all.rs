@@ -0,0 +1,144 @@
+//! Decoding inputs that are nothing but borsh values of one type laid out back to back.
+use core::marker::PhantomData;
+
+use crate::maybestd::boxed::Box;
+use crate::maybestd::format;
+use crate::maybestd::io::{Error, ErrorKind, Read};
+
+use super::BorshDeserialize;
+
+/// Iterate over the values of type `T` that make up the rest of `reader`.
+///
+/// Iteration ends when the input ends cleanly between two values. An input that ends inside a
+/// value, or a value that fails to decode, yields one error and ends iteration, since where the
+/// next value would start is then unknown. The error is an [`Error::At`](crate::Error::At) giving
+/// the offset the failed value started at, relative to where `reader` was when this was called,
+/// around the index of the value in the input and the underlying cause:
+///
+/// ```
+/// use oasis_borsh::de::decode_all;
+/// use oasis_borsh::BorshSerialize;
+///
+/// let mut bytes = Vec::new();
+/// for value in &["a", "bc"] {
+///     value.serialize(&mut bytes).unwrap();
+/// }
+/// let values = decode_all::<String, _>(&bytes[..]).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(values, ["a", "bc"]);
+///
+/// let err = decode_all::<String, _>(&bytes[..8]).last().unwrap().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "at offset 5: error deserializing [1]: \
+///      unexpected end of input reading length prefix: wanted 4 bytes, 3 available",
+/// );
+/// ```
+pub fn decode_all<T: BorshDeserialize, R: Read>(reader: R) -> DecodeAll<T, R> {
+    DecodeAll {
+        reader: Counted {
+            inner: reader,
+            peeked: None,
+            position: 0,
+        },
+        index: 0,
+        done: false,
+        _value: PhantomData,
+    }
+}
+
+/// Iterator over the values of an input, see [`decode_all`].
+pub struct DecodeAll<T, R> {
+    reader: Counted<R>,
+    index: usize,
+    done: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T, R> DecodeAll<T, R> {
+    /// Number of bytes decoded so far: the offset just past the last value returned, or after an
+    /// error, the offset at which decoding stopped.
+    pub fn position(&self) -> u64 {
+        self.reader.position
+    }
+
+    /// The reader, positioned after the last value returned, or after an error, wherever
+    /// decoding stopped.
+    pub fn into_inner(self) -> R {
+        self.reader.inner
+    }
+}
+
+impl<T: BorshDeserialize, R: Read> Iterator for DecodeAll<T, R> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let offset = self.reader.position;
+        let result = match self.reader.at_end() {
+            Ok(true) => {
+                self.done = true;
+                return None;
+            }
+            Ok(false) => T::deserialize(&mut self.reader),
+            Err(err) => Err(err),
+        };
+        let index = self.index;
+        self.index += 1;
+        self.done = result.is_err();
+        Some(result.map_err(|err| {
+            let source = crate::Error::from(err).within("", &format!("[{}]", index));
+            crate::Error::At {
+                offset,
+                source: Box::new(source),
+            }
+            .into()
+        }))
+    }
+}
+
+/// A reader that counts the bytes read from it and can look ahead one byte.
+struct Counted<R> {
+    inner: R,
+    peeked: Option<u8>,
+    position: u64,
+}
+
+impl<R: Read> Counted<R> {
+    /// Whether the input has ended, reading ahead one byte to find out.
+    fn at_end(&mut self) -> Result<bool, Error> {
+        if self.peeked.is_some() {
+            return Ok(false);
+        }
+        let mut byte = [0u8];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(true),
+                Ok(_) => {
+                    self.peeked = Some(byte[0]);
+                    return Ok(false);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let len = match self.peeked.take() {
+            Some(byte) => {
+                buf[0] = byte;
+                1
+            }
+            None => self.inner.read(buf)?,
+        };
+        self.position += len as u64;
+        Ok(len)
+    }
+}
@@ -0,0 +1,76 @@
+//! Reporting of allocation decisions made while decoding.
+//!
+//! Every time a decoder turns a length read from the input into a preallocation, the decision is
+//! passed to the callback installed with [`with_audit`]. This lets security reviews check that
+//! untrusted lengths never translate into unbounded allocations: `reserved` should stay small no
+//! matter how large `declared` gets.
+use std::cell::RefCell;
+
+/// One allocation decision made during decode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Allocation {
+    /// Name of the element type being allocated for, e.g. `u8` for strings and byte buffers.
+    pub element: &'static str,
+    /// Size of one element in bytes.
+    pub element_size: usize,
+    /// Number of elements announced by the input.
+    pub declared: u32,
+    /// Number of elements the decoder reserved capacity for up front.
+    pub reserved: usize,
+}
+
+impl Allocation {
+    /// Bytes reserved up front.
+    pub fn reserved_bytes(&self) -> usize {
+        self.reserved * self.element_size
+    }
+}
+
+type Callback<'a> = Box<dyn FnMut(&Allocation) + 'a>;
+
+thread_local! {
+    static CALLBACK: RefCell<Option<Callback<'static>>> = RefCell::new(None);
+}
+
+/// Run `f`, reporting every allocation decision made by decoders on this thread to `callback`.
+///
+/// Calls may be nested; the innermost callback receives the reports and the outer one is restored
+/// when `f` returns or panics.
+pub fn with_audit<C, F, T>(callback: C, f: F) -> T
+where
+    C: FnMut(&Allocation),
+    F: FnOnce() -> T,
+{
+    struct Restore(Option<Callback<'static>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CALLBACK.with(|cell| *cell.borrow_mut() = previous);
+        }
+    }
+
+    let callback: Callback<'_> = Box::new(callback);
+    // The callback only lives in the thread-local while `f` runs, and `Restore` removes it before
+    // this function returns, so extending its lifetime is sound.
+    let callback: Callback<'static> = unsafe { std::mem::transmute(callback) };
+    let _restore = Restore(CALLBACK.with(|cell| cell.borrow_mut().replace(callback)));
+    f()
+}
+
+pub(crate) fn report<T>(declared: u32, reserved: usize) {
+    CALLBACK.with(|cell| {
+        // Take the callback out while it runs, so a callback that itself decodes does not
+        // re-enter the cell.
+        let callback = cell.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(&Allocation {
+                element: std::any::type_name::<T>(),
+                element_size: std::mem::size_of::<T>(),
+                declared,
+                reserved,
+            });
+            *cell.borrow_mut() = Some(callback);
+        }
+    });
+}
@@ -0,0 +1,104 @@
+//! Cooperative cancellation of decodes, so that a caller can abort decoding a huge malicious or
+//! corrupt payload, e.g. one announcing billions of elements that arrive slowly, instead of
+//! blocking a worker thread until it ends.
+//!
+//! [`CancelToken::run`] makes the token visible to decoders on the current thread. Collections
+//! check it before their first element and every [`INTERVAL`] elements after that, and fail with
+//! [`Error::Cancelled`](crate::Error::Cancelled) once the token is cancelled or its deadline has
+//! passed. Values without collections are bounded by their type, so they are not checked.
+//!
+//! ```
+//! use std::time::Duration;
+//! use oasis_borsh::de::cancel::CancelToken;
+//! use oasis_borsh::BorshDeserialize;
+//!
+//! let token = CancelToken::with_timeout(Duration::from_secs(5));
+//! // Another thread may call `token.cancel()` to stop the decode early.
+//! let decoded = token.run(|| Vec::<u64>::try_from_slice(&[1, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]));
+//! assert_eq!(decoded.unwrap(), vec![7]);
+//! ```
+use std::cell::RefCell;
+use std::io::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of elements of a collection decoded between two checks of the token.
+pub const INTERVAL: usize = 1024;
+
+thread_local! {
+    static TOKEN: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// A handle that cancels the decodes it is [`run`](CancelToken::run) with, when cancelled
+/// explicitly or once its deadline passes. Clones share the same state.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by [`cancel`](CancelToken::cancel).
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    /// A token that is also cancelled once `deadline` has passed.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancelToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// A token that is also cancelled once `timeout` has elapsed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        CancelToken::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Cancel the decodes running with this token or any of its clones, from any thread.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token was cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Run `f` with decoding on this thread cancelled by this token. Calls may be nested; the
+    /// innermost token applies and the outer one is restored when `f` returns or panics.
+    pub fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore(Option<CancelToken>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                TOKEN.with(|cell| *cell.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(TOKEN.with(|cell| cell.borrow_mut().replace(self.clone())));
+        f()
+    }
+}
+
+/// Fail if the token in effect on this thread is cancelled.
+pub(crate) fn check() -> Result<(), Error> {
+    let cancelled = TOKEN.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    });
+    if cancelled {
+        return Err(crate::Error::Cancelled.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! How deeply the values being decoded are nested.
+use crate::maybestd::io::Error;
+
+use super::metrics;
+
+#[cfg(feature = "std")]
+thread_local! {
+    static DEPTH: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// Marks a value as being decoded until dropped, see [`enter`].
+#[must_use]
+pub struct Depth {
+    _metrics: metrics::Depth,
+}
+
+/// Record that decoding enters one more level of nesting, until the returned guard is dropped,
+/// failing with [`crate::Error::TooDeep`] past the
+/// [`max_depth`](super::options::DecodeOptions::max_depth) in effect.
+///
+/// Derived decoders call this before reading their fields, so a payload nesting a recursive type,
+/// like a `Box`-linked list or an expression tree, deeper than the limit fails to decode instead
+/// of overflowing the stack. Hand-written decoders of recursive types should do the same:
+///
+/// ```
+/// use std::io::{Read, Result};
+///
+/// use oasis_borsh::de::enter;
+/// use oasis_borsh::BorshDeserialize;
+///
+/// struct Nested(Option<Box<Nested>>);
+///
+/// impl BorshDeserialize for Nested {
+///     fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+///         let _depth = enter()?;
+///         Ok(Nested(BorshDeserialize::deserialize(reader)?))
+///     }
+/// }
+///
+/// let bytes = vec![1u8; 1 << 20];
+/// let err = Nested::try_from_slice(&bytes).err().unwrap();
+/// assert!(err.to_string().ends_with("nesting exceeds the depth limit of 512"));
+/// ```
+///
+/// Without std there is nowhere to keep count, and the depth is not limited.
+#[inline]
+pub fn enter() -> Result<Depth, Error> {
+    #[cfg(feature = "std")]
+    {
+        let limit = super::options::max_depth();
+        let depth = DEPTH.with(|cell| cell.get()) + 1;
+        if depth > limit {
+            return Err(crate::Error::TooDeep { limit }.into());
+        }
+        DEPTH.with(|cell| cell.set(depth));
+    }
+    Ok(Depth {
+        _metrics: metrics::enter(),
+    })
+}
+
+#[cfg(feature = "std")]
+impl Drop for Depth {
+    fn drop(&mut self) {
+        DEPTH.with(|cell| cell.set(cell.get() - 1));
+    }
+}
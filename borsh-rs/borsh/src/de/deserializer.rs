@@ -0,0 +1,88 @@
+//! Decoding several values, of any types, from one input.
+use crate::maybestd::io::{Error, Read};
+
+use super::{BorshDeserialize, TrackedReader};
+
+/// Decodes values one after the other from a reader, for inputs that hold a sequence of values
+/// of different types, such as a header followed by a body.
+///
+/// Unlike [`try_from_slice`](BorshDeserialize::try_from_slice), [`next`](Self::next) does not
+/// require the value to end the input, and what is left can be inspected or checked at the end:
+///
+/// ```
+/// use oasis_borsh::de::Deserializer;
+/// use oasis_borsh::BorshSerialize;
+///
+/// let mut bytes = Vec::new();
+/// 2u8.serialize(&mut bytes).unwrap();
+/// "payload".serialize(&mut bytes).unwrap();
+/// bytes.push(0xff);
+///
+/// let mut de = Deserializer::new(&bytes[..]);
+/// assert_eq!(de.next::<u8>().unwrap(), 2);
+/// assert_eq!(de.next::<String>().unwrap(), "payload");
+/// assert_eq!(de.offset(), 12);
+/// assert_eq!(de.remaining(), [0xff]);
+/// assert!(de.end().is_err());
+/// ```
+///
+/// Errors give the offset at which decoding stopped, counted from where the reader was when the
+/// `Deserializer` was created. After an error the reader is left inside the value that failed.
+#[derive(Debug)]
+pub struct Deserializer<R> {
+    reader: TrackedReader<R>,
+}
+
+impl<R> Deserializer<R> {
+    pub fn new(reader: R) -> Self {
+        Deserializer {
+            reader: TrackedReader::new(reader),
+        }
+    }
+
+    /// Number of bytes the values decoded so far took.
+    pub fn offset(&self) -> u64 {
+        self.reader.offset()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// The reader, positioned after the last value decoded.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: Read> Deserializer<R> {
+    /// Decode the next value, as a `T`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<T: BorshDeserialize>(&mut self) -> Result<T, Error> {
+        self.reader.decode()
+    }
+}
+
+impl<'a> Deserializer<&'a [u8]> {
+    /// The bytes after the last value decoded.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.get_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> Deserializer<R> {
+    /// Whether the input has no bytes left. Fails only if the reader does.
+    pub fn is_empty(&mut self) -> Result<bool, Error> {
+        std::io::BufRead::fill_buf(&mut self.reader).map(|buf| buf.is_empty())
+    }
+
+    /// Fail with [`crate::Error::TrailingBytes`] unless the input has no bytes left.
+    pub fn end(mut self) -> Result<(), Error> {
+        if self.is_empty()? {
+            Ok(())
+        } else {
+            Err(crate::Error::TrailingBytes.into())
+        }
+    }
+}
@@ -1,5 +1,147 @@
+use crate::maybestd::collections::TryReserveError;
+use crate::maybestd::io::Error;
+use crate::maybestd::vec::Vec;
+
+/// The preallocation limit in effect, see `options`.
+#[cfg(feature = "std")]
+#[inline]
+fn max_preallocation() -> usize {
+    super::options::max_preallocation()
+}
+
+/// Without std there are no thread-locals to override the limit with, so the default applies.
+#[cfg(not(feature = "std"))]
+#[inline]
+fn max_preallocation() -> usize {
+    4096
+}
+
+/// Capacity to reserve for a collection whose length prefix announced `hint` elements, within
+/// the [`max_preallocation`](super::options::DecodeOptions::max_preallocation) in effect.
 #[inline]
 pub fn cautious<T>(hint: u32) -> usize {
-    let el_size = std::mem::size_of::<T>() as u32;
-    std::cmp::max(std::cmp::min(hint, 4096/el_size), 1u32) as _
+    let el_size = core::mem::size_of::<T>();
+    // Zero-sized elements never allocate, so their capacity costs nothing.
+    let reserved = match max_preallocation().checked_div(el_size) {
+        Some(limit) => core::cmp::min(hint as usize, limit),
+        None => 0,
+    };
+    super::metrics::collection(hint);
+    #[cfg(feature = "alloc-audit")]
+    super::audit::report::<T>(hint, reserved);
+    reserved
+}
+
+/// Record that a collection of `hint` elements is decoded without reserving capacity up front.
+#[inline]
+#[allow(clippy::extra_unused_type_parameters)] // Used with `alloc-audit`.
+pub fn unreserved<T>(_hint: u32) {
+    super::metrics::collection(_hint);
+    #[cfg(feature = "alloc-audit")]
+    super::audit::report::<T>(_hint, 0);
+}
+
+/// Record that a collection of `len` elements is decoded into exactly as much capacity, which is
+/// safe once the input is known to hold all of them.
+#[inline]
+#[allow(clippy::extra_unused_type_parameters)] // Used with `alloc-audit`.
+pub fn exact<T>(_len: u32) {
+    super::metrics::collection(_len);
+    #[cfg(feature = "alloc-audit")]
+    super::audit::report::<T>(_len, _len as usize);
+}
+
+/// An empty vector with room for `capacity` elements, failing with
+/// [`crate::Error::AllocationFailed`] rather than aborting if the allocator cannot provide it.
+#[inline]
+pub fn try_with_capacity<T>(capacity: usize) -> Result<Vec<T>, Error> {
+    let mut vec = Vec::new();
+    try_reserve(&mut vec, capacity)?;
+    Ok(vec)
+}
+
+/// `vec.reserve(additional)`, failing rather than aborting if the allocator cannot provide it.
+#[inline]
+pub fn try_reserve<T>(vec: &mut Vec<T>, additional: usize) -> Result<(), Error> {
+    charge_growth::<T>(vec.len(), vec.capacity(), additional)?;
+    vec.try_reserve(additional)
+        .map_err(|err| allocation_failed::<T>(err, additional))
+}
+
+/// Charge the allocation budget for making room for `additional` more elements in a vector of
+/// `len` elements and `capacity`, which grows like `reserve` does: to at least twice its capacity.
+#[inline]
+pub fn charge_growth<T>(len: usize, capacity: usize, additional: usize) -> Result<(), Error> {
+    let wanted = len.saturating_add(additional);
+    if wanted <= capacity {
+        return Ok(());
+    }
+    charge::<T>(core::cmp::max(wanted, capacity.saturating_mul(2)) - capacity)
+}
+
+/// Charge the allocation budget for `count` elements of type `T`, see
+/// [`Limits::max_allocation`](super::options::Limits::max_allocation).
+#[inline]
+#[allow(clippy::extra_unused_type_parameters)] // Used with std.
+pub fn charge<T>(count: usize) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    return super::options::charge(count.saturating_mul(core::mem::size_of::<T>()));
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = count;
+        Ok(())
+    }
+}
+
+/// `vec.push(value)`, growing the vector like `push` does but failing rather than aborting if
+/// the allocator cannot provide the memory.
+#[inline]
+pub fn try_push<T>(vec: &mut Vec<T>, value: T) -> Result<(), Error> {
+    if vec.len() == vec.capacity() {
+        // Amortized like `push`: this at least doubles the capacity.
+        try_reserve(vec, 1)?;
+    }
+    vec.push(value);
+    Ok(())
+}
+
+/// Make room in `map` for one more entry, failing rather than aborting if the allocator cannot
+/// provide it. Grows the table like an insert into a full map does.
+#[cfg(feature = "std")]
+#[inline]
+pub fn try_grow_map<K, V, S>(map: &mut std::collections::HashMap<K, V, S>) -> Result<(), Error>
+where
+    K: Eq + core::hash::Hash,
+    S: core::hash::BuildHasher,
+{
+    if map.len() == map.capacity() {
+        charge_growth::<(K, V)>(map.len(), map.capacity(), 1)?;
+        map.try_reserve(1)
+            .map_err(|err| allocation_failed::<(K, V)>(err, 1))?;
+    }
+    Ok(())
+}
+
+/// [`try_grow_map`] for sets.
+#[cfg(feature = "std")]
+#[inline]
+pub fn try_grow_set<T, S>(set: &mut std::collections::HashSet<T, S>) -> Result<(), Error>
+where
+    T: Eq + core::hash::Hash,
+    S: core::hash::BuildHasher,
+{
+    if set.len() == set.capacity() {
+        charge_growth::<T>(set.len(), set.capacity(), 1)?;
+        set.try_reserve(1)
+            .map_err(|err| allocation_failed::<T>(err, 1))?;
+    }
+    Ok(())
+}
+
+/// The error for failing to make room for `additional` more elements of type `T`.
+pub fn allocation_failed<T>(_err: TryReserveError, additional: usize) -> Error {
+    crate::Error::AllocationFailed {
+        bytes: additional.saturating_mul(core::mem::size_of::<T>()),
+    }
+    .into()
 }
@@ -0,0 +1,169 @@
+//! Statistics gathered while decoding, for capacity planning and anomaly detection on live
+//! traffic without parsing it twice.
+//!
+//! With the `metrics` feature, [`with_metrics`] runs a closure and returns, next to its result,
+//! [`DecodeStats`] about everything decoded on the current thread in the meantime: how many
+//! length-prefixed collections were read and how many elements they announced, how deeply derived
+//! types were nested, and how many bytes each field of a derived type took.
+//!
+//! Field sizes are measured when derived types decode from a slice, which is what
+//! `try_from_slice` does; decoding from a reader records collections and depth only.
+//!
+//! ```
+//! # #[cfg(feature = "metrics")]
+//! # {
+//! use oasis_borsh::de::metrics::with_metrics;
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Batch {
+//!     id: u64,
+//!     items: Vec<String>,
+//! }
+//!
+//! let bytes = Batch { id: 1, items: vec!["a".to_string()] }.try_to_vec().unwrap();
+//! let (batch, stats) = with_metrics(|| Batch::try_from_slice(&bytes));
+//! assert!(batch.is_ok());
+//! assert_eq!(stats.collections, 2);
+//! assert_eq!(stats.elements, 2);
+//! let items = &stats.fields[&(std::any::type_name::<Batch>(), "items")];
+//! assert_eq!(items.bytes, 9);
+//! # }
+//! ```
+//!
+//! Without the feature the hooks decoders call compile to nothing, so they cost nothing.
+use crate::maybestd::io::Error;
+
+#[cfg(feature = "metrics")]
+pub use self::collect::{with_metrics, DecodeStats, FieldStats};
+
+/// Marks a derived type as being decoded until dropped, see [`enter`].
+#[doc(hidden)]
+pub struct Depth(());
+
+/// Called by derived decoders before reading their fields.
+#[doc(hidden)]
+#[inline]
+pub fn enter() -> Depth {
+    #[cfg(feature = "metrics")]
+    collect::update(|collector| {
+        collector.depth += 1;
+        collector.stats.max_depth = core::cmp::max(collector.stats.max_depth, collector.depth);
+    });
+    Depth(())
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for Depth {
+    fn drop(&mut self) {
+        collect::update(|collector| collector.depth = collector.depth.saturating_sub(1));
+    }
+}
+
+/// Called by derived decoders to read the field `name` of `T` from `buf` with `read`.
+#[doc(hidden)]
+#[inline]
+pub fn field<T: ?Sized, V>(
+    _name: &'static str,
+    buf: &mut &[u8],
+    read: impl FnOnce(&mut &[u8]) -> Result<V, Error>,
+) -> Result<V, Error> {
+    #[cfg(feature = "metrics")]
+    let start = buf.len();
+    let value = read(buf)?;
+    #[cfg(feature = "metrics")]
+    collect::update(|collector| {
+        let field = collector
+            .stats
+            .fields
+            .entry((core::any::type_name::<T>(), _name))
+            .or_default();
+        field.count += 1;
+        field.bytes += (start - buf.len()) as u64;
+    });
+    Ok(value)
+}
+
+/// Record that a collection announcing `len` elements is being decoded.
+#[inline]
+pub(crate) fn collection(_len: u32) {
+    #[cfg(feature = "metrics")]
+    collect::update(|collector| {
+        collector.stats.collections += 1;
+        collector.stats.elements += u64::from(_len);
+    });
+}
+
+#[cfg(feature = "metrics")]
+mod collect {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    /// What was decoded on one thread while [`with_metrics`] ran.
+    #[derive(Clone, Default, PartialEq, Eq, Debug)]
+    pub struct DecodeStats {
+        /// Number of length-prefixed collections read, including strings and byte buffers.
+        pub collections: u64,
+        /// Total number of elements announced by the length prefixes of those collections.
+        pub elements: u64,
+        /// Deepest nesting of derived types: 1 when no derived type contained another.
+        pub max_depth: usize,
+        /// Statistics for each field read from a slice, keyed by the name of the derived type
+        /// and the name of the field. Fields of tuple structs are named by their index, and fields
+        /// of enum variants are prefixed with the variant, as in `Variant.field`.
+        pub fields: BTreeMap<(&'static str, &'static str), FieldStats>,
+    }
+
+    /// Sizes of one field of a derived type, over every time it was read.
+    #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+    pub struct FieldStats {
+        /// Number of times the field was read.
+        pub count: u64,
+        /// Total number of bytes it took.
+        pub bytes: u64,
+    }
+
+    #[derive(Default)]
+    pub(super) struct Collector {
+        pub(super) stats: DecodeStats,
+        pub(super) depth: usize,
+    }
+
+    thread_local! {
+        static COLLECTOR: RefCell<Option<Collector>> = const { RefCell::new(None) };
+    }
+
+    /// Run `f`, collecting statistics about everything decoded on this thread until it returns.
+    ///
+    /// Calls may be nested; the innermost call collects the statistics and the outer one resumes
+    /// when `f` returns or panics, without seeing what the inner one collected.
+    pub fn with_metrics<F, T>(f: F) -> (T, DecodeStats)
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore(Option<Collector>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                COLLECTOR.with(|cell| *cell.borrow_mut() = previous);
+            }
+        }
+
+        let restore =
+            Restore(COLLECTOR.with(|cell| cell.borrow_mut().replace(Collector::default())));
+        let value = f();
+        let collector = COLLECTOR.with(|cell| cell.borrow_mut().take());
+        drop(restore);
+        let stats = collector.map(|collector| collector.stats);
+        (value, stats.unwrap_or_default())
+    }
+
+    pub(super) fn update(f: impl FnOnce(&mut Collector)) {
+        COLLECTOR.with(|cell| {
+            if let Some(collector) = cell.borrow_mut().as_mut() {
+                f(collector);
+            }
+        });
+    }
+}
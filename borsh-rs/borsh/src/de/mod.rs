@@ -1,42 +1,402 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::io::{Cursor, Error, Read};
-use std::mem::{forget, size_of};
+use core::mem::{size_of, ManuallyDrop, MaybeUninit};
+use core::ptr;
 
-mod hint;
+use crate::maybestd::boxed::Box;
+use crate::maybestd::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std")]
+use crate::maybestd::collections::{HashMap, HashSet};
+use crate::maybestd::format;
+use crate::maybestd::io::{Error, ErrorKind, Read};
+use crate::maybestd::string::String;
+use crate::maybestd::sync::Arc;
+use crate::maybestd::vec::Vec;
+use crate::spec;
+
+mod all;
+#[cfg(feature = "alloc-audit")]
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod cancel;
+mod depth;
+mod deserializer;
+pub(crate) mod hint;
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod options;
+pub mod seed;
+mod seq;
+pub(crate) mod tracked;
+#[cfg(feature = "unchecked")]
+pub mod unchecked;
+pub mod variant;
 
-const ERROR_NOT_ALL_BYTES_READ: &str = "Not all bytes read";
+pub use all::{decode_all, DecodeAll};
+pub use depth::{enter, Depth};
+pub use deserializer::Deserializer;
+pub use seed::BorshDeserializeSeed;
+pub use seq::SeqReader;
+pub use tracked::TrackedReader;
+pub use variant::{deserialize_variant, BorshDeserializeVariant};
 
 /// A data-structure that can be de-serialized from binary format by NBOR.
 pub trait BorshDeserialize: Sized {
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error>;
 
+    /// Deserialize this instance from the start of `buf`, advancing `buf` past the bytes read.
+    ///
+    /// The default reads `buf` through [`deserialize`](Self::deserialize). The std types and
+    /// derived impls override it to index into the slice directly, which saves the bookkeeping of
+    /// a reader and copies strings and byte arrays in one go. Handwritten impls of hot types
+    /// can do the same; both paths must decode the same bytes to the same value.
+    ///
+    /// A value that fails to decode should leave `buf` at its start, or at the start of the part
+    /// of it that failed, so that [`try_from_slice`](Self::try_from_slice) reports the offset of
+    /// the bad value. The default leaves `buf` at the start of the value.
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        or_rewind(Self::deserialize(buf), buf, start)
+    }
+
+    /// Deserialize a value from `reader` into `place`, overwriting it.
+    ///
+    /// The default replaces `place` with a freshly decoded value. Strings, vectors, maps, sets and
+    /// types that contain them override it to decode into the allocations `place` already owns,
+    /// so decoding many similar messages into the same value mostly avoids the allocator. Derived
+    /// structs decode each field in place; derived enums replace the value.
+    ///
+    /// If decoding fails, `place` is left holding some valid value, which may be partly
+    /// overwritten.
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        *place = Self::deserialize(reader)?;
+        Ok(())
+    }
+
+    /// Decode an array of `N` values from the start of `buf` in one go, for the types that can
+    /// do better than one element at a time. Only `u8` does, by copying the bytes.
+    #[doc(hidden)]
+    #[inline]
+    fn array_from_slice<const N: usize>(_buf: &mut &[u8]) -> Option<Result<[Self; N], Error>> {
+        None
+    }
+
     /// Deserialize this instance from a slice of bytes.
+    ///
+    /// Errors raised while decoding are wrapped in a [`crate::Error::At`] giving the offset in `v`
+    /// of the value that failed: the length prefix, tag or primitive that is invalid or cut
+    /// short, or the map entry, set or string that is rejected as a whole.
     fn try_from_slice(v: &[u8]) -> Result<Self, Error> {
-        let mut c = Cursor::new(v);
-        let result = Self::deserialize(&mut c)?;
-        if c.position() != v.len() as u64 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                ERROR_NOT_ALL_BYTES_READ,
-            ));
+        let mut buf = v;
+        let result = Self::deserialize_slice(&mut buf)
+            .map_err(|err| tracked::at_offset(err, (v.len() - buf.len()) as u64))?;
+        if !buf.is_empty() {
+            return Err(crate::Error::TrailingBytes.into());
         }
         Ok(result)
     }
+
+    /// Deserialize this instance from the start of `v`, returning it with the number of bytes it
+    /// took, so that values concatenated in one buffer can be read one after another.
+    fn try_from_slice_partial(v: &[u8]) -> Result<(Self, usize), Error> {
+        let mut buf = v;
+        let result = Self::deserialize_slice(&mut buf)
+            .map_err(|err| tracked::at_offset(err, (v.len() - buf.len()) as u64))?;
+        Ok((result, v.len() - buf.len()))
+    }
+
+    /// Deserialize this instance from untrusted bytes, failing as soon as a collection declares a
+    /// length over `limits`, see [`options::Limits`].
+    #[cfg(feature = "std")]
+    fn try_from_slice_with_limits(v: &[u8], limits: options::Limits) -> Result<Self, Error> {
+        limits.run(|| Self::try_from_slice(v))
+    }
+
+    /// Deserialize this instance from trusted bytes, skipping UTF-8, NaN and trailing byte checks.
+    ///
+    /// # Safety
+    ///
+    /// See [`unchecked::try_from_slice_unchecked`].
+    #[cfg(feature = "unchecked")]
+    unsafe fn try_from_slice_unchecked(v: &[u8]) -> Result<Self, Error> {
+        unchecked::try_from_slice_unchecked(v)
+    }
+}
+
+/// Whether validation that trusted input does not need is turned off, see `unchecked`.
+#[inline]
+pub(crate) fn skip_checks() -> bool {
+    #[cfg(feature = "unchecked")]
+    return unchecked::active();
+    #[cfg(not(feature = "unchecked"))]
+    false
+}
+
+/// Deserialize a `T` from the start of `v`, ignoring whatever follows it.
+///
+/// Together with the header types generated by `#[borsh_header(Name)]` this reads the leading
+/// fields of a message, such as its version or kind, without decoding the body.
+pub fn deserialize_prefix<T: BorshDeserialize>(mut v: &[u8]) -> Result<T, Error> {
+    T::deserialize_slice(&mut v)
+}
+
+/// Deserialize a `T` from all of `reader`, reading through a buffer so that reading from a file or
+/// socket does not take a system call per field.
+///
+/// Like [`BorshDeserialize::try_from_slice`], this fails with [`crate::Error::TrailingBytes`] if
+/// the reader has more bytes after the value, and gives the offset at which decoding stopped with
+/// other errors. Use `T::deserialize` directly to read one value of many.
+#[cfg(feature = "std")]
+pub fn from_reader<T: BorshDeserialize, R: Read>(reader: R) -> Result<T, Error> {
+    use std::io::BufRead;
+
+    let mut reader = TrackedReader::new(std::io::BufReader::new(reader));
+    let result = reader.decode::<T>()?;
+    if !reader.fill_buf()?.is_empty() {
+        return Err(crate::Error::TrailingBytes.into());
+    }
+    Ok(result)
+}
+
+/// The variant tag at the start of `v`, the encoding of an enum, without decoding the rest, so that
+/// a router can dispatch on the kind of a message before deserializing its payload.
+///
+/// Derived enums marked `#[borsh_wire_tag]` also get a `peek_wire_tag` associated function, which
+/// checks the tag against their variants.
+pub fn peek_tag(v: &[u8]) -> Result<u8, Error> {
+    v.first()
+        .copied()
+        .ok_or_else(|| truncated("enum tag", 1, 0))
+}
+
+/// Like [`peek_tag`], for an encoding at the front of a buffered reader, which is left as it was.
+#[cfg(feature = "std")]
+pub fn peek_tag_buffered<R: std::io::BufRead + ?Sized>(reader: &mut R) -> Result<u8, Error> {
+    peek_tag(reader.fill_buf()?)
+}
+
+/// The error for input that ended with only `available` of the `wanted` bytes of a `what`.
+fn truncated(what: &'static str, wanted: usize, available: usize) -> Error {
+    crate::Error::Truncated {
+        what,
+        wanted,
+        available,
+    }
+    .into()
+}
+
+/// Rename the value that ran out of input in an error from decoding part of it to `what`, with
+/// `counts` giving the bytes wanted and available for the value from those for the part.
+#[inline]
+fn relabel<T>(
+    result: Result<T, Error>,
+    what: &'static str,
+    counts: impl FnOnce(usize, usize) -> (usize, usize),
+) -> Result<T, Error> {
+    result.map_err(|err| {
+        if err.kind() != ErrorKind::UnexpectedEof {
+            return err;
+        }
+        match crate::Error::from(err) {
+            crate::Error::Truncated {
+                wanted, available, ..
+            } => {
+                let (wanted, available) = counts(wanted, available);
+                truncated(what, wanted, available)
+            }
+            err => err.into(),
+        }
+    })
+}
+
+/// Rename the value that ran out of input in `result` to `what`, for a value read as another.
+#[inline]
+fn labelled<T>(result: Result<T, Error>, what: &'static str) -> Result<T, Error> {
+    relabel(result, what, |wanted, available| (wanted, available))
+}
+
+/// Fill `buf` from `reader`, which holds a `what`, saying how much of it there was if the input
+/// ends first. `Read::read_exact` does not tell.
+#[inline]
+pub(crate) fn read_exact<R: Read + ?Sized>(
+    reader: &mut R,
+    buf: &mut [u8],
+    what: &'static str,
+) -> Result<(), Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => return Err(truncated(what, buf.len(), filled)),
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Read the length prefix of a string, sequence or map.
+#[inline]
+pub(crate) fn read_len<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    labelled(u32::deserialize(reader), "length prefix")
+}
+
+/// Like [`read_len`], from the start of `buf`.
+#[inline]
+pub(crate) fn read_len_slice(buf: &mut &[u8]) -> Result<u32, Error> {
+    labelled(u32::deserialize_slice(buf), "length prefix")
+}
+
+/// Read the length prefix of a collection of `kind` from the start of `buf` and
+/// [check](check_len) it, leaving `buf` at the prefix if it is rejected.
+#[inline]
+pub(crate) fn read_checked_len_slice(buf: &mut &[u8], kind: LenKind) -> Result<u32, Error> {
+    let start = *buf;
+    let len = read_len_slice(buf)?;
+    or_rewind(check_len(len.into(), kind), buf, start)?;
+    Ok(len)
+}
+
+/// What a length prefix counts, for the [`Limits`](options::Limits) in effect.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum LenKind {
+    Sequence,
+    String,
+    Map,
+}
+
+/// Fail with [`crate::Error::TooLong`] if a collection of `kind` declares more than the limit in
+/// effect, or with [`crate::Error::TooManyElements`] if a sequence or map declares more than is
+/// left of the element budget, checked before decoding any of its elements.
+#[inline]
+pub(crate) fn check_len(len: u64, kind: LenKind) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    {
+        let limit = options::max_len(kind);
+        if len > u64::from(limit) {
+            let what = match kind {
+                LenKind::Sequence => "sequence",
+                LenKind::String => "string",
+                LenKind::Map => "map",
+            };
+            return Err(crate::Error::TooLong { what, len, limit }.into());
+        }
+        if kind != LenKind::String {
+            options::charge_elements(len)?;
+        }
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = (len, kind);
+    Ok(())
+}
+
+/// Fail with [`crate::Error::DuplicateKey`] unless `unique`, that is unless a map or set got a
+/// key it already had, if the options in effect reject duplicate keys.
+#[inline]
+pub(crate) fn check_unique(unique: bool) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    if !unique && options::reject_duplicate_keys() {
+        return Err(crate::Error::DuplicateKey.into());
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = unique;
+    Ok(())
+}
+
+/// Fail with [`crate::Error::UnsortedKeys`] unless `ascending` finds the keys of a B-tree map or
+/// set in strictly ascending order, if the options in effect require that. `ascending` is only
+/// called then.
+#[inline]
+pub(crate) fn check_ascending<F: FnOnce() -> bool>(ascending: F) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    if options::require_sorted_keys() && !ascending() {
+        return Err(crate::Error::UnsortedKeys.into());
+    }
+    #[cfg(not(feature = "std"))]
+    let _ = ascending;
+    Ok(())
+}
+
+/// Read the variant tag of an enum. Called by derived decoders.
+#[doc(hidden)]
+#[inline]
+pub fn read_tag<R: Read>(reader: &mut R) -> Result<u8, Error> {
+    labelled(u8::deserialize(reader), "enum tag")
+}
+
+/// Like [`read_tag`], from the start of `buf`.
+#[doc(hidden)]
+#[inline]
+pub fn read_tag_slice(buf: &mut &[u8]) -> Result<u8, Error> {
+    labelled(u8::deserialize_slice(buf), "enum tag")
+}
+
+/// Split the first `len` bytes, those of a `what`, off `buf`.
+#[inline]
+fn take<'a>(buf: &mut &'a [u8], len: usize, what: &'static str) -> Result<&'a [u8], Error> {
+    if buf.len() < len {
+        return Err(truncated(what, len, buf.len()));
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+/// Put `buf` back at `start` if `result` is an error, so that a value rejected after it was read
+/// is reported at the offset it starts at rather than the one after it.
+#[inline]
+pub(crate) fn or_rewind<'a, T>(
+    result: Result<T, Error>,
+    buf: &mut &'a [u8],
+    start: &'a [u8],
+) -> Result<T, Error> {
+    if result.is_err() {
+        *buf = start;
+    }
+    result
+}
+
+/// Split the first `N` bytes, those of a `what`, off `buf`.
+#[inline]
+fn take_array<const N: usize>(buf: &mut &[u8], what: &'static str) -> Result<[u8; N], Error> {
+    let mut data = [0u8; N];
+    data.copy_from_slice(take(buf, N, what)?);
+    Ok(data)
 }
 
 impl BorshDeserialize for () {
     fn deserialize<R: Read>(_reader: &mut R) -> Result<Self, Error> {
         Ok(())
     }
+
+    fn deserialize_slice(_buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(())
+    }
 }
 
 impl BorshDeserialize for u8 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let mut res = 0u8;
-        reader.read_exact(std::slice::from_mut(&mut res))?;
+        read_exact(reader, core::slice::from_mut(&mut res), "u8")?;
         Ok(res)
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let (&res, rest) = buf.split_first().ok_or_else(|| truncated("u8", 1, 0))?;
+        *buf = rest;
+        Ok(res)
+    }
+
+    #[inline]
+    fn array_from_slice<const N: usize>(buf: &mut &[u8]) -> Option<Result<[Self; N], Error>> {
+        if buf.len() < N {
+            // Fail as reading byte by byte does, at the first byte missing.
+            let available = buf.len();
+            *buf = &buf[available..];
+            return Some(in_element(Err(truncated("u8", 1, 0)), available));
+        }
+        Some(take_array(buf, "u8"))
+    }
 }
 
 macro_rules! impl_for_integer {
@@ -45,9 +405,14 @@ macro_rules! impl_for_integer {
             #[inline]
             fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
                 let mut data = [0u8; size_of::<$type>()];
-                reader.read_exact(&mut data)?;
+                read_exact(reader, &mut data, stringify!($type))?;
                 Ok($type::from_le_bytes(data))
             }
+
+            #[inline]
+            fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+                Ok($type::from_le_bytes(take_array(buf, stringify!($type))?))
+            }
         }
     };
 }
@@ -63,19 +428,47 @@ impl_for_integer!(u64);
 impl_for_integer!(u128);
 
 // Note NaNs have a portability issue. Specifically, signalling NaNs on MIPS are quiet NaNs on x86,
-// and vice-versa. We disallow NaNs to avoid this issue.
+// and vice-versa. We disallow NaNs to avoid this issue, unless the NaN policy says otherwise.
+/// Whether a decoded NaN is replaced by the canonical one under the
+/// [`NanPolicy`](options::NanPolicy) in effect, failing if the policy rejects NaNs, as it does by
+/// default and always without std.
+#[cold]
+pub(crate) fn canonical_nan() -> Result<bool, Error> {
+    if skip_checks() {
+        return Ok(false);
+    }
+    #[cfg(feature = "std")]
+    match options::nan_policy() {
+        options::NanPolicy::Reject => {}
+        options::NanPolicy::Accept => return Ok(false),
+        options::NanPolicy::Canonicalize => return Ok(true),
+    }
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        "For portability reasons we do not allow to deserialize NaNs.",
+    ))
+}
+
 macro_rules! impl_for_float {
-    ($type: ident, $int_type: ident) => {
+    ($type: ident, $int_type: ident, $canonical: expr) => {
         impl BorshDeserialize for $type {
             fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
                 let mut data = [0u8; size_of::<$type>()];
-                reader.read_exact(&mut data)?;
+                read_exact(reader, &mut data, stringify!($type))?;
+                let res = $type::from_bits($int_type::from_le_bytes(data));
+                if res.is_nan() && canonical_nan()? {
+                    return Ok($type::from_bits($canonical));
+                }
+                Ok(res)
+            }
+
+            #[inline]
+            fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+                let start = *buf;
+                let data = take_array(buf, stringify!($type))?;
                 let res = $type::from_bits($int_type::from_le_bytes(data));
-                if res.is_nan() {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "For portability reasons we do not allow to deserialize NaNs.",
-                    ));
+                if res.is_nan() && or_rewind(canonical_nan(), buf, start)? {
+                    return Ok($type::from_bits($canonical));
                 }
                 Ok(res)
             }
@@ -83,15 +476,35 @@ macro_rules! impl_for_float {
     };
 }
 
-impl_for_float!(f32, u32);
-impl_for_float!(f64, u64);
+impl_for_float!(f32, u32, spec::CANONICAL_NAN_F32);
+impl_for_float!(f64, u64, spec::CANONICAL_NAN_F64);
+
+/// The `bool` encoded as `byte`. Bytes other than 0 and 1 are an error unless
+/// [`strict_bools`](options::DecodeOptions::strict_bools) is off, which needs std.
+#[inline]
+fn decode_bool(byte: u8) -> Result<bool, Error> {
+    match byte {
+        spec::BOOL_FALSE => Ok(false),
+        spec::BOOL_TRUE => Ok(true),
+        #[cfg(feature = "std")]
+        _ if !options::strict_bools() => Ok(false),
+        byte => Err(crate::Error::InvalidBool(byte).into()),
+    }
+}
 
 impl BorshDeserialize for bool {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let mut buf = [0u8];
-        reader.read_exact(&mut buf)?;
-        Ok(buf[0] == 1)
+        read_exact(reader, &mut buf, "bool")?;
+        decode_bool(buf[0])
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let byte = take_array::<1>(buf, "bool")?[0];
+        or_rewind(decode_bool(byte), buf, start)
     }
 }
 
@@ -101,14 +514,33 @@ where
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let mut flag = [0u8];
-        reader.read_exact(&mut flag)?;
-        if flag[0] == 0 {
+        if read_tag(reader)? == spec::OPTION_NONE_TAG {
             Ok(None)
         } else {
             Ok(Some(T::deserialize(reader)?))
         }
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        if read_tag_slice(buf)? == spec::OPTION_NONE_TAG {
+            Ok(None)
+        } else {
+            Ok(Some(T::deserialize_slice(buf)?))
+        }
+    }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        if read_tag(reader)? == spec::OPTION_NONE_TAG {
+            *place = None;
+        } else if let Some(value) = place {
+            T::deserialize_in_place(reader, value)?;
+        } else {
+            *place = Some(T::deserialize(reader)?);
+        }
+        Ok(())
+    }
 }
 
 impl<T, E> BorshDeserialize for Result<T, E>
@@ -118,107 +550,504 @@ where
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let mut flag = [0u8];
-        reader.read_exact(&mut flag)?;
-        Ok(if flag[0] == 0 {
+        Ok(if read_tag(reader)? == spec::RESULT_OK_TAG {
             Ok(T::deserialize(reader)?)
         } else {
             Err(E::deserialize(reader)?)
         })
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(if read_tag_slice(buf)? == spec::RESULT_OK_TAG {
+            Ok(T::deserialize_slice(buf)?)
+        } else {
+            Err(E::deserialize_slice(buf)?)
+        })
+    }
+}
+
+/// Prefix the path of the error in `result` with `segment` of the type named `ty`, see
+/// [`crate::Error::Context`]. Called by derived decoders for each field.
+#[doc(hidden)]
+#[inline]
+pub fn in_field<T>(
+    result: Result<T, Error>,
+    ty: &'static str,
+    segment: &'static str,
+) -> Result<T, Error> {
+    result.map_err(|err| crate::Error::from(err).within(ty, segment).into())
+}
+
+/// Prefix the path of the error in `result` with the index of the element that raised it.
+#[inline]
+pub(crate) fn in_element<T>(result: Result<T, Error>, index: usize) -> Result<T, Error> {
+    result.map_err(|err| {
+        crate::Error::from(err)
+            .within("", &format!("[{}]", index))
+            .into()
+    })
+}
+
+/// Fail if the caller cancelled the decode, see `cancel`. Called before element `index` of a
+/// collection; the token is consulted every `cancel::INTERVAL` elements.
+#[inline]
+pub(crate) fn check_cancelled(_index: usize) -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    if _index.is_multiple_of(cancel::INTERVAL) {
+        return cancel::check();
+    }
+    Ok(())
+}
+
+fn unexpected_variant(variant_idx: u8) -> Error {
+    crate::Error::InvalidTag(variant_idx).into()
+}
+
+impl<B, C> BorshDeserialize for core::ops::ControlFlow<B, C>
+where
+    B: BorshDeserialize,
+    C: BorshDeserialize,
+{
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        match read_tag(reader)? {
+            0 => Ok(core::ops::ControlFlow::Continue(C::deserialize(reader)?)),
+            1 => Ok(core::ops::ControlFlow::Break(B::deserialize(reader)?)),
+            variant_idx => Err(unexpected_variant(variant_idx)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        match read_tag_slice(buf)? {
+            0 => Ok(core::ops::ControlFlow::Continue(C::deserialize_slice(buf)?)),
+            1 => Ok(core::ops::ControlFlow::Break(B::deserialize_slice(buf)?)),
+            variant_idx => or_rewind(Err(unexpected_variant(variant_idx)), buf, start),
+        }
+    }
+}
+
+impl<T> BorshDeserialize for core::task::Poll<T>
+where
+    T: BorshDeserialize,
+{
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        match read_tag(reader)? {
+            0 => Ok(core::task::Poll::Ready(T::deserialize(reader)?)),
+            1 => Ok(core::task::Poll::Pending),
+            variant_idx => Err(unexpected_variant(variant_idx)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        match read_tag_slice(buf)? {
+            0 => Ok(core::task::Poll::Ready(T::deserialize_slice(buf)?)),
+            1 => Ok(core::task::Poll::Pending),
+            variant_idx => or_rewind(Err(unexpected_variant(variant_idx)), buf, start),
+        }
+    }
+}
+
+impl BorshDeserialize for core::cmp::Ordering {
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        ordering(read_tag(reader)?)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let variant_idx = read_tag_slice(buf)?;
+        or_rewind(ordering(variant_idx), buf, start)
+    }
+}
+
+fn ordering(variant_idx: u8) -> Result<core::cmp::Ordering, Error> {
+    match variant_idx {
+        0 => Ok(core::cmp::Ordering::Less),
+        1 => Ok(core::cmp::Ordering::Equal),
+        2 => Ok(core::cmp::Ordering::Greater),
+        variant_idx => Err(unexpected_variant(variant_idx)),
+    }
+}
+
+impl<T> BorshDeserialize for core::ops::Bound<T>
+where
+    T: BorshDeserialize,
+{
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        match read_tag(reader)? {
+            0 => Ok(core::ops::Bound::Included(T::deserialize(reader)?)),
+            1 => Ok(core::ops::Bound::Excluded(T::deserialize(reader)?)),
+            2 => Ok(core::ops::Bound::Unbounded),
+            variant_idx => Err(unexpected_variant(variant_idx)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        match read_tag_slice(buf)? {
+            0 => Ok(core::ops::Bound::Included(T::deserialize_slice(buf)?)),
+            1 => Ok(core::ops::Bound::Excluded(T::deserialize_slice(buf)?)),
+            2 => Ok(core::ops::Bound::Unbounded),
+            variant_idx => or_rewind(Err(unexpected_variant(variant_idx)), buf, start),
+        }
+    }
+}
+
+impl<T: ?Sized> BorshDeserialize for core::marker::PhantomData<T> {
+    #[inline]
+    fn deserialize<R: Read>(_reader: &mut R) -> Result<Self, Error> {
+        Ok(core::marker::PhantomData)
+    }
+
+    #[inline]
+    fn deserialize_slice(_buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(core::marker::PhantomData)
+    }
+}
+
+impl BorshDeserialize for core::ops::RangeFull {
+    #[inline]
+    fn deserialize<R: Read>(_reader: &mut R) -> Result<Self, Error> {
+        Ok(..)
+    }
+
+    #[inline]
+    fn deserialize_slice(_buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(..)
+    }
 }
 
 impl BorshDeserialize for String {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let len = u32::deserialize(reader)?;
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::String)?;
         // TODO(16): return capacity allocation when we have the size of the buffer left from the reader.
-        let mut result = Vec::with_capacity(hint::cautious::<u8>(len));
-        for _ in 0..len {
-            result.push(u8::deserialize(reader)?);
+        let mut result = hint::try_with_capacity(hint::cautious::<u8>(len))?;
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            hint::try_push(&mut result, string_byte(reader, len, i)?)?;
         }
-        String::from_utf8(result)
-            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string()))
+        string_from_utf8(result)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let len = read_checked_len_slice(buf, LenKind::String)?;
+        let bytes = or_rewind(take_bytes(buf, len), buf, start)?;
+        let mut result = hint::try_with_capacity(bytes.len())?;
+        result.extend_from_slice(bytes);
+        or_rewind(string_from_utf8(result), buf, start)
+    }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::String)?;
+        let mut bytes = core::mem::take(place).into_bytes();
+        bytes.clear();
+        hint::try_reserve(&mut bytes, hint::cautious::<u8>(len))?;
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            hint::try_push(&mut bytes, string_byte(reader, len, i)?)?;
+        }
+        *place = string_from_utf8(bytes)?;
+        Ok(())
+    }
+}
+
+/// Read byte `index` of a string of `len` bytes.
+#[inline]
+fn string_byte<R: Read>(reader: &mut R, len: u32, index: usize) -> Result<u8, Error> {
+    relabel(u8::deserialize(reader), "string", |_, _| {
+        (len as usize, index)
+    })
+}
+
+/// Interned when decoded with [`DecodeOptions::intern_strings`](options::DecodeOptions).
+impl BorshDeserialize for Arc<str> {
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        String::deserialize(reader).map(shared_str)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        String::deserialize_slice(buf).map(shared_str)
     }
 }
 
 #[cfg(feature = "std")]
+fn shared_str(value: String) -> Arc<str> {
+    options::intern(value)
+}
+
+#[cfg(not(feature = "std"))]
+fn shared_str(value: String) -> Arc<str> {
+    Arc::from(value)
+}
+
+fn string_from_utf8(bytes: Vec<u8>) -> Result<String, Error> {
+    if skip_checks() {
+        // Only reachable through `unchecked::try_from_slice_unchecked`, whose caller vouches
+        // for the input.
+        return Ok(unsafe { String::from_utf8_unchecked(bytes) });
+    }
+    let string = String::from_utf8(bytes)
+        .map_err(|err| Error::from(crate::Error::InvalidUtf8(err.utf8_error())))?;
+    #[cfg(feature = "unicode-normalization")]
+    let string = options::normalize(string)?;
+    Ok(string)
+}
+
+/// Split a byte string of length `len` off `buf`. Unlike a reader, the slice tells whether all of
+/// it is there before anything is allocated, so it can be copied in one go.
+#[inline]
+fn take_bytes<'a>(buf: &mut &'a [u8], len: u32) -> Result<&'a [u8], Error> {
+    if buf.len() < len as usize {
+        hint::unreserved::<u8>(len);
+        return Err(truncated("string", len as usize, buf.len()));
+    }
+    hint::exact::<u8>(len);
+    take(buf, len as usize, "string")
+}
+
 impl<T> BorshDeserialize for Vec<T>
 where
     T: BorshDeserialize,
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let len = u32::deserialize(reader)?;
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
         if size_of::<T>() == 0 {
+            // A vector of zero-sized values never allocates, but each one is still decoded rather
+            // than copied from the first, as it may own something or count its drops.
+            hint::exact::<T>(len);
             let mut result = Vec::new();
-            result.push(T::deserialize(reader)?);
-
-            let p = result.as_mut_ptr();
-            unsafe {
-                forget(result);
-                let len = len as usize;
-                let result = Vec::from_raw_parts(p, len, len);
-                Ok(result)
+            for i in 0..len as usize {
+                check_cancelled(i)?;
+                result.push(in_element(T::deserialize(reader), i)?);
             }
+            Ok(result)
         } else {
             // TODO(16): return capacity allocation when we can safely do that.
-            let mut result = Vec::with_capacity(hint::cautious::<T>(len));
-            for _ in 0..len {
-                result.push(T::deserialize(reader)?);
+            let mut result = hint::try_with_capacity(hint::cautious::<T>(len))?;
+            for i in 0..len as usize {
+                check_cancelled(i)?;
+                hint::try_push(&mut result, in_element(T::deserialize(reader), i)?)?;
             }
             Ok(result)
         }
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        if size_of::<T>() == 0 {
+            return Self::deserialize(buf);
+        }
+        let len = read_checked_len_slice(buf, LenKind::Sequence)?;
+        let mut result = hint::try_with_capacity(hint::cautious::<T>(len))?;
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            hint::try_push(&mut result, in_element(T::deserialize_slice(buf), i)?)?;
+        }
+        Ok(result)
+    }
+
+    /// Elements already in `place` are decoded in place, then the rest are appended.
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        if size_of::<T>() == 0 {
+            *place = Self::deserialize(reader)?;
+            return Ok(());
+        }
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        let reused = core::cmp::min(len as usize, place.len());
+        place.truncate(reused);
+        hint::try_reserve(place, hint::cautious::<T>(len).saturating_sub(reused))?;
+        for (i, element) in place.iter_mut().enumerate() {
+            check_cancelled(i)?;
+            in_element(T::deserialize_in_place(reader, element), i)?;
+        }
+        for i in reused..len as usize {
+            check_cancelled(i)?;
+            hint::try_push(place, in_element(T::deserialize(reader), i)?)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "std")]
 impl<T, S> BorshDeserialize for HashSet<T, S>
 where
-    T: BorshDeserialize + Eq + std::hash::Hash,
+    T: BorshDeserialize + Eq + core::hash::Hash,
     S: std::hash::BuildHasher + Default,
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let vec = <Vec<T>>::deserialize(reader)?;
-        Ok(vec.into_iter().collect())
+        collect_set(<Vec<T>>::deserialize(reader)?)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let elements = <Vec<T>>::deserialize_slice(buf)?;
+        or_rewind(collect_set(elements), buf, start)
     }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        hint::unreserved::<T>(len);
+        place.clear();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let element = in_element(T::deserialize(reader), i)?;
+            hint::try_grow_set(place)?;
+            in_element(check_unique(place.insert(element)), i)?;
+        }
+        Ok(())
+    }
+}
+
+/// The elements of `vec` in a set, whose table is allocated fallibly.
+#[cfg(feature = "std")]
+fn collect_set<T, S>(vec: Vec<T>) -> Result<HashSet<T, S>, Error>
+where
+    T: Eq + core::hash::Hash,
+    S: std::hash::BuildHasher + Default,
+{
+    let len = vec.len();
+    let mut set = HashSet::default();
+    hint::charge::<T>(len)?;
+    set.try_reserve(len)
+        .map_err(|err| hint::allocation_failed::<T>(err, len))?;
+    set.extend(vec);
+    check_unique(set.len() == len)?;
+    Ok(set)
 }
 
 #[cfg(feature = "std")]
 impl<K, V, S> BorshDeserialize for HashMap<K, V, S>
 where
-    K: BorshDeserialize + Eq + std::hash::Hash,
+    K: BorshDeserialize + Eq + core::hash::Hash,
     V: BorshDeserialize,
     S: std::hash::BuildHasher + Default,
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let len = u32::deserialize(reader)?;
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Map)?;
         // TODO(16): return capacity allocation when we can safely do that.
+        hint::unreserved::<(K, V)>(len);
         let mut result = HashMap::default();
-        for _ in 0..len {
+        for i in 0..len as usize {
+            check_cancelled(i)?;
             let key = K::deserialize(reader)?;
             let value = V::deserialize(reader)?;
-            result.insert(key, value);
+            hint::try_grow_map(&mut result)?;
+            check_unique(result.insert(key, value).is_none())?;
         }
         Ok(result)
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let len = read_checked_len_slice(buf, LenKind::Map)?;
+        hint::unreserved::<(K, V)>(len);
+        let mut result = HashMap::default();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let entry = *buf;
+            let key = K::deserialize_slice(buf)?;
+            let value = V::deserialize_slice(buf)?;
+            hint::try_grow_map(&mut result)?;
+            or_rewind(
+                check_unique(result.insert(key, value).is_none()),
+                buf,
+                entry,
+            )?;
+        }
+        Ok(result)
+    }
+
+    /// Clears `place` and decodes into it, keeping the capacity of its table.
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Map)?;
+        hint::unreserved::<(K, V)>(len);
+        place.clear();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let key = K::deserialize(reader)?;
+            let value = V::deserialize(reader)?;
+            hint::try_grow_map(place)?;
+            check_unique(place.insert(key, value).is_none())?;
+        }
+        Ok(())
+    }
 }
 
-#[cfg(feature = "std")]
 impl<T> BorshDeserialize for BTreeSet<T>
 where
     T: BorshDeserialize + Ord,
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let vec = <Vec<T>>::deserialize(reader)?;
-        Ok(vec.into_iter().collect())
+        collect_tree(<Vec<T>>::deserialize(reader)?)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let elements = <Vec<T>>::deserialize_slice(buf)?;
+        or_rewind(collect_tree(elements), buf, start)
+    }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        hint::unreserved::<T>(len);
+        place.clear();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let element = in_element(T::deserialize(reader), i)?;
+            in_element(
+                check_ascending(|| place.last().is_none_or(|last| *last < element)),
+                i,
+            )?;
+            hint::charge::<T>(1)?;
+            in_element(check_unique(place.insert(element)), i)?;
+        }
+        Ok(())
     }
 }
 
-#[cfg(feature = "std")]
+/// The elements of `vec` in a B-tree set, whose nodes are charged to the allocation budget.
+fn collect_tree<T: Ord>(vec: Vec<T>) -> Result<BTreeSet<T>, Error> {
+    let len = vec.len();
+    check_ascending(|| vec.windows(2).all(|pair| pair[0] < pair[1]))?;
+    hint::charge::<T>(len)?;
+    let set: BTreeSet<T> = vec.into_iter().collect();
+    check_unique(set.len() == len)?;
+    Ok(set)
+}
+
 impl<K, V> BorshDeserialize for BTreeMap<K, V>
 where
     K: BorshDeserialize + Ord,
@@ -226,27 +1055,75 @@ where
 {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let len = u32::deserialize(reader)?;
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Map)?;
+        hint::unreserved::<(K, V)>(len);
         let mut result = BTreeMap::new();
-        for _ in 0..len {
+        for i in 0..len as usize {
+            check_cancelled(i)?;
             let key = K::deserialize(reader)?;
             let value = V::deserialize(reader)?;
-            result.insert(key, value);
+            check_ascending(|| after_last(&result, &key))?;
+            hint::charge::<(K, V)>(1)?;
+            check_unique(result.insert(key, value).is_none())?;
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let len = read_checked_len_slice(buf, LenKind::Map)?;
+        hint::unreserved::<(K, V)>(len);
+        let mut result = BTreeMap::new();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let entry = *buf;
+            let key = K::deserialize_slice(buf)?;
+            let value = V::deserialize_slice(buf)?;
+            or_rewind(check_ascending(|| after_last(&result, &key)), buf, entry)?;
+            hint::charge::<(K, V)>(1)?;
+            or_rewind(
+                check_unique(result.insert(key, value).is_none()),
+                buf,
+                entry,
+            )?;
         }
         Ok(result)
     }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Map)?;
+        hint::unreserved::<(K, V)>(len);
+        place.clear();
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            let key = K::deserialize(reader)?;
+            let value = V::deserialize(reader)?;
+            check_ascending(|| after_last(place, &key))?;
+            hint::charge::<(K, V)>(1)?;
+            check_unique(place.insert(key, value).is_none())?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `key` sorts after all the keys of `map`.
+fn after_last<K: Ord, V>(map: &BTreeMap<K, V>, key: &K) -> bool {
+    map.last_key_value().is_none_or(|(last, _)| last < key)
 }
 
 #[cfg(feature = "std")]
 impl BorshDeserialize for std::net::SocketAddr {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let kind = u8::deserialize(reader)?;
+        let kind = read_tag(reader)?;
         match kind {
             0 => std::net::SocketAddrV4::deserialize(reader).map(std::net::SocketAddr::V4),
             1 => std::net::SocketAddrV6::deserialize(reader).map(std::net::SocketAddr::V6),
-            value => Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
+            value => Err(Error::new(
+                ErrorKind::InvalidInput,
                 format!("Invalid SocketAddr variant: {}", value),
             )),
         }
@@ -278,9 +1155,14 @@ impl BorshDeserialize for std::net::Ipv4Addr {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let mut buf = [0u8; 4];
-        reader.read_exact(&mut buf)?;
+        read_exact(reader, &mut buf, "Ipv4Addr")?;
         Ok(std::net::Ipv4Addr::from(buf))
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(std::net::Ipv4Addr::from(take_array::<4>(buf, "Ipv4Addr")?))
+    }
 }
 
 #[cfg(feature = "std")]
@@ -288,68 +1170,135 @@ impl BorshDeserialize for std::net::Ipv6Addr {
     #[inline]
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
         let mut buf = [0u8; 16];
-        reader.read_exact(&mut buf)?;
+        read_exact(reader, &mut buf, "Ipv6Addr")?;
         Ok(std::net::Ipv6Addr::from(buf))
     }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(std::net::Ipv6Addr::from(take_array::<16>(buf, "Ipv6Addr")?))
+    }
 }
 
-impl BorshDeserialize for Box<[u8]> {
+impl<T: BorshDeserialize> BorshDeserialize for Box<[T]> {
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-        let len = u32::deserialize(reader)?;
-        // TODO(16): return capacity allocation when we can safely do that.
-        let mut result = Vec::with_capacity(hint::cautious::<u8>(len));
-        for _ in 0..len {
-            result.push(u8::deserialize(reader)?);
-        }
-        Ok(result.into_boxed_slice())
+        Ok(Vec::<T>::deserialize(reader)?.into_boxed_slice())
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Vec::<T>::deserialize_slice(buf)?.into_boxed_slice())
     }
 }
 
-macro_rules! impl_arrays {
-    ($($len:expr => ($($n:expr)+))+) => {
-        $(
-            impl<T: BorshDeserialize> BorshDeserialize for [T; $len] {
-                #[inline]
-                fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
-                    // As byte arrays are packed in borsh, this generic implementation should
-                    // produce the same code as an unrolled `reader.read_exact($len)`.
-                    Ok([$(
-                        T::deserialize(reader)
-                        .map_err(|e|
-                            Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!("error deserializing element at index {}: {}", $n, e)
-                            )
-                        )?
-                    ),+])
-                }
-            }
-        )+
-    };
+impl BorshDeserialize for Box<str> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(String::deserialize(reader)?.into_boxed_str())
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(String::deserialize_slice(buf)?.into_boxed_str())
+    }
 }
 
-impl<T: BorshDeserialize> BorshDeserialize for [T; 0] {
-    fn deserialize<R: Read>(_reader: &mut R) -> Result<Self, Error> {
-        Ok([])
+impl<T: BorshDeserialize> BorshDeserialize for Box<T> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Box::new(T::deserialize(reader)?))
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Ok(Box::new(T::deserialize_slice(buf)?))
+    }
+
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        T::deserialize_in_place(reader, place)
     }
 }
 
-oasis_borsh_derive::_gen_seq_macro! {
-    impl_arrays => (1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 32 64 65)
+impl<T: BorshDeserialize, const N: usize> BorshDeserialize for [T; N] {
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        read_array(|| T::deserialize(reader))
+    }
+
+    #[inline]
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        match T::array_from_slice(buf) {
+            Some(result) => result,
+            None => read_array(|| T::deserialize_slice(buf)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_in_place<R: Read>(reader: &mut R, place: &mut Self) -> Result<(), Error> {
+        for (i, element) in place.iter_mut().enumerate() {
+            in_element(T::deserialize_in_place(reader, element), i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the `N` elements of an array with `read`, naming the index of the element that failed.
+fn read_array<T, F, const N: usize>(mut read: F) -> Result<[T; N], Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    /// An array whose first `len` elements are initialized, and dropped if decoding fails.
+    struct Partial<T, const N: usize> {
+        elements: [MaybeUninit<T>; N],
+        len: usize,
+    }
+
+    impl<T, const N: usize> Drop for Partial<T, N> {
+        fn drop(&mut self) {
+            for element in &mut self.elements[..self.len] {
+                unsafe { element.assume_init_drop() };
+            }
+        }
+    }
+
+    let mut partial = Partial::<T, N> {
+        // An array of `MaybeUninit` is valid uninitialized.
+        elements: unsafe { MaybeUninit::uninit().assume_init() },
+        len: 0,
+    };
+    while partial.len < N {
+        partial.elements[partial.len] = MaybeUninit::new(in_element(read(), partial.len)?);
+        partial.len += 1;
+    }
+    // All `N` elements are initialized, and the guard is forgotten so they are not dropped.
+    let partial = ManuallyDrop::new(partial);
+    Ok(unsafe { ptr::read(partial.elements.as_ptr() as *const [T; N]) })
 }
 
 macro_rules! impl_tuples {
     ($($len:literal => ($($name:ident)+))+) => {
         $(
-            impl<$($name: BorshDeserialize),+> BorshDeserialize for ($($name),+) {
+            impl<$($name: BorshDeserialize),+> BorshDeserialize for ($($name,)+) {
                 fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
                     Ok(($($name::deserialize(reader)?,)+))
                 }
+
+                fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+                    Ok(($($name::deserialize_slice(buf)?,)+))
+                }
+
+                #[allow(non_snake_case)]
+                fn deserialize_in_place<R: Read>(
+                    reader: &mut R,
+                    place: &mut Self,
+                ) -> Result<(), Error> {
+                    let ($($name,)+) = place;
+                    $($name::deserialize_in_place(reader, $name)?;)+
+                    Ok(())
+                }
             }
         )*
     }
 }
 
 oasis_borsh_derive::_gen_seq_macro! {
-    impl_tuples => T :: (2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19)
+    impl_tuples => T :: (
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+        17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    )
 }
@@ -0,0 +1,670 @@
+//! Settings of the decoders that callers can change for the duration of a call.
+//!
+//! Decoders are plain trait methods, so settings reach them through a thread-local installed by
+//! [`DecodeOptions::run`] and its shorthands, rather than through arguments. Calls may be nested;
+//! the innermost options apply and the outer ones are restored when the call returns or panics.
+//!
+//! ```
+//! use oasis_borsh::de::options::DecodeOptions;
+//!
+//! let bytes = vec![7u64; 100_000];
+//! let encoded = oasis_borsh::BorshSerialize::try_to_vec(&bytes).unwrap();
+//! // A snapshot written by this process: reserve the whole vector up front.
+//! let decoded: Vec<u64> = DecodeOptions::trusted().try_from_slice(&encoded).unwrap();
+//! assert_eq!(decoded, bytes);
+//! ```
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::io::{Error, Read};
+use std::sync::Arc;
+
+#[cfg(feature = "unicode-normalization")]
+use std::io::ErrorKind;
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+use super::{BorshDeserialize, LenKind};
+
+/// The default of [`DecodeOptions::max_preallocation`].
+pub const DEFAULT_MAX_PREALLOCATION: usize = 4096;
+
+/// The default of [`DecodeOptions::max_depth`], deep enough for any reasonable data and shallow
+/// enough for derived decoders to fit in the 2 MiB stack of a spawned thread.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+thread_local! {
+    static MAX_PREALLOCATION: Cell<usize> = const { Cell::new(DEFAULT_MAX_PREALLOCATION) };
+    static MAX_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_DEPTH) };
+    static LIMITS: Cell<Limits> = const { Cell::new(Limits::NONE) };
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static ELEMENTS: Cell<u64> = const { Cell::new(0) };
+    static STRICT_BOOLS: Cell<bool> = const { Cell::new(true) };
+    static REJECT_DUPLICATE_KEYS: Cell<bool> = const { Cell::new(false) };
+    static REQUIRE_SORTED_KEYS: Cell<bool> = const { Cell::new(false) };
+    static NAN_POLICY: Cell<NanPolicy> = const { Cell::new(NanPolicy::Reject) };
+    static INTERNED: RefCell<Option<HashSet<Arc<str>>>> = const { RefCell::new(None) };
+    #[cfg(feature = "unicode-normalization")]
+    static NORMALIZATION: Cell<Normalization> = const { Cell::new(Normalization::Keep) };
+}
+
+/// The preallocation limit in effect on this thread.
+#[inline]
+pub(crate) fn max_preallocation() -> usize {
+    MAX_PREALLOCATION.with(|cell| cell.get())
+}
+
+/// The nesting limit in effect on this thread.
+#[inline]
+pub(crate) fn max_depth() -> usize {
+    MAX_DEPTH.with(|cell| cell.get())
+}
+
+/// The length limit in effect on this thread for a collection of `kind`.
+#[inline]
+pub(crate) fn max_len(kind: LenKind) -> u32 {
+    LIMITS.with(|cell| {
+        let limits = cell.get();
+        match kind {
+            LenKind::Sequence => limits.max_sequence_len,
+            LenKind::String => limits.max_string_len,
+            LenKind::Map => limits.max_map_entries,
+        }
+    })
+}
+
+/// Whether `bool`s encoded as bytes other than 0 or 1 are rejected on this thread.
+#[inline]
+pub(crate) fn strict_bools() -> bool {
+    STRICT_BOOLS.with(|cell| cell.get())
+}
+
+/// Whether maps and sets that repeat a key fail to decode on this thread.
+#[inline]
+pub(crate) fn reject_duplicate_keys() -> bool {
+    REJECT_DUPLICATE_KEYS.with(|cell| cell.get())
+}
+
+/// Whether B-tree maps and sets whose keys are out of order fail to decode on this thread.
+#[inline]
+pub(crate) fn require_sorted_keys() -> bool {
+    REQUIRE_SORTED_KEYS.with(|cell| cell.get())
+}
+
+/// The NaN policy in effect on this thread.
+#[inline]
+pub(crate) fn nan_policy() -> NanPolicy {
+    NAN_POLICY.with(|cell| cell.get())
+}
+
+/// Count `bytes` about to be allocated against the allocation budget in effect on this thread,
+/// failing with [`crate::Error::OverBudget`] if they do not fit in what is left of it.
+#[inline]
+pub(crate) fn charge(bytes: usize) -> Result<(), Error> {
+    let budget = LIMITS.with(|cell| cell.get().max_allocation);
+    if budget == usize::MAX {
+        return Ok(());
+    }
+    ALLOCATED.with(|cell| {
+        let allocated = cell.get().saturating_add(bytes);
+        if allocated > budget {
+            return Err(crate::Error::OverBudget { budget }.into());
+        }
+        cell.set(allocated);
+        Ok(())
+    })
+}
+
+/// Count the `len` elements or entries a collection declared against the element budget in
+/// effect on this thread, failing with [`crate::Error::TooManyElements`] if they do not fit in
+/// what is left of it.
+#[inline]
+pub(crate) fn charge_elements(len: u64) -> Result<(), Error> {
+    let budget = LIMITS.with(|cell| cell.get().max_elements);
+    if budget == u64::MAX {
+        return Ok(());
+    }
+    ELEMENTS.with(|cell| {
+        let elements = cell.get().saturating_add(len);
+        if elements > budget {
+            return Err(crate::Error::TooManyElements { budget }.into());
+        }
+        cell.set(elements);
+        Ok(())
+    })
+}
+
+/// `value` as an `Arc<str>`, shared with the equal strings decoded before it if interning is on.
+pub(crate) fn intern(value: String) -> Arc<str> {
+    INTERNED.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(interned) => match interned.get(value.as_str()) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared = Arc::<str>::from(value);
+                interned.insert(shared.clone());
+                shared
+            }
+        },
+        None => Arc::from(value),
+    })
+}
+
+/// What happens to NaN floats, which borsh rejects by default because they are not portable:
+/// some platforms read the quiet NaNs of others as signalling ones and the other way around, and
+/// the payload bits of a NaN rarely survive arithmetic.
+///
+/// Telemetry and scientific data use NaN for missing or undefined values, and can accept NaNs as
+/// they are or have every NaN replaced by one canonical NaN,
+/// [`spec::CANONICAL_NAN_F32`](crate::spec::CANONICAL_NAN_F32) or
+/// [`CANONICAL_NAN_F64`](crate::spec::CANONICAL_NAN_F64), so that all NaNs encode alike:
+///
+/// ```
+/// use oasis_borsh::de::options::NanPolicy;
+/// use oasis_borsh::{BorshDeserialize, BorshSerialize};
+///
+/// let readings = vec![1.5f64, f64::NAN];
+/// let bytes = NanPolicy::Accept.run(|| readings.try_to_vec()).unwrap();
+/// let decoded: Vec<f64> = NanPolicy::Accept.run(|| Vec::try_from_slice(&bytes)).unwrap();
+/// assert!(decoded[1].is_nan());
+/// assert!(Vec::<f64>::try_from_slice(&bytes).is_err());
+/// ```
+///
+/// The policy applies to encoding as well as decoding, where rejecting a NaN panics. Types that
+/// exclude NaN, like [`CanonicalF64`](crate::canonical_float::CanonicalF64), reject it whatever
+/// the policy.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum NanPolicy {
+    /// NaNs fail to decode with `ErrorKind::InvalidInput`, and encoding one panics. The default.
+    #[default]
+    Reject,
+    /// NaNs are encoded and decoded bit for bit.
+    Accept,
+    /// NaNs are encoded and decoded as the canonical NaN.
+    Canonicalize,
+}
+
+impl NanPolicy {
+    /// Run `f` with this policy applied to all encoding and decoding on this thread, leaving the
+    /// other options in effect as they are.
+    pub fn run<F, T>(self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore(NanPolicy);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let policy = self.0;
+                NAN_POLICY.with(|cell| cell.set(policy));
+            }
+        }
+
+        let _restore = Restore(NAN_POLICY.with(|cell| cell.replace(self)));
+        f()
+    }
+}
+
+/// What happens to decoded strings that are not in Unicode Normalization Form C.
+///
+/// Text that looks the same can be encoded by different sequences of code points, e.g. "é" as one
+/// precomposed code point or as "e" followed by a combining accent. Fields that are signed or
+/// compared byte for byte need one canonical form, and NFC is the usual choice.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Normalization {
+    /// Strings are decoded as they are.
+    Keep,
+    /// Strings are converted to NFC while decoding.
+    Nfc,
+    /// Strings that are not in NFC fail to decode with `ErrorKind::InvalidData`.
+    RequireNfc,
+}
+
+/// Apply the normalization in effect on this thread to a decoded string.
+#[cfg(feature = "unicode-normalization")]
+pub(crate) fn normalize(value: String) -> Result<String, Error> {
+    let normalization = NORMALIZATION.with(|cell| cell.get());
+    if normalization == Normalization::Keep || is_nfc_quick(value.chars()) == IsNormalized::Yes {
+        return Ok(value);
+    }
+    let normalized = value.nfc().collect::<String>();
+    if normalized == value {
+        return Ok(value);
+    }
+    match normalization {
+        Normalization::RequireNfc => Err(Error::new(
+            ErrorKind::InvalidData,
+            "String is not in Unicode normalization form C",
+        )),
+        _ => Ok(normalized),
+    }
+}
+
+/// The largest lengths decoded collections may declare, for input from untrusted peers.
+///
+/// A length prefix costs its sender four bytes, and even with bounded preallocation a prefix of
+/// `0xFFFFFFFF` makes the decoder try to decode four billion elements before it finds the input
+/// too short, or allocate as much if the elements are tiny and the input long. With limits, a
+/// length over its limit fails with [`crate::Error::TooLong`] as soon as it is read:
+///
+/// ```
+/// use oasis_borsh::de::options::Limits;
+/// use oasis_borsh::BorshDeserialize;
+///
+/// let limits = Limits::NONE.max_sequence_len(1000);
+/// let bytes = [0xff, 0xff, 0xff, 0xff];
+/// let err = Vec::<u8>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "at offset 0: sequence of length 4294967295 exceeds the limit of 1000"
+/// );
+/// ```
+///
+/// Sets and `Vec`s count as sequences, and strings are limited in bytes.
+///
+/// Lengths that are each within their limit can still multiply: a vector of a thousand vectors
+/// of a thousand strings of a thousand bytes is a gigabyte.
+/// [`max_allocation`](Self::max_allocation) bounds the memory decoding reserves for vectors,
+/// strings, sets and maps in total, across everything decoded in one [`run`](Self::run), and
+/// decoding fails with [`crate::Error::OverBudget`] before it reserves more. Memory is counted as
+/// collections grow, in elements of capacity times the size of the element type, and is not given
+/// back when a decoded value is dropped; what the elements own, like the heap part of a `Box`, is
+/// not counted unless it is itself one of these collections.
+///
+/// Small collections can also multiply into more work than memory: a million empty vectors take
+/// little space, and a vector of four billion `()` none at all, but decoding them takes as many
+/// steps. [`max_elements`](Self::max_elements) bounds the number of elements and entries of all
+/// the sequences and maps decoded in one run, counted as their lengths are read, and decoding
+/// fails with [`crate::Error::TooManyElements`] past it. The bytes of strings are not counted.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Limits {
+    /// Maximum number of elements of a vector, set or other sequence.
+    pub max_sequence_len: u32,
+    /// Maximum number of bytes of a string.
+    pub max_string_len: u32,
+    /// Maximum number of entries of a map.
+    pub max_map_entries: u32,
+    /// Maximum number of bytes reserved for decoded collections, in total.
+    pub max_allocation: usize,
+    /// Maximum number of elements and entries of all sequences and maps, in total.
+    pub max_elements: u64,
+}
+
+impl Limits {
+    /// No limits beyond those of the encoding, the default.
+    pub const NONE: Limits = Limits {
+        max_sequence_len: u32::MAX,
+        max_string_len: u32::MAX,
+        max_map_entries: u32::MAX,
+        max_allocation: usize::MAX,
+        max_elements: u64::MAX,
+    };
+
+    /// Limits that leave room for any reasonable message: 16 Mi elements, 16 MiB strings, 1 Mi
+    /// map entries, 256 MiB of memory and 64 Mi elements in total.
+    pub const UNTRUSTED: Limits = Limits {
+        max_sequence_len: 1 << 24,
+        max_string_len: 1 << 24,
+        max_map_entries: 1 << 20,
+        max_allocation: 1 << 28,
+        max_elements: 1 << 26,
+    };
+
+    /// Limits for targets with little memory: 64 Ki elements, bytes and entries, 1 MiB of
+    /// memory and 256 Ki elements in total.
+    pub const EMBEDDED: Limits = Limits {
+        max_sequence_len: 1 << 16,
+        max_string_len: 1 << 16,
+        max_map_entries: 1 << 16,
+        max_allocation: 1 << 20,
+        max_elements: 1 << 18,
+    };
+
+    pub fn max_sequence_len(mut self, len: u32) -> Self {
+        self.max_sequence_len = len;
+        self
+    }
+
+    pub fn max_string_len(mut self, len: u32) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    pub fn max_map_entries(mut self, len: u32) -> Self {
+        self.max_map_entries = len;
+        self
+    }
+
+    pub fn max_allocation(mut self, bytes: usize) -> Self {
+        self.max_allocation = bytes;
+        self
+    }
+
+    pub fn max_elements(mut self, elements: u64) -> Self {
+        self.max_elements = elements;
+        self
+    }
+
+    /// Run `f` with these limits applied to all decoding on this thread, leaving the other
+    /// options in effect as they are. `f` starts with the whole allocation and element budgets.
+    pub fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore(Limits, usize, u64);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let (limits, allocated, elements) = (self.0, self.1, self.2);
+                LIMITS.with(|cell| cell.set(limits));
+                ALLOCATED.with(|cell| cell.set(allocated));
+                ELEMENTS.with(|cell| cell.set(elements));
+            }
+        }
+
+        let _restore = Restore(
+            LIMITS.with(|cell| cell.replace(*self)),
+            ALLOCATED.with(|cell| cell.replace(0)),
+            ELEMENTS.with(|cell| cell.replace(0)),
+        );
+        f()
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::NONE
+    }
+}
+
+/// Vetted combinations of [`DecodeOptions`], for callers who want safe settings for their kind of
+/// input without weighing every option.
+///
+/// ```
+/// use oasis_borsh::de::options::{DecodeOptions, Profile};
+///
+/// let bytes = oasis_borsh::BorshSerialize::try_to_vec(&vec![1u32, 2, 3]).unwrap();
+/// let decoded: Vec<u32> = DecodeOptions::from(Profile::Consensus)
+///     .try_from_slice(&bytes)
+///     .unwrap();
+/// assert_eq!(decoded, [1, 2, 3]);
+/// ```
+///
+/// Each profile sets every option, so that the settings of a profile only change when this
+/// crate adds options, and then deliberately.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub enum Profile {
+    /// Input from untrusted peers whose decoding must agree byte for byte between nodes, such as
+    /// transactions and blocks: bounded allocations, and only canonical encodings accepted.
+    Consensus,
+    /// Input from untrusted peers that is only displayed or stored, such as user-provided
+    /// metadata: bounded allocations, and encodings that have a canonical equivalent fixed up
+    /// rather than rejected. `bool`s other than 0 or 1 decode as `false`, and NaNs as the canonical
+    /// NaN.
+    Lenient,
+    /// Targets with little memory: small allocations up front, and repeated strings shared.
+    Embedded,
+    /// Input this process or a trusted party wrote, such as snapshots: decoded as fast as
+    /// possible, and NaNs accepted as they are, see [`DecodeOptions::trusted`].
+    Trusted,
+}
+
+impl Profile {
+    /// The options this profile stands for.
+    pub fn options(self) -> DecodeOptions {
+        match self {
+            Profile::Consensus => DecodeOptions {
+                max_preallocation: DEFAULT_MAX_PREALLOCATION,
+                max_depth: DEFAULT_MAX_DEPTH,
+                limits: Limits::UNTRUSTED,
+                strict_bools: true,
+                reject_duplicate_keys: true,
+                require_sorted_keys: true,
+                nan_policy: NanPolicy::Reject,
+                intern_strings: false,
+                #[cfg(feature = "unicode-normalization")]
+                normalization: Normalization::RequireNfc,
+            },
+            Profile::Lenient => DecodeOptions {
+                max_preallocation: DEFAULT_MAX_PREALLOCATION,
+                max_depth: DEFAULT_MAX_DEPTH,
+                limits: Limits::UNTRUSTED,
+                strict_bools: false,
+                reject_duplicate_keys: false,
+                require_sorted_keys: false,
+                nan_policy: NanPolicy::Canonicalize,
+                intern_strings: false,
+                #[cfg(feature = "unicode-normalization")]
+                normalization: Normalization::Nfc,
+            },
+            Profile::Embedded => DecodeOptions {
+                max_preallocation: 256,
+                max_depth: 64,
+                limits: Limits::EMBEDDED,
+                strict_bools: true,
+                reject_duplicate_keys: false,
+                require_sorted_keys: false,
+                nan_policy: NanPolicy::Reject,
+                intern_strings: true,
+                #[cfg(feature = "unicode-normalization")]
+                normalization: Normalization::Keep,
+            },
+            Profile::Trusted => DecodeOptions {
+                max_preallocation: usize::MAX,
+                max_depth: usize::MAX,
+                limits: Limits::NONE,
+                strict_bools: true,
+                reject_duplicate_keys: false,
+                require_sorted_keys: false,
+                nan_policy: NanPolicy::Accept,
+                intern_strings: false,
+                #[cfg(feature = "unicode-normalization")]
+                normalization: Normalization::Keep,
+            },
+        }
+    }
+}
+
+impl From<Profile> for DecodeOptions {
+    fn from(profile: Profile) -> Self {
+        profile.options()
+    }
+}
+
+/// How decoders treat their input.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DecodeOptions {
+    /// How many bytes a collection may reserve up front based on the length prefix read from the
+    /// input. Longer collections grow as their elements are actually decoded.
+    ///
+    /// A length prefix costs its sender four bytes, so on untrusted input a large limit lets a
+    /// peer make the decoder reserve up to this much memory per collection for next to nothing.
+    /// Input that is trusted, e.g. a snapshot this process wrote, decodes faster with no limit,
+    /// as collections then never reallocate.
+    pub max_preallocation: usize,
+    /// How many levels deep derived types, and others that call [`enter`](super::enter), may be
+    /// nested, so that deeply nested input fails to decode rather than overflowing the stack.
+    pub max_depth: usize,
+    /// The largest lengths collections may declare, and the memory they may take in total.
+    pub limits: Limits,
+    /// Whether a `bool` encoded as a byte other than 0 or 1 fails to decode with
+    /// [`crate::Error::InvalidBool`], as it does by default, rather than decoding as `false`.
+    ///
+    /// Turning this off accepts what versions that did not check wrote, but lets many encodings
+    /// stand for one value, which breaks anything that compares or hashes encodings.
+    pub strict_bools: bool,
+    /// Whether a map or set that holds the same key more than once fails to decode with
+    /// [`crate::Error::DuplicateKey`], rather than keeping the last entry with that key.
+    ///
+    /// Off by default, as encoders other than this crate's may write duplicates, but input whose
+    /// encoding must be unique, such as anything that is signed or hashed, should reject them.
+    pub reject_duplicate_keys: bool,
+    /// Whether a `BTreeMap` or `BTreeSet` whose keys are not in strictly ascending order, the
+    /// order they are encoded in, fails to decode with [`crate::Error::UnsortedKeys`], so that
+    /// each of them has exactly one encoding. This also rejects duplicate keys.
+    ///
+    /// The order is the `Ord` of the keys, which for integers wider than a byte is numeric rather
+    /// than the order of their little-endian bytes. `HashMap` and `HashSet` are not checked; use
+    /// `schema::value::validate` to check them against a schema.
+    pub require_sorted_keys: bool,
+    /// What happens to NaN floats, which are rejected by default. The policy also applies to
+    /// encoding inside [`run`](DecodeOptions::run).
+    pub nan_policy: NanPolicy,
+    /// Whether equal `Arc<str>` values decoded within one [`run`](DecodeOptions::run) share a
+    /// single allocation, which saves memory on payloads that repeat the same strings, such as
+    /// symbol or field names, many times. The strings are kept for the duration of the call.
+    pub intern_strings: bool,
+    /// What happens to decoded strings that are not in NFC, including those inside `Arc<str>`
+    /// and `Box<str>`.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalization: Normalization,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            max_preallocation: DEFAULT_MAX_PREALLOCATION,
+            max_depth: DEFAULT_MAX_DEPTH,
+            limits: Limits::NONE,
+            strict_bools: true,
+            reject_duplicate_keys: false,
+            require_sorted_keys: false,
+            nan_policy: NanPolicy::Reject,
+            intern_strings: false,
+            #[cfg(feature = "unicode-normalization")]
+            normalization: Normalization::Keep,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Options for trusted input: collections reserve their full declared length.
+    ///
+    /// Only the preallocation limit is lifted; [`Profile::Trusted`] relaxes the other checks too.
+    pub fn trusted() -> Self {
+        DecodeOptions {
+            max_preallocation: usize::MAX,
+            ..DecodeOptions::default()
+        }
+    }
+
+    /// Set the preallocation limit in bytes.
+    pub fn max_preallocation(mut self, bytes: usize) -> Self {
+        self.max_preallocation = bytes;
+        self
+    }
+
+    /// Set the nesting limit.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set the length limits.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Turn the rejection of `bool`s other than 0 or 1 on or off.
+    pub fn strict_bools(mut self, strict: bool) -> Self {
+        self.strict_bools = strict;
+        self
+    }
+
+    /// Turn the rejection of maps and sets that repeat a key on or off.
+    pub fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+        self.reject_duplicate_keys = reject;
+        self
+    }
+
+    /// Turn the rejection of B-tree maps and sets with keys out of order on or off.
+    pub fn require_sorted_keys(mut self, require: bool) -> Self {
+        self.require_sorted_keys = require;
+        self
+    }
+
+    /// Set what happens to NaN floats.
+    pub fn nan_policy(mut self, policy: NanPolicy) -> Self {
+        self.nan_policy = policy;
+        self
+    }
+
+    /// Turn interning of decoded `Arc<str>` values on or off.
+    pub fn intern_strings(mut self, intern: bool) -> Self {
+        self.intern_strings = intern;
+        self
+    }
+
+    /// Set what happens to decoded strings that are not in NFC.
+    #[cfg(feature = "unicode-normalization")]
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Run `f` with these options applied to all decoding on this thread.
+    pub fn run<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore {
+            max_preallocation: usize,
+            max_depth: usize,
+            strict_bools: bool,
+            reject_duplicate_keys: bool,
+            require_sorted_keys: bool,
+            interned: Option<HashSet<Arc<str>>>,
+            #[cfg(feature = "unicode-normalization")]
+            normalization: Normalization,
+        }
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let max_preallocation = self.max_preallocation;
+                MAX_PREALLOCATION.with(|cell| cell.set(max_preallocation));
+                let max_depth = self.max_depth;
+                MAX_DEPTH.with(|cell| cell.set(max_depth));
+                let strict_bools = self.strict_bools;
+                STRICT_BOOLS.with(|cell| cell.set(strict_bools));
+                let reject_duplicate_keys = self.reject_duplicate_keys;
+                REJECT_DUPLICATE_KEYS.with(|cell| cell.set(reject_duplicate_keys));
+                let require_sorted_keys = self.require_sorted_keys;
+                REQUIRE_SORTED_KEYS.with(|cell| cell.set(require_sorted_keys));
+                let interned = self.interned.take();
+                INTERNED.with(|cell| *cell.borrow_mut() = interned);
+                #[cfg(feature = "unicode-normalization")]
+                NORMALIZATION.with(|cell| cell.set(self.normalization));
+            }
+        }
+
+        let interned = if self.intern_strings {
+            Some(HashSet::new())
+        } else {
+            None
+        };
+        let _restore = Restore {
+            max_preallocation: MAX_PREALLOCATION.with(|cell| cell.replace(self.max_preallocation)),
+            max_depth: MAX_DEPTH.with(|cell| cell.replace(self.max_depth)),
+            strict_bools: STRICT_BOOLS.with(|cell| cell.replace(self.strict_bools)),
+            reject_duplicate_keys: REJECT_DUPLICATE_KEYS
+                .with(|cell| cell.replace(self.reject_duplicate_keys)),
+            require_sorted_keys: REQUIRE_SORTED_KEYS
+                .with(|cell| cell.replace(self.require_sorted_keys)),
+            interned: INTERNED.with(|cell| cell.replace(interned)),
+            #[cfg(feature = "unicode-normalization")]
+            normalization: NORMALIZATION.with(|cell| cell.replace(self.normalization)),
+        };
+        self.limits.run(|| self.nan_policy.run(f))
+    }
+
+    /// [`BorshDeserialize::try_from_slice`] with these options.
+    pub fn try_from_slice<T: BorshDeserialize>(&self, v: &[u8]) -> Result<T, Error> {
+        self.run(|| T::try_from_slice(v))
+    }
+
+    /// [`BorshDeserialize::deserialize`] with these options.
+    pub fn deserialize<T: BorshDeserialize, R: Read>(&self, reader: &mut R) -> Result<T, Error> {
+        self.run(|| T::deserialize(reader))
+    }
+}
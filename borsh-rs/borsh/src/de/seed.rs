@@ -0,0 +1,85 @@
+//! Decoding values that need external state, such as an arena to allocate nodes in or a table
+//! that maps the ids on the wire to the objects they refer to.
+//!
+//! A [`BorshDeserializeSeed<S>`] type is decoded with a `&mut S`, the seed, which it hands to the
+//! parts of itself that need it. Deriving it decodes the fields marked `#[borsh(seed)]` with the
+//! seed and the others with [`BorshDeserialize`]. The seed type is named with
+//! `#[borsh_seed(Type)]`; without it the impl is generic over the seed, for types that only pass
+//! it on to their fields.
+//!
+//! ```
+//! use oasis_borsh::{BorshDeserialize, BorshDeserializeSeed, BorshSerialize};
+//!
+//! /// Names stored once and referred to by index.
+//! struct Names(Vec<String>);
+//!
+//! /// An index into `Names` on the wire, the name it refers to in memory.
+//! struct Name(String);
+//!
+//! impl BorshDeserializeSeed<Names> for Name {
+//!     fn deserialize_seed<R: std::io::Read>(
+//!         names: &mut Names,
+//!         reader: &mut R,
+//!     ) -> std::io::Result<Self> {
+//!         let index = u32::deserialize(reader)? as usize;
+//!         let name = names.0.get(index).ok_or_else(|| {
+//!             std::io::Error::new(std::io::ErrorKind::InvalidData, "Unknown name")
+//!         })?;
+//!         Ok(Name(name.clone()))
+//!     }
+//! }
+//!
+//! #[derive(BorshDeserializeSeed)]
+//! struct Entry {
+//!     #[borsh(seed)]
+//!     name: Name,
+//!     value: u64,
+//! }
+//!
+//! let mut names = Names(vec!["height".to_string()]);
+//! let bytes = (0u32, 7u64).try_to_vec().unwrap();
+//! let entry = Entry::deserialize_seed(&mut names, &mut &bytes[..]).unwrap();
+//! assert_eq!((entry.name.0.as_str(), entry.value), ("height", 7));
+//! ```
+use crate::maybestd::boxed::Box;
+use crate::maybestd::io::{Error, Read};
+use crate::maybestd::vec::Vec;
+
+use super::{check_cancelled, check_len, hint, in_element, read_len, read_tag, LenKind};
+
+/// A data-structure that is de-serialized with the help of a seed of type `S`.
+pub trait BorshDeserializeSeed<S: ?Sized>: Sized {
+    fn deserialize_seed<R: Read>(seed: &mut S, reader: &mut R) -> Result<Self, Error>;
+}
+
+impl<S: ?Sized, T: BorshDeserializeSeed<S>> BorshDeserializeSeed<S> for Option<T> {
+    fn deserialize_seed<R: Read>(seed: &mut S, reader: &mut R) -> Result<Self, Error> {
+        if read_tag(reader)? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(T::deserialize_seed(seed, reader)?))
+        }
+    }
+}
+
+impl<S: ?Sized, T: BorshDeserializeSeed<S>> BorshDeserializeSeed<S> for Vec<T> {
+    fn deserialize_seed<R: Read>(seed: &mut S, reader: &mut R) -> Result<Self, Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        let mut result = hint::try_with_capacity(hint::cautious::<T>(len))?;
+        for i in 0..len as usize {
+            check_cancelled(i)?;
+            hint::try_push(
+                &mut result,
+                in_element(T::deserialize_seed(seed, reader), i)?,
+            )?;
+        }
+        Ok(result)
+    }
+}
+
+impl<S: ?Sized, T: BorshDeserializeSeed<S>> BorshDeserializeSeed<S> for Box<T> {
+    fn deserialize_seed<R: Read>(seed: &mut S, reader: &mut R) -> Result<Self, Error> {
+        Ok(Box::new(T::deserialize_seed(seed, reader)?))
+    }
+}
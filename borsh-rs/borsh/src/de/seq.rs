@@ -0,0 +1,97 @@
+//! Decoding the elements of a length-prefixed sequence one at a time.
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::maybestd::io::{Error, Read};
+
+use super::{check_cancelled, check_len, hint, in_element, read_len, BorshDeserialize, LenKind};
+
+/// Iterator over the elements of a sequence encoded like `Vec<T>`, decoded from a reader as they
+/// are asked for, so that a sequence too large to hold in memory can be processed in one pass.
+///
+/// ```
+/// use oasis_borsh::de::SeqReader;
+/// use oasis_borsh::BorshSerialize;
+///
+/// let bytes = vec![1u64, 2, 3].try_to_vec().unwrap();
+/// let mut seq = SeqReader::<u64, _>::new(&bytes[..]).unwrap();
+/// assert_eq!(seq.len(), 3);
+/// assert_eq!(seq.by_ref().sum::<Result<u64, _>>().unwrap(), 6);
+/// assert!(seq.into_inner().is_empty());
+/// ```
+///
+/// An element that fails to decode yields one error, naming its index as `[i]`, and ends
+/// iteration, since where the next element would start is then unknown.
+pub struct SeqReader<T, R> {
+    reader: R,
+    len: u32,
+    index: u32,
+    failed: bool,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T: BorshDeserialize, R: Read> SeqReader<T, R> {
+    /// Read the `u32` length of the sequence from `reader`, leaving it at the first element.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let len = read_len(&mut reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        hint::unreserved::<T>(len);
+        Ok(SeqReader {
+            reader,
+            len,
+            index: 0,
+            failed: false,
+            _element: PhantomData,
+        })
+    }
+}
+
+impl<T, R> SeqReader<T, R> {
+    /// Number of elements the sequence announced.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Whether the sequence announced no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of elements not yet decoded, or zero after an error.
+    pub fn remaining(&self) -> u32 {
+        if self.failed {
+            0
+        } else {
+            self.len - self.index
+        }
+    }
+
+    /// The reader, positioned after the last element returned. It is past the end of the sequence
+    /// only once every element has been.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<T: BorshDeserialize, R: Read> Iterator for SeqReader<T, R> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let index = self.index as usize;
+        let result = check_cancelled(index)
+            .and_then(|()| in_element(T::deserialize(&mut self.reader), index));
+        self.index += 1;
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // An error ends iteration early, so only the upper bound is known.
+        (0, Some(self.remaining() as usize))
+    }
+}
+
+impl<T: BorshDeserialize, R: Read> FusedIterator for SeqReader<T, R> {}
@@ -0,0 +1,88 @@
+//! A reader that knows how far into its input it is.
+use crate::maybestd::boxed::Box;
+use crate::maybestd::io::{Error, Read};
+
+use super::BorshDeserialize;
+
+/// Wraps a reader, counting the bytes read through it, so that errors can say where in the input
+/// decoding stopped.
+///
+/// A reader cannot be rewound, so this is past the start of the value that failed by however much
+/// of it was read, where [`try_from_slice`](BorshDeserialize::try_from_slice) gives the start of
+/// the value. [`from_reader`](super::from_reader) tracks its input this way.
+///
+/// ```
+/// use oasis_borsh::de::TrackedReader;
+///
+/// let bytes = [1u8, 0, 0, 0, 0, 0, 0, 0, 2, 0];
+/// let mut reader = TrackedReader::new(&bytes[..]);
+/// assert_eq!(reader.decode::<u64>().unwrap(), 1);
+/// assert_eq!(reader.offset(), 8);
+/// let err = reader.decode::<u32>().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "at offset 10: unexpected end of input reading u32: wanted 4 bytes, 2 available"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct TrackedReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R> TrackedReader<R> {
+    pub fn new(inner: R) -> Self {
+        TrackedReader { inner, offset: 0 }
+    }
+
+    /// Number of bytes read so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> TrackedReader<R> {
+    /// Deserialize a `T`, failing with a [`crate::Error::At`] giving the offset at which decoding
+    /// stopped.
+    pub fn decode<T: BorshDeserialize>(&mut self) -> Result<T, Error> {
+        let result = T::deserialize(self);
+        result.map_err(|err| at_offset(err, self.offset))
+    }
+}
+
+impl<R: Read> Read for TrackedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let read = self.inner.read(buf)?;
+        self.offset += read as u64;
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> std::io::BufRead for TrackedReader<R> {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.offset += amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+/// Record that `err` occurred `offset` bytes into the input.
+pub(crate) fn at_offset(err: Error, offset: u64) -> Error {
+    crate::Error::At {
+        offset,
+        source: Box::new(crate::Error::from(err)),
+    }
+    .into()
+}
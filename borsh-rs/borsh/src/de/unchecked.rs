@@ -0,0 +1,43 @@
+//! Decoding of trusted input with validation turned off.
+//!
+//! Inside [`try_from_slice_unchecked`] strings are not checked to be UTF-8, floats are not checked
+//! for NaN, and bytes left over after the value are ignored. This is meant for data this process
+//! or a trusted peer produced and whose integrity is established some other way, e.g. by a
+//! checksum, on paths where decode throughput matters.
+use std::cell::Cell;
+use std::io::Error;
+
+use super::BorshDeserialize;
+
+thread_local! {
+    static UNCHECKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the decoder running on this thread is inside [`try_from_slice_unchecked`].
+#[inline]
+pub(crate) fn active() -> bool {
+    UNCHECKED.with(|cell| cell.get())
+}
+
+/// Deserialize a `T` from the start of `v` without validating strings, floats or trailing bytes.
+///
+/// # Safety
+///
+/// `v` must start with a valid encoding of a `T`. Strings in it that are not UTF-8 are undefined
+/// behavior once used.
+///
+/// The checks stay off on this thread until `T::deserialize` returns, so anything else decoded
+/// meanwhile, e.g. a separate buffer decoded by a handwritten impl, must be valid as well.
+pub unsafe fn try_from_slice_unchecked<T: BorshDeserialize>(mut v: &[u8]) -> Result<T, Error> {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0;
+            UNCHECKED.with(|cell| cell.set(previous));
+        }
+    }
+
+    let _restore = Restore(UNCHECKED.with(|cell| cell.replace(true)));
+    T::deserialize(&mut v)
+}
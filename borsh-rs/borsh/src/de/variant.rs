@@ -0,0 +1,64 @@
+//! Decoding enums whose tag is not part of their encoding.
+//!
+//! Some protocols carry the kind of a message in a transport header, a topic name or a frame type,
+//! and the body is the fields of the variant alone. [`deserialize_variant`] decodes such a body
+//! given the tag from elsewhere. Derived [`BorshDeserialize`] impls of enums come with a
+//! [`BorshDeserializeVariant`] impl, which resolves `#[borsh(alias_tag)]`s like the tag read from
+//! the input would be:
+//!
+//! ```
+//! use oasis_borsh::de::deserialize_variant;
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshDeserialize, PartialEq, Debug)]
+//! enum Request {
+//!     Ping,
+//!     Get { key: String },
+//! }
+//!
+//! // The frame type was 1, and the body holds the key.
+//! let body = "height".try_to_vec().unwrap();
+//! let request: Request = deserialize_variant(1, &mut &body[..]).unwrap();
+//! assert_eq!(request, Request::Get { key: "height".to_string() });
+//! assert!(deserialize_variant::<Request, _>(2, &mut &body[..]).is_err());
+//! ```
+use crate::maybestd::io::{Error, Read};
+use crate::spec;
+
+use super::BorshDeserialize;
+
+/// An enum that can be decoded from the fields of one of its variants, given the variant's tag.
+pub trait BorshDeserializeVariant: Sized {
+    /// Decode the variant with tag `tag` from `reader`, failing with
+    /// [`crate::Error::InvalidTag`] if there is none.
+    fn deserialize_variant<R: Read>(tag: u8, reader: &mut R) -> Result<Self, Error>;
+}
+
+/// Decode an `E` whose tag `tag` was read elsewhere, see the module documentation.
+pub fn deserialize_variant<E, R>(tag: u8, reader: &mut R) -> Result<E, Error>
+where
+    E: BorshDeserializeVariant,
+    R: Read,
+{
+    E::deserialize_variant(tag, reader)
+}
+
+impl<T: BorshDeserialize> BorshDeserializeVariant for Option<T> {
+    fn deserialize_variant<R: Read>(tag: u8, reader: &mut R) -> Result<Self, Error> {
+        if tag == spec::OPTION_NONE_TAG {
+            Ok(None)
+        } else {
+            Ok(Some(T::deserialize(reader)?))
+        }
+    }
+}
+
+impl<T: BorshDeserialize, E: BorshDeserialize> BorshDeserializeVariant for Result<T, E> {
+    fn deserialize_variant<R: Read>(tag: u8, reader: &mut R) -> Result<Self, Error> {
+        Ok(if tag == spec::RESULT_OK_TAG {
+            Ok(T::deserialize(reader)?)
+        } else {
+            Err(E::deserialize(reader)?)
+        })
+    }
+}
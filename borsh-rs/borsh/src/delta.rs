@@ -0,0 +1,168 @@
+//! Compact wire encodings for sorted `u64` sequences, such as lists of ids.
+//!
+//! Both wrappers behave like a plain `Vec<u64>` in memory. On the wire they store the number of
+//! elements as a `u32` followed by the differences between consecutive elements (the first element
+//! is its difference from zero) as minimal LEB128 varints, so dense id lists shrink to about a byte
+//! per element. Serializing a sequence that is not sorted in non-decreasing order is an error.
+use core::ops::{Deref, DerefMut};
+
+use crate::de::hint;
+use crate::maybestd::io::{Error, ErrorKind, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::varint;
+use crate::{BorshDeserialize, BorshSerialize};
+
+fn deltas(values: &[u64]) -> impl Iterator<Item = Result<u64, Error>> + '_ {
+    let mut prev = 0u64;
+    values.iter().map(move |&value| {
+        let delta = value.checked_sub(prev).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Delta-encoded values must be sorted",
+            )
+        })?;
+        prev = value;
+        Ok(delta)
+    })
+}
+
+fn accumulate(prev: u64, delta: u64) -> Result<u64, Error> {
+    prev.checked_add(delta)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Delta-encoded value overflows u64"))
+}
+
+fn write_len<W: Write>(len: usize, writer: &mut W) -> Result<(), Error> {
+    if len > u32::MAX as usize {
+        return Err(Error::new(ErrorKind::InvalidInput, "Sequence is too long"));
+    }
+    (len as u32).serialize(writer)
+}
+
+macro_rules! impl_vec_wrapper {
+    ($type: ident) => {
+        impl $type {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            pub fn into_inner(self) -> Vec<u64> {
+                self.0
+            }
+        }
+
+        impl Deref for $type {
+            type Target = Vec<u64>;
+
+            fn deref(&self) -> &Vec<u64> {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $type {
+            fn deref_mut(&mut self) -> &mut Vec<u64> {
+                &mut self.0
+            }
+        }
+
+        impl From<Vec<u64>> for $type {
+            fn from(values: Vec<u64>) -> Self {
+                $type(values)
+            }
+        }
+
+        impl From<$type> for Vec<u64> {
+            fn from(values: $type) -> Self {
+                values.0
+            }
+        }
+    };
+}
+
+/// A sorted `Vec<u64>` encoded as varint deltas.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct DeltaVec(pub Vec<u64>);
+
+impl_vec_wrapper!(DeltaVec);
+
+impl BorshSerialize for DeltaVec {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_len(self.0.len(), writer)?;
+        for delta in deltas(&self.0) {
+            varint::write_u64(writer, delta?)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for DeltaVec {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = u32::deserialize(reader)?;
+        let mut result = Vec::with_capacity(hint::cautious::<u64>(len));
+        let mut prev = 0u64;
+        for _ in 0..len {
+            prev = accumulate(prev, varint::read_u64(reader)?)?;
+            result.push(prev);
+        }
+        Ok(DeltaVec(result))
+    }
+}
+
+/// A sorted `Vec<u64>` encoded as run-length encoded varint deltas.
+///
+/// After the element count, the deltas are stored as `(delta, run)` varint pairs, where `run` is
+/// the number of consecutive elements with that delta. Runs are maximal and non-empty, so the
+/// encoding stays canonical. This suits sequences with long stretches of consecutive or evenly
+/// spaced values. Note that, as with any run-length encoding, a few bytes of input can describe up
+/// to `u32::MAX` elements.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct DeltaRleVec(pub Vec<u64>);
+
+impl_vec_wrapper!(DeltaRleVec);
+
+impl BorshSerialize for DeltaRleVec {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_len(self.0.len(), writer)?;
+        let mut run: Option<(u64, u64)> = None;
+        for delta in deltas(&self.0) {
+            let delta = delta?;
+            run = match run {
+                Some((current, count)) if current == delta => Some((current, count + 1)),
+                Some((current, count)) => {
+                    varint::write_u64(writer, current)?;
+                    varint::write_u64(writer, count)?;
+                    Some((delta, 1))
+                }
+                None => Some((delta, 1)),
+            };
+        }
+        if let Some((current, count)) = run {
+            varint::write_u64(writer, current)?;
+            varint::write_u64(writer, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for DeltaRleVec {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = u32::deserialize(reader)? as u64;
+        let mut result = Vec::with_capacity(hint::cautious::<u64>(len as u32));
+        let mut prev = 0u64;
+        let mut prev_delta = None;
+        let mut remaining = len;
+        while remaining > 0 {
+            let delta = varint::read_u64(reader)?;
+            let run = varint::read_u64(reader)?;
+            if run == 0 || run > remaining || prev_delta == Some(delta) {
+                return Err(Error::new(ErrorKind::InvalidData, "Invalid delta run"));
+            }
+            for _ in 0..run {
+                prev = accumulate(prev, delta)?;
+                result.push(prev);
+            }
+            prev_delta = Some(delta);
+            remaining -= run;
+        }
+        Ok(DeltaRleVec(result))
+    }
+}
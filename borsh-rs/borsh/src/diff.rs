@@ -0,0 +1,183 @@
+//! Structural deltas between two values of the same type.
+//!
+//! [`diff`] produces a [`Delta`] describing how to turn one value into another, and [`apply`]
+//! replays it. Derived structs encode their delta as a bitmap of changed fields (one bit per
+//! serialized field, rounded up to whole bytes) followed by the deltas of the changed fields in
+//! declaration order, recursing into fields whose types also derive `BorshDiff`. Everything else,
+//! including enums and std collections, is replaced wholesale: its delta is simply the borsh
+//! encoding of the new value. Values are compared by their borsh encoding, so a field counts as
+//! changed exactly when its serialized form changed.
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::ops::{Bound, ControlFlow, RangeFull};
+use core::task::Poll;
+
+use crate as oasis_borsh;
+use crate::maybestd::boxed::Box;
+use crate::maybestd::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std")]
+use crate::maybestd::collections::{HashMap, HashSet};
+use crate::maybestd::io::{Error, ErrorKind, Read};
+use crate::maybestd::string::String;
+use crate::maybestd::vec::Vec;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A type whose changes can be expressed as a compact delta.
+pub trait BorshDiff: BorshSerialize + BorshDeserialize {
+    /// Append the delta from `old` to `new` to `out`. Returns `false`, leaving `out` untouched, if
+    /// the two values serialize identically.
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error>;
+
+    /// Read one delta produced by [`BorshDiff::diff_into`] from `reader` and apply it to `self`.
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error>;
+}
+
+/// Changes between two values, see [`diff`].
+///
+/// An empty delta means the values were identical. On the wire a delta is a length-prefixed byte
+/// string, like `Vec<u8>`.
+#[derive(Clone, Default, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
+pub struct Delta(pub Vec<u8>);
+
+impl Delta {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Compute the delta that turns `old` into `new`.
+pub fn diff<T: BorshDiff>(old: &T, new: &T) -> Result<Delta, Error> {
+    let mut out = Vec::new();
+    T::diff_into(old, new, &mut out)?;
+    Ok(Delta(out))
+}
+
+/// Apply a delta computed by [`diff`] to `value`.
+pub fn apply<T: BorshDiff>(value: &mut T, delta: &Delta) -> Result<(), Error> {
+    if delta.is_empty() {
+        return Ok(());
+    }
+    let mut buf = &delta.0[..];
+    value.apply_diff(&mut buf)?;
+    if !buf.is_empty() {
+        return Err(crate::Error::TrailingBytes.into());
+    }
+    Ok(())
+}
+
+/// `diff_into` for types that are replaced wholesale when they change.
+#[doc(hidden)]
+pub fn replace_diff_into<T: BorshSerialize + ?Sized>(
+    old: &T,
+    new: &T,
+    out: &mut Vec<u8>,
+) -> Result<bool, Error> {
+    let new = new.try_to_vec()?;
+    if old.try_to_vec()? == new {
+        return Ok(false);
+    }
+    out.extend_from_slice(&new);
+    Ok(true)
+}
+
+/// `apply_diff` for types that are replaced wholesale when they change.
+#[doc(hidden)]
+pub fn replace_apply_diff<T: BorshDeserialize, R: Read>(
+    value: &mut T,
+    reader: &mut R,
+) -> Result<(), Error> {
+    *value = T::deserialize(reader)?;
+    Ok(())
+}
+
+/// Error for a struct delta whose bitmap has no bits or unknown bits set.
+#[doc(hidden)]
+pub fn invalid_bitmap() -> Error {
+    Error::new(ErrorKind::InvalidData, "Invalid delta field bitmap")
+}
+
+macro_rules! impl_by_replacement {
+    ($($type: ty)+) => {
+    $(
+        impl BorshDiff for $type {
+            fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+                replace_diff_into(old, new, out)
+            }
+
+            fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+                replace_apply_diff(self, reader)
+            }
+        }
+    )+
+    };
+}
+
+impl_by_replacement!(() bool f32 f64 i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 String Box<[u8]>);
+impl_by_replacement!(Ordering RangeFull Box<str>);
+#[cfg(feature = "std")]
+impl_by_replacement!(std::net::SocketAddr std::net::SocketAddrV4 std::net::SocketAddrV6);
+#[cfg(feature = "std")]
+impl_by_replacement!(std::net::Ipv4Addr std::net::Ipv6Addr);
+
+macro_rules! impl_generic_by_replacement {
+    ($($type: ident < $($param: ident),+ >)+) => {
+    $(
+        impl<$($param),+> BorshDiff for $type<$($param),+>
+        where
+            $type<$($param),+>: BorshSerialize + BorshDeserialize,
+        {
+            fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+                replace_diff_into(old, new, out)
+            }
+
+            fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+                replace_apply_diff(self, reader)
+            }
+        }
+    )+
+    };
+}
+
+impl_generic_by_replacement!(Option<T> Result<T, E> Vec<T> BTreeSet<T> BTreeMap<K, V>);
+#[cfg(feature = "std")]
+impl_generic_by_replacement!(HashSet<T, S> HashMap<K, V, S>);
+impl_generic_by_replacement!(ControlFlow<B, C> Poll<T> Bound<T> PhantomData<T>);
+
+impl<T, const N: usize> BorshDiff for [T; N]
+where
+    [T; N]: BorshSerialize + BorshDeserialize,
+{
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
+
+macro_rules! impl_tuples {
+    ($($len:literal => ($($name:ident)+))+) => {
+    $(
+        impl<$($name),+> BorshDiff for ($($name,)+)
+        where
+            $($name: BorshSerialize + BorshDeserialize),+
+        {
+            fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+                replace_diff_into(old, new, out)
+            }
+
+            fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+                replace_apply_diff(self, reader)
+            }
+        }
+    )+
+    };
+}
+
+oasis_borsh_derive::_gen_seq_macro! {
+    impl_tuples => T :: (
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+        17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    )
+}
@@ -0,0 +1,145 @@
+//! Serialization over `embedded_io` readers and writers, with or without std.
+//!
+//! Blocking transports are bridged through [`EmbeddedIo`], which implements the `Read` and
+//! `Write` traits of [`maybestd::io`](crate::maybestd), so the regular
+//! `BorshSerialize`/`BorshDeserialize` machinery can run directly over a UART or SPI driver in
+//! `no_std` firmware. [`to_writer`] and [`from_reader`] exchange plain borsh encodings.
+//!
+//! # Async framing
+//!
+//! Async transports cannot drive the synchronous decoder byte by byte, so [`write_frame_async`]
+//! and [`read_frame_async`] use a framing of their own: a `u32` little-endian byte count followed
+//! by the borsh encoding of the value, i.e. the borsh encoding of the payload as a `Vec<u8>`. The
+//! two sides must agree on it: a blocking peer reads a frame with `from_reader::<Vec<u8>, _>` and
+//! decodes the payload from that, and writes one with `to_writer` of the encoded payload.
+#[cfg(feature = "embedded-io-async")]
+use core::cmp::min;
+
+use crate::maybestd::format;
+use crate::maybestd::io::{Error, ErrorKind, Read, Write};
+#[cfg(feature = "embedded-io-async")]
+use crate::maybestd::vec::Vec;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Upper bound on how much of a frame is buffered before the corresponding bytes have arrived.
+#[cfg(feature = "embedded-io-async")]
+const FRAME_CHUNK: usize = 4096;
+
+fn to_io_error<E: embedded_io::Error>(err: E) -> Error {
+    let kind = match err.kind() {
+        embedded_io::ErrorKind::InvalidInput => ErrorKind::InvalidInput,
+        embedded_io::ErrorKind::InvalidData => ErrorKind::InvalidData,
+        embedded_io::ErrorKind::Interrupted => ErrorKind::Interrupted,
+        embedded_io::ErrorKind::OutOfMemory => ErrorKind::OutOfMemory,
+        embedded_io::ErrorKind::WriteZero => ErrorKind::WriteZero,
+        _ => ErrorKind::Other,
+    };
+    Error::new(kind, format!("{:?}", err))
+}
+
+/// Adapter exposing an `embedded_io` reader or writer as a borsh one.
+pub struct EmbeddedIo<T>(T);
+
+impl<T> EmbeddedIo<T> {
+    pub fn new(inner: T) -> Self {
+        EmbeddedIo(inner)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: embedded_io::Read> Read for EmbeddedIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.0.read(buf).map_err(to_io_error)
+    }
+}
+
+impl<T: embedded_io::Write> Write for EmbeddedIo<T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.0.write(buf).map_err(to_io_error)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.0.flush().map_err(to_io_error)
+    }
+}
+
+/// Serialize `value` into a blocking `embedded_io` writer and flush it.
+pub fn to_writer<T, W>(value: &T, writer: &mut W) -> Result<(), Error>
+where
+    T: BorshSerialize + ?Sized,
+    W: embedded_io::Write,
+{
+    let mut writer = EmbeddedIo::new(writer);
+    value.serialize(&mut writer)?;
+    writer.flush()
+}
+
+/// Deserialize one value from a blocking `embedded_io` reader, leaving any following bytes unread.
+pub fn from_reader<T, R>(reader: &mut R) -> Result<T, Error>
+where
+    T: BorshDeserialize,
+    R: embedded_io::Read,
+{
+    T::deserialize(&mut EmbeddedIo::new(reader))
+}
+
+/// Serialize `value` into an async `embedded_io_async` writer as a length-prefixed frame, see
+/// [async framing](self#async-framing).
+#[cfg(feature = "embedded-io-async")]
+pub async fn write_frame_async<T, W>(value: &T, writer: &mut W) -> Result<(), Error>
+where
+    T: BorshSerialize + ?Sized,
+    W: embedded_io_async::Write,
+{
+    let payload = value.try_to_vec()?;
+    let len = (payload.len() as u32).to_le_bytes();
+    writer.write_all(&len).await.map_err(to_io_error)?;
+    writer.write_all(&payload).await.map_err(to_io_error)?;
+    writer.flush().await.map_err(to_io_error)
+}
+
+/// Read one length-prefixed frame, see [async framing](self#async-framing), from an async
+/// `embedded_io_async` reader and deserialize it.
+///
+/// The frame buffer grows as bytes arrive, so a corrupt length prefix cannot make us allocate
+/// more than has actually been received. The payload must be consumed entirely by `T`.
+#[cfg(feature = "embedded-io-async")]
+pub async fn read_frame_async<T, R>(reader: &mut R) -> Result<T, Error>
+where
+    T: BorshDeserialize,
+    R: embedded_io_async::Read,
+{
+    let mut len = [0u8; 4];
+    read_exact_async(reader, &mut len).await?;
+    let len = u32::from_le_bytes(len) as usize;
+    let mut payload = Vec::with_capacity(min(len, FRAME_CHUNK));
+    while payload.len() < len {
+        let start = payload.len();
+        let end = min(len, start + FRAME_CHUNK);
+        payload.resize(end, 0);
+        read_exact_async(reader, &mut payload[start..]).await?;
+    }
+    T::try_from_slice(&payload)
+}
+
+#[cfg(feature = "embedded-io-async")]
+async fn read_exact_async<R: embedded_io_async::Read>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<(), Error> {
+    reader.read_exact(buf).await.map_err(|err| match err {
+        embedded_io::ReadExactError::UnexpectedEof => crate::Error::UnexpectedEof.into(),
+        embedded_io::ReadExactError::Other(err) => to_io_error(err),
+    })
+}
@@ -0,0 +1,176 @@
+//! Encrypt-then-frame envelopes around borsh payloads, with a pluggable AEAD cipher.
+//!
+//! An encrypted frame is laid out as:
+//!
+//! 1. the nonce, [`Aead::NONCE_LEN`] bytes;
+//! 2. a `u32` little-endian length of what follows;
+//! 3. the ciphertext of the borsh encoding, followed by the [`Aead::TAG_LEN`]-byte tag.
+//!
+//! The nonce and length, the first `NONCE_LEN + 4` bytes, are authenticated as associated data,
+//! so a frame cannot be truncated or have its nonce swapped without failing to open. Borsh does
+//! not ship a cipher: implement [`Aead`] for the one your service uses.
+//!
+//! Every frame sealed with the same key needs a distinct nonce, which the caller provides. Reusing
+//! a nonce breaks the confidentiality, and with most ciphers the integrity, of both frames.
+use std::convert::TryFrom;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// An authenticated cipher with associated data, keyed by the implementor.
+pub trait Aead {
+    /// Length of a nonce in bytes.
+    const NONCE_LEN: usize;
+    /// Length of the authentication tag appended to ciphertexts, in bytes.
+    const TAG_LEN: usize;
+
+    /// Encrypt `buffer` in place and append the tag.
+    fn seal(&self, nonce: &[u8], associated_data: &[u8], buffer: &mut Vec<u8>)
+        -> Result<(), Error>;
+
+    /// Check the tag at the end of `buffer` and decrypt the rest in place, removing the tag.
+    /// Fails without revealing anything about the plaintext if the tag does not match.
+    fn open(&self, nonce: &[u8], associated_data: &[u8], buffer: &mut Vec<u8>)
+        -> Result<(), Error>;
+}
+
+/// Serialize `value` into an encrypted frame sealed with `nonce`.
+pub fn to_vec_encrypted<T, A>(value: &T, aead: &A, nonce: &[u8]) -> Result<Vec<u8>, Error>
+where
+    T: BorshSerialize + ?Sized,
+    A: Aead,
+{
+    let mut writer = EncryptedWriter::new(Vec::new(), aead, nonce)?;
+    value.serialize(&mut writer)?;
+    writer.finish()
+}
+
+/// Deserialize a value from an encrypted frame that makes up all of `v`.
+pub fn from_slice_encrypted<T, A>(v: &[u8], aead: &A) -> Result<T, Error>
+where
+    T: BorshDeserialize,
+    A: Aead,
+{
+    let mut input = v;
+    let reader = EncryptedReader::new(&mut input, aead)?;
+    if !input.is_empty() {
+        return Err(crate::Error::TrailingBytes.into());
+    }
+    T::try_from_slice(reader.plaintext())
+}
+
+/// A writer that collects plaintext and writes it to `inner` as one encrypted frame on
+/// [`finish`](EncryptedWriter::finish).
+///
+/// AEAD ciphers authenticate a message as a whole, so the plaintext is buffered until then.
+pub struct EncryptedWriter<'a, W, A> {
+    inner: W,
+    aead: &'a A,
+    nonce: Vec<u8>,
+    plaintext: Vec<u8>,
+}
+
+impl<'a, W: Write, A: Aead> EncryptedWriter<'a, W, A> {
+    pub fn new(inner: W, aead: &'a A, nonce: &[u8]) -> Result<Self, Error> {
+        if nonce.len() != A::NONCE_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Nonce must be {} bytes long", A::NONCE_LEN),
+            ));
+        }
+        Ok(EncryptedWriter {
+            inner,
+            aead,
+            nonce: nonce.to_vec(),
+            plaintext: Vec::new(),
+        })
+    }
+
+    /// Seal the plaintext written so far, write the frame to the inner writer and return it.
+    pub fn finish(mut self) -> Result<W, Error> {
+        let len = self.plaintext.len() + A::TAG_LEN;
+        let len = u32::try_from(len).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "Encrypted payload does not fit a u32 length",
+            )
+        })?;
+        let mut header = self.nonce;
+        header.extend_from_slice(&len.to_le_bytes());
+        let mut buffer = self.plaintext;
+        self.aead
+            .seal(&header[..A::NONCE_LEN], &header, &mut buffer)?;
+        if buffer.len() != len as usize {
+            return Err(Error::other("Cipher produced a tag of the wrong length"));
+        }
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&buffer)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W, A> Write for EncryptedWriter<'_, W, A> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.plaintext.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A reader over the plaintext of one encrypted frame.
+///
+/// The whole frame is read from the inner reader and opened when the reader is created, so no
+/// plaintext is handed out before its tag has been checked.
+pub struct EncryptedReader {
+    plaintext: Vec<u8>,
+    position: usize,
+}
+
+impl EncryptedReader {
+    /// Read one frame from `inner` and open it, leaving `inner` right after the frame.
+    pub fn new<R: Read, A: Aead>(inner: &mut R, aead: &A) -> Result<Self, Error> {
+        let mut header = vec![0u8; A::NONCE_LEN + 4];
+        inner.read_exact(&mut header)?;
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&header[A::NONCE_LEN..]);
+        let len = u32::from_le_bytes(len) as usize;
+        if len < A::TAG_LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Encrypted frame is shorter than its tag",
+            ));
+        }
+        // The buffer grows with the bytes actually read rather than with the declared length.
+        let mut buffer = Vec::new();
+        inner.by_ref().take(len as u64).read_to_end(&mut buffer)?;
+        if buffer.len() != len {
+            return Err(crate::Error::UnexpectedEof.into());
+        }
+        aead.open(&header[..A::NONCE_LEN], &header, &mut buffer)?;
+        Ok(EncryptedReader {
+            plaintext: buffer,
+            position: 0,
+        })
+    }
+
+    /// The decrypted contents of the frame.
+    pub fn plaintext(&self) -> &[u8] {
+        &self.plaintext
+    }
+
+    /// Whether all of the plaintext has been read.
+    pub fn is_exhausted(&self) -> bool {
+        self.position == self.plaintext.len()
+    }
+}
+
+impl Read for EncryptedReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = (&self.plaintext[self.position..]).read(buf)?;
+        self.position += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,243 @@
+//! What went wrong while encoding or decoding.
+//!
+//! The borsh traits report failures as `io::Error`, so that they compose with the readers and
+//! writers values travel through. The errors borsh raises itself carry an [`Error`] inside, which
+//! `Error::from` recovers, so callers can act on the cause instead of parsing messages:
+//!
+//! ```
+//! use oasis_borsh::{BorshDeserialize, Error};
+//!
+//! match Error::from(u32::try_from_slice(&[1, 2]).unwrap_err()).root_cause() {
+//!     Error::Truncated { what: "u32", wanted: 4, available: 2 } => {}
+//!     err => panic!("unexpected error: {}", err),
+//! }
+//! ```
+//!
+//! Converting back with `io::Error::from` gives the error borsh would have returned, so code that
+//! matches on `io::ErrorKind` or on messages keeps working.
+use core::fmt;
+use core::str::Utf8Error;
+
+use crate::maybestd::boxed::Box;
+use crate::maybestd::format;
+use crate::maybestd::io::{self, ErrorKind};
+use crate::maybestd::string::{String, ToString};
+
+/// The cause of a failed encode or decode.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input ended before the value did, in a reader that did not say where.
+    UnexpectedEof,
+    /// The input ended with only `available` of the `wanted` bytes of a `what`, which names a
+    /// primitive type like `u64`, or a `length prefix`, `enum tag` or `string`.
+    Truncated {
+        what: &'static str,
+        wanted: usize,
+        available: usize,
+    },
+    /// A string was not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// An enum tag that names none of the variants of the type being decoded.
+    InvalidTag(u8),
+    /// A `bool` was encoded as a byte other than 0 or 1, see
+    /// `de::options::DecodeOptions::strict_bools`.
+    InvalidBool(u8),
+    /// The bytes were encoded from another type than the `expected` one, see `type_guard`.
+    WrongType { expected: &'static str },
+    /// Bytes were left over after decoding a value that should have taken all of them.
+    TrailingBytes,
+    /// A map or set held the same key twice, see
+    /// `de::options::DecodeOptions::reject_duplicate_keys`.
+    DuplicateKey,
+    /// A B-tree map or set listed a key after one that is not smaller, see
+    /// `de::options::DecodeOptions::require_sorted_keys`.
+    UnsortedKeys,
+    /// The caller cancelled the decode, see `de::cancel`.
+    Cancelled,
+    /// A `what`, a `sequence`, `string` or `map`, declared a length of `len`, over the `limit` in
+    /// effect, see `de::options::Limits`.
+    TooLong {
+        what: &'static str,
+        len: u64,
+        limit: u32,
+    },
+    /// Decoding would have reserved more than the `budget` of bytes in effect for the whole value,
+    /// see `de::options::Limits::max_allocation`.
+    OverBudget { budget: usize },
+    /// The sequences and maps being decoded declared more than the `budget` of elements and entries
+    /// in effect for the whole value, see `de::options::Limits::max_elements`.
+    TooManyElements { budget: u64 },
+    /// Values were nested more than `limit` levels deep, see `de::enter`.
+    TooDeep { limit: usize },
+    /// The allocator could not provide `bytes` more bytes for a decoded vector, string or hash
+    /// table. B-tree collections allocate a small node at a time and are not covered.
+    AllocationFailed { bytes: usize },
+    /// An error from the underlying reader or writer, or one borsh does not classify.
+    Io(io::Error),
+    /// Any other error, described by its message.
+    Custom(String),
+    /// `source` occurred while decoding a field or element of a larger value.
+    ///
+    /// `ty` names the outermost derived type the error passed through, or is empty if there was
+    /// none, and `path` leads from there to the failing part, as in `Block` and
+    /// `.transactions[3].signature`. Enum variants appear as `::Variant`.
+    Context {
+        ty: &'static str,
+        path: String,
+        source: Box<Error>,
+    },
+    /// `source` occurred while decoding the value that starts `offset` bytes into the input.
+    ///
+    /// Decoders reading from a slice know where each value starts. Those reading from a stream,
+    /// like `de::TrackedReader`, cannot go back, and give the offset at which they stopped.
+    At { offset: u64, source: Box<Error> },
+}
+
+impl Error {
+    /// The `io::ErrorKind` of the `io::Error` this converts into.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::UnexpectedEof | Error::Truncated { .. } => ErrorKind::UnexpectedEof,
+            Error::InvalidUtf8(_)
+            | Error::InvalidBool(_)
+            | Error::TrailingBytes
+            | Error::DuplicateKey
+            | Error::UnsortedKeys
+            | Error::WrongType { .. }
+            | Error::TooLong { .. }
+            | Error::OverBudget { .. }
+            | Error::TooManyElements { .. }
+            | Error::TooDeep { .. }
+            | Error::Custom(_) => ErrorKind::InvalidData,
+            Error::InvalidTag(_) => ErrorKind::InvalidInput,
+            Error::Cancelled => ErrorKind::Other,
+            Error::AllocationFailed { .. } => ErrorKind::OutOfMemory,
+            Error::Io(err) => err.kind(),
+            Error::Context { source, .. } | Error::At { source, .. } => source.kind(),
+        }
+    }
+
+    /// The error itself, or the one it wraps if it is a [`Error::Context`] or [`Error::At`].
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } | Error::At { source, .. } => source.root_cause(),
+            err => err,
+        }
+    }
+
+    /// Record that the error occurred in `segment` of the type named `ty`, or of an element if
+    /// `ty` is empty.
+    pub(crate) fn within(self, ty: &'static str, segment: &str) -> Error {
+        match self {
+            Error::Context { path, source, .. } => Error::Context {
+                ty,
+                path: format!("{}{}", segment, path),
+                source,
+            },
+            source => Error::Context {
+                ty,
+                path: segment.to_string(),
+                source: Box::new(source),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnexpectedEof => f.write_str("failed to fill whole buffer"),
+            Error::Truncated {
+                what,
+                wanted,
+                available,
+            } => write!(
+                f,
+                "unexpected end of input reading {}: wanted {} bytes, {} available",
+                what, wanted, available
+            ),
+            Error::InvalidUtf8(err) => err.fmt(f),
+            Error::InvalidTag(tag) => write!(f, "Unexpected variant index: {:?}", tag),
+            Error::InvalidBool(value) => write!(f, "Invalid bool value: {}", value),
+            Error::WrongType { expected } => {
+                write!(f, "bytes were not encoded from a {}", expected)
+            }
+            Error::TrailingBytes => f.write_str("Not all bytes read"),
+            Error::DuplicateKey => f.write_str("duplicate key in map or set"),
+            Error::UnsortedKeys => f.write_str("keys of map or set are not in ascending order"),
+            Error::Cancelled => f.write_str("Decoding was cancelled"),
+            Error::TooLong { what, len, limit } => write!(
+                f,
+                "{} of length {} exceeds the limit of {}",
+                what, len, limit
+            ),
+            Error::OverBudget { budget } => write!(
+                f,
+                "decoding needs more than the memory budget of {} bytes",
+                budget
+            ),
+            Error::TooManyElements { budget } => write!(
+                f,
+                "decoding needs more than the budget of {} elements",
+                budget
+            ),
+            Error::TooDeep { limit } => {
+                write!(f, "nesting exceeds the depth limit of {}", limit)
+            }
+            Error::AllocationFailed { bytes } => {
+                write!(f, "memory allocation of {} bytes failed", bytes)
+            }
+            Error::Io(err) => err.fmt(f),
+            Error::Custom(message) => f.write_str(message),
+            Error::Context { ty, path, source } => {
+                write!(f, "error deserializing {}{}: {}", ty, path, source)
+            }
+            Error::At { offset, source } => write!(f, "at offset {}: {}", offset, source),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::Context { source, .. } | Error::At { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Recovers the `Error` borsh raised, or classifies an error from elsewhere by its kind.
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        #[cfg(feature = "std")]
+        let err = match err.downcast::<Error>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        #[cfg(not(feature = "std"))]
+        let err = match err.into_borsh() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        match err.kind() {
+            ErrorKind::UnexpectedEof => Error::UnexpectedEof,
+            _ => Error::Io(err),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            #[cfg(feature = "std")]
+            err => io::Error::new(err.kind(), err),
+            #[cfg(not(feature = "std"))]
+            err => io::Error::from_borsh(err),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Extension regions, through which derived structs stay readable as they gain fields.
+//!
+//! A struct marked `#[borsh_extension(field)]` is encoded as usual, followed by a region: a `u32`
+//! length and that many bytes. The region holds the fields marked `#[borsh(extension)]`, in wire
+//! order, and after them any bytes that the decoding version of the type does not know about,
+//! which are kept in `field`, an [`Extension`], and written back when the value is encoded again.
+//!
+//! A type can thus grow by appending `#[borsh(extension)]` fields:
+//!
+//! * older versions skip the new fields, and preserve them through a decode and re-encode;
+//! * newer versions decode regions written by older ones, which end early, with the missing
+//!   fields set to their `Default`.
+//!
+//! ```
+//! use oasis_borsh::extension::Extension;
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! #[borsh_extension(unknown)]
+//! struct AccountV1 {
+//!     balance: u64,
+//!     unknown: Extension,
+//! }
+//!
+//! #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+//! #[borsh_extension(unknown)]
+//! struct AccountV2 {
+//!     balance: u64,
+//!     #[borsh(extension)]
+//!     memo: String,
+//!     unknown: Extension,
+//! }
+//!
+//! let v2 = AccountV2 { balance: 5, memo: "hi".to_string(), unknown: Extension::new() };
+//! let v1 = AccountV1::try_from_slice(&v2.try_to_vec().unwrap()).unwrap();
+//! assert_eq!(v1.balance, 5);
+//! let bytes = v1.try_to_vec().unwrap();
+//! assert_eq!(AccountV2::try_from_slice(&bytes).unwrap(), v2);
+//! ```
+//!
+//! Extension fields can only be appended: removing or reordering them, or changing their types,
+//! breaks the layout as it would for any other field.
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::prefix::LengthPrefix;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// The bytes of an extension region that the type it was decoded into has no fields for.
+///
+/// On its own, an extension is encoded like the `Vec<u8>` of those bytes.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Extension {
+    unknown: Vec<u8>,
+}
+
+impl Extension {
+    /// An extension with no unknown bytes, for values built rather than decoded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bytes left over in the region after the known extension fields.
+    pub fn unknown(&self) -> &[u8] {
+        &self.unknown
+    }
+
+    /// Whether the region had no bytes beyond the known extension fields.
+    pub fn is_empty(&self) -> bool {
+        self.unknown.is_empty()
+    }
+
+    /// Drop the unknown bytes, so that they are not written back.
+    pub fn clear(&mut self) {
+        self.unknown.clear();
+    }
+}
+
+impl BorshSerialize for Extension {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.unknown.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.unknown.serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        self.unknown.size_hint()
+    }
+}
+
+impl BorshDeserialize for Extension {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Extension {
+            unknown: Vec::deserialize(reader)?,
+        })
+    }
+}
+
+impl BorshDiff for Extension {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
+
+/// Called by derived encoders to write a region of the `known` extension fields followed by the
+/// bytes of `extension`.
+#[doc(hidden)]
+pub fn write_region<W: Write>(
+    writer: &mut W,
+    known: &[u8],
+    extension: &Extension,
+) -> Result<(), Error> {
+    u32::write_len(known.len() + extension.unknown.len(), writer)?;
+    writer.write_all(known)?;
+    writer.write_all(&extension.unknown)
+}
+
+/// Called by derived decoders to read a region.
+#[doc(hidden)]
+pub fn read_region<R: Read>(reader: &mut R) -> Result<Vec<u8>, Error> {
+    Vec::deserialize(reader)
+}
+
+/// Called by derived decoders with what is left of a region after the known extension fields.
+#[doc(hidden)]
+pub fn unknown(rest: &[u8]) -> Extension {
+    Extension {
+        unknown: rest.to_vec(),
+    }
+}
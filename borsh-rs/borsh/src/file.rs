@@ -0,0 +1,121 @@
+//! Saving values to files and loading them back, for snapshots and state files.
+//!
+//! [`to_file`] never leaves a half-written file behind: the value is written to a temporary file
+//! next to the target, which then replaces it in one rename. Readers see either the old contents
+//! or the new ones, and a crash while writing leaves the old file intact.
+//!
+//! ```
+//! # let dir = std::env::temp_dir().join(format!("borsh-file-doc-{}", std::process::id()));
+//! # std::fs::create_dir_all(&dir).unwrap();
+//! let path = dir.join("state.bin");
+//! oasis_borsh::to_file(&path, &vec![1u32, 2, 3]).unwrap();
+//! let state: Vec<u32> = oasis_borsh::from_file(&path).unwrap();
+//! assert_eq!(state, [1, 2, 3]);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Distinguishes the temporary files of concurrent writes from one process.
+static NEXT_TEMP: AtomicUsize = AtomicUsize::new(0);
+
+/// How [`FileOptions::write`] saves a value.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FileOptions {
+    sync: bool,
+}
+
+impl FileOptions {
+    /// Atomic replacement without waiting for the data to reach the disk, as [`to_file`] does.
+    pub fn new() -> Self {
+        FileOptions::default()
+    }
+
+    /// Whether to wait until the new contents and the rename are on disk before returning, so
+    /// that they survive a power failure, not just a crash of the process. Off by default, since
+    /// it can take milliseconds.
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Serialize `value` into the file at `path`, atomically replacing what was there.
+    pub fn write<T, P>(&self, path: P, value: &T) -> Result<(), Error>
+    where
+        T: BorshSerialize + ?Sized,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let temp = temp_path(path);
+        let result = self.write_temp(&temp, value).and_then(|()| {
+            fs::rename(&temp, path)?;
+            if self.sync {
+                sync_dir(path)?;
+            }
+            Ok(())
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    fn write_temp<T: BorshSerialize + ?Sized>(&self, temp: &Path, value: &T) -> Result<(), Error> {
+        let file = OpenOptions::new().write(true).create_new(true).open(temp)?;
+        let mut writer = BufWriter::new(file);
+        value.serialize(&mut writer)?;
+        writer.flush()?;
+        if self.sync {
+            writer.get_ref().sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+/// A path for the temporary file of a write to `path`, in the same directory so that renaming
+/// it over `path` does not cross file systems.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    path.with_file_name(name)
+}
+
+/// Make the rename to `path` durable by syncing its directory, where the platform allows it.
+fn sync_dir(path: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Serialize `value` into the file at `path`, atomically replacing what was there, see
+/// [`FileOptions`] to also sync it to disk.
+pub fn to_file<T, P>(path: P, value: &T) -> Result<(), Error>
+where
+    T: BorshSerialize + ?Sized,
+    P: AsRef<Path>,
+{
+    FileOptions::new().write(path, value)
+}
+
+/// Deserialize a `T` from the whole of the file at `path`, failing if bytes are left over.
+pub fn from_file<T: BorshDeserialize, P: AsRef<Path>>(path: P) -> Result<T, Error> {
+    crate::from_reader(File::open(path)?)
+}
@@ -0,0 +1,213 @@
+//! A fixed-size byte string written as hex, for hashes, addresses and keys shared between
+//! projects.
+//!
+//! ```
+//! use oasis_borsh::fixed_bytes::FixedBytes;
+//!
+//! let hash: FixedBytes<4> = "0xdeadbeef".parse().unwrap();
+//! assert_eq!(hash.to_string(), "deadbeef");
+//! assert_eq!(<[u8; 4]>::from(hash), [0xde, 0xad, 0xbe, 0xef]);
+//! ```
+//!
+//! A `FixedBytes<N>` is encoded exactly like `[u8; N]`, and like a [`Tagged`] with any marker, so
+//! it converts from and to both. Parsing accepts `2 * N` hex digits of either case, optionally
+//! prefixed with `0x`; formatting writes lowercase digits without a prefix.
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::fixed_size::BorshFixedSize;
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::memcpy::Memcpy;
+#[cfg(feature = "std")]
+use crate::schema::{Declaration, Definition};
+use crate::tagged::Tagged;
+#[cfg(feature = "std")]
+use crate::BorshSchema;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// `N` bytes, displayed and parsed as hex.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct FixedBytes<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> FixedBytes<N> {
+    pub const fn new(bytes: [u8; N]) -> Self {
+        FixedBytes(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<const N: usize> Default for FixedBytes<N> {
+    fn default() -> Self {
+        FixedBytes([0; N])
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FixedBytes({})", self)
+    }
+}
+
+/// Why a string is not the hex of a `FixedBytes<N>`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseFixedBytesError {
+    /// The string does not have `2 * N` digits; holds the number of bytes it has.
+    InvalidLength(usize),
+    /// The byte at the given index, counted after any `0x` prefix, is not a hex digit.
+    InvalidDigit(usize),
+}
+
+impl fmt::Display for ParseFixedBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseFixedBytesError::InvalidLength(len) => {
+                write!(f, "wrong number of hex digits: {}", len)
+            }
+            ParseFixedBytesError::InvalidDigit(index) => {
+                write!(f, "invalid hex digit at index {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFixedBytesError {}
+
+impl<const N: usize> FromStr for FixedBytes<N> {
+    type Err = ParseFixedBytesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+        if digits.len() != 2 * N {
+            return Err(ParseFixedBytesError::InvalidLength(digits.len()));
+        }
+        let digit = |index: usize| {
+            (digits[index] as char)
+                .to_digit(16)
+                .map(|value| value as u8)
+                .ok_or(ParseFixedBytesError::InvalidDigit(index))
+        };
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = digit(2 * i)? << 4 | digit(2 * i + 1)?;
+        }
+        Ok(FixedBytes(bytes))
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FixedBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        FixedBytes(bytes)
+    }
+}
+
+impl<const N: usize> From<FixedBytes<N>> for [u8; N] {
+    fn from(bytes: FixedBytes<N>) -> Self {
+        bytes.0
+    }
+}
+
+impl<T: ?Sized, const N: usize> From<Tagged<T, N>> for FixedBytes<N> {
+    fn from(tagged: Tagged<T, N>) -> Self {
+        FixedBytes(tagged.into_bytes())
+    }
+}
+
+impl<T: ?Sized, const N: usize> From<FixedBytes<N>> for Tagged<T, N> {
+    fn from(bytes: FixedBytes<N>) -> Self {
+        Tagged::new(bytes.0)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for FixedBytes<N> {
+    type Error = core::array::TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; N]>::try_from(bytes).map(FixedBytes)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for FixedBytes<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> BorshSerialize for FixedBytes<N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&self.0)
+    }
+
+    fn serialized_size(&self) -> usize {
+        N
+    }
+
+    fn size_hint(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize> BorshDeserialize for FixedBytes<N> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut bytes = [0u8; N];
+        reader.read_exact(&mut bytes)?;
+        Ok(FixedBytes(bytes))
+    }
+}
+
+/// Described as `[u8; N]`, which it is on the wire.
+#[cfg(feature = "std")]
+impl<const N: usize> BorshSchema for FixedBytes<N> {
+    fn add_definitions_recursively(
+        definitions: &mut std::collections::HashMap<Declaration, Definition>,
+    ) {
+        <[u8; N]>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <[u8; N]>::declaration()
+    }
+}
+
+impl<const N: usize> BorshFixedSize for FixedBytes<N> {
+    const SIZE: usize = N;
+}
+
+impl<const N: usize> BorshDiff for FixedBytes<N> {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
+
+// Sound since the struct is a transparent wrapper around the bytes.
+unsafe impl<const N: usize> Memcpy for FixedBytes<N> {}
@@ -0,0 +1,113 @@
+//! Types whose encoding always takes the same number of bytes.
+//!
+//! [`BorshFixedSize::SIZE`] is known at compile time, so encodings of such types fit in stack
+//! buffers, offsets into records of them can be computed without decoding, and layouts can be
+//! pinned down with a compile-time assertion:
+//!
+//! ```
+//! use oasis_borsh::{BorshFixedSize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshFixedSize)]
+//! struct Entry {
+//!     key: [u8; 8],
+//!     version: u32,
+//!     deleted: bool,
+//! }
+//!
+//! const _: () = assert!(Entry::SIZE == 13);
+//!
+//! let entry = Entry { key: *b"abcdefgh", version: 1, deleted: false };
+//! let mut buf = [0u8; Entry::SIZE];
+//! entry.serialize(&mut &mut buf[..]).unwrap();
+//! ```
+//!
+//! The derive sums the sizes of the encoded fields of a struct. An enum has a fixed size if all
+//! of its variants do and they are equal, which is checked when `SIZE` is evaluated; it is the
+//! size of a variant plus one for the tag.
+
+/// A type whose values all encode to [`SIZE`](Self::SIZE) bytes.
+pub trait BorshFixedSize {
+    /// Number of bytes every value of the type encodes to.
+    const SIZE: usize;
+}
+
+macro_rules! impl_for_primitives {
+    ($($type: ty)+) => {
+    $(
+        impl BorshFixedSize for $type {
+            const SIZE: usize = core::mem::size_of::<$type>();
+        }
+    )+
+    };
+}
+
+impl_for_primitives!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64);
+
+impl BorshFixedSize for () {
+    const SIZE: usize = 0;
+}
+
+impl BorshFixedSize for bool {
+    const SIZE: usize = 1;
+}
+
+impl<T: ?Sized> BorshFixedSize for core::marker::PhantomData<T> {
+    const SIZE: usize = 0;
+}
+
+impl BorshFixedSize for core::ops::RangeFull {
+    const SIZE: usize = 0;
+}
+
+impl BorshFixedSize for core::cmp::Ordering {
+    const SIZE: usize = 1;
+}
+
+#[cfg(feature = "std")]
+impl BorshFixedSize for std::net::Ipv4Addr {
+    const SIZE: usize = 4;
+}
+
+#[cfg(feature = "std")]
+impl BorshFixedSize for std::net::Ipv6Addr {
+    const SIZE: usize = 16;
+}
+
+#[cfg(feature = "std")]
+impl BorshFixedSize for std::net::SocketAddrV4 {
+    const SIZE: usize = 4 + 2;
+}
+
+#[cfg(feature = "std")]
+impl BorshFixedSize for std::net::SocketAddrV6 {
+    const SIZE: usize = 16 + 2;
+}
+
+impl<T: BorshFixedSize, const N: usize> BorshFixedSize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+}
+
+impl<T: BorshFixedSize> BorshFixedSize for &T {
+    const SIZE: usize = T::SIZE;
+}
+
+impl<T: BorshFixedSize> BorshFixedSize for crate::maybestd::boxed::Box<T> {
+    const SIZE: usize = T::SIZE;
+}
+
+macro_rules! impl_tuples {
+    ($($len:literal => ($($name:ident)+))+) => {
+        $(
+            impl<$($name: BorshFixedSize),+> BorshFixedSize for ($($name,)+) {
+                const SIZE: usize = 0 $(+ $name::SIZE)+;
+            }
+        )+
+    };
+}
+
+oasis_borsh_derive::_gen_seq_macro! {
+    impl_tuples => T :: (
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+        17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    )
+}
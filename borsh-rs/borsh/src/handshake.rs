@@ -0,0 +1,174 @@
+//! Agreeing on message types with a peer before exchanging payloads.
+//!
+//! Each side lists the types it can send or receive in a [`Registry`], and [`Registry::handshake`]
+//! sends the [`fingerprint`] of each type's schema to the peer and receives the peer's list in
+//! return. The resulting [`Negotiation`] tells which types both sides encode the same way, so
+//! that a service can refuse to talk, or stop using a type, instead of decoding garbage after a
+//! peer changed a schema.
+//!
+//! ```
+//! use oasis_borsh::handshake::Registry;
+//! use oasis_borsh::{BorshSchema, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshSchema)]
+//! struct Ping {
+//!     nonce: u64,
+//! }
+//!
+//! let registry = Registry::new().with::<Ping>();
+//! // A peer that registered the same types, replayed from a buffer.
+//! let mut peer = Vec::new();
+//! registry.write_hello(&mut peer).unwrap();
+//!
+//! let mut sent = Vec::new();
+//! let negotiation = registry.handshake(&mut &peer[..], &mut sent).unwrap();
+//! negotiation.require::<Ping>().unwrap();
+//! assert_eq!(sent, peer);
+//! ```
+//!
+//! The hello message is the magic bytes `BRHS`, a version byte and the borsh encoding of a
+//! `Vec<(String, u64)>` of declarations and fingerprints, sorted by declaration.
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, Read, Write};
+
+use crate::header::fingerprint;
+use crate::schema::{BorshSchema, Declaration};
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Bytes every hello message starts with.
+pub const MAGIC: [u8; 4] = *b"BRHS";
+/// Version of the hello message layout.
+pub const VERSION: u8 = 1;
+
+/// The types one side of a connection knows, by declaration, with their schema fingerprints.
+#[derive(Clone, Default, Debug)]
+pub struct Registry {
+    types: BTreeMap<Declaration, u64>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Add `T` to the registry.
+    pub fn register<T: BorshSchema + ?Sized>(&mut self) -> &mut Self {
+        self.types.insert(T::declaration(), fingerprint::<T>());
+        self
+    }
+
+    /// Like [`register`](Self::register), for building a registry in one expression.
+    pub fn with<T: BorshSchema + ?Sized>(mut self) -> Self {
+        self.register::<T>();
+        self
+    }
+
+    /// Write the hello message listing the registered types into `writer`, and flush it.
+    pub fn write_hello<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut hello = Vec::new();
+        hello.extend_from_slice(&MAGIC);
+        hello.push(VERSION);
+        let types = self.types.iter().collect::<Vec<_>>();
+        types.serialize(&mut hello)?;
+        writer.write_all(&hello)?;
+        writer.flush()
+    }
+
+    /// Read the hello message of the peer from `reader` and compare its types to these.
+    pub fn read_hello<R: Read>(&self, reader: &mut R) -> Result<Negotiation, Error> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Missing handshake hello",
+            ));
+        }
+        if header[4] != VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unsupported handshake version: {}", header[4]),
+            ));
+        }
+        let remote = <Vec<(Declaration, u64)>>::deserialize(reader)?;
+        let mut negotiation = Negotiation::default();
+        let mut remote = remote.into_iter().collect::<BTreeMap<_, _>>();
+        for (declaration, local) in &self.types {
+            match remote.remove(declaration) {
+                Some(fingerprint) if fingerprint == *local => {
+                    negotiation.compatible.push(declaration.clone())
+                }
+                Some(_) => negotiation.mismatched.push(declaration.clone()),
+                None => negotiation.local_only.push(declaration.clone()),
+            }
+        }
+        negotiation.remote_only = remote.into_keys().collect();
+        Ok(negotiation)
+    }
+
+    /// Send the hello message to the peer through `writer`, then read the peer's from `reader`.
+    ///
+    /// Both sides write before they read, so the hello messages cross on the wire; they are small
+    /// enough to fit in the buffers of a socket or pipe.
+    pub fn handshake<R: Read, W: Write>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<Negotiation, Error> {
+        self.write_hello(writer)?;
+        self.read_hello(reader)
+    }
+}
+
+/// How the types of the two sides of a handshake compare, each list sorted by declaration.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Negotiation {
+    /// Types both sides have with the same schema.
+    pub compatible: Vec<Declaration>,
+    /// Types both sides have, but with different schemas.
+    pub mismatched: Vec<Declaration>,
+    /// Types only this side has.
+    pub local_only: Vec<Declaration>,
+    /// Types only the peer has.
+    pub remote_only: Vec<Declaration>,
+}
+
+impl Negotiation {
+    /// Whether both sides have the type declared as `declaration`, with the same schema.
+    pub fn is_compatible(&self, declaration: &str) -> bool {
+        self.compatible
+            .binary_search_by(|compatible| compatible.as_str().cmp(declaration))
+            .is_ok()
+    }
+
+    /// Fail unless both sides have `T` with the same schema.
+    pub fn require<T: BorshSchema + ?Sized>(&self) -> Result<(), Error> {
+        let declaration = T::declaration();
+        if self.is_compatible(&declaration) {
+            return Ok(());
+        }
+        let reason = if self.mismatched.contains(&declaration) {
+            "has a different schema on the peer"
+        } else {
+            "is not registered on both sides"
+        };
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Handshake failed: {} {}", declaration, reason),
+        ))
+    }
+
+    /// Fail unless both sides have the same types with the same schemas.
+    pub fn require_all(&self) -> Result<(), Error> {
+        if self.mismatched.is_empty() && self.local_only.is_empty() && self.remote_only.is_empty() {
+            return Ok(());
+        }
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Handshake failed: mismatched {:?}, only local {:?}, only remote {:?}",
+                self.mismatched, self.local_only, self.remote_only
+            ),
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+//! Hashing values by their borsh encoding, for content-addressed keys that agree across services.
+//!
+//! [`BorshHash`] is implemented for every [`BorshSerialize`] type, derived or not. It streams the
+//! encoding into a [`Hasher`] as it is produced, without collecting it into a `Vec` first, and
+//! feeds the hasher in fixed-size blocks, so the result depends only on the bytes of the encoding
+//! and not on how the serializers of the type happen to split their writes. Two values with the
+//! same encoding therefore hash the same, even if their types differ.
+//!
+//! ```
+//! use std::collections::hash_map::DefaultHasher;
+//! use oasis_borsh::hash::hash;
+//!
+//! let a = hash::<DefaultHasher, _>(&("key", 1u32)).unwrap();
+//! let b = hash::<DefaultHasher, _>(&(String::from("key"), 1u32)).unwrap();
+//! assert_eq!(a, b);
+//! ```
+//!
+//! Cryptographic digests usually implement `io::Write`; to key storage by one of them, serialize
+//! the value straight into the digest instead.
+use core::hash::Hasher;
+
+use crate::maybestd::io::{Error, Write};
+use crate::BorshSerialize;
+
+/// Size of the blocks the encoding is fed to hashers in.
+const BLOCK: usize = 64;
+
+/// Values that can be hashed by their borsh encoding.
+pub trait BorshHash {
+    /// Feed the borsh encoding of `self` into `hasher`.
+    fn borsh_hash<H: Hasher>(&self, hasher: &mut H) -> Result<(), Error>;
+}
+
+impl<T: BorshSerialize + ?Sized> BorshHash for T {
+    fn borsh_hash<H: Hasher>(&self, hasher: &mut H) -> Result<(), Error> {
+        let mut writer = HashWriter {
+            hasher,
+            block: [0; BLOCK],
+            len: 0,
+        };
+        self.serialize(&mut writer)?;
+        writer.hasher.write(&writer.block[..writer.len]);
+        Ok(())
+    }
+}
+
+/// The hash of the borsh encoding of `value`, computed with a new `H`.
+pub fn hash<H, T>(value: &T) -> Result<u64, Error>
+where
+    H: Hasher + Default,
+    T: BorshSerialize + ?Sized,
+{
+    let mut hasher = H::default();
+    value.borsh_hash(&mut hasher)?;
+    Ok(hasher.finish())
+}
+
+/// A writer that passes the bytes written to it on to `hasher` in blocks of `BLOCK` bytes.
+struct HashWriter<'a, H> {
+    hasher: &'a mut H,
+    block: [u8; BLOCK],
+    len: usize,
+}
+
+impl<H: Hasher> Write for HashWriter<'_, H> {
+    fn write(&mut self, mut buf: &[u8]) -> Result<usize, Error> {
+        let written = buf.len();
+        while !buf.is_empty() {
+            let n = core::cmp::min(BLOCK - self.len, buf.len());
+            self.block[self.len..self.len + n].copy_from_slice(&buf[..n]);
+            self.len += n;
+            buf = &buf[n..];
+            if self.len == BLOCK {
+                self.hasher.write(&self.block);
+                self.len = 0;
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+//! Opt-in self-describing framing for stored blobs.
+//!
+//! A header of [`HEADER_LEN`] bytes is written in front of the regular borsh payload: the
+//! [`MAGIC`] bytes, the [`FORMAT_VERSION`] and the [`fingerprint`] of the type's schema. On decode
+//! the header is checked before the payload is touched, so a blob written for one type fails with
+//! a schema mismatch error instead of decoding garbage into another type.
+use std::io::{Cursor, Error, ErrorKind, Read, Write};
+
+use crate::schema::BorshSchema;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Bytes every self-describing blob starts with.
+pub const MAGIC: [u8; 4] = *b"BRSH";
+/// Version of the header layout.
+pub const FORMAT_VERSION: u8 = 1;
+/// Length of the header in bytes: magic, version and a `u64` fingerprint.
+pub const HEADER_LEN: usize = 13;
+
+/// Fingerprint of the schema of `T`.
+///
+/// This is the 64-bit FNV-1a hash of the borsh encoding of `T::schema_container()`, so any change
+/// to the type names, field names, field types or variants that make up `T` changes it.
+pub fn fingerprint<T: BorshSchema + ?Sized>() -> u64 {
+    let container = T::schema_container()
+        .try_to_vec()
+        .expect("Writing to a Vec never fails");
+    fnv1a64(&container)
+}
+
+pub(crate) fn fnv1a64(data: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in data {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Write the header describing `T` into `writer`.
+pub fn write_header<T: BorshSchema + ?Sized, W: Write>(writer: &mut W) -> Result<(), Error> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION])?;
+    writer.write_all(&fingerprint::<T>().to_le_bytes())
+}
+
+/// Read a header from `reader` and check that it describes `T`.
+pub fn read_header<T: BorshSchema + ?Sized, R: Read>(reader: &mut R) -> Result<(), Error> {
+    let mut header = [0u8; HEADER_LEN];
+    reader.read_exact(&mut header)?;
+    if header[..4] != MAGIC {
+        return Err(Error::new(ErrorKind::InvalidData, "Missing borsh header"));
+    }
+    if header[4] != FORMAT_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unsupported borsh header version: {}", header[4]),
+        ));
+    }
+    let mut found = [0u8; 8];
+    found.copy_from_slice(&header[5..]);
+    let found = u64::from_le_bytes(found);
+    let expected = fingerprint::<T>();
+    if found != expected {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Schema mismatch: expected fingerprint {:016x}, found {:016x}",
+                expected, found
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Serialize `value` prefixed with its header.
+pub fn to_vec_with_header<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: BorshSerialize + BorshSchema + ?Sized,
+{
+    let mut result = Vec::new();
+    write_header::<T, _>(&mut result)?;
+    value.serialize(&mut result)?;
+    Ok(result)
+}
+
+/// Deserialize a value written by [`to_vec_with_header`], checking the header first.
+pub fn from_slice_with_header<T>(v: &[u8]) -> Result<T, Error>
+where
+    T: BorshDeserialize + BorshSchema,
+{
+    let mut c = Cursor::new(v);
+    read_header::<T, _>(&mut c)?;
+    T::try_from_slice(&v[c.position() as usize..])
+}
@@ -0,0 +1,259 @@
+//! Borsh messages over pipes, such as the stdin and stdout of a subprocess.
+//!
+//! A pipe carries bytes, not messages, so an [`IpcSender`] writes each message as a frame that an
+//! [`IpcReceiver`] on the other end splits back out: a `u32` little-endian payload length, a flags
+//! byte, a `u32` little-endian CRC-32 of the payload when the checksum flag (`1`) is set, and the
+//! borsh encoding of the message. The receiver reads checksummed and plain frames alike, so only
+//! the sender chooses.
+//!
+//! ```
+//! use oasis_borsh::ipc::{IpcReceiver, IpcSender};
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+//! enum Request {
+//!     Load { path: String },
+//!     Shutdown,
+//! }
+//!
+//! // A plugin would use `IpcSender::stdout()` and its host the child's stdin.
+//! let mut pipe = Vec::new();
+//! let mut sender = IpcSender::new(&mut pipe).checksummed(true);
+//! sender.send(&Request::Load { path: "a.so".to_string() }).unwrap();
+//! sender.send(&Request::Shutdown).unwrap();
+//!
+//! let mut receiver = IpcReceiver::<Request, _>::new(&pipe[..]);
+//! assert_eq!(receiver.recv().unwrap(), Some(Request::Load { path: "a.so".to_string() }));
+//! assert_eq!(receiver.recv().unwrap(), Some(Request::Shutdown));
+//! // The sender closed the pipe between two frames.
+//! assert_eq!(receiver.recv().unwrap(), None);
+//! ```
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::marker::PhantomData;
+
+use crate::crc32::crc32;
+use crate::{BorshDeserialize, BorshSerialize};
+
+const FLAG_CHECKSUMMED: u8 = 1;
+
+/// Writes messages of type `T` as frames to `W`, see the module documentation.
+pub struct IpcSender<T, W> {
+    writer: W,
+    checksummed: bool,
+    _message: PhantomData<fn(&T)>,
+}
+
+impl<T> IpcSender<T, io::Stdout> {
+    /// A sender writing to the standard output of this process.
+    pub fn stdout() -> Self {
+        IpcSender::new(io::stdout())
+    }
+}
+
+impl<T, W> IpcSender<T, W> {
+    /// A sender writing plain frames to `writer`.
+    pub fn new(writer: W) -> Self {
+        IpcSender {
+            writer,
+            checksummed: false,
+            _message: PhantomData,
+        }
+    }
+
+    /// Whether each frame carries a CRC-32 of its payload.
+    pub fn checksummed(mut self, checksummed: bool) -> Self {
+        self.checksummed = checksummed;
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<T: BorshSerialize, W: Write> IpcSender<T, W> {
+    /// Write `message` as one frame and flush it, so the receiver sees it right away.
+    pub fn send(&mut self, message: &T) -> Result<(), Error> {
+        let payload = message.try_to_vec()?;
+        if payload.len() > u32::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "Message is too large"));
+        }
+        let mut frame = Vec::with_capacity(payload.len() + 9);
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        if self.checksummed {
+            frame.push(FLAG_CHECKSUMMED);
+            frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+        } else {
+            frame.push(0);
+        }
+        frame.extend_from_slice(&payload);
+        // One write per frame, so frames from senders sharing a pipe do not interleave as much.
+        self.writer.write_all(&frame)?;
+        self.writer.flush()
+    }
+}
+
+/// Reads messages of type `T` from frames written by an [`IpcSender`] to `R`.
+pub struct IpcReceiver<T, R> {
+    reader: R,
+    max_len: u32,
+    require_checksums: bool,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T> IpcReceiver<T, io::Stdin> {
+    /// A receiver reading from the standard input of this process.
+    pub fn stdin() -> Self {
+        IpcReceiver::new(io::stdin())
+    }
+}
+
+impl<T, R> IpcReceiver<T, R> {
+    /// A receiver reading frames of any length from `reader`.
+    pub fn new(reader: R) -> Self {
+        IpcReceiver {
+            reader,
+            max_len: u32::MAX,
+            require_checksums: false,
+            _message: PhantomData,
+        }
+    }
+
+    /// Reject frames whose payload is longer than `max_len` bytes before reading the payload.
+    pub fn max_len(mut self, max_len: u32) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Reject frames that do not carry a checksum.
+    pub fn require_checksums(mut self, require_checksums: bool) -> Self {
+        self.require_checksums = require_checksums;
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<T: BorshDeserialize, R: Read> IpcReceiver<T, R> {
+    /// Read the next message, or `None` if the pipe was closed between two frames.
+    ///
+    /// A pipe closed in the middle of a frame is an `UnexpectedEof` error, and a frame that is
+    /// corrupt or does not decode as a `T` is an `InvalidData` error. After a checksum mismatch
+    /// or a payload that does not decode, the next frame can still be received; after any other
+    /// error the receiver has lost track of where frames start and should be dropped.
+    pub fn recv(&mut self) -> Result<Option<T>, Error> {
+        let mut header = [0u8; 5];
+        if !self.read_header(&mut header)? {
+            return Ok(None);
+        }
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let checksum = match header[4] {
+            0 if self.require_checksums => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Frame carries no checksum",
+                ))
+            }
+            0 => None,
+            FLAG_CHECKSUMMED => {
+                let mut checksum = [0u8; 4];
+                self.read_exact(&mut checksum)?;
+                Some(u32::from_le_bytes(checksum))
+            }
+            flags => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Unknown frame flags {:#04x}", flags),
+                ))
+            }
+        };
+        if len > self.max_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Frame of {} bytes exceeds the limit of {}",
+                    len, self.max_len
+                ),
+            ));
+        }
+        let mut payload = Vec::with_capacity(crate::de::hint::cautious::<u8>(len));
+        self.reader
+            .by_ref()
+            .take(u64::from(len))
+            .read_to_end(&mut payload)?;
+        if payload.len() != len as usize {
+            return Err(truncated());
+        }
+        if checksum.is_some_and(|checksum| crc32(&payload) != checksum) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Frame checksum mismatch",
+            ));
+        }
+        T::try_from_slice(&payload).map(Some).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Undecodable frame ({})", err),
+            )
+        })
+    }
+
+    /// Fill `header`, or return `false` if the reader ends before its first byte.
+    fn read_header(&mut self, header: &mut [u8]) -> Result<bool, Error> {
+        let mut filled = 0;
+        while filled < header.len() {
+            match self.reader.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(false),
+                Ok(0) => return Err(truncated()),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(true)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.reader.read_exact(buf).map_err(|err| {
+            if err.kind() == ErrorKind::UnexpectedEof {
+                truncated()
+            } else {
+                err
+            }
+        })
+    }
+}
+
+fn truncated() -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        "Pipe closed in the middle of a frame",
+    )
+}
+
+impl<T: BorshDeserialize, R: Read> Iterator for IpcReceiver<T, R> {
+    type Item = Result<T, Error>;
+
+    /// Like [`recv`](IpcReceiver::recv), ending when the pipe is closed between two frames.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv().transpose()
+    }
+}
@@ -0,0 +1,322 @@
+//! Support for [`serde_json::Value`], for carrying schemaless JSON inside borsh messages.
+//!
+//! A value is encoded as a one-byte tag followed by its contents:
+//!
+//! | Tag | JSON    | Contents                                                                  |
+//! |-----|---------|---------------------------------------------------------------------------|
+//! | 0   | null    | nothing                                                                   |
+//! | 1   | boolean | a `bool`                                                                  |
+//! | 2   | number  | a number, see below                                                       |
+//! | 3   | string  | a `String`                                                                |
+//! | 4   | array   | a `Vec<Value>`                                                            |
+//! | 5   | object  | a `u32` count, then each key as a `String` followed by its value          |
+//!
+//! Object members are written in ascending order of their keys, whatever order the map keeps
+//! them in, so the encoding does not depend on serde_json's `preserve_order` feature. When
+//! decoding, a key that appears twice keeps its last value.
+//!
+//! A number is a one-byte kind followed by its value: 0 and a `u64` for non-negative integers,
+//! 1 and an `i64` for negative integers, and 2 and an `f64` for all other numbers. Decoding
+//! rejects non-negative integers of kind 1, so each number has a single encoding, and floats
+//! that JSON cannot represent.
+//!
+//! The schema describes this layout as the enums `JsonValue` and `JsonNumber`, with objects as a
+//! `BTreeMap<string, JsonValue>`.
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::mem::size_of;
+
+use serde_json::{Map, Number, Value};
+
+use crate::de::{check_len, check_unique, hint, or_rewind, LenKind};
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::schema::{BorshSchema, Declaration, Definition};
+use crate::ser::extrapolate;
+use crate::{BorshDeserialize, BorshSerialize};
+
+const NULL: u8 = 0;
+const BOOL: u8 = 1;
+const NUMBER: u8 = 2;
+const STRING: u8 = 3;
+const ARRAY: u8 = 4;
+const OBJECT: u8 = 5;
+
+const POS_INT: u8 = 0;
+const NEG_INT: u8 = 1;
+const FLOAT: u8 = 2;
+
+impl BorshSerialize for Value {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            Value::Null => NULL.serialize(writer),
+            Value::Bool(value) => {
+                BOOL.serialize(writer)?;
+                value.serialize(writer)
+            }
+            Value::Number(value) => {
+                NUMBER.serialize(writer)?;
+                value.serialize(writer)
+            }
+            Value::String(value) => {
+                STRING.serialize(writer)?;
+                value.serialize(writer)
+            }
+            Value::Array(value) => {
+                ARRAY.serialize(writer)?;
+                value.serialize(writer)
+            }
+            Value::Object(value) => {
+                OBJECT.serialize(writer)?;
+                value.serialize(writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            Value::Null => 0,
+            Value::Bool(value) => value.serialized_size(),
+            Value::Number(value) => value.serialized_size(),
+            Value::String(value) => value.serialized_size(),
+            Value::Array(value) => value.serialized_size(),
+            Value::Object(value) => value.serialized_size(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            Value::Null => 0,
+            Value::Bool(value) => value.size_hint(),
+            Value::Number(value) => value.size_hint(),
+            Value::String(value) => value.size_hint(),
+            Value::Array(value) => value.size_hint(),
+            Value::Object(value) => value.size_hint(),
+        }
+        .saturating_add(1)
+    }
+}
+
+impl BorshDeserialize for Value {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(match u8::deserialize(reader)? {
+            NULL => Value::Null,
+            BOOL => Value::Bool(bool::deserialize(reader)?),
+            NUMBER => Value::Number(Number::deserialize(reader)?),
+            STRING => Value::String(String::deserialize(reader)?),
+            ARRAY => Value::Array(Vec::deserialize(reader)?),
+            OBJECT => Value::Object(Map::deserialize(reader)?),
+            tag => return Err(crate::Error::InvalidTag(tag).into()),
+        })
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        Ok(match u8::deserialize_slice(buf)? {
+            NULL => Value::Null,
+            BOOL => Value::Bool(bool::deserialize_slice(buf)?),
+            NUMBER => Value::Number(Number::deserialize_slice(buf)?),
+            STRING => Value::String(String::deserialize_slice(buf)?),
+            ARRAY => Value::Array(Vec::deserialize_slice(buf)?),
+            OBJECT => Value::Object(Map::deserialize_slice(buf)?),
+            tag => return or_rewind(Err(crate::Error::InvalidTag(tag).into()), buf, start),
+        })
+    }
+}
+
+impl BorshSerialize for Number {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        if let Some(value) = self.as_u64() {
+            POS_INT.serialize(writer)?;
+            value.serialize(writer)
+        } else if let Some(value) = self.as_i64() {
+            NEG_INT.serialize(writer)?;
+            value.serialize(writer)
+        } else {
+            // Only serde_json's `arbitrary_precision` feature makes numbers that are none of the
+            // three.
+            let value = self.as_f64().ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "JSON number does not fit an f64")
+            })?;
+            FLOAT.serialize(writer)?;
+            value.serialize(writer)
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + 8
+    }
+
+    fn size_hint(&self) -> usize {
+        1 + 8
+    }
+}
+
+impl BorshDeserialize for Number {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        match u8::deserialize(reader)? {
+            POS_INT => Ok(Number::from(u64::deserialize(reader)?)),
+            NEG_INT => negative(i64::deserialize(reader)?),
+            FLOAT => float(f64::deserialize(reader)?),
+            kind => Err(crate::Error::InvalidTag(kind).into()),
+        }
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let result = match u8::deserialize_slice(buf)? {
+            POS_INT => Ok(Number::from(u64::deserialize_slice(buf)?)),
+            NEG_INT => negative(i64::deserialize_slice(buf)?),
+            FLOAT => float(f64::deserialize_slice(buf)?),
+            kind => Err(crate::Error::InvalidTag(kind).into()),
+        };
+        or_rewind(result, buf, start)
+    }
+}
+
+fn negative(value: i64) -> Result<Number, Error> {
+    if value >= 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Non-negative JSON integer encoded as negative",
+        ));
+    }
+    Ok(Number::from(value))
+}
+
+fn float(value: f64) -> Result<Number, Error> {
+    Number::from_f64(value).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "JSON numbers cannot be infinite or NaN",
+        )
+    })
+}
+
+impl BorshSerialize for Map<String, Value> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut members = self.iter().collect::<Vec<_>>();
+        members.sort_by_key(|(key, _)| *key);
+        (members.len() as u32).serialize(writer)?;
+        for (key, value) in members {
+            key.serialize(writer)?;
+            value.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        size_of::<u32>()
+            + self
+                .iter()
+                .map(|(key, value)| key.serialized_size() + value.serialized_size())
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let entries = self.iter().next().map_or(0, |(key, value)| {
+            extrapolate(
+                self.len(),
+                key.size_hint().saturating_add(value.size_hint()),
+            )
+        });
+        size_of::<u32>().saturating_add(entries)
+    }
+}
+
+impl BorshDeserialize for Map<String, Value> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = u32::deserialize(reader)?;
+        check_len(len.into(), LenKind::Map)?;
+        hint::unreserved::<(String, Value)>(len);
+        let mut result = Map::new();
+        for _ in 0..len {
+            let key = String::deserialize(reader)?;
+            let value = Value::deserialize(reader)?;
+            hint::charge::<(String, Value)>(1)?;
+            check_unique(result.insert(key, value).is_none())?;
+        }
+        Ok(result)
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        let start = *buf;
+        let len = u32::deserialize_slice(buf)?;
+        or_rewind(check_len(len.into(), LenKind::Map), buf, start)?;
+        hint::unreserved::<(String, Value)>(len);
+        let mut result = Map::new();
+        for _ in 0..len {
+            let entry = *buf;
+            let key = String::deserialize_slice(buf)?;
+            let value = Value::deserialize_slice(buf)?;
+            hint::charge::<(String, Value)>(1)?;
+            or_rewind(
+                check_unique(result.insert(key, value).is_none()),
+                buf,
+                entry,
+            )?;
+        }
+        Ok(result)
+    }
+}
+
+impl BorshSchema for Value {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Null".to_string(), <()>::declaration()),
+                ("Bool".to_string(), bool::declaration()),
+                ("Number".to_string(), Number::declaration()),
+                ("String".to_string(), String::declaration()),
+                ("Array".to_string(), <Vec<Value>>::declaration()),
+                ("Object".to_string(), Map::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            Number::add_definitions_recursively(definitions);
+            <Vec<Value>>::add_definitions_recursively(definitions);
+            Map::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        "JsonValue".to_string()
+    }
+}
+
+impl BorshSchema for Number {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("PosInt".to_string(), u64::declaration()),
+                ("NegInt".to_string(), i64::declaration()),
+                ("Float".to_string(), f64::declaration()),
+            ],
+        };
+        Self::add_definition(Self::declaration(), definition, definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "JsonNumber".to_string()
+    }
+}
+
+/// Described as a `BTreeMap`, since members are written in key order.
+impl BorshSchema for Map<String, Value> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <std::collections::BTreeMap<String, Value>>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <std::collections::BTreeMap<String, Value>>::declaration()
+    }
+}
+
+impl BorshDiff for Value {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
@@ -1,7 +1,77 @@
-pub use oasis_borsh_derive::{BorshDeserialize, BorshSerialize};
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+pub use oasis_borsh_derive::{
+    BorshColumnar, BorshDeserialize, BorshDeserializeSeed, BorshDiff, BorshFixedSize, BorshSchema,
+    BorshSerialize, BorshSerializeCtx,
+};
+
+#[cfg(feature = "allocator-api")]
+pub mod allocator;
+pub mod canonical_float;
+#[cfg(feature = "bytes")]
+pub mod chain;
+pub mod chunks;
+pub mod columnar;
+#[cfg(feature = "std")]
+mod crc32;
 pub mod de;
+pub mod delta;
+pub mod diff;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod encrypted;
+mod error;
+pub mod extension;
+#[cfg(feature = "std")]
+pub mod file;
+pub mod fixed_bytes;
+pub mod fixed_size;
+#[cfg(feature = "std")]
+pub mod handshake;
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod header;
+#[cfg(feature = "std")]
+pub mod ipc;
+#[cfg(feature = "serde_json")]
+mod json;
+pub mod maybestd;
+pub mod memcpy;
+pub mod prefix;
+pub mod raw;
+#[cfg(feature = "std")]
+pub mod record_log;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "secrecy")]
+mod secret;
 pub mod ser;
+pub mod spec;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod tagged;
+#[cfg(feature = "type-guard")]
+pub mod type_guard;
+mod varint;
 
-pub use de::BorshDeserialize;
-pub use ser::BorshSerialize;
+pub use columnar::BorshColumnar;
+#[cfg(feature = "std")]
+pub use de::from_reader;
+pub use de::{BorshDeserialize, BorshDeserializeSeed};
+pub use error::Error;
+#[cfg(feature = "std")]
+pub use file::{from_file, to_file};
+pub use fixed_size::BorshFixedSize;
+pub use hash::BorshHash;
+#[cfg(feature = "std")]
+pub use record_log::RecordLog;
+#[cfg(feature = "std")]
+pub use schema::BorshSchema;
+#[cfg(feature = "std")]
+pub use ser::to_writer;
+pub use ser::{serialized_len, BorshSerialize, BorshSerializeCtx, BorshSerializeDyn};
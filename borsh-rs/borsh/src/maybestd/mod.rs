@@ -0,0 +1,12 @@
+//! The parts of std that borsh uses, taken from `core` and `alloc` without the `std` feature.
+//!
+//! Derived impls name `io` and the allocating types through this module, so they compile both in
+//! crates that use std and in `no_std` crates. Without std, `io` is a minimal replacement for
+//! `std::io` with the same names.
+#[cfg(not(feature = "std"))]
+pub use alloc::{borrow, boxed, collections, format, rc, string, sync, vec};
+#[cfg(feature = "std")]
+pub use std::{borrow, boxed, collections, format, io, rc, string, sync, vec};
+
+#[cfg(not(feature = "std"))]
+pub mod io;
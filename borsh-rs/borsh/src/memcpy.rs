@@ -0,0 +1,145 @@
+//! Encoding by a single byte copy, for types whose memory layout on little-endian targets is
+//! exactly their borsh encoding.
+//!
+//! `#[borsh_memcpy]` on a `#[repr(C)]` struct whose fields are all [`Memcpy`] makes the derived
+//! `BorshSerialize` implement [`Memcpy`] for it, after checking at compile time that the struct has
+//! no padding, and makes both derives copy the struct's bytes on little-endian targets. The wire
+//! format is the same as for the field-by-field encoding, which is still used on big-endian ones.
+//! NaNs in float fields follow the [`NanPolicy`](crate::de::options::NanPolicy) in effect either
+//! way: rejected by default, written and read bit for bit, or replaced by the canonical NaN.
+//! `BorshDeserialize` relies on the impl, so `#[borsh_memcpy]` structs derive both.
+//!
+//! [`deserialize_into`] decodes such a type straight into caller-provided storage, e.g. a slot of
+//! an object pool or a static buffer on an embedded target, without the decoded value passing
+//! through the stack.
+use core::mem::{size_of, ManuallyDrop, MaybeUninit};
+
+use crate::maybestd::io::{Error, Read, Write};
+use crate::{spec, BorshDeserialize};
+
+/// A type whose in-memory representation on little-endian targets is its borsh encoding.
+///
+/// # Safety
+///
+/// The type must have no padding, every bit pattern must be a valid value, and its bytes must be
+/// what `BorshSerialize` writes on a little-endian target.
+pub unsafe trait Memcpy: Sized {
+    /// Whether any float in the value is NaN, which the NaN policy may not allow to copy as is.
+    fn has_nan(&self) -> bool {
+        false
+    }
+
+    /// Replace every NaN float in the value by the canonical NaN.
+    fn canonicalize_nans(&mut self) {}
+}
+
+macro_rules! impl_for_integers {
+    ($($type: ident)+) => {
+    $(
+        unsafe impl Memcpy for $type {}
+    )+
+    };
+}
+
+impl_for_integers!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+macro_rules! impl_for_float {
+    ($type: ident, $canonical: expr) => {
+        unsafe impl Memcpy for $type {
+            #[inline]
+            fn has_nan(&self) -> bool {
+                self.is_nan()
+            }
+
+            #[inline]
+            fn canonicalize_nans(&mut self) {
+                if self.is_nan() {
+                    *self = $type::from_bits($canonical);
+                }
+            }
+        }
+    };
+}
+
+impl_for_float!(f32, spec::CANONICAL_NAN_F32);
+impl_for_float!(f64, spec::CANONICAL_NAN_F64);
+
+unsafe impl<T: Memcpy, const N: usize> Memcpy for [T; N] {
+    #[inline]
+    fn has_nan(&self) -> bool {
+        self.iter().any(Memcpy::has_nan)
+    }
+
+    #[inline]
+    fn canonicalize_nans(&mut self) {
+        for item in self {
+            item.canonicalize_nans();
+        }
+    }
+}
+
+fn bytes_of<T: Memcpy>(value: &T) -> &[u8] {
+    // Memcpy types have no padding, so all of their bytes are initialized.
+    unsafe { core::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
+
+/// Write the bytes of `value`. Only a valid encoding on little-endian targets.
+///
+/// Like the field-by-field encoding, this panics on a NaN unless the NaN policy in effect allows
+/// it.
+#[inline]
+pub fn serialize<T: Memcpy, W: Write>(value: &T, writer: &mut W) -> Result<(), Error> {
+    if value.has_nan() && crate::ser::canonical_nan() {
+        // Canonicalize a bitwise copy, which must not be dropped as it does not own anything.
+        let mut copy = ManuallyDrop::new(unsafe { core::ptr::read(value) });
+        copy.canonicalize_nans();
+        return writer.write_all(bytes_of(&*copy));
+    }
+    writer.write_all(bytes_of(value))
+}
+
+/// Read the bytes of `value` from `reader`.
+fn read_bytes<T: Memcpy, R: Read>(reader: &mut R, value: &mut T) -> Result<(), Error> {
+    let bytes =
+        unsafe { core::slice::from_raw_parts_mut(value as *mut T as *mut u8, size_of::<T>()) };
+    reader.read_exact(bytes)?;
+    if value.has_nan() && crate::de::canonical_nan()? {
+        value.canonicalize_nans();
+    }
+    Ok(())
+}
+
+/// Read a value as its bytes. Only a valid decoding on little-endian targets.
+#[inline]
+pub fn deserialize<T: Memcpy, R: Read>(reader: &mut R) -> Result<T, Error> {
+    // All-zero bytes are a valid value of any Memcpy type, so this never holds an invalid one.
+    let mut value: T = unsafe { core::mem::zeroed() };
+    read_bytes(reader, &mut value)?;
+    Ok(value)
+}
+
+/// Deserialize a `T` from `reader` into `slot`, returning a reference to the initialized value.
+///
+/// On little-endian targets the bytes are read directly into `slot`; elsewhere the value is
+/// decoded field by field and then moved there. If decoding fails, `slot` is to be treated as
+/// uninitialized.
+#[inline]
+pub fn deserialize_into<'a, T, R>(
+    reader: &mut R,
+    slot: &'a mut MaybeUninit<T>,
+) -> Result<&'a mut T, Error>
+where
+    T: Memcpy + BorshDeserialize,
+    R: Read,
+{
+    if cfg!(target_endian = "big") {
+        return Ok(slot.write(T::deserialize(reader)?));
+    }
+    // As in `deserialize`, zeroing the slot makes it hold a valid value before it is read into.
+    let value = unsafe {
+        slot.as_mut_ptr().write_bytes(0, 1);
+        slot.assume_init_mut()
+    };
+    read_bytes(reader, value)?;
+    Ok(value)
+}
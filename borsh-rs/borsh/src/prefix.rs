@@ -0,0 +1,406 @@
+//! Collections encoded with a length prefix other than the default `u32`.
+//!
+//! `#[borsh(len = "u8")]`, `"u16"` or `"u64"` on a field of a derived type routes it through
+//! [`serialize`] and [`deserialize`] here. The elements are encoded exactly as usual; only the
+//! width of the length in front of them changes.
+use core::convert::TryFrom;
+use core::mem::size_of;
+
+use crate::de::{check_ascending, check_len, check_unique, hint, or_rewind, LenKind};
+use crate::maybestd::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std")]
+use crate::maybestd::collections::{HashMap, HashSet};
+use crate::maybestd::format;
+use crate::maybestd::io::{Error, ErrorKind, Read, Write};
+use crate::maybestd::string::String;
+use crate::maybestd::vec::Vec;
+use crate::ser::{extrapolate, SizeWriter};
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// An unsigned integer type that can hold the length of a collection.
+pub trait LengthPrefix {
+    /// Name of the integer type, as used in `#[borsh(len = "...")]` and schema declarations.
+    const NAME: &'static str;
+
+    /// Write `len`, failing if it does not fit this type.
+    fn write_len<W: Write>(len: usize, writer: &mut W) -> Result<(), Error>;
+
+    /// Read a length, failing if it does not fit `usize`.
+    fn read_len<R: Read>(reader: &mut R) -> Result<usize, Error>;
+}
+
+macro_rules! impl_length_prefix {
+    ($($type: ident)+) => {
+    $(
+        impl LengthPrefix for $type {
+            const NAME: &'static str = stringify!($type);
+
+            fn write_len<W: Write>(len: usize, writer: &mut W) -> Result<(), Error> {
+                let len = $type::try_from(len).map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Length {} does not fit in a {} prefix", len, Self::NAME),
+                    )
+                })?;
+                len.serialize(writer)
+            }
+
+            fn read_len<R: Read>(reader: &mut R) -> Result<usize, Error> {
+                let len = $type::deserialize(reader)?;
+                usize::try_from(len).map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Length {} does not fit in usize", len),
+                    )
+                })
+            }
+        }
+    )+
+    };
+}
+
+impl_length_prefix!(u8 u16 u32 u64);
+
+/// A collection that can be serialized behind a length prefix of any width.
+pub trait SerializePrefixed {
+    /// Number of elements, i.e. the value of the length prefix.
+    fn prefixed_len(&self) -> usize;
+
+    /// Serialize the elements, without the length prefix.
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+
+    /// Estimate of the size of the elements, see [`BorshSerialize::size_hint`].
+    fn elements_size_hint(&self) -> usize {
+        0
+    }
+}
+
+/// A collection that can be deserialized from behind a length prefix of any width.
+pub trait DeserializePrefixed: Sized {
+    /// Deserialize `len` elements, the length prefix having been read already.
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error>;
+}
+
+/// Serialize `value` with a length prefix of type `L`.
+pub fn serialize<L, T, W>(value: &T, writer: &mut W) -> Result<(), Error>
+where
+    L: LengthPrefix,
+    T: SerializePrefixed + ?Sized,
+    W: Write,
+{
+    L::write_len(value.prefixed_len(), writer)?;
+    value.serialize_elements(writer)
+}
+
+/// Number of bytes [`serialize`] writes for `value` with a length prefix of type `L`.
+pub fn serialized_size<L, T>(value: &T) -> usize
+where
+    L: LengthPrefix,
+    T: SerializePrefixed + ?Sized,
+{
+    let mut counter = SizeWriter::new();
+    let _ = serialize::<L, T, _>(value, &mut counter);
+    counter.written()
+}
+
+/// Estimate of the number of bytes [`serialize`] writes for `value`, see
+/// [`BorshSerialize::size_hint`].
+pub fn size_hint<L, T>(value: &T) -> usize
+where
+    L: LengthPrefix,
+    T: SerializePrefixed + ?Sized,
+{
+    size_of::<L>().saturating_add(value.elements_size_hint())
+}
+
+/// Deserialize a value with a length prefix of type `L`.
+pub fn deserialize<L, T, R>(reader: &mut R) -> Result<T, Error>
+where
+    L: LengthPrefix,
+    T: DeserializePrefixed,
+    R: Read,
+{
+    let len = L::read_len(reader)?;
+    T::deserialize_elements(len, reader)
+}
+
+/// Like [`deserialize`], from the start of `buf`, which is left at the length prefix if the value
+/// fails to decode.
+pub fn deserialize_slice<L, T>(buf: &mut &[u8]) -> Result<T, Error>
+where
+    L: LengthPrefix,
+    T: DeserializePrefixed,
+{
+    let start = *buf;
+    or_rewind(deserialize::<L, T, _>(buf), buf, start)
+}
+
+/// Capacity to reserve for `len` elements, bounded like every other decode path.
+fn capacity<T>(len: usize) -> usize {
+    hint::cautious::<T>(u32::try_from(len).unwrap_or(u32::MAX))
+}
+
+impl SerializePrefixed for str {
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(self.as_bytes())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.len()
+    }
+}
+
+impl SerializePrefixed for String {
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(self.as_bytes())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.len()
+    }
+}
+
+impl DeserializePrefixed for String {
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        check_len(len as u64, LenKind::String)?;
+        let bytes = read_elements::<u8, _>(len, reader)?;
+        if crate::de::skip_checks() {
+            return Ok(unsafe { String::from_utf8_unchecked(bytes) });
+        }
+        String::from_utf8(bytes).map_err(|err| crate::Error::InvalidUtf8(err.utf8_error()).into())
+    }
+}
+
+impl<T: BorshSerialize> SerializePrefixed for [T] {
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for item in self {
+            item.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.first()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()))
+    }
+}
+
+impl<T: BorshSerialize> SerializePrefixed for Vec<T> {
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.as_slice().serialize_elements(writer)
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.as_slice().elements_size_hint()
+    }
+}
+
+impl<T: BorshDeserialize> DeserializePrefixed for Vec<T> {
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        check_len(len as u64, LenKind::Sequence)?;
+        read_elements(len, reader)
+    }
+}
+
+/// Decode `len` elements, once the length has been checked against the limit for its kind.
+fn read_elements<T: BorshDeserialize, R: Read>(
+    len: usize,
+    reader: &mut R,
+) -> Result<Vec<T>, Error> {
+    if size_of::<T>() == 0 && len > 0 {
+        // A wide prefix of zero-sized elements would have us loop for as long as it says.
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Zero-sized elements cannot have a custom length prefix",
+        ));
+    }
+    let mut result = hint::try_with_capacity(capacity::<T>(len))?;
+    for _ in 0..len {
+        hint::try_push(&mut result, T::deserialize(reader)?)?;
+    }
+    Ok(result)
+}
+
+#[cfg(feature = "std")]
+impl<T, S> SerializePrefixed for HashSet<T, S>
+where
+    T: BorshSerialize + PartialOrd,
+    S: std::hash::BuildHasher,
+{
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut vec = self.iter().collect::<Vec<_>>();
+        vec.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for item in vec {
+            item.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.iter()
+            .next()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, S> DeserializePrefixed for HashSet<T, S>
+where
+    T: BorshDeserialize + Eq + std::hash::Hash,
+    S: std::hash::BuildHasher + Default,
+{
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        let vec = <Vec<T>>::deserialize_elements(len, reader)?;
+        hint::charge::<T>(len)?;
+        let set: Self = vec.into_iter().collect();
+        check_unique(set.len() == len)?;
+        Ok(set)
+    }
+}
+
+impl<T> SerializePrefixed for BTreeSet<T>
+where
+    T: BorshSerialize,
+{
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for item in self {
+            item.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.iter()
+            .next()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()))
+    }
+}
+
+impl<T> DeserializePrefixed for BTreeSet<T>
+where
+    T: BorshDeserialize + Ord,
+{
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        let vec = <Vec<T>>::deserialize_elements(len, reader)?;
+        check_ascending(|| vec.windows(2).all(|pair| pair[0] < pair[1]))?;
+        hint::charge::<T>(len)?;
+        let set: Self = vec.into_iter().collect();
+        check_unique(set.len() == len)?;
+        Ok(set)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> SerializePrefixed for HashMap<K, V, S>
+where
+    K: BorshSerialize + PartialOrd,
+    V: BorshSerialize,
+    S: std::hash::BuildHasher,
+{
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let mut vec = self.iter().collect::<Vec<_>>();
+        vec.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        for (key, value) in vec {
+            key.serialize(writer)?;
+            value.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.iter().next().map_or(0, |(key, value)| {
+            extrapolate(
+                self.len(),
+                key.size_hint().saturating_add(value.size_hint()),
+            )
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> DeserializePrefixed for HashMap<K, V, S>
+where
+    K: BorshDeserialize + Eq + std::hash::Hash,
+    V: BorshDeserialize,
+    S: std::hash::BuildHasher + Default,
+{
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        check_len(len as u64, LenKind::Map)?;
+        let vec = read_elements::<(K, V), _>(len, reader)?;
+        hint::charge::<(K, V)>(len)?;
+        let map: Self = vec.into_iter().collect();
+        check_unique(map.len() == len)?;
+        Ok(map)
+    }
+}
+
+impl<K, V> SerializePrefixed for BTreeMap<K, V>
+where
+    K: BorshSerialize,
+    V: BorshSerialize,
+{
+    fn prefixed_len(&self) -> usize {
+        self.len()
+    }
+
+    fn serialize_elements<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        for (key, value) in self {
+            key.serialize(writer)?;
+            value.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn elements_size_hint(&self) -> usize {
+        self.iter().next().map_or(0, |(key, value)| {
+            extrapolate(
+                self.len(),
+                key.size_hint().saturating_add(value.size_hint()),
+            )
+        })
+    }
+}
+
+impl<K, V> DeserializePrefixed for BTreeMap<K, V>
+where
+    K: BorshDeserialize + Ord,
+    V: BorshDeserialize,
+{
+    fn deserialize_elements<R: Read>(len: usize, reader: &mut R) -> Result<Self, Error> {
+        check_len(len as u64, LenKind::Map)?;
+        let vec = read_elements::<(K, V), _>(len, reader)?;
+        check_ascending(|| vec.windows(2).all(|pair| pair[0].0 < pair[1].0))?;
+        hint::charge::<(K, V)>(len)?;
+        let map: Self = vec.into_iter().collect();
+        check_unique(map.len() == len)?;
+        Ok(map)
+    }
+}
@@ -0,0 +1,138 @@
+//! Payloads kept as encoded bytes, for forwarding values a decoder does not know the type of.
+//!
+//! A [`RawBorsh`] is encoded like `Vec<u8>`: a `u32` length followed by that many bytes, which
+//! are usually the borsh encoding of some value. Decoding keeps the bytes as they are, and
+//! encoding writes them back verbatim, so a router can decode the envelope of a message and
+//! forward its body untouched, without knowing its type or even being able to decode it:
+//!
+//! ```
+//! use oasis_borsh::raw::RawBorsh;
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Envelope {
+//!     destination: String,
+//!     body: RawBorsh,
+//! }
+//!
+//! let sent = Envelope {
+//!     destination: "billing".to_string(),
+//!     body: RawBorsh::from_value(&(7u64, "invoice")).unwrap(),
+//! };
+//! let bytes = sent.try_to_vec().unwrap();
+//!
+//! // The router only looks at the destination, and forwards the same bytes.
+//! let routed = Envelope::try_from_slice(&bytes).unwrap();
+//! assert_eq!(routed.try_to_vec().unwrap(), bytes);
+//!
+//! // The recipient knows what the body holds.
+//! let body: (u64, String) = routed.body.decode().unwrap();
+//! assert_eq!(body, (7, "invoice".to_string()));
+//! ```
+use core::ops::Deref;
+
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+#[cfg(feature = "std")]
+use crate::schema::{Declaration, Definition};
+#[cfg(feature = "std")]
+use crate::BorshSchema;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A length-prefixed region of bytes, kept undecoded, see the module documentation.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct RawBorsh(Vec<u8>);
+
+impl RawBorsh {
+    /// Wrap `bytes`, which are written out as they are.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        RawBorsh(bytes)
+    }
+
+    /// The encoding of `value`.
+    pub fn from_value<T: BorshSerialize + ?Sized>(value: &T) -> Result<Self, Error> {
+        value.try_to_vec().map(RawBorsh)
+    }
+
+    /// Decode the bytes as a `T`, which must take all of them.
+    pub fn decode<T: BorshDeserialize>(&self) -> Result<T, Error> {
+        T::try_from_slice(&self.0)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for RawBorsh {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for RawBorsh {
+    fn from(bytes: Vec<u8>) -> Self {
+        RawBorsh(bytes)
+    }
+}
+
+impl From<RawBorsh> for Vec<u8> {
+    fn from(raw: RawBorsh) -> Self {
+        raw.0
+    }
+}
+
+impl BorshSerialize for RawBorsh {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.0.serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint()
+    }
+}
+
+impl BorshDeserialize for RawBorsh {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Vec::<u8>::deserialize(reader).map(RawBorsh)
+    }
+
+    fn deserialize_slice(buf: &mut &[u8]) -> Result<Self, Error> {
+        Vec::<u8>::deserialize_slice(buf).map(RawBorsh)
+    }
+}
+
+/// Described as the `Vec<u8>` it is on the wire.
+#[cfg(feature = "std")]
+impl BorshSchema for RawBorsh {
+    fn add_definitions_recursively(
+        definitions: &mut std::collections::HashMap<Declaration, Definition>,
+    ) {
+        Vec::<u8>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        Vec::<u8>::declaration()
+    }
+}
+
+impl BorshDiff for RawBorsh {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
@@ -0,0 +1,332 @@
+//! Append-only log of borsh records, suitable as a write-ahead log.
+//!
+//! A log file starts with a short header (magic, format version, flags) followed by records laid
+//! out back to back. Each record is a `u32` little-endian payload length, a `u32` CRC-32 of the
+//! payload when the log is checksummed, and the borsh encoding of the record itself.
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::crc32::crc32;
+use crate::{BorshDeserialize, BorshSerialize};
+
+const MAGIC: [u8; 4] = *b"BRLG";
+const VERSION: u8 = 1;
+const FLAG_CHECKSUMMED: u8 = 1;
+const HEADER_LEN: u64 = 6;
+
+/// An append-only file of length-prefixed borsh records of type `T`.
+pub struct RecordLog<T> {
+    file: File,
+    checksummed: bool,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<T> RecordLog<T> {
+    /// Open the log at `path`, creating it if it does not exist.
+    ///
+    /// `checksummed` selects whether each record carries a CRC-32 of its payload. Reopening an
+    /// existing log with a different setting is an error.
+    pub fn open<P: AsRef<Path>>(path: P, checksummed: bool) -> Result<Self, Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let flags = if checksummed { FLAG_CHECKSUMMED } else { 0 };
+        if file.metadata()?.len() == 0 {
+            file.write_all(&MAGIC)?;
+            file.write_all(&[VERSION, flags])?;
+            file.sync_data()?;
+        } else {
+            let mut header = [0u8; HEADER_LEN as usize];
+            file.read_exact(&mut header)?;
+            if header[..4] != MAGIC || header[4] != VERSION {
+                return Err(Error::new(ErrorKind::InvalidData, "Not a record log file"));
+            }
+            if header[5] != flags {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Record log checksum setting does not match the file",
+                ));
+            }
+        }
+        Ok(RecordLog {
+            file,
+            checksummed,
+            _record: PhantomData,
+        })
+    }
+
+    /// Whether records in this log carry a CRC-32 checksum.
+    pub fn is_checksummed(&self) -> bool {
+        self.checksummed
+    }
+
+    /// Flush appended records to stable storage.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        self.file.sync_data()
+    }
+
+    /// Iterate over the records of the log from the beginning.
+    ///
+    /// Iteration stops after the first error; a truncated or corrupt tail is reported as an
+    /// `InvalidData` error rather than silently ignored.
+    pub fn replay(&mut self) -> Result<Replay<'_, T>, Error> {
+        let end = self.file.metadata()?.len();
+        self.file.seek(SeekFrom::Start(HEADER_LEN))?;
+        Ok(Replay {
+            reader: BufReader::new(&self.file),
+            checksummed: self.checksummed,
+            offset: HEADER_LEN,
+            position: HEADER_LEN,
+            end,
+            done: false,
+            _record: PhantomData,
+        })
+    }
+
+    /// Iterate over the records of the log from the beginning, skipping over corrupt ones.
+    ///
+    /// Where [`replay`](Self::replay) stops, this reports the span of the log that held no intact
+    /// record and carries on after it, so that one bad record does not cost the rest of the log.
+    /// The span ends at the boundary the corrupt record's length points to, if a record starts
+    /// there. Otherwise the length itself may be what is corrupt: a checksummed log is then
+    /// scanned for the next offset where a record with a matching checksum starts, while in a log
+    /// without checksums records cannot be told from garbage and the span runs to the end.
+    ///
+    /// Scanning reads a candidate record at every offset, so a long run of garbage in a large log
+    /// takes a while to get through. Errors other than corruption, such as I/O errors, end
+    /// iteration.
+    pub fn replay_recovering(&mut self) -> Result<Recovering<'_, T>, Error> {
+        Ok(Recovering {
+            replay: self.replay()?,
+        })
+    }
+
+    /// Truncate the log right after the last intact record, discarding a torn or corrupt tail.
+    ///
+    /// Returns the number of records that were kept.
+    pub fn truncate_at_corruption(&mut self) -> Result<usize, Error>
+    where
+        T: BorshDeserialize,
+    {
+        let mut replay = self.replay()?;
+        let mut kept = 0;
+        let mut valid_end = HEADER_LEN;
+        loop {
+            match replay.next() {
+                Some(Ok(_)) => {
+                    kept += 1;
+                    valid_end = replay.offset;
+                }
+                Some(Err(err)) if err.kind() != ErrorKind::InvalidData => return Err(err),
+                _ => break,
+            }
+        }
+        self.file.set_len(valid_end)?;
+        self.file.sync_data()?;
+        Ok(kept)
+    }
+}
+
+impl<T: BorshSerialize> RecordLog<T> {
+    /// Append `record` to the end of the log and return the offset at which it was written.
+    ///
+    /// The record is not guaranteed to be durable until [`RecordLog::sync`] is called.
+    pub fn append(&mut self, record: &T) -> Result<u64, Error> {
+        let payload = record.try_to_vec()?;
+        if payload.len() > u32::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "Record is too large"));
+        }
+        let mut frame = Vec::with_capacity(payload.len() + 8);
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        if self.checksummed {
+            frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+        }
+        frame.extend_from_slice(&payload);
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&frame)?;
+        Ok(offset)
+    }
+}
+
+/// Iterator over the records of a [`RecordLog`], see [`RecordLog::replay`].
+pub struct Replay<'a, T> {
+    reader: BufReader<&'a File>,
+    checksummed: bool,
+    offset: u64,
+    /// Offset `reader` is at.
+    position: u64,
+    end: u64,
+    done: bool,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Replay<'a, T> {
+    /// Offset just past the last record returned.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl<'a, T: BorshDeserialize> Replay<'a, T> {
+    fn corrupt(&self, what: &str) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{} in record at offset {}", what, self.offset),
+        )
+    }
+
+    fn read_record(&mut self) -> Result<T, Error> {
+        let (len, checksum) = match self.read_header(self.offset)? {
+            Header::Truncated => return Err(self.corrupt("Truncated header")),
+            Header::Overlong => return Err(self.corrupt("Truncated payload")),
+            Header::Fits { len, checksum } => (len, checksum),
+        };
+        let mut payload = vec![0u8; len as usize];
+        self.reader.read_exact(&mut payload)?;
+        self.position += len;
+        if self.checksummed && crc32(&payload) != checksum {
+            return Err(self.corrupt("Checksum mismatch"));
+        }
+        let record = T::try_from_slice(&payload)
+            .map_err(|err| self.corrupt(&format!("Undecodable payload ({})", err)))?;
+        self.offset = self.position;
+        Ok(record)
+    }
+}
+
+/// The record header at some offset of a log.
+enum Header {
+    /// The log ends before the header does.
+    Truncated,
+    /// The log ends before the payload the header announces does.
+    Overlong,
+    /// The header and its payload fit in the log.
+    Fits { len: u64, checksum: u32 },
+}
+
+impl<'a, T> Replay<'a, T> {
+    fn header_len(&self) -> u64 {
+        if self.checksummed {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Read the record header at `offset`, leaving the reader at the payload if it fits.
+    fn read_header(&mut self, offset: u64) -> Result<Header, Error> {
+        let header_len = self.header_len();
+        if self.end - offset < header_len {
+            return Ok(Header::Truncated);
+        }
+        if offset != self.position {
+            // Relative seeks keep what the reader has buffered when they land inside it.
+            self.reader
+                .seek_relative(offset as i64 - self.position as i64)?;
+        }
+        let mut header = [0u8; 8];
+        self.reader.read_exact(&mut header[..header_len as usize])?;
+        self.position = offset + header_len;
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        if self.end - self.position < len {
+            return Ok(Header::Overlong);
+        }
+        let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        Ok(Header::Fits { len, checksum })
+    }
+
+    /// Whether a record with a matching checksum starts at `offset` of a checksummed log.
+    fn intact_at(&mut self, offset: u64) -> Result<bool, Error> {
+        let (len, checksum) = match self.read_header(offset)? {
+            Header::Fits { len, checksum } => (len, checksum),
+            _ => return Ok(false),
+        };
+        let mut payload = vec![0u8; len as usize];
+        self.reader.read_exact(&mut payload)?;
+        self.position += len;
+        Ok(crc32(&payload) == checksum)
+    }
+
+    /// The offset of the first record that can be trusted after the corrupt one at `start`, or
+    /// the end of the log if there is none.
+    fn resync(&mut self, start: u64) -> Result<u64, Error> {
+        if let Header::Fits { len, .. } = self.read_header(start)? {
+            let boundary = start + self.header_len() + len;
+            if !self.checksummed || boundary == self.end || self.intact_at(boundary)? {
+                return Ok(boundary);
+            }
+        }
+        if self.checksummed {
+            for offset in start + 1..self.end {
+                if self.intact_at(offset)? {
+                    return Ok(offset);
+                }
+            }
+        }
+        Ok(self.end)
+    }
+}
+
+impl<'a, T: BorshDeserialize> Iterator for Replay<'a, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset == self.end {
+            return None;
+        }
+        let result = self.read_record();
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+/// An entry of a log replayed with [`RecordLog::replay_recovering`].
+#[derive(Debug)]
+pub enum Recovered<T> {
+    /// An intact record.
+    Record(T),
+    /// The bytes from offset `start` up to `end` held no intact record and were skipped. `error`
+    /// describes what was wrong with the record at `start`.
+    Corrupt { start: u64, end: u64, error: Error },
+}
+
+/// Iterator over the records of a [`RecordLog`], see [`RecordLog::replay_recovering`].
+pub struct Recovering<'a, T> {
+    replay: Replay<'a, T>,
+}
+
+impl<'a, T> Recovering<'a, T> {
+    /// Offset just past the last record or corrupt span returned.
+    pub fn offset(&self) -> u64 {
+        self.replay.offset
+    }
+}
+
+impl<'a, T: BorshDeserialize> Iterator for Recovering<'a, T> {
+    type Item = Result<Recovered<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let replay = &mut self.replay;
+        if replay.done || replay.offset == replay.end {
+            return None;
+        }
+        let start = replay.offset;
+        let result = match replay.read_record() {
+            Ok(record) => Ok(Recovered::Record(record)),
+            Err(error) if error.kind() == ErrorKind::InvalidData => {
+                replay.resync(start).map(|end| {
+                    replay.offset = end;
+                    Recovered::Corrupt { start, end, error }
+                })
+            }
+            Err(err) => Err(err),
+        };
+        replay.done = result.is_err();
+        Some(result)
+    }
+}
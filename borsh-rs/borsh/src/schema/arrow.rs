@@ -0,0 +1,595 @@
+//! Conversion between borsh data and Apache Arrow.
+//!
+//! A type whose schema is a struct maps to an Arrow schema with one column per field, and a slice
+//! of such values maps to a `RecordBatch` with one row per value. Types are mapped as follows:
+//!
+//! | borsh                          | Arrow                                     |
+//! |--------------------------------|-------------------------------------------|
+//! | `bool`, `u8`..`u64`, `i8`..`i64`, `f32`, `f64` | the matching primitive type |
+//! | `u128`, `i128`                 | `FixedSizeBinary(16)`, little-endian      |
+//! | `string`                       | `Utf8`                                    |
+//! | `nil`                          | `Null`                                    |
+//! | `Option<T>`                    | `T`, nullable                             |
+//! | `[u8; N]`                      | `FixedSizeBinary(N)`                      |
+//! | `[T; N]`                       | `FixedSizeList(T, N)`                     |
+//! | `Vec<u8>`                      | `Binary`                                  |
+//! | other sequences, sets and maps | `List(T)`, map entries as `(K, V)` structs |
+//! | structs and tuples             | `Struct`, unnamed fields named `0`, `1`, ...  |
+//!
+//! Enums other than `Option`, nested options and recursive types have no faithful Arrow
+//! counterpart and are rejected.
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+
+use arrow_array::types::*;
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, BooleanArray, FixedSizeBinaryArray, FixedSizeListArray,
+    ListArray, NullArray, PrimitiveArray, RecordBatch, RecordBatchOptions, StringArray,
+    StructArray,
+};
+use arrow_buffer::{NullBuffer, OffsetBuffer};
+use arrow_schema::{ArrowError, DataType, Field, FieldRef, Fields, Schema};
+
+use crate::schema::value::{self, definition, Value};
+use crate::schema::{self, BorshSchema, BorshSchemaContainer, Declaration, Definition};
+use crate::{BorshDeserialize, BorshSerialize};
+
+fn unsupported(declaration: &str, reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "Type {} cannot be converted to Arrow: {}",
+            declaration, reason
+        ),
+    )
+}
+
+fn arrow_error(err: ArrowError) -> Error {
+    Error::new(ErrorKind::InvalidData, err.to_string())
+}
+
+fn mismatch(declaration: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("Arrow data does not match type {}", declaration),
+    )
+}
+
+/// If `declaration` is an `Option<T>`, the declaration of `T`.
+fn option_inner<'a>(container: &'a BorshSchemaContainer, declaration: &str) -> Option<&'a str> {
+    match container.definitions.get(declaration) {
+        Some(Definition::Enum { variants })
+            if variants.len() == 2
+                && variants[0].0 == "None"
+                && variants[0].1 == "nil"
+                && variants[1].0 == "Some" =>
+        {
+            Some(&variants[1].1)
+        }
+        _ => None,
+    }
+}
+
+/// Names and declarations of the fields of a struct or tuple definition.
+fn struct_fields<'a>(
+    container: &'a BorshSchemaContainer,
+    declaration: &str,
+) -> Result<Vec<(String, &'a Declaration)>, Error> {
+    match definition(container, declaration)? {
+        Definition::Struct { fields } => Ok(match fields {
+            schema::Fields::NamedFields(fields) => fields
+                .iter()
+                .map(|(name, declaration)| (name.clone(), declaration))
+                .collect(),
+            schema::Fields::UnnamedFields(fields) => fields
+                .iter()
+                .enumerate()
+                .map(|(idx, declaration)| (idx.to_string(), declaration))
+                .collect(),
+            schema::Fields::Empty => Vec::new(),
+        }),
+        Definition::Tuple { elements } => Ok(elements
+            .iter()
+            .enumerate()
+            .map(|(idx, declaration)| (idx.to_string(), declaration))
+            .collect()),
+        _ => Err(unsupported(declaration, "expected a struct or a tuple")),
+    }
+}
+
+fn field(container: &BorshSchemaContainer, name: &str, declaration: &str) -> Result<Field, Error> {
+    match option_inner(container, declaration) {
+        Some(inner) => {
+            if option_inner(container, inner).is_some() {
+                return Err(unsupported(declaration, "nested options"));
+            }
+            Ok(Field::new(name, data_type(container, inner)?, true))
+        }
+        None => Ok(Field::new(name, data_type(container, declaration)?, false)),
+    }
+}
+
+fn item_field(container: &BorshSchemaContainer, declaration: &str) -> Result<FieldRef, Error> {
+    Ok(Arc::new(field(container, "item", declaration)?))
+}
+
+fn arrow_fields(container: &BorshSchemaContainer, declaration: &str) -> Result<Fields, Error> {
+    struct_fields(container, declaration)?
+        .into_iter()
+        .map(|(name, declaration)| field(container, &name, declaration))
+        .collect()
+}
+
+fn data_type(container: &BorshSchemaContainer, declaration: &str) -> Result<DataType, Error> {
+    Ok(match declaration {
+        "nil" => DataType::Null,
+        "bool" => DataType::Boolean,
+        "u8" => DataType::UInt8,
+        "u16" => DataType::UInt16,
+        "u32" => DataType::UInt32,
+        "u64" => DataType::UInt64,
+        "i8" => DataType::Int8,
+        "i16" => DataType::Int16,
+        "i32" => DataType::Int32,
+        "i64" => DataType::Int64,
+        "u128" | "i128" => DataType::FixedSizeBinary(16),
+        "f32" => DataType::Float32,
+        "f64" => DataType::Float64,
+        "string" => DataType::Utf8,
+        _ => match definition(container, declaration)? {
+            Definition::Array { length, elements } if elements == "u8" => {
+                DataType::FixedSizeBinary(*length as i32)
+            }
+            Definition::Array { length, elements } => {
+                DataType::FixedSizeList(item_field(container, elements)?, *length as i32)
+            }
+            Definition::Sequence { elements } if elements == "u8" => DataType::Binary,
+            Definition::Sequence { elements }
+            | Definition::Set { elements }
+            | Definition::Map { entries: elements } => {
+                DataType::List(item_field(container, elements)?)
+            }
+            Definition::Tuple { .. } | Definition::Struct { .. } => {
+                DataType::Struct(arrow_fields(container, declaration)?)
+            }
+            Definition::Enum { .. } if option_inner(container, declaration).is_some() => {
+                return Err(unsupported(
+                    declaration,
+                    "options are only supported as fields",
+                ))
+            }
+            Definition::Enum { .. } => return Err(unsupported(declaration, "enums")),
+            // The width of the length prefix has no bearing on the Arrow representation.
+            Definition::Prefixed { sequence, .. } => return data_type(container, sequence),
+        },
+    })
+}
+
+/// The Arrow schema of a batch of values of the struct type described by `container`.
+pub fn to_arrow_schema(container: &BorshSchemaContainer) -> Result<Schema, Error> {
+    if container.is_recursive() {
+        return Err(unsupported(&container.declaration, "recursive types"));
+    }
+    Ok(Schema::new(arrow_fields(
+        container,
+        &container.declaration,
+    )?))
+}
+
+fn nulls(values: &[Option<&Value>]) -> Option<NullBuffer> {
+    if values.iter().all(Option::is_some) {
+        None
+    } else {
+        Some(NullBuffer::from(
+            values.iter().map(Option::is_some).collect::<Vec<_>>(),
+        ))
+    }
+}
+
+macro_rules! build_primitive {
+    ($declaration: expr, $values: expr, $variant: ident, $arrow_type: ty) => {{
+        let values = $values
+            .iter()
+            .map(|value| match value {
+                None => Ok(None),
+                Some(Value::$variant(v)) => Ok(Some(*v)),
+                Some(_) => Err(mismatch($declaration)),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Arc::new(PrimitiveArray::<$arrow_type>::from(values)) as ArrayRef
+    }};
+}
+
+fn bytes_of(declaration: &str, values: &[Value]) -> Result<Vec<u8>, Error> {
+    values
+        .iter()
+        .map(|value| match value {
+            Value::U8(v) => Ok(*v),
+            _ => Err(mismatch(declaration)),
+        })
+        .collect()
+}
+
+/// Build the Arrow array for a column of values of type `declaration`; `None` marks null slots.
+fn build(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    values: &[Option<&Value>],
+) -> Result<ArrayRef, Error> {
+    if let Some(inner) = option_inner(container, declaration) {
+        let values = values
+            .iter()
+            .map(|value| match value {
+                None | Some(Value::Enum { variant: 0, .. }) => Ok(None),
+                Some(Value::Enum { variant: 1, value }) => Ok(Some(&**value)),
+                Some(_) => Err(mismatch(declaration)),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        return build(container, inner, &values);
+    }
+    Ok(match declaration {
+        "nil" => Arc::new(NullArray::new(values.len())),
+        "bool" => {
+            let values = values
+                .iter()
+                .map(|value| match value {
+                    None => Ok(None),
+                    Some(Value::Bool(v)) => Ok(Some(*v)),
+                    Some(_) => Err(mismatch(declaration)),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Arc::new(BooleanArray::from(values))
+        }
+        "u8" => build_primitive!(declaration, values, U8, UInt8Type),
+        "u16" => build_primitive!(declaration, values, U16, UInt16Type),
+        "u32" => build_primitive!(declaration, values, U32, UInt32Type),
+        "u64" => build_primitive!(declaration, values, U64, UInt64Type),
+        "i8" => build_primitive!(declaration, values, I8, Int8Type),
+        "i16" => build_primitive!(declaration, values, I16, Int16Type),
+        "i32" => build_primitive!(declaration, values, I32, Int32Type),
+        "i64" => build_primitive!(declaration, values, I64, Int64Type),
+        "f32" => build_primitive!(declaration, values, F32, Float32Type),
+        "f64" => build_primitive!(declaration, values, F64, Float64Type),
+        "u128" | "i128" => {
+            let values = values
+                .iter()
+                .map(|value| match value {
+                    None => Ok(None),
+                    Some(Value::U128(v)) => Ok(Some(v.to_le_bytes())),
+                    Some(Value::I128(v)) => Ok(Some(v.to_le_bytes())),
+                    Some(_) => Err(mismatch(declaration)),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Arc::new(
+                FixedSizeBinaryArray::try_from_sparse_iter_with_size(values.into_iter(), 16)
+                    .map_err(arrow_error)?,
+            )
+        }
+        "string" => {
+            let values = values
+                .iter()
+                .map(|value| match value {
+                    None => Ok(None),
+                    Some(Value::String(v)) => Ok(Some(v.as_str())),
+                    Some(_) => Err(mismatch(declaration)),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Arc::new(StringArray::from(values))
+        }
+        _ => match definition(container, declaration)? {
+            Definition::Array { length, elements } if elements == "u8" => {
+                let values = values
+                    .iter()
+                    .map(|value| match value {
+                        None => Ok(None),
+                        Some(Value::Array(v)) => bytes_of(declaration, v).map(Some),
+                        Some(_) => Err(mismatch(declaration)),
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Arc::new(
+                    FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                        values.into_iter(),
+                        *length as i32,
+                    )
+                    .map_err(arrow_error)?,
+                )
+            }
+            Definition::Array { length, elements } => {
+                let mut children = Vec::with_capacity(values.len() * *length as usize);
+                for value in values {
+                    match value {
+                        None => children.extend((0..*length).map(|_| None)),
+                        Some(Value::Array(v)) if v.len() == *length as usize => {
+                            children.extend(v.iter().map(Some))
+                        }
+                        Some(_) => return Err(mismatch(declaration)),
+                    }
+                }
+                Arc::new(
+                    FixedSizeListArray::try_new(
+                        item_field(container, elements)?,
+                        *length as i32,
+                        build(container, elements, &children)?,
+                        nulls(values),
+                    )
+                    .map_err(arrow_error)?,
+                )
+            }
+            Definition::Sequence { elements } if elements == "u8" => {
+                let values = values
+                    .iter()
+                    .map(|value| match value {
+                        None => Ok(None),
+                        Some(Value::Sequence(v)) => bytes_of(declaration, v).map(Some),
+                        Some(_) => Err(mismatch(declaration)),
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Arc::new(values.into_iter().collect::<BinaryArray>())
+            }
+            Definition::Sequence { elements }
+            | Definition::Set { elements }
+            | Definition::Map { entries: elements } => {
+                let mut children = Vec::new();
+                let mut lengths = Vec::with_capacity(values.len());
+                for value in values {
+                    match value {
+                        None => lengths.push(0),
+                        Some(Value::Sequence(v)) => {
+                            lengths.push(v.len());
+                            children.extend(v.iter().map(Some));
+                        }
+                        Some(_) => return Err(mismatch(declaration)),
+                    }
+                }
+                Arc::new(
+                    ListArray::try_new(
+                        item_field(container, elements)?,
+                        OffsetBuffer::from_lengths(lengths),
+                        build(container, elements, &children)?,
+                        nulls(values),
+                    )
+                    .map_err(arrow_error)?,
+                )
+            }
+            Definition::Tuple { .. } | Definition::Struct { .. } => {
+                let fields = struct_fields(container, declaration)?;
+                let columns = values
+                    .iter()
+                    .map(|value| match value {
+                        None => Ok(None),
+                        Some(Value::Tuple(v)) | Some(Value::Struct(v))
+                            if v.len() == fields.len() =>
+                        {
+                            Ok(Some(v))
+                        }
+                        Some(_) => Err(mismatch(declaration)),
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                if fields.is_empty() {
+                    return Ok(Arc::new(StructArray::new_empty_fields(
+                        values.len(),
+                        nulls(values),
+                    )));
+                }
+                let children = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, field_declaration))| {
+                        let column = columns
+                            .iter()
+                            .map(|row| row.map(|row| &row[idx]))
+                            .collect::<Vec<_>>();
+                        build(container, field_declaration, &column)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Arc::new(
+                    StructArray::try_new(
+                        arrow_fields(container, declaration)?,
+                        children,
+                        nulls(values),
+                    )
+                    .map_err(arrow_error)?,
+                )
+            }
+            Definition::Enum { .. } => return Err(unsupported(declaration, "enums")),
+            Definition::Prefixed { sequence, .. } => return build(container, sequence, values),
+        },
+    })
+}
+
+/// Encode `values` as an Arrow record batch with one row per value.
+pub fn to_record_batch<T: BorshSerialize + BorshSchema>(
+    values: &[T],
+) -> Result<RecordBatch, Error> {
+    let container = T::schema_container();
+    let schema = Arc::new(to_arrow_schema(&container)?);
+    let rows = values
+        .iter()
+        .map(|v| value::decode(&container, &v.try_to_vec()?))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let rows = rows.iter().map(Some).collect::<Vec<_>>();
+    let columns = match build(&container, &container.declaration, &rows)?
+        .as_any()
+        .downcast_ref::<StructArray>()
+    {
+        Some(array) => array.columns().to_vec(),
+        None => return Err(mismatch(&container.declaration)),
+    };
+    let options = RecordBatchOptions::new().with_row_count(Some(values.len()));
+    RecordBatch::try_new_with_options(schema, columns, &options).map_err(arrow_error)
+}
+
+macro_rules! read_primitive {
+    ($declaration: expr, $array: expr, $row: expr, $variant: ident, $arrow_type: ty) => {
+        Value::$variant(
+            $array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<$arrow_type>>()
+                .ok_or_else(|| mismatch($declaration))?
+                .value($row),
+        )
+    };
+}
+
+fn downcast<'a, A: 'static>(declaration: &str, array: &'a dyn Array) -> Result<&'a A, Error> {
+    array
+        .as_any()
+        .downcast_ref::<A>()
+        .ok_or_else(|| mismatch(declaration))
+}
+
+/// Read the value of type `declaration` at `row` of `array`.
+fn read(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    array: &dyn Array,
+    row: usize,
+) -> Result<Value, Error> {
+    if let Some(inner) = option_inner(container, declaration) {
+        return Ok(if array.is_null(row) {
+            Value::Enum {
+                variant: 0,
+                value: Box::new(Value::Nil),
+            }
+        } else {
+            Value::Enum {
+                variant: 1,
+                value: Box::new(read(container, inner, array, row)?),
+            }
+        });
+    }
+    if array.is_null(row) && declaration != "nil" {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unexpected null for non-optional type {}", declaration),
+        ));
+    }
+    Ok(match declaration {
+        "nil" => Value::Nil,
+        "bool" => Value::Bool(downcast::<BooleanArray>(declaration, array)?.value(row)),
+        "u8" => read_primitive!(declaration, array, row, U8, UInt8Type),
+        "u16" => read_primitive!(declaration, array, row, U16, UInt16Type),
+        "u32" => read_primitive!(declaration, array, row, U32, UInt32Type),
+        "u64" => read_primitive!(declaration, array, row, U64, UInt64Type),
+        "i8" => read_primitive!(declaration, array, row, I8, Int8Type),
+        "i16" => read_primitive!(declaration, array, row, I16, Int16Type),
+        "i32" => read_primitive!(declaration, array, row, I32, Int32Type),
+        "i64" => read_primitive!(declaration, array, row, I64, Int64Type),
+        "f32" => read_primitive!(declaration, array, row, F32, Float32Type),
+        "f64" => read_primitive!(declaration, array, row, F64, Float64Type),
+        "u128" | "i128" => {
+            let bytes = downcast::<FixedSizeBinaryArray>(declaration, array)?.value(row);
+            let mut data = [0u8; 16];
+            if bytes.len() != data.len() {
+                return Err(mismatch(declaration));
+            }
+            data.copy_from_slice(bytes);
+            if declaration == "u128" {
+                Value::U128(u128::from_le_bytes(data))
+            } else {
+                Value::I128(i128::from_le_bytes(data))
+            }
+        }
+        "string" => Value::String(
+            downcast::<StringArray>(declaration, array)?
+                .value(row)
+                .to_string(),
+        ),
+        _ => match definition(container, declaration)? {
+            Definition::Array { length, elements } if elements == "u8" => {
+                let bytes = downcast::<FixedSizeBinaryArray>(declaration, array)?.value(row);
+                if bytes.len() != *length as usize {
+                    return Err(mismatch(declaration));
+                }
+                Value::Array(bytes.iter().map(|b| Value::U8(*b)).collect())
+            }
+            Definition::Array { length, elements } => {
+                let list = downcast::<FixedSizeListArray>(declaration, array)?;
+                if list.value_length() != *length as i32 {
+                    return Err(mismatch(declaration));
+                }
+                let child = list.value(row);
+                Value::Array(
+                    (0..child.len())
+                        .map(|idx| read(container, elements, &child, idx))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            Definition::Sequence { elements } if elements == "u8" => {
+                let bytes = downcast::<BinaryArray>(declaration, array)?.value(row);
+                Value::Sequence(bytes.iter().map(|b| Value::U8(*b)).collect())
+            }
+            Definition::Sequence { elements }
+            | Definition::Set { elements }
+            | Definition::Map { entries: elements } => {
+                let child = downcast::<ListArray>(declaration, array)?.value(row);
+                Value::Sequence(
+                    (0..child.len())
+                        .map(|idx| read(container, elements, &child, idx))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            Definition::Tuple { elements } => {
+                let array = downcast::<StructArray>(declaration, array)?;
+                if array.num_columns() != elements.len() {
+                    return Err(mismatch(declaration));
+                }
+                Value::Tuple(
+                    elements
+                        .iter()
+                        .zip(array.columns())
+                        .map(|(element, column)| read(container, element, column, row))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            Definition::Struct { fields } => {
+                let array = downcast::<StructArray>(declaration, array)?;
+                let fields = value::field_declarations(fields);
+                if array.num_columns() != fields.len() {
+                    return Err(mismatch(declaration));
+                }
+                Value::Struct(
+                    fields
+                        .into_iter()
+                        .zip(array.columns())
+                        .map(|(field, column)| read(container, field, column, row))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            Definition::Enum { .. } => return Err(unsupported(declaration, "enums")),
+            Definition::Prefixed { sequence, .. } => return read(container, sequence, array, row),
+        },
+    })
+}
+
+/// Decode every row of `batch` into a value of type `T`.
+pub fn from_record_batch<T: BorshDeserialize + BorshSchema>(
+    batch: &RecordBatch,
+) -> Result<Vec<T>, Error> {
+    let container = T::schema_container();
+    let expected = to_arrow_schema(&container)?;
+    if batch.schema().fields() != expected.fields() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Record batch schema does not match type {}",
+                container.declaration
+            ),
+        ));
+    }
+    let fields = struct_fields(&container, &container.declaration)?;
+    (0..batch.num_rows())
+        .map(|row| {
+            let values = fields
+                .iter()
+                .zip(batch.columns())
+                .map(|((_, declaration), column)| read(&container, declaration, column, row))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let row_value = match definition(&container, &container.declaration)? {
+                Definition::Tuple { .. } => Value::Tuple(values),
+                _ => Value::Struct(values),
+            };
+            T::try_from_slice(&value::encode(&container, &row_value)?)
+        })
+        .collect()
+}
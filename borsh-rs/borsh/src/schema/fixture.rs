@@ -0,0 +1,257 @@
+//! Checking that payloads produced by other borsh implementations, such as borsh-js, decode here
+//! as they did there.
+//!
+//! A fixture is a JSON file describing one payload:
+//!
+//! ```json
+//! {
+//!     "schema": { "declaration": "Point", "definitions": { ... } },
+//!     "bytes": "0100000002000000",
+//!     "expected": { "x": 1, "y": 2 }
+//! }
+//! ```
+//!
+//! `schema` is a [`BorshSchemaContainer`] in the form its serde impls use, `bytes` the payload in
+//! hex, and `expected` the value the producing implementation encoded, written as JSON like so:
+//!
+//! | borsh                                   | JSON                                         |
+//! |-----------------------------------------|----------------------------------------------|
+//! | `nil`, structs without fields           | `null`                                       |
+//! | `bool`, `u8`..`u32`, `i8`..`i32`, `f32`, `f64` | a boolean or number                   |
+//! | `u64`, `u128`, `i64`, `i128`            | a decimal string, or a number if exact       |
+//! | `string`                                | a string                                     |
+//! | arrays, sequences, tuples, tuple structs | an array; map entries as `[key, value]`     |
+//! | structs with named fields               | an object keyed by field name                |
+//! | `Option<T>`                             | `null` or the value                          |
+//! | other enums                             | `{ "Variant": value }`                       |
+//!
+//! A fixture passes if the payload decodes, without leftover bytes, to a value equal to
+//! `expected`, and encoding that value reproduces the payload, so regressions in either
+//! direction surface in this crate's tests:
+//!
+//! ```no_run
+//! oasis_borsh::schema::fixture::check_dir("tests/fixtures").unwrap();
+//! ```
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Number, Value as Json};
+
+use crate::schema::value::{self, definition, Value};
+use crate::schema::{BorshSchemaContainer, Definition, Fields};
+
+/// One payload, the schema it was encoded with, and the value it should decode to.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Fixture {
+    pub schema: BorshSchemaContainer,
+    #[serde(deserialize_with = "hex")]
+    pub bytes: Vec<u8>,
+    pub expected: Json,
+}
+
+fn hex<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    let digit = |c: u8| (c as char).to_digit(16).map(|digit| digit as u8);
+    if !hex.len().is_multiple_of(2) {
+        return Err(serde::de::Error::custom("odd number of hex digits"));
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect::<Option<_>>()
+        .ok_or_else(|| serde::de::Error::custom("invalid hex digit"))
+}
+
+fn mismatch(path: &str, expected: &Json, actual: &Json) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("{}: expected {}, decoded {}", path, expected, actual),
+    )
+}
+
+impl Fixture {
+    /// Parse a fixture from its JSON text.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    /// Read and parse the fixture in the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Decode the payload and check it against `expected`, then check that it re-encodes to the
+    /// same bytes. The error names the first part of the value that differs.
+    pub fn check(&self) -> Result<(), Error> {
+        let decoded = value::decode(&self.schema, &self.bytes)?;
+        let actual = to_json(&self.schema, &decoded)?;
+        compare(&self.schema.declaration, &self.expected, &actual)?;
+        if value::encode(&self.schema, &decoded)? != self.bytes {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Decoded value does not re-encode to the payload",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Check every `.json` file in `dir` as a fixture, in file name order, and return how many
+/// there were. The error of the first failing fixture is prefixed with its file name.
+pub fn check_dir<P: AsRef<Path>>(dir: P) -> Result<usize, Error> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == "json")
+    });
+    paths.sort();
+    for path in &paths {
+        Fixture::load(path)
+            .and_then(|fixture| fixture.check())
+            .map_err(|err| Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+    }
+    Ok(paths.len())
+}
+
+/// The JSON form of `value`, a value of the type described by `container`, as laid out in the
+/// [module docs](self). Wide integers are written as decimal strings.
+pub fn to_json(container: &BorshSchemaContainer, value: &Value) -> Result<Json, Error> {
+    to_json_as(container, &container.declaration, value)
+}
+
+fn to_json_as(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    value: &Value,
+) -> Result<Json, Error> {
+    let elements = |declaration: &str, values: &[Value]| -> Result<Json, Error> {
+        values
+            .iter()
+            .map(|value| to_json_as(container, declaration, value))
+            .collect()
+    };
+    Ok(match value {
+        Value::Nil => Json::Null,
+        Value::Bool(value) => Json::Bool(*value),
+        Value::U8(value) => Json::from(*value),
+        Value::U16(value) => Json::from(*value),
+        Value::U32(value) => Json::from(*value),
+        Value::I8(value) => Json::from(*value),
+        Value::I16(value) => Json::from(*value),
+        Value::I32(value) => Json::from(*value),
+        Value::U64(value) => Json::String(value.to_string()),
+        Value::U128(value) => Json::String(value.to_string()),
+        Value::I64(value) => Json::String(value.to_string()),
+        Value::I128(value) => Json::String(value.to_string()),
+        Value::F32(value) => float(f64::from(*value))?,
+        Value::F64(value) => float(*value)?,
+        Value::String(value) => Json::String(value.clone()),
+        Value::Array(values) | Value::Sequence(values) => {
+            match definition(container, declaration)? {
+                Definition::Array {
+                    elements: element, ..
+                }
+                | Definition::Sequence { elements: element }
+                | Definition::Set { elements: element }
+                | Definition::Map { entries: element } => elements(element, values)?,
+                Definition::Prefixed { sequence, .. } => {
+                    return to_json_as(container, sequence, value)
+                }
+                _ => return Err(unexpected(declaration)),
+            }
+        }
+        Value::Tuple(values) => match definition(container, declaration)? {
+            Definition::Tuple { elements } if elements.len() == values.len() => Json::Array(
+                elements
+                    .iter()
+                    .zip(values)
+                    .map(|(declaration, value)| to_json_as(container, declaration, value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(unexpected(declaration)),
+        },
+        Value::Struct(values) => match definition(container, declaration)? {
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) if fields.len() == values.len() => Json::Object(
+                    fields
+                        .iter()
+                        .zip(values)
+                        .map(|((name, declaration), value)| {
+                            Ok((name.clone(), to_json_as(container, declaration, value)?))
+                        })
+                        .collect::<Result<Map<_, _>, Error>>()?,
+                ),
+                Fields::UnnamedFields(fields) if fields.len() == values.len() => Json::Array(
+                    fields
+                        .iter()
+                        .zip(values)
+                        .map(|(declaration, value)| to_json_as(container, declaration, value))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Fields::Empty if values.is_empty() => Json::Null,
+                _ => return Err(unexpected(declaration)),
+            },
+            _ => return Err(unexpected(declaration)),
+        },
+        Value::Enum { variant, value } => match definition(container, declaration)? {
+            Definition::Enum { variants } => {
+                let (name, variant_declaration) = variants
+                    .get(*variant as usize)
+                    .ok_or_else(|| unexpected(declaration))?;
+                let json = to_json_as(container, variant_declaration, value)?;
+                if declaration.starts_with("Option<") {
+                    json
+                } else {
+                    let mut object = Map::new();
+                    object.insert(name.clone(), json);
+                    Json::Object(object)
+                }
+            }
+            _ => return Err(unexpected(declaration)),
+        },
+    })
+}
+
+fn float(value: f64) -> Result<Json, Error> {
+    Number::from_f64(value).map(Json::Number).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "JSON numbers cannot be infinite or NaN",
+        )
+    })
+}
+
+fn unexpected(declaration: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("Value does not match the definition of {}", declaration),
+    )
+}
+
+/// Compare `expected` with `actual`, accepting numbers where `actual` has integers as strings.
+fn compare(path: &str, expected: &Json, actual: &Json) -> Result<(), Error> {
+    match (expected, actual) {
+        (Json::Number(number), Json::String(string)) if number.to_string() == *string => Ok(()),
+        (Json::Number(left), Json::Number(right)) if left.as_f64() == right.as_f64() => Ok(()),
+        (Json::Array(left), Json::Array(right)) if left.len() == right.len() => left
+            .iter()
+            .zip(right)
+            .enumerate()
+            .try_for_each(|(idx, (left, right))| {
+                compare(&format!("{}[{}]", path, idx), left, right)
+            }),
+        (Json::Object(left), Json::Object(right))
+            if left.len() == right.len() && left.keys().all(|key| right.contains_key(key)) =>
+        {
+            left.iter().try_for_each(|(key, left)| {
+                compare(&format!("{}.{}", path, key), left, &right[key])
+            })
+        }
+        _ if expected == actual => Ok(()),
+        _ => Err(mismatch(path, expected, actual)),
+    }
+}
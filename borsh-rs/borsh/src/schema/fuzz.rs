@@ -0,0 +1,291 @@
+//! Fuzzing aids derived from a schema.
+//!
+//! [`dictionary`] lists byte strings that are meaningful to a decoder of the described type
+//! (enum tags, length prefixes, boundary values of the integer types it uses), and [`seed_corpus`]
+//! produces valid encodings that exercise every enum variant. Both help coverage-guided fuzzers
+//! get past the first few bytes of a downstream decoder quickly.
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+
+use crate::schema::value::{self, definition, field_declarations, sequence_elements, Value};
+use crate::schema::{BorshSchemaContainer, Declaration, Definition};
+
+/// How deep generated values may nest before generation prefers the smallest alternatives.
+const MAX_DEPTH: usize = 16;
+/// Generation gives up on types that cannot be built within this depth, e.g. `struct A(Box<A>)`.
+const HARD_MAX_DEPTH: usize = 64;
+/// Sequence and string lengths listed in the dictionary.
+const LENGTH_PREFIXES: &[u32] = &[0, 1, 2, 4, 8, 16, 32, 64, 255, 256, 65535, 65536, u32::MAX];
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// All declarations reachable from the container's root type.
+fn reachable(container: &BorshSchemaContainer) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    let mut order = Vec::new();
+    let mut stack = vec![container.declaration.as_str()];
+    while let Some(declaration) = stack.pop() {
+        if !seen.insert(declaration) {
+            continue;
+        }
+        order.push(declaration);
+        if let Some(definition) = container.definitions.get(declaration) {
+            stack.extend(definition.references().into_iter().map(|r| r.as_str()));
+        }
+    }
+    order
+}
+
+macro_rules! int_boundaries {
+    ($entries: expr, $name: expr, $type: ident) => {{
+        let name = $name;
+        $entries.push((
+            format!("{}_zero", name),
+            (0 as $type).to_le_bytes().to_vec(),
+        ));
+        $entries.push((format!("{}_one", name), (1 as $type).to_le_bytes().to_vec()));
+        $entries.push((format!("{}_max", name), $type::MAX.to_le_bytes().to_vec()));
+        if $type::MIN != 0 {
+            $entries.push((format!("{}_min", name), $type::MIN.to_le_bytes().to_vec()));
+        }
+    }};
+}
+
+/// Named dictionary tokens for decoders of the type described by `container`.
+///
+/// Tokens with identical bytes are only listed once, under the first name that produced them.
+pub fn dictionary(container: &BorshSchemaContainer) -> Vec<(String, Vec<u8>)> {
+    let mut entries = Vec::new();
+    let mut has_lengths = false;
+    for declaration in reachable(container) {
+        match declaration {
+            "bool" => {
+                entries.push(("bool_false".to_string(), vec![0]));
+                entries.push(("bool_true".to_string(), vec![1]));
+            }
+            "u8" => int_boundaries!(entries, "u8", u8),
+            "u16" => int_boundaries!(entries, "u16", u16),
+            "u32" => int_boundaries!(entries, "u32", u32),
+            "u64" => int_boundaries!(entries, "u64", u64),
+            "u128" => int_boundaries!(entries, "u128", u128),
+            "i8" => int_boundaries!(entries, "i8", i8),
+            "i16" => int_boundaries!(entries, "i16", i16),
+            "i32" => int_boundaries!(entries, "i32", i32),
+            "i64" => int_boundaries!(entries, "i64", i64),
+            "i128" => int_boundaries!(entries, "i128", i128),
+            "string" => has_lengths = true,
+            _ => match container.definitions.get(declaration) {
+                Some(Definition::Sequence { .. })
+                | Some(Definition::Set { .. })
+                | Some(Definition::Map { .. }) => has_lengths = true,
+                Some(Definition::Prefixed { length, .. }) => {
+                    let max = match length.as_str() {
+                        "u8" => u8::MAX as u64,
+                        "u16" => u16::MAX as u64,
+                        "u32" => u32::MAX as u64,
+                        _ => u64::MAX,
+                    };
+                    let width = (max.count_ones() / 8) as usize;
+                    for &len in LENGTH_PREFIXES.iter().filter(|&&len| len as u64 <= max) {
+                        let bytes = (len as u64).to_le_bytes()[..width].to_vec();
+                        entries.push((format!("len_{}_{}", length, len), bytes));
+                    }
+                }
+                Some(Definition::Enum { variants }) => {
+                    for (idx, (variant, _)) in variants.iter().enumerate() {
+                        let name = format!("tag_{}_{}", sanitize(declaration), sanitize(variant));
+                        entries.push((name, vec![idx as u8]));
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    if has_lengths {
+        for &len in LENGTH_PREFIXES {
+            entries.push((format!("len_{}", len), len.to_le_bytes().to_vec()));
+        }
+    }
+    let mut seen = HashSet::new();
+    entries.retain(|(_, bytes)| seen.insert(bytes.clone()));
+    entries
+}
+
+/// Write the dictionary in the format understood by libFuzzer's `-dict=` and AFL's `-x`.
+pub fn write_dictionary<W: Write>(
+    container: &BorshSchemaContainer,
+    writer: &mut W,
+) -> Result<(), Error> {
+    writeln!(writer, "# borsh dictionary for {}", container.declaration)?;
+    for (name, bytes) in dictionary(container) {
+        let escaped = bytes
+            .iter()
+            .map(|b| format!("\\x{:02X}", b))
+            .collect::<String>();
+        writeln!(writer, "{}=\"{}\"", name, escaped)?;
+    }
+    Ok(())
+}
+
+/// Deterministic xorshift generator, so corpora are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+struct Generator<'a> {
+    container: &'a BorshSchemaContainer,
+    rng: Rng,
+    /// Index of the seed being generated; seed `n` prefers variant `n` of every enum.
+    seed: usize,
+}
+
+impl<'a> Generator<'a> {
+    fn boundary(&mut self) -> u64 {
+        match self.rng.below(4) {
+            0 => 0,
+            1 => 1,
+            2 => u64::MAX,
+            _ => self.rng.next(),
+        }
+    }
+
+    fn generate(&mut self, declaration: &Declaration, depth: usize) -> Result<Value, Error> {
+        if depth > HARD_MAX_DEPTH {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Cannot generate a finite value of type {}", declaration),
+            ));
+        }
+        let minimal = depth > MAX_DEPTH;
+        Ok(match declaration.as_str() {
+            "nil" => Value::Nil,
+            "bool" => Value::Bool(self.rng.below(2) == 1),
+            "u8" => Value::U8(self.boundary() as u8),
+            "u16" => Value::U16(self.boundary() as u16),
+            "u32" => Value::U32(self.boundary() as u32),
+            "u64" => Value::U64(self.boundary()),
+            "u128" => Value::U128(self.boundary() as u128),
+            "i8" => Value::I8(self.boundary() as i8),
+            "i16" => Value::I16(self.boundary() as i16),
+            "i32" => Value::I32(self.boundary() as i32),
+            "i64" => Value::I64(self.boundary() as i64),
+            "i128" => Value::I128(self.boundary() as i64 as i128),
+            "f32" => Value::F32(self.rng.below(1000) as f32 / 8.0),
+            "f64" => Value::F64(self.rng.below(1000) as f64 / 8.0),
+            "string" => {
+                let len = if minimal { 0 } else { self.rng.below(8) };
+                Value::String((0..len).map(|i| (b'a' + (i as u8 % 26)) as char).collect())
+            }
+            _ => match definition(self.container, declaration)? {
+                Definition::Array { length, elements } => Value::Array(
+                    (0..*length)
+                        .map(|_| self.generate(elements, depth + 1))
+                        .collect::<Result<_, _>>()?,
+                ),
+                definition @ Definition::Sequence { .. }
+                | definition @ Definition::Set { .. }
+                | definition @ Definition::Map { .. } => {
+                    let (elements, order) = sequence_elements(definition).unwrap();
+                    let len = if minimal { 0 } else { self.rng.below(4) };
+                    let mut values = (0..len)
+                        .map(|_| self.generate(elements, depth + 1))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // Maps and sets are only valid with their keys ascending and distinct.
+                    if values.iter().all(|value| order.key(value).is_some()) {
+                        values.sort_by(|a, b| {
+                            let (a, b) = (order.key(a), order.key(b));
+                            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                        });
+                        values.dedup_by(|a, b| order.key(a) == order.key(b));
+                    }
+                    Value::Sequence(values)
+                }
+                Definition::Tuple { elements } => Value::Tuple(
+                    elements
+                        .iter()
+                        .map(|element| self.generate(element, depth + 1))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Definition::Enum { variants } => {
+                    if variants.is_empty() {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Cannot generate a value of empty enum {}", declaration),
+                        ));
+                    }
+                    // Past the soft depth limit always take the first variant, which for
+                    // `Option` and most recursive enums is the terminating one.
+                    let variant = if minimal {
+                        0
+                    } else {
+                        self.seed % variants.len()
+                    };
+                    let value = self.generate(&variants[variant].1, depth + 1)?;
+                    Value::Enum {
+                        variant: variant as u8,
+                        value: Box::new(value),
+                    }
+                }
+                Definition::Struct { fields } => Value::Struct(
+                    field_declarations(fields)
+                        .into_iter()
+                        .map(|field| self.generate(field, depth + 1))
+                        .collect::<Result<_, _>>()?,
+                ),
+                // Generated sequences are short enough for any prefix width.
+                Definition::Prefixed { sequence, .. } => self.generate(sequence, depth + 1)?,
+            },
+        })
+    }
+}
+
+/// Generate `count` valid encodings of the type described by `container`.
+///
+/// Generation is deterministic. Seed `n` picks variant `n` (modulo the variant count) of every
+/// enum it meets, so `count` should be at least the largest number of variants of any reachable
+/// enum to cover every tag.
+pub fn seed_corpus(container: &BorshSchemaContainer, count: usize) -> Result<Vec<Vec<u8>>, Error> {
+    let mut generator = Generator {
+        container,
+        rng: Rng(0x2545_f491_4f6c_dd1d),
+        seed: 0,
+    };
+    let mut corpus = Vec::with_capacity(count);
+    for seed in 0..count {
+        generator.seed = seed;
+        let value = generator.generate(&container.declaration, 0)?;
+        corpus.push(value::encode(container, &value)?);
+    }
+    Ok(corpus)
+}
+
+/// Write [`seed_corpus`] into `dir` as files named `seed-0000`, `seed-0001`, and so on.
+pub fn write_seed_corpus<P: AsRef<Path>>(
+    container: &BorshSchemaContainer,
+    dir: P,
+    count: usize,
+) -> Result<(), Error> {
+    fs::create_dir_all(&dir)?;
+    for (idx, seed) in seed_corpus(container, count)?.into_iter().enumerate() {
+        fs::write(dir.as_ref().join(format!("seed-{:04}", idx)), seed)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,552 @@
+//! Since Borsh is not a self-descriptive format we have a way to describe types serialized with Borsh
+//! so that we can deserialize serialized blobs without having Rust types available. Additionally,
+//! this can be used to serialize content provided in a different format, e.g. JSON object
+//! `{"user": "alice", "message": "Message"}` can be serialized by JS code into Borsh format such
+//! that it can be deserialized into `struct UserMessage {user: String, message: String}` on Rust
+//! side.
+//!
+//! The important components are: `BorshSchema` trait, `Definition` and `Declaration` types, and
+//! `BorshSchemaContainer` struct.
+//! * `BorshSchema` trait allows any type that implements it to be self-descriptive, i.e. generate
+//!   it's own schema;
+//! * `Declaration` is used to describe the type identifier, e.g. `HashMap<u64, String>`;
+//! * `Definition` is used to describe the structure of the type;
+//! * `BorshSchemaContainer` is used to store all declarations and definitions that are needed to
+//!   work with a single type.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::marker::PhantomData;
+
+use crate as oasis_borsh;
+use crate::{BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "fixtures")]
+pub mod fixture;
+pub mod fuzz;
+pub mod proto;
+pub mod value;
+
+/// The type that we use to represent the declaration of the Borsh type.
+pub type Declaration = String;
+/// The type that we use for the name of the variant.
+pub type VariantName = String;
+/// The name of the field in the struct (can be used to convert JSON to Borsh using the schema).
+pub type FieldName = String;
+
+/// The type that we use to represent the definition of the Borsh type.
+#[derive(Clone, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Definition {
+    /// A fixed-size array with the length known at the compile time and the same-type elements.
+    Array { length: u32, elements: Declaration },
+    /// A sequence of elements of length known at the run time and the same-type elements.
+    Sequence { elements: Declaration },
+    /// A fixed-size tuple with the length known at the compile time and the elements of different
+    /// types.
+    Tuple { elements: Vec<Declaration> },
+    /// A tagged union, a.k.a enum. Tagged-unions have variants with associated structures.
+    Enum {
+        variants: Vec<(VariantName, Declaration)>,
+    },
+    /// A structure, structurally similar to a tuple.
+    Struct { fields: Fields },
+    /// A string or a sequence whose length is encoded as `length`, one of `u8`, `u16`, `u32` and
+    /// `u64`, instead of `u32`.
+    Prefixed {
+        length: Declaration,
+        sequence: Declaration,
+    },
+    /// A set, encoded as a sequence of unique elements in ascending order, see
+    /// [`value`](value#canonical-order-of-map-keys-and-set-elements) for the order.
+    Set { elements: Declaration },
+    /// A map, encoded as a sequence of `(key, value)` tuples of type `entries` with unique keys in
+    /// ascending order.
+    Map { entries: Declaration },
+}
+
+/// The collection representing the fields of a struct.
+#[derive(Clone, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fields {
+    /// The struct with named fields.
+    NamedFields(Vec<(FieldName, Declaration)>),
+    /// The struct with unnamed fields, structurally identical to a tuple.
+    UnnamedFields(Vec<Declaration>),
+    /// The struct with no fields.
+    Empty,
+}
+
+/// All schema information needed to deserialize a single type.
+#[derive(Clone, PartialEq, Eq, Debug, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BorshSchemaContainer {
+    /// Declaration of the type.
+    pub declaration: Declaration,
+    /// All definitions needed to deserialize the given type.
+    pub definitions: HashMap<Declaration, Definition>,
+}
+
+impl Definition {
+    /// Declarations of the types this definition is made of.
+    pub fn references(&self) -> Vec<&Declaration> {
+        match self {
+            Definition::Array { elements, .. }
+            | Definition::Sequence { elements }
+            | Definition::Set { elements }
+            | Definition::Map { entries: elements } => vec![elements],
+            Definition::Tuple { elements } => elements.iter().collect(),
+            Definition::Enum { variants } => variants.iter().map(|(_, v)| v).collect(),
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => fields.iter().map(|(_, f)| f).collect(),
+                Fields::UnnamedFields(fields) => fields.iter().collect(),
+                Fields::Empty => Vec::new(),
+            },
+            Definition::Prefixed { sequence, .. } => vec![sequence],
+        }
+    }
+}
+
+impl BorshSchemaContainer {
+    /// Whether the type refers back to itself, directly or through other types, like a linked list
+    /// does through `Option<Box<Self>>`. Values of such a type nest arbitrarily deep, so it has no
+    /// counterpart in formats without recursion, e.g. Arrow.
+    pub fn is_recursive(&self) -> bool {
+        // Depth-first, without recursion since the schema may come from untrusted input: reaching
+        // a declaration that is still on the current path closes a cycle.
+        let mut done = HashSet::new();
+        let mut path = HashSet::new();
+        let mut stack = vec![(self.declaration.as_str(), false)];
+        while let Some((declaration, leaving)) = stack.pop() {
+            if leaving {
+                path.remove(declaration);
+                done.insert(declaration);
+                continue;
+            }
+            if path.contains(declaration) {
+                return true;
+            }
+            if done.contains(declaration) {
+                continue;
+            }
+            path.insert(declaration);
+            stack.push((declaration, true));
+            if let Some(definition) = self.definitions.get(declaration) {
+                stack.extend(
+                    definition
+                        .references()
+                        .into_iter()
+                        .map(|reference| (reference.as_str(), false)),
+                );
+            }
+        }
+        false
+    }
+}
+
+/// The declaration and definition of the type that can be used to (de)serialize Borsh without
+/// the Rust type that produced it.
+pub trait BorshSchema {
+    /// Recursively, using DFS, add type definitions required for this type. For primitive types
+    /// this is an empty map. Type definition explains how to serialize/deserialize a type.
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>);
+
+    /// Add this type's definition to the map, unless it is already there. Returns `true` if the
+    /// definition was inserted, in which case the caller should continue recursing into the
+    /// types it refers to; this is what makes self-referential types terminate.
+    fn add_definition(
+        declaration: Declaration,
+        definition: Definition,
+        definitions: &mut HashMap<Declaration, Definition>,
+    ) -> bool {
+        match definitions.entry(declaration) {
+            std::collections::hash_map::Entry::Occupied(occ) => {
+                let existing_def = occ.get();
+                assert_eq!(existing_def, &definition, "Redefining type schema for the same type name. Types with the same names are not supported.");
+                false
+            }
+            std::collections::hash_map::Entry::Vacant(vac) => {
+                vac.insert(definition);
+                true
+            }
+        }
+    }
+
+    /// Get the name of the type without brackets.
+    fn declaration() -> Declaration;
+
+    fn schema_container() -> BorshSchemaContainer {
+        let mut definitions = HashMap::new();
+        Self::add_definitions_recursively(&mut definitions);
+        BorshSchemaContainer {
+            declaration: Self::declaration(),
+            definitions,
+        }
+    }
+}
+
+impl<T: BorshSchema + ?Sized> BorshSchema for Box<T> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        T::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        T::declaration()
+    }
+}
+
+impl BorshSchema for () {
+    fn add_definitions_recursively(_definitions: &mut HashMap<Declaration, Definition>) {}
+
+    fn declaration() -> Declaration {
+        "nil".to_string()
+    }
+}
+
+macro_rules! impl_for_renamed_primitives {
+    ($($type: ty : $name: ident)+) => {
+    $(
+        impl BorshSchema for $type {
+            fn add_definitions_recursively(_definitions: &mut HashMap<Declaration, Definition>) {}
+            fn declaration() -> Declaration {
+                stringify!($name).to_string()
+            }
+        }
+    )+
+    };
+}
+
+macro_rules! impl_for_primitives {
+    ($($type: ident)+) => {
+    impl_for_renamed_primitives!{$($type : $type)+}
+    };
+}
+
+impl_for_primitives!(bool f32 f64 i8 i16 i32 i64 i128 u8 u16 u32 u64 u128);
+impl_for_renamed_primitives!(String: string);
+impl_for_renamed_primitives!(str: string);
+
+impl<T: BorshSchema, const N: usize> BorshSchema for [T; N] {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Array {
+            length: N as u32,
+            elements: T::declaration(),
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Array<{}, {}>"#, T::declaration(), N)
+    }
+}
+
+impl<T: BorshSchema> BorshSchema for Option<T> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("None".to_string(), <()>::declaration()),
+                ("Some".to_string(), T::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Option<{}>"#, T::declaration())
+    }
+}
+
+impl<T: BorshSchema, E: BorshSchema> BorshSchema for Result<T, E> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Ok".to_string(), T::declaration()),
+                ("Err".to_string(), E::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+            E::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Result<{}, {}>"#, T::declaration(), E::declaration())
+    }
+}
+
+impl<B: BorshSchema, C: BorshSchema> BorshSchema for std::ops::ControlFlow<B, C> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Continue".to_string(), C::declaration()),
+                ("Break".to_string(), B::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            B::add_definitions_recursively(definitions);
+            C::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"ControlFlow<{}, {}>"#, B::declaration(), C::declaration())
+    }
+}
+
+impl<T: BorshSchema> BorshSchema for std::task::Poll<T> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Ready".to_string(), T::declaration()),
+                ("Pending".to_string(), <()>::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Poll<{}>"#, T::declaration())
+    }
+}
+
+impl BorshSchema for std::cmp::Ordering {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Less".to_string(), <()>::declaration()),
+                ("Equal".to_string(), <()>::declaration()),
+                ("Greater".to_string(), <()>::declaration()),
+            ],
+        };
+        Self::add_definition(Self::declaration(), definition, definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "Ordering".to_string()
+    }
+}
+
+impl<T: BorshSchema> BorshSchema for std::ops::Bound<T> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("Included".to_string(), T::declaration()),
+                ("Excluded".to_string(), T::declaration()),
+                ("Unbounded".to_string(), <()>::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Bound<{}>"#, T::declaration())
+    }
+}
+
+/// Described as `()`, since it is encoded as nothing.
+impl<T: ?Sized> BorshSchema for PhantomData<T> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <()>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <()>::declaration()
+    }
+}
+
+/// Described as `()`, since it is encoded as nothing.
+impl BorshSchema for std::ops::RangeFull {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <()>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <()>::declaration()
+    }
+}
+
+macro_rules! impl_for_sequences {
+    ($($type: ident => $kind: ident)+) => {
+    $(
+        impl<T: BorshSchema> BorshSchema for $type<T> {
+            fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+                let definition = Definition::$kind { elements: T::declaration() };
+                if Self::add_definition(Self::declaration(), definition, definitions) {
+                    T::add_definitions_recursively(definitions);
+                }
+            }
+
+            fn declaration() -> Declaration {
+                format!(concat!(stringify!($type), "<{}>"), T::declaration())
+            }
+        }
+    )+
+    };
+}
+
+impl_for_sequences!(Vec => Sequence HashSet => Set BTreeSet => Set);
+
+impl<T: BorshSchema> BorshSchema for [T] {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <Vec<T>>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <Vec<T>>::declaration()
+    }
+}
+
+macro_rules! impl_for_maps {
+    ($($type: ident)+) => {
+    $(
+        impl<K: BorshSchema, V: BorshSchema> BorshSchema for $type<K, V> {
+            fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+                let definition = Definition::Map {
+                    entries: <(K, V)>::declaration(),
+                };
+                if Self::add_definition(Self::declaration(), definition, definitions) {
+                    <(K, V)>::add_definitions_recursively(definitions);
+                }
+            }
+
+            fn declaration() -> Declaration {
+                format!(
+                    concat!(stringify!($type), "<{}, {}>"),
+                    K::declaration(),
+                    V::declaration()
+                )
+            }
+        }
+    )+
+    };
+}
+
+impl_for_maps!(HashMap BTreeMap);
+
+/// The schema of `T` encoded with a length prefix of type `L`, see `#[borsh(len = "...")]`.
+pub struct Prefixed<T: ?Sized, L>(PhantomData<L>, PhantomData<T>);
+
+impl<T: BorshSchema + ?Sized, L: BorshSchema> BorshSchema for Prefixed<T, L> {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Prefixed {
+            length: L::declaration(),
+            sequence: T::declaration(),
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            T::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        format!(r#"Prefixed<{}, {}>"#, T::declaration(), L::declaration())
+    }
+}
+
+macro_rules! impl_tuples {
+    ($($len:literal => ($($name:ident)+))+) => {
+    $(
+    impl<$($name),+> BorshSchema for ($($name,)+)
+    where
+        $($name: BorshSchema),+
+    {
+        fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+            let elements = vec![$($name::declaration()),+];
+            let definition = Definition::Tuple { elements };
+            if Self::add_definition(Self::declaration(), definition, definitions) {
+                $(
+                    $name::add_definitions_recursively(definitions);
+                )+
+            }
+        }
+
+        fn declaration() -> Declaration {
+            let params = vec![$($name::declaration()),+];
+            format!(r#"Tuple<{}>"#, params.join(", "))
+        }
+    }
+    )+
+    };
+}
+
+oasis_borsh_derive::_gen_seq_macro! {
+    impl_tuples => T :: (
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+        17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    )
+}
+
+impl BorshSchema for std::net::SocketAddr {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let definition = Definition::Enum {
+            variants: vec![
+                ("V4".to_string(), std::net::SocketAddrV4::declaration()),
+                ("V6".to_string(), std::net::SocketAddrV6::declaration()),
+            ],
+        };
+        if Self::add_definition(Self::declaration(), definition, definitions) {
+            std::net::SocketAddrV4::add_definitions_recursively(definitions);
+            std::net::SocketAddrV6::add_definitions_recursively(definitions);
+        }
+    }
+
+    fn declaration() -> Declaration {
+        "SocketAddr".to_string()
+    }
+}
+
+macro_rules! impl_for_socket_addrs {
+    ($($type: ident => $ip: ident)+) => {
+    $(
+        impl BorshSchema for std::net::$type {
+            fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+                let definition = Definition::Struct {
+                    fields: Fields::NamedFields(vec![
+                        ("ip".to_string(), std::net::$ip::declaration()),
+                        ("port".to_string(), u16::declaration()),
+                    ]),
+                };
+                if Self::add_definition(Self::declaration(), definition, definitions) {
+                    std::net::$ip::add_definitions_recursively(definitions);
+                }
+            }
+
+            fn declaration() -> Declaration {
+                stringify!($type).to_string()
+            }
+        }
+    )+
+    };
+}
+
+impl_for_socket_addrs!(SocketAddrV4 => Ipv4Addr SocketAddrV6 => Ipv6Addr);
+
+macro_rules! impl_for_ip_addrs {
+    ($($type: ident => $len: expr)+) => {
+    $(
+        impl BorshSchema for std::net::$type {
+            fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+                let definition = Definition::Array {
+                    length: $len,
+                    elements: u8::declaration(),
+                };
+                Self::add_definition(Self::declaration(), definition, definitions);
+            }
+
+            fn declaration() -> Declaration {
+                stringify!($type).to_string()
+            }
+        }
+    )+
+    };
+}
+
+impl_for_ip_addrs!(Ipv4Addr => 4 Ipv6Addr => 16);
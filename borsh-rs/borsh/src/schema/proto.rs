@@ -0,0 +1,316 @@
+//! Conversion of borsh schemas to Protocol Buffers (proto3) definitions.
+//!
+//! [`to_proto`] emits a `.proto` file with a message for every struct, tuple and enum reachable
+//! from the type of a schema container, so that services speaking protobuf can be kept in step
+//! with the borsh types mechanically. Only the shapes carry over: the two wire formats are
+//! unrelated, and values still have to be converted field by field where they meet. Types are
+//! mapped as follows:
+//!
+//! | borsh                             | protobuf                                            |
+//! |-----------------------------------|-----------------------------------------------------|
+//! | `bool`, `string`, `f32`, `f64`    | `bool`, `string`, `float`, `double`                 |
+//! | `u8`, `u16`, `u32`                | `uint32`                                            |
+//! | `i8`, `i16`, `i32`                | `int32`                                             |
+//! | `u64`, `i64`                      | `uint64`, `int64`                                   |
+//! | `u128`, `i128`                    | `bytes`, 16 bytes little-endian                     |
+//! | `nil`                             | the empty message `Nil`                             |
+//! | `Option<T>`                       | `optional T`                                        |
+//! | `Vec<u8>`, `[u8; N]`              | `bytes`                                             |
+//! | other sequences, sets and arrays  | `repeated T`                                        |
+//! | maps with integer, bool or string keys | `map<K, V>`                                    |
+//! | other maps                        | `repeated` key-value tuple messages                 |
+//! | structs and tuples                | messages, unnamed fields named `field_0`, `field_1`, ... |
+//! | enums                             | messages with a `oneof variant`, one field per variant |
+//!
+//! Caveats:
+//!
+//! * Borsh has no field numbers. They are assigned from 1 in borsh field order, so inserting,
+//!   removing or reordering a field renumbers the fields after it, which protobuf peers see as a
+//!   breaking change. Regenerate both sides together.
+//! * Narrow integers are widened: protobuf accepts values that do not fit the borsh type, and
+//!   those must be rejected when converting. The same goes for the length of `u128`, `i128` and
+//!   `[u8; N]` bytes, and for the number of elements of arrays.
+//! * protobuf cannot nest `optional`, `repeated` and `map`, nor put them in a `oneof`. Where
+//!   borsh does, e.g. in `Vec<Option<T>>` or `Option<Vec<T>>`, the inner type becomes a wrapper
+//!   message with a single field `value`.
+//! * Entries of a protobuf map are unordered, whereas borsh maps are encoded in key order.
+//! * Length prefixes set with `#[borsh(len = "...")]` have no counterpart and are dropped.
+//! * Message names are declarations with every run of other characters than letters and digits
+//!   replaced by `_`, e.g. `Tuple<u8, string>` becomes `Tuple_u8_string`. Two declarations that
+//!   end up with the same name are rejected.
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Error, ErrorKind};
+
+use crate::schema::value::{definition, field_declarations};
+use crate::schema::{BorshSchemaContainer, Definition, Fields};
+
+fn unsupported(declaration: &str, reason: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "Type {} cannot be converted to protobuf: {}",
+            declaration, reason
+        ),
+    )
+}
+
+/// How a borsh declaration appears as the type of a protobuf field.
+enum FieldType {
+    Single(String),
+    Optional(String),
+    Repeated(String),
+    Map(String, String),
+}
+
+impl FieldType {
+    fn render(&self, name: &str, number: usize) -> String {
+        match self {
+            FieldType::Single(ty) => format!("{} {} = {};", ty, name, number),
+            FieldType::Optional(ty) => format!("optional {} {} = {};", ty, name, number),
+            FieldType::Repeated(ty) => format!("repeated {} {} = {};", ty, name, number),
+            FieldType::Map(key, value) => {
+                format!("map<{}, {}> {} = {};", key, value, name, number)
+            }
+        }
+    }
+}
+
+/// The protobuf scalar type of a primitive declaration.
+fn scalar(declaration: &str) -> Option<&'static str> {
+    Some(match declaration {
+        "bool" => "bool",
+        "u8" | "u16" | "u32" => "uint32",
+        "u64" => "uint64",
+        "i8" | "i16" | "i32" => "int32",
+        "i64" => "int64",
+        "u128" | "i128" => "bytes",
+        "f32" => "float",
+        "f64" => "double",
+        "string" => "string",
+        _ => return None,
+    })
+}
+
+/// Whether a protobuf map can be keyed by the given borsh declaration.
+fn map_key(declaration: &str) -> Option<&'static str> {
+    match declaration {
+        "u128" | "i128" | "f32" | "f64" => None,
+        _ => scalar(declaration),
+    }
+}
+
+/// If `declaration` is an `Option<T>`, the declaration of `T`.
+fn option_inner<'a>(container: &'a BorshSchemaContainer, declaration: &str) -> Option<&'a str> {
+    match container.definitions.get(declaration) {
+        Some(Definition::Enum { variants })
+            if variants.len() == 2
+                && variants[0].0 == "None"
+                && variants[0].1 == "nil"
+                && variants[1].0 == "Some" =>
+        {
+            Some(&variants[1].1)
+        }
+        _ => None,
+    }
+}
+
+/// The protobuf message name of a declaration.
+fn message_name(declaration: &str) -> String {
+    let mut name = String::new();
+    for c in declaration.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    let name = name.trim_end_matches('_').to_string();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// `CamelCase` variant names as `snake_case` field names.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+struct Generator<'a> {
+    container: &'a BorshSchemaContainer,
+    /// Bodies of the generated messages, by name.
+    messages: BTreeMap<String, Vec<String>>,
+    /// The declaration each message name was taken by.
+    names: HashMap<String, String>,
+}
+
+impl<'a> Generator<'a> {
+    /// Reserve the message name of `declaration`. Returns `None` if the message is generated
+    /// already, or is being generated further up a recursive type.
+    fn reserve(&mut self, declaration: &str) -> Result<Option<String>, Error> {
+        self.reserve_name(message_name(declaration), declaration)
+    }
+
+    fn reserve_name(&mut self, name: String, declaration: &str) -> Result<Option<String>, Error> {
+        match self.names.get(&name) {
+            Some(taken) if taken == declaration => Ok(None),
+            Some(taken) => Err(unsupported(
+                declaration,
+                &format!("its message name {} is taken by {}", name, taken),
+            )),
+            None => {
+                self.names.insert(name.clone(), declaration.to_string());
+                Ok(Some(name))
+            }
+        }
+    }
+
+    fn field_type(&mut self, declaration: &str) -> Result<FieldType, Error> {
+        if let Some(ty) = scalar(declaration) {
+            return Ok(FieldType::Single(ty.to_string()));
+        }
+        if declaration == "nil" {
+            if let Some(name) = self.reserve_name("Nil".to_string(), "nil")? {
+                self.messages.insert(name, Vec::new());
+            }
+            return Ok(FieldType::Single("Nil".to_string()));
+        }
+        if let Some(inner) = option_inner(self.container, declaration) {
+            return Ok(FieldType::Optional(self.single(inner)?));
+        }
+        Ok(match definition(self.container, declaration)? {
+            Definition::Array { elements, .. }
+            | Definition::Sequence { elements }
+            | Definition::Set { elements }
+                if elements == "u8" =>
+            {
+                FieldType::Single("bytes".to_string())
+            }
+            Definition::Map { entries } => match definition(self.container, entries)? {
+                Definition::Tuple { elements: entry } if entry.len() == 2 => {
+                    match map_key(&entry[0]) {
+                        Some(key) => FieldType::Map(key.to_string(), self.single(&entry[1])?),
+                        None => FieldType::Repeated(self.single(entries)?),
+                    }
+                }
+                _ => FieldType::Repeated(self.single(entries)?),
+            },
+            Definition::Array { elements, .. }
+            | Definition::Sequence { elements }
+            | Definition::Set { elements } => FieldType::Repeated(self.single(elements)?),
+            // The width of the length prefix has no protobuf counterpart.
+            Definition::Prefixed { sequence, .. } => self.field_type(sequence)?,
+            Definition::Tuple { .. } | Definition::Struct { .. } | Definition::Enum { .. } => {
+                FieldType::Single(self.message(declaration)?)
+            }
+        })
+    }
+
+    /// The type of a field that can be neither optional, repeated nor a map, wrapping the
+    /// declaration in a message if it would be.
+    fn single(&mut self, declaration: &str) -> Result<String, Error> {
+        match self.field_type(declaration)? {
+            FieldType::Single(ty) => Ok(ty),
+            ty => {
+                if let Some(name) = self.reserve(declaration)? {
+                    self.messages.insert(name, vec![ty.render("value", 1)]);
+                }
+                Ok(message_name(declaration))
+            }
+        }
+    }
+
+    /// Generate the message of a struct, tuple or enum declaration, returning its name.
+    fn message(&mut self, declaration: &str) -> Result<String, Error> {
+        let name = match self.reserve(declaration)? {
+            Some(name) => name,
+            None => return Ok(message_name(declaration)),
+        };
+        let mut body = Vec::new();
+        match definition(self.container, declaration)? {
+            Definition::Struct {
+                fields: Fields::NamedFields(fields),
+            } => {
+                for (i, (field, declaration)) in fields.iter().enumerate() {
+                    body.push(self.field_type(declaration)?.render(field, i + 1));
+                }
+            }
+            Definition::Struct { fields } => {
+                for (i, declaration) in field_declarations(fields).into_iter().enumerate() {
+                    let field = format!("field_{}", i);
+                    body.push(self.field_type(declaration)?.render(&field, i + 1));
+                }
+            }
+            Definition::Tuple { elements } => {
+                for (i, declaration) in elements.iter().enumerate() {
+                    let field = format!("field_{}", i);
+                    body.push(self.field_type(declaration)?.render(&field, i + 1));
+                }
+            }
+            Definition::Enum { variants } => {
+                body.push("oneof variant {".to_string());
+                for (i, (variant, declaration)) in variants.iter().enumerate() {
+                    let ty = FieldType::Single(self.single(declaration)?);
+                    body.push(format!("  {}", ty.render(&snake_case(variant), i + 1)));
+                }
+                body.push("}".to_string());
+            }
+            _ => unreachable!("Only structs, tuples and enums have messages"),
+        }
+        self.messages.insert(name.clone(), body);
+        Ok(name)
+    }
+}
+
+/// The proto3 definitions of the type described by `container`, in package `package` if given.
+///
+/// The message of the type itself is named after its declaration. A type that does not map to a
+/// message on its own, e.g. a `Vec<T>`, gets a message with a single field `value`.
+pub fn to_proto(container: &BorshSchemaContainer, package: Option<&str>) -> Result<String, Error> {
+    let mut generator = Generator {
+        container,
+        messages: BTreeMap::new(),
+        names: HashMap::new(),
+    };
+    let declaration = &container.declaration;
+    match generator.field_type(declaration)? {
+        FieldType::Single(ref ty) if *ty == message_name(declaration) => {}
+        ty => {
+            if let Some(name) = generator.reserve(declaration)? {
+                generator.messages.insert(name, vec![ty.render("value", 1)]);
+            }
+        }
+    }
+
+    let mut proto = format!(
+        "// Generated from the borsh schema of {}.\nsyntax = \"proto3\";\n",
+        container.declaration
+    );
+    if let Some(package) = package {
+        proto.push_str(&format!("\npackage {};\n", package));
+    }
+    for (name, body) in generator.messages {
+        if body.is_empty() {
+            proto.push_str(&format!("\nmessage {} {{}}\n", name));
+            continue;
+        }
+        proto.push_str(&format!("\nmessage {} {{\n", name));
+        for line in body {
+            proto.push_str(&format!("  {}\n", line));
+        }
+        proto.push_str("}\n");
+    }
+    Ok(proto)
+}
@@ -0,0 +1,835 @@
+//! Schema-driven access to borsh payloads without the Rust types that produced them.
+//!
+//! [`decode`] walks a payload according to a [`BorshSchemaContainer`] and produces a [`Value`],
+//! and [`encode`] does the reverse. Values are positional: struct fields, tuple elements and enum
+//! variants are identified by their index, and the names live in the schema.
+//!
+//! # Canonical order of map keys and set elements
+//!
+//! Borsh writes the entries of maps and sets sorted by the `Ord` of their Rust keys, not by the
+//! bytes the keys encode to. The two differ for every multi-byte integer: integers are encoded
+//! little-endian, so `256u128` (`00 01 00 ...`) sorts before `1u128` (`01 00 00 ...`) byte-wise
+//! but after it numerically, and negative integers sort before zero numerically but after it
+//! byte-wise. [`validate`] enforces the numeric order, as given by the [`PartialOrd`] impl of
+//! [`Value`]:
+//!
+//! * integers and floats compare numerically, `false` before `true`;
+//! * strings compare byte-wise, which for UTF-8 is the order of code points;
+//! * arrays, sequences, tuples and structs compare element by element, a prefix before the longer
+//!   value;
+//! * enums, `Option` included, compare by variant index, then by the value of the variant.
+//!
+//! This is the order of derived `Ord` impls, provided that `#[borsh(order = ...)]` does not move
+//! fields away from their declaration order and `#[borsh_wire_tag]` variants are declared in tag
+//! order. Keys with a handwritten `Ord` that disagrees cannot be validated from the schema alone.
+//!
+//! Only declarations whose definition is [`Definition::Map`] or [`Definition::Set`] are checked,
+//! whatever they are named, and other sequences may be in any order. Typed decoding of a
+//! `BTreeMap` or `BTreeSet` enforces the same order through the `Ord` of its keys when
+//! [`require_sorted_keys`](crate::de::options::DecodeOptions::require_sorted_keys) is on, as it is
+//! under [`Profile::Consensus`](crate::de::options::Profile::Consensus). `HashMap` and `HashSet`
+//! have no order to check when decoded by type, so only [`validate`] rejects them out of order.
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::io::{Cursor, Error, ErrorKind, Read, Write};
+
+use crate::de::hint;
+use crate::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A dynamically typed borsh value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    String(String),
+    /// Elements of a fixed-size array.
+    Array(Vec<Value>),
+    /// Elements of a length-prefixed sequence, including sets and maps.
+    Sequence(Vec<Value>),
+    /// Elements of a tuple.
+    Tuple(Vec<Value>),
+    /// Fields of a struct in declaration order.
+    Struct(Vec<Value>),
+    /// An enum variant given by its index, and the struct describing its fields.
+    Enum {
+        variant: u8,
+        value: Box<Value>,
+    },
+}
+
+fn invalid_data(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn invalid_input(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg)
+}
+
+/// Look up the definition of a non-primitive declaration.
+pub(crate) fn definition<'a>(
+    container: &'a BorshSchemaContainer,
+    declaration: &str,
+) -> Result<&'a Definition, Error> {
+    container
+        .definitions
+        .get(declaration)
+        .ok_or_else(|| invalid_input(format!("Schema has no definition for {}", declaration)))
+}
+
+/// Declarations of the fields of a struct definition, in order.
+pub(crate) fn field_declarations(fields: &Fields) -> Vec<&Declaration> {
+    match fields {
+        Fields::NamedFields(fields) => fields.iter().map(|(_, declaration)| declaration).collect(),
+        Fields::UnnamedFields(fields) => fields.iter().collect(),
+        Fields::Empty => Vec::new(),
+    }
+}
+
+/// Decode `bytes` as the type described by `container`. All bytes must be consumed.
+pub fn decode(container: &BorshSchemaContainer, bytes: &[u8]) -> Result<Value, Error> {
+    let mut c = Cursor::new(bytes);
+    let value = decode_from(container, &container.declaration, &mut c)?;
+    if c.position() != bytes.len() as u64 {
+        return Err(invalid_data("Not all bytes read".to_string()));
+    }
+    Ok(value)
+}
+
+/// Decode one value of type `declaration` from `reader`.
+pub fn decode_from<R: Read>(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    reader: &mut R,
+) -> Result<Value, Error> {
+    read(container, declaration, &mut Counting::new(reader)).map_err(|failure| failure.error)
+}
+
+/// Where and why a payload failed [`validate`].
+#[derive(Debug)]
+pub struct Invalid {
+    /// Offset of the first byte of the item that could not be decoded.
+    pub offset: u64,
+    /// Path to the item, e.g. `Event.tags[2].0`.
+    pub path: String,
+    pub error: Error,
+}
+
+impl std::fmt::Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at byte {}: {}", self.path, self.offset, self.error)
+    }
+}
+
+impl std::error::Error for Invalid {}
+
+/// Check that `bytes` is the canonical encoding of a value of the type described by `container`.
+///
+/// Unlike [`decode`], failures report the offset and path of the offending item. A payload is
+/// canonical if it decodes without leftover bytes, encoding the decoded value reproduces it, and
+/// the keys of every map and set are strictly ascending in the order described in the
+/// [module docs](self).
+pub fn validate(container: &BorshSchemaContainer, bytes: &[u8]) -> Result<Value, Invalid> {
+    let mut reader = Counting::new(bytes);
+    reader.canonical = true;
+    let value = read(container, &container.declaration, &mut reader).map_err(|failure| {
+        let mut path = container.declaration.clone();
+        for segment in failure.path.iter().rev() {
+            path.push_str(segment);
+        }
+        Invalid {
+            offset: failure.offset.unwrap_or(0),
+            path,
+            error: failure.error,
+        }
+    })?;
+    let invalid = |offset: u64, error: Error| Invalid {
+        offset,
+        path: container.declaration.clone(),
+        error,
+    };
+    if reader.position != bytes.len() as u64 {
+        return Err(invalid(
+            reader.position,
+            invalid_data("Not all bytes read".to_string()),
+        ));
+    }
+    let encoded = encode(container, &value).map_err(|err| invalid(0, err))?;
+    if encoded != bytes {
+        let offset = encoded
+            .iter()
+            .zip(bytes)
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| encoded.len().min(bytes.len()));
+        return Err(invalid(
+            offset as u64,
+            invalid_data("Non-canonical encoding".to_string()),
+        ));
+    }
+    Ok(value)
+}
+
+/// A reader that keeps track of how many bytes were consumed.
+struct Counting<R> {
+    inner: R,
+    position: u64,
+    /// Whether to reject maps and sets whose keys are out of canonical order.
+    canonical: bool,
+}
+
+impl<R: Read> Counting<R> {
+    fn new(inner: R) -> Self {
+        Counting {
+            inner,
+            position: 0,
+            canonical: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl PartialOrd for Value {
+    /// The canonical order of values of the same type, see the [module docs](self). Values of
+    /// different types, and NaN floats, are not ordered.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => Some(Ordering::Equal),
+            (Value::Bool(a), Value::Bool(b)) => a.partial_cmp(b),
+            (Value::U8(a), Value::U8(b)) => a.partial_cmp(b),
+            (Value::U16(a), Value::U16(b)) => a.partial_cmp(b),
+            (Value::U32(a), Value::U32(b)) => a.partial_cmp(b),
+            (Value::U64(a), Value::U64(b)) => a.partial_cmp(b),
+            (Value::U128(a), Value::U128(b)) => a.partial_cmp(b),
+            (Value::I8(a), Value::I8(b)) => a.partial_cmp(b),
+            (Value::I16(a), Value::I16(b)) => a.partial_cmp(b),
+            (Value::I32(a), Value::I32(b)) => a.partial_cmp(b),
+            (Value::I64(a), Value::I64(b)) => a.partial_cmp(b),
+            (Value::I128(a), Value::I128(b)) => a.partial_cmp(b),
+            (Value::F32(a), Value::F32(b)) => a.partial_cmp(b),
+            (Value::F64(a), Value::F64(b)) => a.partial_cmp(b),
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::Array(a), Value::Array(b))
+            | (Value::Sequence(a), Value::Sequence(b))
+            | (Value::Tuple(a), Value::Tuple(b))
+            | (Value::Struct(a), Value::Struct(b)) => a.partial_cmp(b),
+            (
+                Value::Enum { variant, value },
+                Value::Enum {
+                    variant: other_variant,
+                    value: other_value,
+                },
+            ) => match variant.cmp(other_variant) {
+                Ordering::Equal => value.partial_cmp(other_value),
+                ordering => Some(ordering),
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// Display the value as the type described by `container`, with the names of structs, fields
+    /// and variants from the schema, maps as `{key: value}` and byte arrays and sequences in hex.
+    ///
+    /// `{}` prints the value on one line, `{:#}` indents it over several, like `{:?}` and `{:#?}`
+    /// of the Rust type would. Parts of the value that do not match the schema are printed with
+    /// the `Debug` impl of [`Value`].
+    ///
+    /// ```
+    /// use oasis_borsh::schema::value;
+    /// use oasis_borsh::{BorshSchema, BorshSerialize};
+    ///
+    /// #[derive(BorshSerialize, BorshSchema)]
+    /// struct Transfer {
+    ///     to: [u8; 4],
+    ///     amount: Option<u64>,
+    /// }
+    ///
+    /// let container = Transfer::schema_container();
+    /// let bytes = Transfer { to: [0xde, 0xad, 0xbe, 0xef], amount: Some(5) }.try_to_vec().unwrap();
+    /// let value = value::decode(&container, &bytes).unwrap();
+    /// assert_eq!(
+    ///     value.display(&container).to_string(),
+    ///     "Transfer { to: 0xdeadbeef, amount: Some(5) }"
+    /// );
+    /// ```
+    pub fn display<'a>(&'a self, container: &'a BorshSchemaContainer) -> Pretty<'a> {
+        self.display_as(container, &container.declaration)
+    }
+
+    /// Like [`display`](Value::display), for a value of type `declaration`, such as one read by
+    /// [`decode_from`].
+    pub fn display_as<'a>(
+        &'a self,
+        container: &'a BorshSchemaContainer,
+        declaration: &'a str,
+    ) -> Pretty<'a> {
+        Pretty {
+            container,
+            declaration,
+            value: self,
+        }
+    }
+}
+
+/// A [`Value`] displayed as the type described by a schema, see [`Value::display`]. `Debug`
+/// prints the same.
+#[derive(Clone, Copy)]
+pub struct Pretty<'a> {
+    container: &'a BorshSchemaContainer,
+    declaration: &'a str,
+    value: &'a Value,
+}
+
+impl<'a> Pretty<'a> {
+    fn at(&self, declaration: &'a str, value: &'a Value) -> Pretty<'a> {
+        value.display_as(self.container, declaration)
+    }
+
+    fn fmt_elements(
+        &self,
+        f: &mut std::fmt::Formatter,
+        elements: &'a str,
+        order: Order,
+        values: &'a [Value],
+    ) -> std::fmt::Result {
+        if elements == "u8" && values.iter().all(|value| matches!(value, Value::U8(_))) {
+            f.write_str("0x")?;
+            for value in values {
+                if let Value::U8(byte) = value {
+                    write!(f, "{:02x}", byte)?;
+                }
+            }
+            return Ok(());
+        }
+        if order == Order::Keys {
+            if let Ok(Definition::Tuple { elements: entry }) = definition(self.container, elements)
+            {
+                let entries = values.iter().map(|value| match value {
+                    Value::Tuple(pair) if entry.len() == 2 && pair.len() == 2 => {
+                        Ok((self.at(&entry[0], &pair[0]), self.at(&entry[1], &pair[1])))
+                    }
+                    _ => Err(value),
+                });
+                if let Ok(entries) = entries.collect::<Result<Vec<_>, _>>() {
+                    return f.debug_map().entries(entries).finish();
+                }
+            }
+        }
+        let values = values.iter().map(|value| self.at(elements, value));
+        if order == Order::Elements {
+            f.debug_set().entries(values).finish()
+        } else {
+            f.debug_list().entries(values).finish()
+        }
+    }
+
+    /// Write `values` as the fields of a struct or of an enum variant called `name`, or return
+    /// `None` if they do not match `fields`.
+    fn fmt_fields(
+        &self,
+        f: &mut std::fmt::Formatter,
+        name: &str,
+        fields: &'a Fields,
+        values: &'a [Value],
+    ) -> Option<std::fmt::Result> {
+        Some(match fields {
+            Fields::NamedFields(fields) if fields.len() == values.len() => {
+                let mut s = f.debug_struct(name);
+                for ((field, declaration), value) in fields.iter().zip(values) {
+                    s.field(field, &self.at(declaration, value));
+                }
+                s.finish()
+            }
+            Fields::UnnamedFields(fields) if fields.len() == values.len() => {
+                let mut t = f.debug_tuple(name);
+                for (declaration, value) in fields.iter().zip(values) {
+                    t.field(&self.at(declaration, value));
+                }
+                t.finish()
+            }
+            Fields::Empty if values.is_empty() => f.write_str(name),
+            _ => return None,
+        })
+    }
+
+    fn fmt_variant(
+        &self,
+        f: &mut std::fmt::Formatter,
+        name: &str,
+        declaration: &'a str,
+        value: &'a Value,
+    ) -> std::fmt::Result {
+        match (definition(self.container, declaration), value) {
+            (Ok(Definition::Struct { fields }), Value::Struct(values)) => {
+                if let Some(result) = self.fmt_fields(f, name, fields, values) {
+                    return result;
+                }
+            }
+            (_, Value::Nil) if declaration == "nil" => return f.write_str(name),
+            _ => {}
+        }
+        // A variant holding a value of another type, like `Option::Some`.
+        f.debug_tuple(name)
+            .field(&self.at(declaration, value))
+            .finish()
+    }
+}
+
+impl std::fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::fmt::Debug for Pretty<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.value {
+            Value::Nil => return f.write_str("()"),
+            Value::Bool(v) => return write!(f, "{}", v),
+            Value::U8(v) => return write!(f, "{}", v),
+            Value::U16(v) => return write!(f, "{}", v),
+            Value::U32(v) => return write!(f, "{}", v),
+            Value::U64(v) => return write!(f, "{}", v),
+            Value::U128(v) => return write!(f, "{}", v),
+            Value::I8(v) => return write!(f, "{}", v),
+            Value::I16(v) => return write!(f, "{}", v),
+            Value::I32(v) => return write!(f, "{}", v),
+            Value::I64(v) => return write!(f, "{}", v),
+            Value::I128(v) => return write!(f, "{}", v),
+            Value::F32(v) => return write!(f, "{:?}", v),
+            Value::F64(v) => return write!(f, "{:?}", v),
+            Value::String(v) => return write!(f, "{:?}", v),
+            _ => {}
+        }
+        let definition = definition(self.container, self.declaration);
+        if let (Ok(definition), Value::Sequence(values)) = (&definition, self.value) {
+            if let Some((elements, order)) = sequence_elements(definition) {
+                return self.fmt_elements(f, elements, order, values);
+            }
+        }
+        match (definition, self.value) {
+            (Ok(Definition::Array { elements, .. }), Value::Array(values)) => {
+                self.fmt_elements(f, elements, Order::Any, values)
+            }
+            (Ok(Definition::Prefixed { sequence, .. }), value) => {
+                std::fmt::Debug::fmt(&self.at(sequence, value), f)
+            }
+            (Ok(Definition::Tuple { elements }), Value::Tuple(values))
+                if elements.len() == values.len() =>
+            {
+                let mut t = f.debug_tuple("");
+                for (declaration, value) in elements.iter().zip(values) {
+                    t.field(&self.at(declaration, value));
+                }
+                t.finish()
+            }
+            (Ok(Definition::Struct { fields }), Value::Struct(values)) => self
+                .fmt_fields(f, self.declaration, fields, values)
+                .unwrap_or_else(|| std::fmt::Debug::fmt(self.value, f)),
+            (Ok(Definition::Enum { variants }), Value::Enum { variant, value }) => {
+                match variants.get(*variant as usize) {
+                    Some((name, declaration)) => self.fmt_variant(f, name, declaration, value),
+                    None => std::fmt::Debug::fmt(self.value, f),
+                }
+            }
+            _ => std::fmt::Debug::fmt(self.value, f),
+        }
+    }
+}
+
+/// A decode error, the offset of the innermost item that caused it, and the path to that item,
+/// innermost segment first.
+struct Failure {
+    error: Error,
+    offset: Option<u64>,
+    path: Vec<String>,
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure {
+            error,
+            offset: None,
+            path: Vec::new(),
+        }
+    }
+}
+
+fn at<T>(result: Result<T, Failure>, segment: impl FnOnce() -> String) -> Result<T, Failure> {
+    result.map_err(|mut failure| {
+        failure.path.push(segment());
+        failure
+    })
+}
+
+fn read<R: Read>(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    reader: &mut Counting<R>,
+) -> Result<Value, Failure> {
+    let start = reader.position;
+    read_item(container, declaration, reader).map_err(|mut failure| {
+        failure.offset.get_or_insert(start);
+        failure
+    })
+}
+
+fn read_item<R: Read>(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    reader: &mut Counting<R>,
+) -> Result<Value, Failure> {
+    Ok(match declaration {
+        "nil" => Value::Nil,
+        "bool" => match u8::deserialize(reader)? {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            value => return Err(invalid_data(format!("Invalid bool value: {}", value)).into()),
+        },
+        "u8" => Value::U8(u8::deserialize(reader)?),
+        "u16" => Value::U16(u16::deserialize(reader)?),
+        "u32" => Value::U32(u32::deserialize(reader)?),
+        "u64" => Value::U64(u64::deserialize(reader)?),
+        "u128" => Value::U128(u128::deserialize(reader)?),
+        "i8" => Value::I8(i8::deserialize(reader)?),
+        "i16" => Value::I16(i16::deserialize(reader)?),
+        "i32" => Value::I32(i32::deserialize(reader)?),
+        "i64" => Value::I64(i64::deserialize(reader)?),
+        "i128" => Value::I128(i128::deserialize(reader)?),
+        "f32" => Value::F32(f32::deserialize(reader)?),
+        "f64" => Value::F64(f64::deserialize(reader)?),
+        "string" => Value::String(String::deserialize(reader)?),
+        _ => match definition(container, declaration)? {
+            Definition::Array { length, elements } => {
+                let mut result = Vec::with_capacity(hint::cautious::<Value>(*length));
+                for idx in 0..*length {
+                    result.push(at(read(container, elements, reader), || {
+                        format!("[{}]", idx)
+                    })?);
+                }
+                Value::Array(result)
+            }
+            definition @ Definition::Sequence { .. }
+            | definition @ Definition::Set { .. }
+            | definition @ Definition::Map { .. } => {
+                let len = u32::deserialize(reader)?;
+                read_elements(container, declaration, definition, len as u64, reader)?
+            }
+            Definition::Tuple { elements } => Value::Tuple(
+                elements
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, element)| {
+                        at(read(container, element, reader), || format!(".{}", idx))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Definition::Enum { variants } => {
+                let variant = u8::deserialize(reader)?;
+                let (name, variant_declaration) =
+                    variants.get(variant as usize).ok_or_else(|| {
+                        invalid_data(format!("Unexpected variant index: {:?}", variant))
+                    })?;
+                let value = at(read(container, variant_declaration, reader), || {
+                    format!("::{}", name)
+                })?;
+                Value::Enum {
+                    variant,
+                    value: Box::new(value),
+                }
+            }
+            Definition::Struct { fields } => Value::Struct(
+                field_declarations(fields)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, field)| {
+                        at(read(container, field, reader), || match fields {
+                            Fields::NamedFields(fields) => format!(".{}", fields[idx].0),
+                            _ => format!(".{}", idx),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Definition::Prefixed { length, sequence } => {
+                let len = read_len(length, reader)?;
+                if sequence == "string" {
+                    let mut bytes = Vec::with_capacity(hint::cautious::<u8>(cap(len)));
+                    reader.by_ref().take(len).read_to_end(&mut bytes)?;
+                    if (bytes.len() as u64) < len {
+                        return Err(Error::from(ErrorKind::UnexpectedEof).into());
+                    }
+                    Value::String(
+                        String::from_utf8(bytes).map_err(|err| invalid_data(err.to_string()))?,
+                    )
+                } else {
+                    let definition = definition(container, sequence)?;
+                    if sequence_elements(definition).is_none() {
+                        return Err(invalid_prefixed(sequence).into());
+                    }
+                    read_elements(container, sequence, definition, len, reader)?
+                }
+            }
+        },
+    })
+}
+
+fn invalid_length(length: &str) -> Error {
+    invalid_input(format!("Invalid length prefix type {}", length))
+}
+
+/// Read a length prefix of the integer type `length`.
+fn read_len<R: Read>(length: &str, reader: &mut R) -> Result<u64, Error> {
+    Ok(match length {
+        "u8" => u8::deserialize(reader)? as u64,
+        "u16" => u16::deserialize(reader)? as u64,
+        "u32" => u32::deserialize(reader)? as u64,
+        "u64" => u64::deserialize(reader)?,
+        _ => return Err(invalid_length(length)),
+    })
+}
+
+/// Write `len` as a length prefix of the integer type `length`.
+fn write_len<W: Write>(length: &str, len: usize, writer: &mut W) -> Result<(), Error> {
+    let too_long = |_| invalid_input(format!("Sequence is too long for a {} prefix", length));
+    match length {
+        "u8" => u8::try_from(len).map_err(too_long)?.serialize(writer),
+        "u16" => u16::try_from(len).map_err(too_long)?.serialize(writer),
+        "u32" => u32::try_from(len).map_err(too_long)?.serialize(writer),
+        "u64" => (len as u64).serialize(writer),
+        _ => Err(invalid_length(length)),
+    }
+}
+
+fn invalid_prefixed(sequence: &str) -> Error {
+    invalid_input(format!(
+        "Only strings and sequences can have a length prefix, found {}",
+        sequence
+    ))
+}
+
+/// A length clamped to the `u32` that capacity hints take.
+fn cap(len: u64) -> u32 {
+    len.min(u32::MAX as u64) as u32
+}
+
+/// How the elements of a sequence are ordered in canonical payloads.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Order {
+    /// In any order, like those of a `Vec`.
+    Any,
+    /// Strictly ascending, like the elements of a set.
+    Elements,
+    /// Strictly ascending by the first field of the `(key, value)` tuples, like map entries.
+    Keys,
+}
+
+impl Order {
+    /// The part of `element` that orders it, if any.
+    pub(crate) fn key(self, element: &Value) -> Option<&Value> {
+        match (self, element) {
+            (Order::Any, _) => None,
+            (Order::Elements, _) => Some(element),
+            (Order::Keys, Value::Tuple(entry)) => entry.first(),
+            (Order::Keys, _) => None,
+        }
+    }
+}
+
+/// The declaration of the elements of a sequence, set or map, and how they are ordered, or `None`
+/// for other definitions.
+pub(crate) fn sequence_elements(definition: &Definition) -> Option<(&Declaration, Order)> {
+    match definition {
+        Definition::Sequence { elements } => Some((elements, Order::Any)),
+        Definition::Set { elements } => Some((elements, Order::Elements)),
+        Definition::Map { entries } => Some((entries, Order::Keys)),
+        _ => None,
+    }
+}
+
+fn read_elements<R: Read>(
+    container: &BorshSchemaContainer,
+    sequence: &str,
+    definition: &Definition,
+    len: u64,
+    reader: &mut Counting<R>,
+) -> Result<Value, Failure> {
+    let (elements, order) =
+        sequence_elements(definition).ok_or_else(|| invalid_prefixed(sequence))?;
+    let mut result: Vec<Value> = Vec::with_capacity(hint::cautious::<Value>(cap(len)));
+    for idx in 0..len {
+        let start = reader.position;
+        let element = at(read(container, elements, reader), || format!("[{}]", idx))?;
+        if reader.canonical {
+            let keys = result
+                .last()
+                .and_then(|previous| Some((order.key(previous)?, order.key(&element)?)));
+            if let Some((previous, key)) = keys {
+                let error = match previous.partial_cmp(key) {
+                    Some(Ordering::Less) => None,
+                    Some(Ordering::Equal) => Some(format!("Duplicate key in {}", sequence)),
+                    Some(Ordering::Greater) => {
+                        Some(format!("Keys of {} are not in ascending order", sequence))
+                    }
+                    None => Some(format!("Keys of {} cannot be ordered", sequence)),
+                };
+                if let Some(error) = error {
+                    return Err(Failure {
+                        error: invalid_data(error),
+                        offset: Some(start),
+                        path: vec![format!("[{}]", idx)],
+                    });
+                }
+            }
+        }
+        result.push(element);
+    }
+    Ok(Value::Sequence(result))
+}
+
+/// Encode `value` as the type described by `container`.
+pub fn encode(container: &BorshSchemaContainer, value: &Value) -> Result<Vec<u8>, Error> {
+    let mut result = Vec::new();
+    encode_into(container, &container.declaration, value, &mut result)?;
+    Ok(result)
+}
+
+fn mismatch(declaration: &str, value: &Value) -> Error {
+    invalid_input(format!(
+        "Value {:?} does not match type {}",
+        value, declaration
+    ))
+}
+
+fn expect_len(declaration: &str, expected: usize, found: usize) -> Result<(), Error> {
+    if expected != found {
+        return Err(invalid_input(format!(
+            "Expected {} elements for type {}, found {}",
+            expected, declaration, found
+        )));
+    }
+    Ok(())
+}
+
+/// Encode `value` as the type `declaration` into `writer`.
+pub fn encode_into<W: Write>(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+    value: &Value,
+    writer: &mut W,
+) -> Result<(), Error> {
+    match (declaration, value) {
+        ("nil", Value::Nil) => Ok(()),
+        ("bool", Value::Bool(v)) => v.serialize(writer),
+        ("u8", Value::U8(v)) => v.serialize(writer),
+        ("u16", Value::U16(v)) => v.serialize(writer),
+        ("u32", Value::U32(v)) => v.serialize(writer),
+        ("u64", Value::U64(v)) => v.serialize(writer),
+        ("u128", Value::U128(v)) => v.serialize(writer),
+        ("i8", Value::I8(v)) => v.serialize(writer),
+        ("i16", Value::I16(v)) => v.serialize(writer),
+        ("i32", Value::I32(v)) => v.serialize(writer),
+        ("i64", Value::I64(v)) => v.serialize(writer),
+        ("i128", Value::I128(v)) => v.serialize(writer),
+        ("f32", Value::F32(v)) if !v.is_nan() => v.serialize(writer),
+        ("f64", Value::F64(v)) if !v.is_nan() => v.serialize(writer),
+        ("string", Value::String(v)) => v.serialize(writer),
+        ("nil", _)
+        | ("bool", _)
+        | ("u8", _)
+        | ("u16", _)
+        | ("u32", _)
+        | ("u64", _)
+        | ("u128", _)
+        | ("i8", _)
+        | ("i16", _)
+        | ("i32", _)
+        | ("i64", _)
+        | ("i128", _)
+        | ("f32", _)
+        | ("f64", _)
+        | ("string", _) => Err(mismatch(declaration, value)),
+        _ => match (definition(container, declaration)?, value) {
+            (Definition::Array { length, elements }, Value::Array(values)) => {
+                expect_len(declaration, *length as usize, values.len())?;
+                for value in values {
+                    encode_into(container, elements, value, writer)?;
+                }
+                Ok(())
+            }
+            (
+                Definition::Sequence { elements }
+                | Definition::Set { elements }
+                | Definition::Map { entries: elements },
+                Value::Sequence(values),
+            ) => {
+                if values.len() > u32::MAX as usize {
+                    return Err(invalid_input("Sequence is too long".to_string()));
+                }
+                (values.len() as u32).serialize(writer)?;
+                for value in values {
+                    encode_into(container, elements, value, writer)?;
+                }
+                Ok(())
+            }
+            (Definition::Prefixed { length, sequence }, Value::String(v))
+                if sequence == "string" =>
+            {
+                write_len(length, v.len(), writer)?;
+                writer.write_all(v.as_bytes())
+            }
+            (Definition::Prefixed { length, sequence }, Value::Sequence(values)) => {
+                let (elements, _) = sequence_elements(definition(container, sequence)?)
+                    .ok_or_else(|| invalid_prefixed(sequence))?;
+                write_len(length, values.len(), writer)?;
+                for value in values {
+                    encode_into(container, elements, value, writer)?;
+                }
+                Ok(())
+            }
+            (Definition::Tuple { elements }, Value::Tuple(values)) => {
+                expect_len(declaration, elements.len(), values.len())?;
+                for (element, value) in elements.iter().zip(values) {
+                    encode_into(container, element, value, writer)?;
+                }
+                Ok(())
+            }
+            (Definition::Enum { variants }, Value::Enum { variant, value }) => {
+                let (_, variant_declaration) =
+                    variants.get(*variant as usize).ok_or_else(|| {
+                        invalid_input(format!("Unexpected variant index: {:?}", variant))
+                    })?;
+                variant.serialize(writer)?;
+                encode_into(container, variant_declaration, value, writer)
+            }
+            (Definition::Struct { fields }, Value::Struct(values)) => {
+                let fields = field_declarations(fields);
+                expect_len(declaration, fields.len(), values.len())?;
+                for (field, value) in fields.into_iter().zip(values) {
+                    encode_into(container, field, value, writer)?;
+                }
+                Ok(())
+            }
+            _ => Err(mismatch(declaration, value)),
+        },
+    }
+}
@@ -0,0 +1,52 @@
+//! Support for [`secrecy::Secret`].
+//!
+//! A `Secret<T>` is encoded exactly like `T`. Decoding is always available with the `secrecy`
+//! feature, so credential-bearing configs can be loaded with borsh. Serializing requires the
+//! additional `secrecy-serialize` feature: without it, deriving `BorshSerialize` for a type that
+//! holds a secret fails to compile instead of silently writing the secret out.
+//!
+//! Decoding moves the value into the `Secret` as soon as it is complete, but buffers of a value
+//! that fails to decode part-way, e.g. a string with invalid UTF-8, are dropped without zeroizing.
+use std::collections::HashMap;
+use std::io::{Error, Read};
+
+use secrecy::Secret;
+use zeroize::Zeroize;
+
+use crate::schema::{BorshSchema, Declaration, Definition};
+use crate::BorshDeserialize;
+
+impl<T> BorshDeserialize for Secret<T>
+where
+    T: BorshDeserialize + Zeroize,
+{
+    #[inline]
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Secret::new(T::deserialize(reader)?))
+    }
+}
+
+#[cfg(feature = "secrecy-serialize")]
+impl<T> crate::BorshSerialize for Secret<T>
+where
+    T: crate::BorshSerialize + Zeroize,
+{
+    #[inline]
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Error> {
+        use secrecy::ExposeSecret;
+        self.expose_secret().serialize(writer)
+    }
+}
+
+impl<T> BorshSchema for Secret<T>
+where
+    T: BorshSchema + Zeroize,
+{
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        T::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        T::declaration()
+    }
+}
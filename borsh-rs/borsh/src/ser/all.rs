@@ -0,0 +1,66 @@
+//! Encoding values of one type back to back, the inverse of [`decode_all`](crate::de::decode_all).
+use crate::maybestd::io::{Error, Write};
+use crate::maybestd::vec::Vec;
+
+use super::BorshSerialize;
+
+/// Write the encodings of `values` to `writer` one after another, returning the number of bytes
+/// written.
+///
+/// If `index` is given, the offset each value starts at, counted from where `writer` was when
+/// this was called, is appended to it, so that the values can later be read individually:
+///
+/// ```
+/// use oasis_borsh::ser::encode_all;
+/// use oasis_borsh::BorshDeserialize;
+///
+/// let mut bytes = Vec::new();
+/// let mut index = Vec::new();
+/// let len = encode_all(&["a", "bc", "def"], &mut bytes, Some(&mut index)).unwrap();
+/// assert_eq!(len, bytes.len() as u64);
+/// assert_eq!(index, [0, 5, 11]);
+/// assert_eq!(String::try_from_slice(&bytes[5..11]).unwrap(), "bc");
+/// ```
+///
+/// On error, `index` holds the offsets of the values that were written in full.
+pub fn encode_all<T, I, W>(
+    values: I,
+    writer: &mut W,
+    mut index: Option<&mut Vec<u64>>,
+) -> Result<u64, Error>
+where
+    T: BorshSerialize,
+    I: IntoIterator<Item = T>,
+    W: Write,
+{
+    let mut writer = Counted {
+        inner: writer,
+        position: 0,
+    };
+    for value in values {
+        let offset = writer.position;
+        value.serialize(&mut writer)?;
+        if let Some(index) = index.as_mut() {
+            index.push(offset);
+        }
+    }
+    Ok(writer.position)
+}
+
+/// A writer that counts the bytes written through it.
+struct Counted<W> {
+    inner: W,
+    position: u64,
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let len = self.inner.write(buf)?;
+        self.position += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,107 @@
+//! Serializing with a context that types record auxiliary data in, such as a string interning
+//! table or statistics about what was written, without global mutable state.
+//!
+//! A [`BorshSerializeCtx<C>`] type is serialized with a `&mut C`, which it hands to the parts of
+//! itself that use it. Deriving it serializes the fields marked `#[borsh(ctx)]` with the context
+//! and the others with [`BorshSerialize`]. The context type is named with `#[borsh_ctx(Type)]`;
+//! without it the impl is generic over the context, for types that only pass it on to their
+//! fields.
+//!
+//! ```
+//! use oasis_borsh::{BorshSerialize, BorshSerializeCtx};
+//!
+//! /// Symbols written so far, so that repeated ones are written as the index of the first.
+//! #[derive(Default)]
+//! struct Symbols(Vec<String>);
+//!
+//! struct Symbol(String);
+//!
+//! impl BorshSerializeCtx<Symbols> for Symbol {
+//!     fn serialize_with_ctx<W: std::io::Write>(
+//!         &self,
+//!         writer: &mut W,
+//!         symbols: &mut Symbols,
+//!     ) -> std::io::Result<()> {
+//!         match symbols.0.iter().position(|symbol| *symbol == self.0) {
+//!             Some(index) => (1u8, index as u32).serialize(writer),
+//!             None => {
+//!                 symbols.0.push(self.0.clone());
+//!                 (0u8, &self.0).serialize(writer)
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(BorshSerializeCtx)]
+//! struct Order {
+//!     #[borsh(ctx)]
+//!     symbol: Symbol,
+//!     quantity: u32,
+//! }
+//!
+//! let orders = vec![
+//!     Order { symbol: Symbol("OASIS".to_string()), quantity: 1 },
+//!     Order { symbol: Symbol("OASIS".to_string()), quantity: 2 },
+//! ];
+//! let mut symbols = Symbols::default();
+//! let bytes = orders.try_to_vec_with_ctx(&mut symbols).unwrap();
+//! assert_eq!(bytes.len(), 4 + (1 + 9 + 4) + (1 + 4 + 4));
+//! assert_eq!(symbols.0, vec!["OASIS"]);
+//! ```
+use crate::maybestd::boxed::Box;
+use crate::maybestd::io::{Error, Write};
+use crate::maybestd::vec::Vec;
+
+use super::DEFAULT_SERIALIZER_CAPACITY;
+
+/// A data-structure that is serialized with the help of a context of type `C`.
+pub trait BorshSerializeCtx<C: ?Sized> {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error>;
+
+    /// Serialize this instance into a vector of bytes with `ctx`.
+    fn try_to_vec_with_ctx(&self, ctx: &mut C) -> Result<Vec<u8>, Error> {
+        let mut result = Vec::with_capacity(DEFAULT_SERIALIZER_CAPACITY);
+        self.serialize_with_ctx(&mut result, ctx)?;
+        Ok(result)
+    }
+}
+
+impl<C: ?Sized, T: BorshSerializeCtx<C>> BorshSerializeCtx<C> for Option<T> {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error> {
+        match self {
+            None => writer.write_all(&[0]),
+            Some(value) => {
+                writer.write_all(&[1])?;
+                value.serialize_with_ctx(writer, ctx)
+            }
+        }
+    }
+}
+
+impl<C: ?Sized, T: BorshSerializeCtx<C>> BorshSerializeCtx<C> for [T] {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error> {
+        writer.write_all(&(self.len() as u32).to_le_bytes())?;
+        for item in self {
+            item.serialize_with_ctx(writer, ctx)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: ?Sized, T: BorshSerializeCtx<C>> BorshSerializeCtx<C> for Vec<T> {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error> {
+        self.as_slice().serialize_with_ctx(writer, ctx)
+    }
+}
+
+impl<C: ?Sized, T: BorshSerializeCtx<C> + ?Sized> BorshSerializeCtx<C> for Box<T> {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error> {
+        T::serialize_with_ctx(self, writer, ctx)
+    }
+}
+
+impl<C: ?Sized, T: BorshSerializeCtx<C> + ?Sized> BorshSerializeCtx<C> for &T {
+    fn serialize_with_ctx<W: Write>(&self, writer: &mut W, ctx: &mut C) -> Result<(), Error> {
+        T::serialize_with_ctx(self, writer, ctx)
+    }
+}
@@ -0,0 +1,68 @@
+//! Serializing values whose type is only known at runtime.
+use crate::maybestd::io::{Error, Write};
+
+use super::BorshSerialize;
+
+/// An object-safe form of [`BorshSerialize`], implemented for every type that implements it, so
+/// that values of different types can be held as `Box<dyn BorshSerializeDyn>` and serialized
+/// through it.
+///
+/// `dyn BorshSerializeDyn` itself implements `BorshSerialize`, with or without `Send` and `Sync`,
+/// so boxes, references and collections of trait objects serialize like those of any other type:
+///
+/// ```
+/// use oasis_borsh::{BorshSerialize, BorshSerializeDyn};
+///
+/// let values: Vec<Box<dyn BorshSerializeDyn>> = vec![Box::new(1u8), Box::new("two".to_string())];
+/// assert_eq!(values.try_to_vec().unwrap(), [2, 0, 0, 0, 1, 3, 0, 0, 0, b't', b'w', b'o']);
+/// ```
+pub trait BorshSerializeDyn {
+    /// `BorshSerialize::serialize` with the writer behind a trait object.
+    fn serialize_dyn(&self, writer: &mut dyn Write) -> Result<(), Error>;
+
+    /// `BorshSerialize::serialized_size`.
+    fn serialized_size_dyn(&self) -> usize;
+
+    /// `BorshSerialize::size_hint`.
+    fn size_hint_dyn(&self) -> usize;
+}
+
+impl<T: BorshSerialize + ?Sized> BorshSerializeDyn for T {
+    fn serialize_dyn(&self, mut writer: &mut dyn Write) -> Result<(), Error> {
+        self.serialize(&mut writer)
+    }
+
+    fn serialized_size_dyn(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn size_hint_dyn(&self) -> usize {
+        self.size_hint()
+    }
+}
+
+macro_rules! impl_for_trait_objects {
+    ($($object: ty),+) => {
+    $(
+        impl BorshSerialize for $object {
+            fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+                self.serialize_dyn(writer)
+            }
+
+            fn serialized_size(&self) -> usize {
+                self.serialized_size_dyn()
+            }
+
+            fn size_hint(&self) -> usize {
+                self.size_hint_dyn()
+            }
+        }
+    )+
+    };
+}
+
+impl_for_trait_objects!(
+    dyn BorshSerializeDyn,
+    dyn BorshSerializeDyn + Send,
+    dyn BorshSerializeDyn + Send + Sync
+);
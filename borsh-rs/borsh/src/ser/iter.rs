@@ -0,0 +1,124 @@
+//! Encoding sequences and maps straight from iterators, for data that is produced as it is
+//! written.
+use crate::maybestd::format;
+use crate::maybestd::io::{Error, ErrorKind, Write};
+use crate::prefix::LengthPrefix;
+
+use super::BorshSerialize;
+
+/// Write the items of `iter` as a sequence of `len` elements, encoded like a `Vec` of them,
+/// without collecting them first.
+///
+/// The length prefix is written before the first item, so `len` has to be known up front; it is
+/// an error for `iter` to yield a different number of items, although the items before the
+/// mismatch have already been written by then.
+///
+/// ```
+/// use oasis_borsh::ser::serialize_iter;
+/// use oasis_borsh::BorshSerialize;
+///
+/// let mut bytes = Vec::new();
+/// serialize_iter(3, (1..=3u16).map(|n| n * 10), &mut bytes).unwrap();
+/// assert_eq!(bytes, vec![10u16, 20, 30].try_to_vec().unwrap());
+/// ```
+pub fn serialize_iter<T, I, W>(len: usize, iter: I, writer: &mut W) -> Result<(), Error>
+where
+    T: BorshSerialize,
+    I: IntoIterator<Item = T>,
+    W: Write,
+{
+    u32::write_len(len, writer)?;
+    let mut written = 0;
+    for item in iter {
+        if written == len {
+            return Err(wrong_len(len, "more"));
+        }
+        item.serialize(writer)?;
+        written += 1;
+    }
+    if written < len {
+        return Err(wrong_len(len, "fewer"));
+    }
+    Ok(())
+}
+
+/// [`serialize_iter`] with the length taken from the iterator itself.
+pub fn serialize_exact_iter<T, I, W>(iter: I, writer: &mut W) -> Result<(), Error>
+where
+    T: BorshSerialize,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+    W: Write,
+{
+    let iter = iter.into_iter();
+    serialize_iter(iter.len(), iter, writer)
+}
+
+/// Write the pairs of `iter` as a map of `len` entries, encoded like a `BTreeMap` of them,
+/// without collecting them first, e.g. from a database cursor.
+///
+/// As with [`serialize_iter`], `iter` has to yield exactly `len` pairs. Their keys have to be in
+/// strictly increasing order, the order maps are encoded in, so that the output decodes to the
+/// same entries and is the encoding of the map they form.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use oasis_borsh::ser::serialize_map_iter;
+/// use oasis_borsh::BorshSerialize;
+///
+/// let mut bytes = Vec::new();
+/// serialize_map_iter(2, vec![("a", 1u8), ("b", 2)], &mut bytes).unwrap();
+/// let map: BTreeMap<_, _> = vec![("a", 1u8), ("b", 2)].into_iter().collect();
+/// assert_eq!(bytes, map.try_to_vec().unwrap());
+/// ```
+pub fn serialize_map_iter<K, V, I, W>(len: usize, iter: I, writer: &mut W) -> Result<(), Error>
+where
+    K: BorshSerialize + PartialOrd,
+    V: BorshSerialize,
+    I: IntoIterator<Item = (K, V)>,
+    W: Write,
+{
+    u32::write_len(len, writer)?;
+    let mut written = 0;
+    let mut last_key: Option<K> = None;
+    for (key, value) in iter {
+        if written == len {
+            return Err(wrong_len(len, "more"));
+        }
+        if last_key.as_ref().is_some_and(|last_key| *last_key >= key) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Map keys are not in strictly increasing order",
+            ));
+        }
+        key.serialize(writer)?;
+        value.serialize(writer)?;
+        last_key = Some(key);
+        written += 1;
+    }
+    if written < len {
+        return Err(wrong_len(len, "fewer"));
+    }
+    Ok(())
+}
+
+/// [`serialize_map_iter`] with the length taken from the iterator itself.
+pub fn serialize_exact_map_iter<K, V, I, W>(iter: I, writer: &mut W) -> Result<(), Error>
+where
+    K: BorshSerialize + PartialOrd,
+    V: BorshSerialize,
+    I: IntoIterator<Item = (K, V)>,
+    I::IntoIter: ExactSizeIterator,
+    W: Write,
+{
+    let iter = iter.into_iter();
+    serialize_map_iter(iter.len(), iter, writer)
+}
+
+fn wrong_len(len: usize, than: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("Iterator yielded {} than the {} items announced", than, len),
+    )
+}
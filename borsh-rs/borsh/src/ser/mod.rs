@@ -1,30 +1,189 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::io::{Error, Write};
+#[cfg(not(feature = "allocator-api"))]
+use crate::maybestd::boxed::Box;
+use crate::maybestd::collections::BTreeMap;
+#[cfg(feature = "std")]
+use crate::maybestd::collections::{HashMap, HashSet};
+use crate::maybestd::io::{Error, Write};
+use crate::maybestd::rc::Rc;
+use crate::maybestd::string::String;
+use crate::maybestd::sync::Arc;
+use crate::maybestd::vec::Vec;
+use crate::spec;
+
+mod all;
+pub mod ctx;
+mod dynamic;
+mod iter;
+
+pub use all::encode_all;
+pub use ctx::BorshSerializeCtx;
+pub use dynamic::BorshSerializeDyn;
+pub use iter::{
+    serialize_exact_iter, serialize_exact_map_iter, serialize_iter, serialize_map_iter,
+};
 
 const DEFAULT_SERIALIZER_CAPACITY: usize = 1024;
 
+/// The most `try_to_vec` and `serialize_into` reserve up front from a `size_hint`. Beyond it the
+/// buffer grows as bytes are written, so a collection whose first element is much larger than the
+/// rest, which the hint extrapolates from, cannot reserve far more than the encoding takes.
+const MAX_HINTED_CAPACITY: usize = 1 << 20;
+
 /// A data-structure that can be serialized into binary format by NBOR.
 pub trait BorshSerialize {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
 
     /// Serialize this instance into a vector of bytes.
+    ///
+    /// The vector is preallocated to `size_hint` bytes, so that large values are written without
+    /// growing it, but to no less than a default capacity, since the hint of a type containing
+    /// types without one falls short, and to no more than 1 MiB, since it may overshoot.
     fn try_to_vec(&self) -> Result<Vec<u8>, Error> {
-        let mut result = Vec::with_capacity(DEFAULT_SERIALIZER_CAPACITY);
+        let capacity = self
+            .size_hint()
+            .clamp(DEFAULT_SERIALIZER_CAPACITY, MAX_HINTED_CAPACITY);
+        let mut result = Vec::with_capacity(capacity);
         self.serialize(&mut result)?;
         Ok(result)
     }
+
+    /// Append the serialization of this instance to `buf`, so that a buffer can be reused across
+    /// values instead of allocating one per value; clear it first to replace its contents.
+    ///
+    /// Room for `size_hint` more bytes, up to 1 MiB, is reserved up front. If serializing fails,
+    /// `buf` is left as it was, apart from its capacity.
+    fn serialize_into(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        let len = buf.len();
+        buf.reserve(self.size_hint().min(MAX_HINTED_CAPACITY));
+        let result = self.serialize(buf);
+        if result.is_err() {
+            buf.truncate(len);
+        }
+        result
+    }
+
+    /// Serialize this instance into the start of `buf`, returning the number of bytes written,
+    /// for environments that cannot allocate.
+    ///
+    /// Fails with `ErrorKind::WriteZero` if `buf` is too small, in which case its contents are
+    /// unspecified; `serialized_size` tells how large it needs to be.
+    fn serialize_to_slice(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = buf.len();
+        let mut rest = buf;
+        self.serialize(&mut rest)?;
+        Ok(len - rest.len())
+    }
+
+    /// Number of bytes `serialize` writes for this instance, e.g. to size an output buffer or to
+    /// enforce a size limit before serializing.
+    ///
+    /// Types whose size follows from their fields compute it without serializing; by default the
+    /// instance is serialized into a writer that only counts bytes. For an instance that fails to
+    /// serialize, the result is the number of bytes written before the failure.
+    fn serialized_size(&self) -> usize {
+        let mut counter = SizeWriter::new();
+        let _ = self.serialize(&mut counter);
+        counter.written()
+    }
+
+    /// A cheap estimate of `serialized_size`, for preallocating buffers. It is exact for types of
+    /// a fixed size and for strings; collections extrapolate from their first element, so the
+    /// cost does not grow with their length. The default of 0 means nothing is known.
+    ///
+    /// Impls combine the hints of their parts with saturating arithmetic, as an extrapolated hint
+    /// can exceed `usize::MAX` on 32-bit targets.
+    fn size_hint(&self) -> usize {
+        0
+    }
+}
+
+/// The size hint of `len` elements, extrapolated from the hint `first` of the first one.
+#[inline]
+pub(crate) fn extrapolate(len: usize, first: usize) -> usize {
+    len.saturating_mul(first)
+}
+
+/// A writer that discards the bytes written to it, counting them.
+///
+/// Serializing into it measures an encoding without materializing it, e.g. to fill in the length
+/// field of a protocol header or to account for storage before writing.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct SizeWriter {
+    written: usize,
+}
+
+impl SizeWriter {
+    pub fn new() -> Self {
+        SizeWriter { written: 0 }
+    }
+
+    /// Number of bytes written so far.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+}
+
+impl Write for SizeWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Number of bytes `value` serializes to, found by serializing it into a [`SizeWriter`].
+///
+/// Unlike [`BorshSerialize::serialized_size`], which may compute the size from the fields without
+/// serializing, this fails if serializing `value` would.
+pub fn serialized_len<T: BorshSerialize + ?Sized>(value: &T) -> Result<usize, Error> {
+    let mut counter = SizeWriter::new();
+    value.serialize(&mut counter)?;
+    Ok(counter.written())
 }
 
+/// Serialize `value` into `writer` through a buffer, flushing it at the end, so that writing to a
+/// file or socket does not take a system call per field.
+///
+/// Pass `&mut writer` to keep using the writer afterwards.
+#[cfg(feature = "std")]
+pub fn to_writer<W: Write, T: BorshSerialize + ?Sized>(writer: W, value: &T) -> Result<(), Error> {
+    let mut writer = std::io::BufWriter::new(writer);
+    value.serialize(&mut writer)?;
+    writer.flush()
+}
+
+/// Size of the length prefix of sequences, strings and maps.
+const LEN_SIZE: usize = spec::LENGTH_PREFIX_SIZE;
+
 impl BorshSerialize for () {
     fn serialize<W: Write>(&self, _writer: &mut W) -> Result<(), Error> {
         Ok(())
     }
+
+    fn serialized_size(&self) -> usize {
+        0
+    }
+
+    fn size_hint(&self) -> usize {
+        0
+    }
 }
 
 impl BorshSerialize for u8 {
     #[inline]
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-        writer.write_all(std::slice::from_ref(self))
+        writer.write_all(core::slice::from_ref(self))
+    }
+
+    fn serialized_size(&self) -> usize {
+        1
+    }
+
+    fn size_hint(&self) -> usize {
+        1
     }
 }
 
@@ -35,6 +194,14 @@ macro_rules! impl_for_integer {
             fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
                 writer.write_all(&self.to_le_bytes())
             }
+
+            fn serialized_size(&self) -> usize {
+                core::mem::size_of::<$type>()
+            }
+
+            fn size_hint(&self) -> usize {
+                core::mem::size_of::<$type>()
+            }
         }
     };
 }
@@ -50,29 +217,64 @@ impl_for_integer!(u64);
 impl_for_integer!(u128);
 
 // Note NaNs have a portability issue. Specifically, signalling NaNs on MIPS are quiet NaNs on x86,
-// and vice-versa. We disallow NaNs to avoid this issue.
+// and vice-versa. We disallow NaNs to avoid this issue, unless the NaN policy says otherwise.
+/// Whether a NaN about to be encoded is replaced by the canonical one under the
+/// [`NanPolicy`](crate::de::options::NanPolicy) in effect, panicking if the policy rejects NaNs,
+/// as it does by default and always without std.
+#[cold]
+pub(crate) fn canonical_nan() -> bool {
+    #[cfg(feature = "std")]
+    match crate::de::options::nan_policy() {
+        crate::de::options::NanPolicy::Reject => {}
+        crate::de::options::NanPolicy::Accept => return false,
+        crate::de::options::NanPolicy::Canonicalize => return true,
+    }
+    panic!("For portability reasons we do not allow to serialize NaNs.");
+}
+
 macro_rules! impl_for_float {
-    ($type: ident) => {
+    ($type: ident, $canonical: expr) => {
         impl BorshSerialize for $type {
             #[inline]
             fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-                assert!(
-                    !self.is_nan(),
-                    "For portability reasons we do not allow to serialize NaNs."
-                );
-                writer.write_all(&self.to_bits().to_le_bytes())
+                let mut bits = self.to_bits();
+                if self.is_nan() && canonical_nan() {
+                    bits = $canonical;
+                }
+                writer.write_all(&bits.to_le_bytes())
+            }
+
+            fn serialized_size(&self) -> usize {
+                core::mem::size_of::<$type>()
+            }
+
+            fn size_hint(&self) -> usize {
+                core::mem::size_of::<$type>()
             }
         }
     };
 }
 
-impl_for_float!(f32);
-impl_for_float!(f64);
+impl_for_float!(f32, spec::CANONICAL_NAN_F32);
+impl_for_float!(f64, spec::CANONICAL_NAN_F64);
 
 impl BorshSerialize for bool {
     #[inline]
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-        (if *self { 1u8 } else { 0u8 }).serialize(writer)
+        let byte = if *self {
+            spec::BOOL_TRUE
+        } else {
+            spec::BOOL_FALSE
+        };
+        byte.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1
+    }
+
+    fn size_hint(&self) -> usize {
+        1
     }
 }
 
@@ -83,13 +285,27 @@ where
     #[inline]
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         match self {
-            None => 0u8.serialize(writer),
+            None => spec::OPTION_NONE_TAG.serialize(writer),
             Some(value) => {
-                1u8.serialize(writer)?;
+                spec::OPTION_SOME_TAG.serialize(writer)?;
                 value.serialize(writer)
             }
         }
     }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            None => 1,
+            Some(value) => 1 + value.serialized_size(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            None => 1,
+            Some(value) => value.size_hint().saturating_add(1),
+        }
+    }
 }
 
 impl<T, E> BorshSerialize for Result<T, E>
@@ -101,34 +317,248 @@ where
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         match self {
             Ok(value) => {
-                0u8.serialize(writer)?;
+                spec::RESULT_OK_TAG.serialize(writer)?;
                 value.serialize(writer)
-            },
+            }
             Err(value) => {
+                spec::RESULT_ERR_TAG.serialize(writer)?;
+                value.serialize(writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            Ok(value) => value.serialized_size(),
+            Err(value) => value.serialized_size(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            Ok(value) => value.size_hint(),
+            Err(value) => value.size_hint(),
+        }
+        .saturating_add(1)
+    }
+}
+
+/// Encoded like `enum ControlFlow<B, C> { Continue(C), Break(B) }`.
+impl<B, C> BorshSerialize for core::ops::ControlFlow<B, C>
+where
+    B: BorshSerialize,
+    C: BorshSerialize,
+{
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            core::ops::ControlFlow::Continue(value) => {
+                0u8.serialize(writer)?;
+                value.serialize(writer)
+            }
+            core::ops::ControlFlow::Break(value) => {
+                1u8.serialize(writer)?;
+                value.serialize(writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            core::ops::ControlFlow::Continue(value) => value.serialized_size(),
+            core::ops::ControlFlow::Break(value) => value.serialized_size(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            core::ops::ControlFlow::Continue(value) => value.size_hint(),
+            core::ops::ControlFlow::Break(value) => value.size_hint(),
+        }
+        .saturating_add(1)
+    }
+}
+
+/// Encoded like `enum Poll<T> { Ready(T), Pending }`.
+impl<T> BorshSerialize for core::task::Poll<T>
+where
+    T: BorshSerialize,
+{
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            core::task::Poll::Ready(value) => {
+                0u8.serialize(writer)?;
+                value.serialize(writer)
+            }
+            core::task::Poll::Pending => 1u8.serialize(writer),
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            core::task::Poll::Ready(value) => 1 + value.serialized_size(),
+            core::task::Poll::Pending => 1,
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            core::task::Poll::Ready(value) => value.size_hint().saturating_add(1),
+            core::task::Poll::Pending => 1,
+        }
+    }
+}
+
+/// Encoded like `enum Ordering { Less, Equal, Greater }`.
+impl BorshSerialize for core::cmp::Ordering {
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let variant_idx: u8 = match self {
+            core::cmp::Ordering::Less => 0,
+            core::cmp::Ordering::Equal => 1,
+            core::cmp::Ordering::Greater => 2,
+        };
+        variant_idx.serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1
+    }
+
+    fn size_hint(&self) -> usize {
+        1
+    }
+}
+
+/// Encoded like `enum Bound<T> { Included(T), Excluded(T), Unbounded }`.
+impl<T> BorshSerialize for core::ops::Bound<T>
+where
+    T: BorshSerialize,
+{
+    #[inline]
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match self {
+            core::ops::Bound::Included(value) => {
+                0u8.serialize(writer)?;
+                value.serialize(writer)
+            }
+            core::ops::Bound::Excluded(value) => {
                 1u8.serialize(writer)?;
                 value.serialize(writer)
             }
+            core::ops::Bound::Unbounded => 2u8.serialize(writer),
         }
     }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            core::ops::Bound::Included(value) | core::ops::Bound::Excluded(value) => {
+                1 + value.serialized_size()
+            }
+            core::ops::Bound::Unbounded => 1,
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            core::ops::Bound::Included(value) | core::ops::Bound::Excluded(value) => {
+                value.size_hint().saturating_add(1)
+            }
+            core::ops::Bound::Unbounded => 1,
+        }
+    }
+}
+
+/// Encoded as nothing, like `()`.
+impl<T: ?Sized> BorshSerialize for core::marker::PhantomData<T> {
+    #[inline]
+    fn serialize<W: Write>(&self, _writer: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        0
+    }
+
+    fn size_hint(&self) -> usize {
+        0
+    }
+}
+
+/// Encoded as nothing, like `()`.
+impl BorshSerialize for core::ops::RangeFull {
+    #[inline]
+    fn serialize<W: Write>(&self, _writer: &mut W) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        0
+    }
+
+    fn size_hint(&self) -> usize {
+        0
+    }
 }
 
 impl BorshSerialize for String {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         self.as_bytes().serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+
+    fn size_hint(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+}
+
+impl BorshSerialize for str {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.as_bytes().serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+
+    fn size_hint(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
 }
 
 impl BorshSerialize for &str {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         self.as_bytes().serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
+
+    fn size_hint(&self) -> usize {
+        LEN_SIZE + self.len()
+    }
 }
 
-#[cfg(feature = "std")]
+/// With the `allocator-api` feature, `Vec<T, A>` is implemented for every allocator instead, see
+/// `allocator`.
+#[cfg(not(feature = "allocator-api"))]
 impl<T: BorshSerialize> BorshSerialize for Vec<T> {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         self.as_slice().serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        self.as_slice().serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        self.as_slice().size_hint()
+    }
 }
 
 impl<T> BorshSerialize for [T]
@@ -143,8 +573,22 @@ where
         }
         Ok(())
     }
-}
 
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(BorshSerialize::serialized_size)
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let elements = self
+            .first()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()));
+        LEN_SIZE.saturating_add(elements)
+    }
+}
 
 impl<T> BorshSerialize for &[T]
 where
@@ -158,12 +602,79 @@ where
         }
         Ok(())
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(BorshSerialize::serialized_size)
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let elements = self
+            .first()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()));
+        LEN_SIZE.saturating_add(elements)
+    }
 }
 
 impl<T: BorshSerialize> BorshSerialize for &T {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         (*self).serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+}
+
+impl<T: BorshSerialize + ?Sized> BorshSerialize for &mut T {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (**self).serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+}
+
+/// Encoded like the shared value, so `Rc<str>` and `Rc<[T]>` encode like `String` and `Vec<T>`.
+impl<T: BorshSerialize + ?Sized> BorshSerialize for Rc<T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (**self).serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+}
+
+/// Encoded like the shared value, so `Arc<str>` and `Arc<[T]>` encode like `String` and `Vec<T>`.
+impl<T: BorshSerialize + ?Sized> BorshSerialize for Arc<T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (**self).serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
 }
 
 #[cfg(feature = "std")]
@@ -182,6 +693,22 @@ where
         }
         Ok(())
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(BorshSerialize::serialized_size)
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let elements = self
+            .iter()
+            .next()
+            .map_or(0, |item| extrapolate(self.len(), item.size_hint()));
+        LEN_SIZE.saturating_add(elements)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -194,6 +721,8 @@ where
     #[inline]
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         let mut vec = self.iter().collect::<Vec<_>>();
+        // Sorted by the keys themselves rather than their encoding, so that e.g. integer keys
+        // are in numeric order. See `schema::value` for the canonical order.
         vec.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
         (vec.len() as u32).serialize(writer)?;
         for (key, value) in vec {
@@ -202,9 +731,26 @@ where
         }
         Ok(())
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(|(key, value)| key.serialized_size() + value.serialized_size())
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let entries = self.iter().next().map_or(0, |(key, value)| {
+            extrapolate(
+                self.len(),
+                key.size_hint().saturating_add(value.size_hint()),
+            )
+        });
+        LEN_SIZE.saturating_add(entries)
+    }
 }
 
-#[cfg(feature = "std")]
 impl<K, V> BorshSerialize for BTreeMap<K, V>
 where
     K: BorshSerialize + PartialOrd,
@@ -219,6 +765,24 @@ where
         }
         Ok(())
     }
+
+    fn serialized_size(&self) -> usize {
+        LEN_SIZE
+            + self
+                .iter()
+                .map(|(key, value)| key.serialized_size() + value.serialized_size())
+                .sum::<usize>()
+    }
+
+    fn size_hint(&self) -> usize {
+        let entries = self.iter().next().map_or(0, |(key, value)| {
+            extrapolate(
+                self.len(),
+                key.size_hint().saturating_add(value.size_hint()),
+            )
+        });
+        LEN_SIZE.saturating_add(entries)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -236,6 +800,20 @@ impl BorshSerialize for std::net::SocketAddr {
             }
         }
     }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            std::net::SocketAddr::V4(addr) => addr.serialized_size(),
+            std::net::SocketAddr::V6(addr) => addr.serialized_size(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        1 + match self {
+            std::net::SocketAddr::V4(addr) => addr.size_hint(),
+            std::net::SocketAddr::V6(addr) => addr.size_hint(),
+        }
+    }
 }
 
 #[cfg(feature = "std")]
@@ -245,6 +823,14 @@ impl BorshSerialize for std::net::SocketAddrV4 {
         self.ip().serialize(writer)?;
         self.port().serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        4 + 2
+    }
+
+    fn size_hint(&self) -> usize {
+        4 + 2
+    }
 }
 
 #[cfg(feature = "std")]
@@ -254,6 +840,14 @@ impl BorshSerialize for std::net::SocketAddrV6 {
         self.ip().serialize(writer)?;
         self.port().serialize(writer)
     }
+
+    fn serialized_size(&self) -> usize {
+        16 + 2
+    }
+
+    fn size_hint(&self) -> usize {
+        16 + 2
+    }
 }
 
 #[cfg(feature = "std")]
@@ -262,6 +856,14 @@ impl BorshSerialize for std::net::Ipv4Addr {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         writer.write_all(&self.octets())
     }
+
+    fn serialized_size(&self) -> usize {
+        4
+    }
+
+    fn size_hint(&self) -> usize {
+        4
+    }
 }
 
 #[cfg(feature = "std")]
@@ -270,71 +872,79 @@ impl BorshSerialize for std::net::Ipv6Addr {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
         writer.write_all(&self.octets())
     }
+
+    fn serialized_size(&self) -> usize {
+        16
+    }
+
+    fn size_hint(&self) -> usize {
+        16
+    }
 }
 
-impl BorshSerialize for Box<[u8]> {
+/// Encoded like the boxed value, so `Box<str>` and `Box<[T]>` encode like `String` and `Vec<T>`.
+#[cfg(not(feature = "allocator-api"))]
+impl<T: BorshSerialize + ?Sized> BorshSerialize for Box<T> {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-        (self.len() as u32).serialize(writer)?;
-        writer.write_all(self)
+        (**self).serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        (**self).serialized_size()
+    }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
     }
 }
 
-impl<T: BorshSerialize> BorshSerialize for Box<T> {
+impl<T: BorshSerialize, const N: usize> BorshSerialize for [T; N] {
+    #[inline]
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-        (&**self).serialize(writer)
+        for el in self.iter() {
+            el.serialize(writer)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.iter().map(BorshSerialize::serialized_size).sum()
+    }
+
+    fn size_hint(&self) -> usize {
+        self.first().map_or(0, |el| extrapolate(N, el.size_hint()))
     }
 }
 
-macro_rules! impl_arrays {
-    ($($len:expr)+) => {
-    $(
-      impl<T> BorshSerialize for [T; $len]
-      where T: BorshSerialize
-      {
-        #[inline]
-        fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-            for el in self.iter() {
-                el.serialize(writer)?;
+macro_rules! impl_tuples {
+    ($($len:literal => ($($name:ident)+))+) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($name: BorshSerialize),+> BorshSerialize for ($($name,)+) {
+                #[inline]
+                fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+                    let ($($name,)+) = self;
+                    $($name.serialize(writer)?;)+
+                    Ok(())
+                }
+
+                fn serialized_size(&self) -> usize {
+                    let ($($name,)+) = self;
+                    0 $(+ $name.serialized_size())+
+                }
+
+                fn size_hint(&self) -> usize {
+                    let ($($name,)+) = self;
+                    0usize $(.saturating_add($name.size_hint()))+
+                }
             }
-            Ok(())
-        }
-      }
-      )+
+        )+
     };
 }
 
-impl_arrays!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 32 64 65);
-
-macro_rules! impl_tuple {
-    ($($idx:tt $name:ident)+) => {
-      impl<$($name),+> BorshSerialize for ($($name),+)
-      where $($name: BorshSerialize,)+
-      {
-        #[inline]
-        fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
-            $(self.$idx.serialize(writer)?;)+
-            Ok(())
-        }
-      }
-    };
+oasis_borsh_derive::_gen_seq_macro! {
+    impl_tuples => T :: (
+        1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+        17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    )
 }
-
-impl_tuple!(0 T0 1 T1);
-impl_tuple!(0 T0 1 T1 2 T2);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15 16 T16);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15 16 T16 17 T17);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15 16 T16 17 T17 18 T18);
-impl_tuple!(0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15 16 T16 17 T17 18 T18 19 T19);
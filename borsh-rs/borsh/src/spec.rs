@@ -0,0 +1,129 @@
+//! The parameters of the borsh wire format, for tooling that reads or writes it without going
+//! through the traits of this crate, such as decoders generated for other languages or packet
+//! dissectors.
+//!
+//! The encoders and decoders here use these same constants, so they cannot drift apart from what
+//! this crate writes. They are part of the format and will not change within a major version:
+//!
+//! ```
+//! use oasis_borsh::spec::{self, Endianness, Spec};
+//! use oasis_borsh::BorshSerialize;
+//!
+//! let bytes = Some(vec![true]).try_to_vec().unwrap();
+//! assert_eq!(bytes[0], spec::OPTION_SOME_TAG);
+//! assert_eq!(bytes[1..1 + spec::LENGTH_PREFIX_SIZE], 1u32.to_le_bytes());
+//! assert_eq!(bytes[1 + spec::LENGTH_PREFIX_SIZE], spec::BOOL_TRUE);
+//!
+//! let format = Spec::CURRENT;
+//! assert_eq!(format.endianness, Endianness::Little);
+//! assert_eq!(format.enum_tag_size, 1);
+//! ```
+use core::convert::TryFrom;
+use core::mem::size_of;
+
+/// The integer type of the length in front of sequences, strings and maps.
+pub type LengthPrefix = u32;
+
+/// The integer type of the tag in front of enum variants, and of `Option` and `Result`.
+pub type EnumTag = u8;
+
+/// Size in bytes of the length in front of sequences, strings and maps.
+pub const LENGTH_PREFIX_SIZE: usize = size_of::<LengthPrefix>();
+
+/// The largest number of elements, bytes or entries a collection can hold.
+pub const MAX_LENGTH: u64 = LengthPrefix::MAX as u64;
+
+/// Size in bytes of an enum tag.
+pub const ENUM_TAG_SIZE: usize = size_of::<EnumTag>();
+
+/// The largest number of variants an enum can have.
+pub const MAX_VARIANTS: usize = EnumTag::MAX as usize + 1;
+
+/// The byte order of integers, floats and lengths.
+pub const ENDIANNESS: Endianness = Endianness::Little;
+
+/// The byte of `false`. Decoders reject bytes other than this and [`BOOL_TRUE`].
+pub const BOOL_FALSE: u8 = 0;
+
+/// The byte of `true`.
+pub const BOOL_TRUE: u8 = 1;
+
+/// The tag of `None`.
+pub const OPTION_NONE_TAG: EnumTag = 0;
+
+/// The tag of `Some`, followed by the value. Decoders treat any tag but [`OPTION_NONE_TAG`] as
+/// `Some`.
+pub const OPTION_SOME_TAG: EnumTag = 1;
+
+/// The tag of `Ok`, followed by the value.
+pub const RESULT_OK_TAG: EnumTag = 0;
+
+/// The tag of `Err`, followed by the error. Decoders treat any tag but [`RESULT_OK_TAG`] as
+/// `Err`.
+pub const RESULT_ERR_TAG: EnumTag = 1;
+
+/// The bits of the one NaN `f32` encoders write and decoders produce when they canonicalize NaNs,
+/// a positive quiet NaN. Otherwise NaNs are rejected, see `de::options::NanPolicy`.
+pub const CANONICAL_NAN_F32: u32 = 0x7fc0_0000;
+
+/// The bits of the one NaN `f64` encoders write and decoders produce when they canonicalize NaNs.
+pub const CANONICAL_NAN_F64: u64 = 0x7ff8_0000_0000_0000;
+
+/// The order of the bytes of a multi-byte number.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// The parameters of a wire format, as one value that can be compared or reported.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Spec {
+    pub length_prefix_size: usize,
+    pub enum_tag_size: usize,
+    pub endianness: Endianness,
+    pub bool_false: u8,
+    pub bool_true: u8,
+    pub option_none_tag: EnumTag,
+    pub option_some_tag: EnumTag,
+    pub result_ok_tag: EnumTag,
+    pub result_err_tag: EnumTag,
+}
+
+impl Spec {
+    /// The format this crate reads and writes.
+    pub const CURRENT: Spec = Spec {
+        length_prefix_size: LENGTH_PREFIX_SIZE,
+        enum_tag_size: ENUM_TAG_SIZE,
+        endianness: ENDIANNESS,
+        bool_false: BOOL_FALSE,
+        bool_true: BOOL_TRUE,
+        option_none_tag: OPTION_NONE_TAG,
+        option_some_tag: OPTION_SOME_TAG,
+        result_ok_tag: RESULT_OK_TAG,
+        result_err_tag: RESULT_ERR_TAG,
+    };
+
+    /// The encoding of the length `len` of a collection, or `None` if it is too long to encode.
+    pub fn encode_length(&self, len: usize) -> Option<[u8; LENGTH_PREFIX_SIZE]> {
+        let len = LengthPrefix::try_from(len).ok()?;
+        Some(match self.endianness {
+            Endianness::Little => len.to_le_bytes(),
+            Endianness::Big => len.to_be_bytes(),
+        })
+    }
+
+    /// The length encoded in `bytes`.
+    pub fn decode_length(&self, bytes: [u8; LENGTH_PREFIX_SIZE]) -> u64 {
+        u64::from(match self.endianness {
+            Endianness::Little => LengthPrefix::from_le_bytes(bytes),
+            Endianness::Big => LengthPrefix::from_be_bytes(bytes),
+        })
+    }
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Spec::CURRENT
+    }
+}
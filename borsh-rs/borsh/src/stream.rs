@@ -0,0 +1,231 @@
+//! Byte fields that bypass memory.
+//!
+//! A [`StreamedBytes`] field is encoded exactly like `Vec<u8>`. Decoded normally it is buffered in
+//! memory, but decoded with [`from_reader_streaming`] its contents are copied straight into a
+//! caller-provided [`Write`] sink (a file, a hasher) in small pieces, and the decoded field only
+//! records how many bytes went there. This keeps messages that embed multi-gigabyte blobs
+//! decodable in bounded memory.
+//!
+//! Symmetrically, a field created with [`StreamedBytes::from_reader`] is serialized by copying a
+//! known number of bytes from a [`Read`] source, so large files can be wrapped in borsh envelopes
+//! without loading them first.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::sync::{Arc, Mutex};
+
+use crate::de::{check_len, read_len, LenKind};
+use crate::schema::{BorshSchema, Declaration, Definition};
+use crate::spec::LENGTH_PREFIX_SIZE;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// A byte field that can be streamed instead of buffered, see the module documentation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StreamedBytes(Inner);
+
+type Source = Arc<Mutex<Option<Box<dyn Read + Send>>>>;
+
+#[derive(Clone)]
+enum Inner {
+    Buffered(Vec<u8>),
+    /// The contents went to a sink while decoding.
+    Streamed {
+        len: u32,
+    },
+    /// The contents are read from `source` when serializing. Clones share the source, which can
+    /// only be read once.
+    Source {
+        len: u32,
+        source: Source,
+    },
+}
+
+impl PartialEq for Inner {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Inner::Buffered(a), Inner::Buffered(b)) => a == b,
+            (Inner::Streamed { len: a }, Inner::Streamed { len: b }) => a == b,
+            (Inner::Source { source: a, .. }, Inner::Source { source: b, .. }) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Inner {}
+
+impl fmt::Debug for Inner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Inner::Buffered(bytes) => f.debug_tuple("Buffered").field(bytes).finish(),
+            Inner::Streamed { len } => f.debug_struct("Streamed").field("len", len).finish(),
+            Inner::Source { len, .. } => f.debug_struct("Source").field("len", len).finish(),
+        }
+    }
+}
+
+impl StreamedBytes {
+    /// Number of bytes in the field.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Inner::Buffered(bytes) => bytes.len(),
+            Inner::Streamed { len } | Inner::Source { len, .. } => *len as usize,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the contents were streamed into a sink rather than kept.
+    pub fn is_streamed(&self) -> bool {
+        matches!(self.0, Inner::Streamed { .. })
+    }
+
+    /// The contents, unless they were streamed into a sink.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.0 {
+            Inner::Buffered(bytes) => Some(bytes),
+            Inner::Streamed { .. } | Inner::Source { .. } => None,
+        }
+    }
+
+    /// A field whose `len` bytes of contents are read from `source` when it is serialized.
+    ///
+    /// The source is read at most once; serializing the field, or a clone of it, a second time
+    /// fails. Serializing also fails if `source` ends before `len` bytes.
+    pub fn from_reader<R: Read + Send + 'static>(source: R, len: u32) -> Self {
+        StreamedBytes(Inner::Source {
+            len,
+            source: Arc::new(Mutex::new(Some(Box::new(source)))),
+        })
+    }
+}
+
+impl From<Vec<u8>> for StreamedBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        StreamedBytes(Inner::Buffered(bytes))
+    }
+}
+
+type Sink<'a> = &'a mut dyn Write;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink<'static>>> = const { RefCell::new(None) };
+}
+
+/// Deserialize a `T` from `reader`, copying the contents of every [`StreamedBytes`] field into
+/// `sink`, in wire order, instead of buffering them.
+pub fn from_reader_streaming<T, R, W>(reader: &mut R, sink: &mut W) -> Result<T, Error>
+where
+    T: BorshDeserialize,
+    R: Read,
+    W: Write,
+{
+    struct Restore(Option<Sink<'static>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SINK.with(|cell| *cell.borrow_mut() = previous);
+        }
+    }
+
+    let sink: Sink<'_> = sink;
+    // The sink only lives in the thread-local while `T::deserialize` runs, and `Restore` removes it
+    // before this function returns, so extending its lifetime is sound.
+    let sink: Sink<'static> = unsafe { std::mem::transmute(sink) };
+    let _restore = Restore(SINK.with(move |cell| cell.borrow_mut().replace(sink)));
+    let result = T::deserialize(reader)?;
+    SINK.with(|cell| cell.borrow_mut().take().map(|sink| sink.flush()))
+        .transpose()?;
+    Ok(result)
+}
+
+impl BorshDeserialize for StreamedBytes {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = read_len(reader)?;
+        check_len(len.into(), LenKind::Sequence)?;
+        // Take the sink out while copying, so a sink that itself decodes does not re-enter it.
+        let sink = SINK.with(|cell| cell.borrow_mut().take());
+        let mut sink = match sink {
+            Some(sink) => sink,
+            None => return Ok(StreamedBytes(Inner::Buffered(read_bytes(reader, len)?))),
+        };
+        let copied = io::copy(&mut reader.by_ref().take(len as u64), &mut sink);
+        SINK.with(move |cell| *cell.borrow_mut() = Some(sink));
+        if copied? != len as u64 {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Unexpected end of input in streamed bytes",
+            ));
+        }
+        Ok(StreamedBytes(Inner::Streamed { len }))
+    }
+}
+
+fn read_bytes<R: Read>(reader: &mut R, len: u32) -> Result<Vec<u8>, Error> {
+    let mut result = Vec::with_capacity(crate::de::hint::cautious::<u8>(len));
+    reader.by_ref().take(len as u64).read_to_end(&mut result)?;
+    if result.len() != len as usize {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "Unexpected end of input in streamed bytes",
+        ));
+    }
+    Ok(result)
+}
+
+impl BorshSerialize for StreamedBytes {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        match &self.0 {
+            Inner::Buffered(bytes) => bytes.serialize(writer),
+            Inner::Streamed { .. } => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Streamed bytes were not kept and cannot be serialized",
+            )),
+            Inner::Source { len, source } => {
+                // A poisoned lock means an earlier read panicked; the source is consumed either way.
+                let source = source.lock().unwrap_or_else(|err| err.into_inner()).take();
+                let mut source = source.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        "Streamed bytes source was already consumed",
+                    )
+                })?;
+                len.serialize(writer)?;
+                let copied = io::copy(&mut source.by_ref().take(*len as u64), writer)?;
+                if copied != *len as u64 {
+                    return Err(Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "Streamed bytes source ended before the declared length",
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Computed from the length, as counting the bytes would consume a one-shot source.
+    fn serialized_size(&self) -> usize {
+        match &self.0 {
+            // Serializing fails before writing anything.
+            Inner::Streamed { .. } => 0,
+            _ => self.size_hint(),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        LENGTH_PREFIX_SIZE.saturating_add(self.len())
+    }
+}
+
+impl BorshSchema for StreamedBytes {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        <Vec<u8>>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <Vec<u8>>::declaration()
+    }
+}
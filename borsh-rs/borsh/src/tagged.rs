@@ -0,0 +1,174 @@
+//! Fixed-size byte strings distinguished by a marker type, for hashes, keys and other identifiers.
+//!
+//! ```
+//! use oasis_borsh::tagged::Tagged;
+//!
+//! enum Block {}
+//! enum Transaction {}
+//!
+//! type BlockHash = Tagged<Block, 32>;
+//! type TxHash = Tagged<Transaction, 32>;
+//!
+//! let block = BlockHash::new([1; 32]);
+//! let tx = TxHash::new(*block.as_bytes());
+//! // `block == tx` does not compile: the two are different types with the same encoding.
+//! assert_eq!(block.as_bytes(), tx.as_bytes());
+//! ```
+//!
+//! A `Tagged<T, N>` is encoded exactly like `[u8; N]`. Its trait impls place no bounds on the
+//! marker type, so the marker can be an empty enum, and types holding a `Tagged` can derive
+//! `Clone`, `Eq`, `Hash`, `Ord` and the borsh traits without the marker implementing any of them.
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+use crate::diff::{replace_apply_diff, replace_diff_into, BorshDiff};
+use crate::fixed_size::BorshFixedSize;
+use crate::maybestd::io::{Error, Read, Write};
+use crate::maybestd::vec::Vec;
+use crate::memcpy::Memcpy;
+#[cfg(feature = "std")]
+use crate::schema::{Declaration, Definition};
+#[cfg(feature = "std")]
+use crate::BorshSchema;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// `N` bytes identifying a `T`.
+#[repr(transparent)]
+pub struct Tagged<T: ?Sized, const N: usize>([u8; N], PhantomData<fn() -> T>);
+
+impl<T: ?Sized, const N: usize> Tagged<T, N> {
+    pub const fn new(bytes: [u8; N]) -> Self {
+        Tagged(bytes, PhantomData)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> [u8; N] {
+        self.0
+    }
+}
+
+impl<T: ?Sized, const N: usize> Clone for Tagged<T, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized, const N: usize> Copy for Tagged<T, N> {}
+
+impl<T: ?Sized, const N: usize> Default for Tagged<T, N> {
+    fn default() -> Self {
+        Tagged::new([0; N])
+    }
+}
+
+impl<T: ?Sized, const N: usize> PartialEq for Tagged<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: ?Sized, const N: usize> Eq for Tagged<T, N> {}
+
+impl<T: ?Sized, const N: usize> PartialOrd for Tagged<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized, const N: usize> Ord for Tagged<T, N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T: ?Sized, const N: usize> Hash for Tagged<T, N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Formats the bytes as lowercase hex.
+impl<T: ?Sized, const N: usize> fmt::Debug for Tagged<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ?Sized, const N: usize> From<[u8; N]> for Tagged<T, N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Tagged::new(bytes)
+    }
+}
+
+impl<T: ?Sized, const N: usize> From<Tagged<T, N>> for [u8; N] {
+    fn from(tagged: Tagged<T, N>) -> Self {
+        tagged.0
+    }
+}
+
+impl<T: ?Sized, const N: usize> AsRef<[u8]> for Tagged<T, N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<T: ?Sized, const N: usize> BorshSerialize for Tagged<T, N> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&self.0)
+    }
+
+    fn serialized_size(&self) -> usize {
+        N
+    }
+
+    fn size_hint(&self) -> usize {
+        N
+    }
+}
+
+impl<T: ?Sized, const N: usize> BorshDeserialize for Tagged<T, N> {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut bytes = [0u8; N];
+        reader.read_exact(&mut bytes)?;
+        Ok(Tagged::new(bytes))
+    }
+}
+
+/// Described as `[u8; N]`, which it is on the wire.
+#[cfg(feature = "std")]
+impl<T: ?Sized, const N: usize> BorshSchema for Tagged<T, N> {
+    fn add_definitions_recursively(
+        definitions: &mut std::collections::HashMap<Declaration, Definition>,
+    ) {
+        <[u8; N]>::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        <[u8; N]>::declaration()
+    }
+}
+
+impl<T: ?Sized, const N: usize> BorshFixedSize for Tagged<T, N> {
+    const SIZE: usize = N;
+}
+
+impl<T: ?Sized, const N: usize> BorshDiff for Tagged<T, N> {
+    fn diff_into(old: &Self, new: &Self, out: &mut Vec<u8>) -> Result<bool, Error> {
+        replace_diff_into(old, new, out)
+    }
+
+    fn apply_diff<R: Read>(&mut self, reader: &mut R) -> Result<(), Error> {
+        replace_apply_diff(self, reader)
+    }
+}
+
+// Sound since the struct is a transparent wrapper around the bytes.
+unsafe impl<T: ?Sized, const N: usize> Memcpy for Tagged<T, N> {}
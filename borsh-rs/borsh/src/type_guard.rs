@@ -0,0 +1,89 @@
+//! Catching bytes decoded as a different type than they were encoded from.
+//!
+//! Borsh encodings do not say what they encode, so decoding the bytes of one type as another
+//! often succeeds and yields garbage, a bug that is easy to introduce when refactoring which types
+//! travel where. [`to_vec_guarded`] writes a [`GUARD_LEN`]-byte [`fingerprint`] of the type in
+//! front of the encoding, and [`from_slice_guarded`] checks it and fails with
+//! [`crate::Error::WrongType`] if it belongs to another type:
+//!
+//! ```
+//! use oasis_borsh::type_guard::{from_slice_guarded, to_vec_guarded};
+//! use oasis_borsh::{BorshDeserialize, BorshSerialize};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Deposit {
+//!     amount: u64,
+//! }
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Withdrawal {
+//!     amount: u64,
+//! }
+//!
+//! let bytes = to_vec_guarded(&Deposit { amount: 5 }).unwrap();
+//! assert!(from_slice_guarded::<Deposit>(&bytes).is_ok());
+//! assert!(from_slice_guarded::<Withdrawal>(&bytes).is_err());
+//! ```
+//!
+//! Only these two functions write and check the fingerprint; every other way to encode and decode,
+//! `try_to_vec` and `try_from_slice` included, is unaffected, so both ends of a guarded channel
+//! have to opt in.
+//!
+//! The fingerprint hashes [`core::any::type_name`], which names the type with its module path and
+//! generic arguments, but may change between compiler versions and changes whenever a type is
+//! renamed or moved. The guard is thus meant for tests and debug builds of programs that encode
+//! and decode with the same build, not for data that is stored or sent to other programs.
+use core::any::type_name;
+use std::io::Error;
+
+use crate::de::tracked;
+use crate::header::fnv1a64;
+use crate::{BorshDeserialize, BorshSerialize};
+
+/// Size in bytes of the fingerprint written in front of guarded encodings.
+pub const GUARD_LEN: usize = 8;
+
+/// The fingerprint of `T`: the 64-bit FNV-1a hash of its type name, little-endian.
+///
+/// References are encoded like what they point to, so `&T` and `&mut T` have the fingerprint of
+/// `T`.
+pub fn fingerprint<T: ?Sized>() -> [u8; GUARD_LEN] {
+    let mut name = type_name::<T>();
+    while let Some(referent) = name.strip_prefix('&') {
+        name = referent.trim_start_matches("mut ");
+    }
+    fnv1a64(name.as_bytes()).to_le_bytes()
+}
+
+/// Serialize `value` into a vector of bytes, behind the fingerprint of `T`.
+pub fn to_vec_guarded<T: BorshSerialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut result = fingerprint::<T>().to_vec();
+    value.serialize_into(&mut result)?;
+    Ok(result)
+}
+
+/// Deserialize a `T` from `v`, which must start with the fingerprint of `T` and hold nothing after
+/// the value. Offsets in errors count the fingerprint.
+pub fn from_slice_guarded<T: BorshDeserialize>(v: &[u8]) -> Result<T, Error> {
+    if v.len() < GUARD_LEN {
+        return Err(crate::Error::Truncated {
+            what: "type guard",
+            wanted: GUARD_LEN,
+            available: v.len(),
+        }
+        .into());
+    }
+    if v[..GUARD_LEN] != fingerprint::<T>() {
+        return Err(crate::Error::WrongType {
+            expected: type_name::<T>(),
+        }
+        .into());
+    }
+    let mut buf = &v[GUARD_LEN..];
+    let result = T::deserialize_slice(&mut buf)
+        .map_err(|err| tracked::at_offset(err, (v.len() - buf.len()) as u64))?;
+    if !buf.is_empty() {
+        return Err(crate::Error::TrailingBytes.into());
+    }
+    Ok(result)
+}
@@ -0,0 +1,43 @@
+// Minimal unsigned LEB128 encoding, used by the compact sequence wrappers.
+use crate::maybestd::io::{Error, ErrorKind, Read, Write};
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, mut value: u64) -> Result<(), Error> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buf[..len])
+}
+
+/// Reads a varint, rejecting overlong encodings so that every value has exactly one encoding.
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = 0u8;
+        reader.read_exact(core::slice::from_mut(&mut byte))?;
+        if shift == 63 && byte > 1 {
+            return Err(Error::new(ErrorKind::InvalidData, "Varint overflows u64"));
+        }
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            if byte == 0 && shift != 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Non-canonical varint encoding",
+                ));
+            }
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+/// `Transfer` used to be tag 3 and `Burn` tags 4 and 5, before the variants were renumbered.
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Instruction {
+    Mint(u64),
+    #[borsh(alias_tag = 3)]
+    Transfer {
+        to: String,
+        amount: u64,
+    },
+    #[borsh(alias_tag = 4, alias_tag = 5)]
+    Burn,
+}
+
+#[test]
+fn test_alias_tag_decodes() {
+    let transfer = Instruction::Transfer {
+        to: "alice".to_string(),
+        amount: 5,
+    };
+    let mut bytes = transfer.try_to_vec().unwrap();
+    assert_eq!(bytes[0], 1);
+    assert_eq!(Instruction::try_from_slice(&bytes).unwrap(), transfer);
+    bytes[0] = 3;
+    assert_eq!(Instruction::try_from_slice(&bytes).unwrap(), transfer);
+    assert_eq!(Instruction::deserialize(&mut &bytes[..]).unwrap(), transfer);
+    assert_eq!(
+        Instruction::try_from_slice(&[4]).unwrap(),
+        Instruction::Burn
+    );
+    assert_eq!(
+        Instruction::try_from_slice(&[5]).unwrap(),
+        Instruction::Burn
+    );
+}
+
+#[test]
+fn test_alias_tag_encodes_primary_tag() {
+    assert_eq!(Instruction::Burn.try_to_vec().unwrap(), vec![2]);
+}
+
+#[test]
+fn test_unknown_tag_still_fails() {
+    let err = Instruction::try_from_slice(&[6]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "alloc-audit")]
+use std::collections::HashMap;
+
+use oasis_borsh::de::audit::{with_audit, Allocation};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+fn audit<T: BorshDeserialize>(bytes: &[u8]) -> Vec<Allocation> {
+    let mut reports = Vec::new();
+    let _ = with_audit(|a| reports.push(*a), || T::try_from_slice(bytes));
+    reports
+}
+
+#[test]
+fn test_reports_declared_lengths() {
+    let bytes = vec![vec![1u64, 2], vec![]].try_to_vec().unwrap();
+    let reports = audit::<Vec<Vec<u64>>>(&bytes);
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].declared, 2);
+    assert_eq!(reports[1].element, "u64");
+    assert_eq!(reports[1].reserved_bytes(), 16);
+}
+
+#[test]
+fn test_untrusted_lengths_are_capped() {
+    // A string that claims to be 4 GiB long.
+    let reports = audit::<String>(&[0xff, 0xff, 0xff, 0xff, b'a']);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].declared, u32::MAX);
+    assert!(reports[0].reserved_bytes() <= 4096);
+
+    let reports = audit::<HashMap<u32, u32>>(&[0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(reports[0].reserved, 0);
+}
+
+#[test]
+fn test_nesting_restores_outer_callback() {
+    let bytes = "ab".to_string().try_to_vec().unwrap();
+    let mut outer = 0;
+    with_audit(
+        |_| outer += 1,
+        || {
+            let mut inner = 0;
+            with_audit(|_| inner += 1, || String::try_from_slice(&bytes).unwrap());
+            assert_eq!(inner, 1);
+            String::try_from_slice(&bytes).unwrap();
+        },
+    );
+    assert_eq!(outer, 1);
+    assert!(audit::<String>(&bytes).len() == 1);
+}
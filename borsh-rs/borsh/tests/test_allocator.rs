@@ -0,0 +1,97 @@
+#![cfg(feature = "allocator-api")]
+#![feature(allocator_api)]
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use oasis_borsh::allocator::BorshDeserializeIn;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+/// Counts the bytes allocated through it, and fails allocations past `limit`.
+struct Arena {
+    allocated: Cell<usize>,
+    limit: usize,
+}
+
+impl Arena {
+    fn new(limit: usize) -> Self {
+        Arena {
+            allocated: Cell::new(0),
+            limit,
+        }
+    }
+}
+
+unsafe impl Allocator for Arena {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        if layout.size() > self.limit {
+            return Err(AllocError);
+        }
+        self.allocated.set(self.allocated.get() + layout.size());
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_vec_round_trip() {
+    let arena = Arena::new(1 << 20);
+    let values = (0..100u32).collect::<Vec<_>>();
+    let bytes = values.try_to_vec().unwrap();
+    let decoded = Vec::<u32, &Arena>::try_from_slice_in(&bytes, &&arena).unwrap();
+    assert_eq!(decoded, values);
+    assert!(arena.allocated.get() >= 400);
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+
+    let boxed = Box::<[u32], &Arena>::deserialize_in(&mut &bytes[..], &&arena).unwrap();
+    assert_eq!(&*boxed, &values[..]);
+    assert_eq!(boxed.try_to_vec().unwrap(), bytes);
+
+    let units = Vec::<(), &Arena>::try_from_slice_in(&[5, 0, 0, 0], &&arena).unwrap();
+    assert_eq!(units.len(), 5);
+}
+
+#[test]
+fn test_box() {
+    let arena = Arena::new(1 << 20);
+    let bytes = (7u64, "seven".to_string()).try_to_vec().unwrap();
+    let boxed = Box::<(u64, String), &Arena>::try_from_slice_in(&bytes, &&arena).unwrap();
+    assert_eq!(boxed.0, 7);
+    assert_eq!(boxed.try_to_vec().unwrap(), bytes);
+    assert_eq!(<(u64, String)>::try_from_slice(&bytes).unwrap().1, "seven");
+}
+
+#[test]
+fn test_errors() {
+    let arena = Arena::new(64);
+    let bytes = vec![0u64; 100].try_to_vec().unwrap();
+    let err = Vec::<u64, &Arena>::try_from_slice_in(&bytes, &&arena).unwrap_err();
+    assert!(matches!(Error::from(err), Error::AllocationFailed { .. }));
+
+    let mut bytes = vec![1u8].try_to_vec().unwrap();
+    bytes.push(0);
+    let err = Vec::<u8, Global>::try_from_slice_in(&bytes, &Global).unwrap_err();
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+}
+
+#[test]
+fn test_zero_sized_elements() {
+    static DECODED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl BorshDeserialize for Counted {
+        fn deserialize<R: std::io::Read>(_reader: &mut R) -> Result<Self, std::io::Error> {
+            DECODED.fetch_add(1, Ordering::SeqCst);
+            Ok(Counted)
+        }
+    }
+
+    let units = Vec::<Counted, Global>::try_from_slice_in(&[3, 0, 0, 0], &Global).unwrap();
+    assert_eq!(units.len(), 3);
+    assert_eq!(DECODED.load(Ordering::SeqCst), 3);
+}
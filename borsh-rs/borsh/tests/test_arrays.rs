@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Key {
+    compressed: [u8; 33],
+    limbs: [u64; 128],
+}
+
+#[test]
+fn test_arbitrary_lengths() {
+    let key = Key {
+        compressed: [7; 33],
+        limbs: [u64::MAX; 128],
+    };
+    let bytes = key.try_to_vec().unwrap();
+    assert_eq!(bytes.len(), 33 + 128 * 8);
+    assert_eq!(key.serialized_size(), bytes.len());
+    assert_eq!(key.size_hint(), bytes.len());
+    assert_eq!(Key::try_from_slice(&bytes).unwrap(), key);
+    assert_eq!(Key::deserialize(&mut &bytes[..]).unwrap(), key);
+}
+
+#[test]
+fn test_non_copy_elements() {
+    let names = [String::from("a"), String::from("bc"), String::new()];
+    let bytes = names.try_to_vec().unwrap();
+    assert_eq!(<[String; 3]>::try_from_slice(&bytes).unwrap(), names);
+
+    let empty: [String; 0] = [];
+    assert!(empty.try_to_vec().unwrap().is_empty());
+    assert_eq!(<[String; 0]>::try_from_slice(&[]).unwrap(), empty);
+}
+
+#[test]
+fn test_truncated() {
+    let bytes = [1u16; 100].try_to_vec().unwrap();
+    let err = <[u16; 100]>::try_from_slice(&bytes[..199]).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("at offset 198: error deserializing [99]:"));
+}
+
+#[derive(BorshDeserialize)]
+struct Counted(u8);
+
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_partial_array_is_dropped() {
+    // The elements decoded before the failure are dropped, exactly once each.
+    assert!(<[Counted; 4]>::try_from_slice(&[1, 2, 3]).is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+    let counted = <[Counted; 4]>::try_from_slice(&[1, 2, 3, 4]).unwrap();
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+    drop(counted);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 7);
+}
+
+#[test]
+fn test_byte_arrays() {
+    let bytes: Vec<u8> = (0..40).collect();
+    let mut buf = &bytes[..];
+    let head = <[u8; 32]>::deserialize_slice(&mut buf).unwrap();
+    assert_eq!(head[..], bytes[..32]);
+    assert_eq!(buf, &bytes[32..]);
+    assert_eq!(<[u8; 32]>::deserialize(&mut &bytes[..]).unwrap(), head);
+    assert!(<[u8; 41]>::deserialize_slice(&mut &bytes[..]).is_err());
+}
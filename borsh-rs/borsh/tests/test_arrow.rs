@@ -0,0 +1,115 @@
+#![cfg(feature = "arrow")]
+use arrow_schema::DataType;
+use oasis_borsh::schema::arrow::{from_record_batch, to_arrow_schema, to_record_batch};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug, Clone)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug, Clone)]
+struct Event {
+    id: u64,
+    name: String,
+    amount: u128,
+    payload: Vec<u8>,
+    hash: [u8; 4],
+    path: Vec<Point>,
+    parent: Option<Point>,
+    note: Option<String>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+enum Kind {
+    A,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+struct WithEnum {
+    kind: Kind,
+}
+
+fn events() -> Vec<Event> {
+    vec![
+        Event {
+            id: 1,
+            name: "first".to_string(),
+            amount: u128::MAX,
+            payload: vec![1, 2, 3],
+            hash: [0; 4],
+            path: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+            parent: None,
+            note: Some("hello".to_string()),
+        },
+        Event {
+            id: 2,
+            name: "second".to_string(),
+            amount: 5,
+            payload: vec![],
+            hash: [9; 4],
+            path: vec![],
+            parent: Some(Point { x: -1, y: -2 }),
+            note: None,
+        },
+    ]
+}
+
+#[test]
+fn test_arrow_schema() {
+    let schema = to_arrow_schema(&Event::schema_container()).unwrap();
+    let types = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().as_str(), f.data_type().clone(), f.is_nullable()))
+        .collect::<Vec<_>>();
+    assert_eq!(types[0], ("id", DataType::UInt64, false));
+    assert_eq!(types[2], ("amount", DataType::FixedSizeBinary(16), false));
+    assert_eq!(types[3], ("payload", DataType::Binary, false));
+    assert_eq!(types[4], ("hash", DataType::FixedSizeBinary(4), false));
+    assert!(matches!(types[5].1, DataType::List(_)));
+    assert!(matches!(types[6].1, DataType::Struct(_)));
+    assert!(types[6].2);
+    assert_eq!(types[7], ("note", DataType::Utf8, true));
+}
+
+#[test]
+fn test_record_batch_roundtrip() {
+    let events = events();
+    let batch = to_record_batch(&events).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.num_columns(), 8);
+    assert_eq!(from_record_batch::<Event>(&batch).unwrap(), events);
+}
+
+#[test]
+fn test_enums_rejected() {
+    let err = to_arrow_schema(&WithEnum::schema_container()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Type Kind cannot be converted to Arrow: enums"
+    );
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Node {
+    value: u32,
+    next: Option<Box<Node>>,
+}
+
+#[test]
+fn test_recursive_rejected() {
+    let err = to_arrow_schema(&Node::schema_container()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Type Node cannot be converted to Arrow: recursive types"
+    );
+}
+
+#[test]
+fn test_mismatched_batch() {
+    let batch = to_record_batch(&[Point { x: 1, y: 2 }]).unwrap();
+    assert!(from_record_batch::<Event>(&batch).is_err());
+}
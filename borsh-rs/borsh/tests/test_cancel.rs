@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use oasis_borsh::de::cancel::CancelToken;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+/// A reader that announces four billion `u64`s and then produces zeros forever, slowly.
+struct Endless {
+    header: &'static [u8],
+}
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if !self.header.is_empty() {
+            return self.header.read(buf);
+        }
+        thread::sleep(Duration::from_micros(10));
+        let len = buf.len().min(64);
+        buf[..len].iter_mut().for_each(|b| *b = 0);
+        Ok(len)
+    }
+}
+
+#[test]
+fn test_cancelled_token_stops_collections() {
+    let token = CancelToken::new();
+    token.cancel();
+    let bytes = vec![1u32, 2, 3].try_to_vec().unwrap();
+    let err = token
+        .run(|| Vec::<u32>::try_from_slice(&bytes))
+        .unwrap_err();
+    assert!(matches!(Error::from(err).root_cause(), Error::Cancelled));
+    let map = BTreeMap::<u8, String>::new().try_to_vec().unwrap();
+    // Empty collections have no elements to check before.
+    assert!(token
+        .run(|| BTreeMap::<u8, String>::try_from_slice(&map))
+        .is_ok());
+    // Outside `run` the token has no effect.
+    assert_eq!(Vec::<u32>::try_from_slice(&bytes).unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let token = CancelToken::new();
+    let canceller = token.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        canceller.cancel();
+    });
+    let mut reader = Endless {
+        header: &[0xff, 0xff, 0xff, 0xff],
+    };
+    let err = token
+        .run(|| Vec::<u64>::deserialize(&mut reader))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    assert!(err.to_string().contains("Decoding was cancelled"));
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_deadline() {
+    let token = CancelToken::with_timeout(Duration::from_millis(20));
+    assert!(!token.is_cancelled());
+    let started = Instant::now();
+    let mut reader = Endless {
+        header: &[0xff, 0xff, 0xff, 0xff],
+    };
+    assert!(token.run(|| String::deserialize(&mut reader)).is_err());
+    assert!(token.is_cancelled());
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_nested_tokens() {
+    let outer = CancelToken::new();
+    outer.cancel();
+    let bytes = vec![vec![1u8]].try_to_vec().unwrap();
+    let inner = CancelToken::new();
+    assert!(outer
+        .run(|| inner.run(|| Vec::<Vec<u8>>::try_from_slice(&bytes)))
+        .is_ok());
+    assert!(outer
+        .run(|| Vec::<Vec<u8>>::try_from_slice(&bytes))
+        .is_err());
+}
@@ -0,0 +1,83 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::canonical_float::{CanonicalF32, CanonicalF64, NonCanonicalFloat};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Hash, Debug)]
+struct Price {
+    amount: CanonicalF64,
+    weight: CanonicalF32,
+}
+
+#[test]
+fn test_new_rejects() {
+    assert_eq!(CanonicalF64::new(f64::NAN), Err(NonCanonicalFloat::NaN));
+    assert_eq!(
+        CanonicalF64::new(-0.0),
+        Err(NonCanonicalFloat::NegativeZero)
+    );
+    assert_eq!(
+        CanonicalF64::new(f64::MIN_POSITIVE / 2.0),
+        Err(NonCanonicalFloat::Subnormal)
+    );
+    assert_eq!(
+        CanonicalF32::new(-f32::MIN_POSITIVE / 4.0),
+        Err(NonCanonicalFloat::Subnormal)
+    );
+    assert_eq!(CanonicalF64::new(1.5).unwrap().get(), 1.5);
+    assert_eq!(
+        CanonicalF64::new(f64::INFINITY).unwrap().get(),
+        f64::INFINITY
+    );
+}
+
+#[test]
+fn test_normalized() {
+    for &value in &[-0.0, f64::MIN_POSITIVE / 2.0, -f64::MIN_POSITIVE / 2.0] {
+        let canonical = CanonicalF64::normalized(value).unwrap();
+        assert_eq!(canonical.get().to_bits(), 0);
+    }
+    assert_eq!(CanonicalF32::normalized(-2.0).unwrap().get(), -2.0);
+    assert_eq!(
+        CanonicalF32::normalized(f32::NAN),
+        Err(NonCanonicalFloat::NaN)
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let price = Price {
+        amount: CanonicalF64::new(12.25).unwrap(),
+        weight: CanonicalF32::normalized(-0.0).unwrap(),
+    };
+    let bytes = price.try_to_vec().unwrap();
+    assert_eq!(bytes, (12.25f64, 0.0f32).try_to_vec().unwrap());
+    assert_eq!(Price::try_from_slice(&bytes).unwrap(), price);
+    assert_eq!(Price::deserialize(&mut &bytes[..]).unwrap(), price);
+}
+
+#[test]
+fn test_decode_rejects() {
+    for &value in &[-0.0f64, f64::MIN_POSITIVE / 2.0] {
+        let bytes = value.try_to_vec().unwrap();
+        let err = CanonicalF64::try_from_slice(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = CanonicalF64::deserialize(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+    let bytes = (-0.0f32).try_to_vec().unwrap();
+    let err = CanonicalF32::try_from_slice(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "at offset 0: float is negative zero");
+    assert!(CanonicalF32::try_from_slice(&f32::NAN.to_bits().to_le_bytes()).is_err());
+}
+
+#[test]
+fn test_ordering() {
+    let mut values = [3.0, -1.0, 0.0, f64::NEG_INFINITY]
+        .iter()
+        .map(|&value| CanonicalF64::new(value).unwrap())
+        .collect::<Vec<_>>();
+    values.sort();
+    let values = values.into_iter().map(f64::from).collect::<Vec<_>>();
+    assert_eq!(values, [f64::NEG_INFINITY, -1.0, 0.0, 3.0]);
+}
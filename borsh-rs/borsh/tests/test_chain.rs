@@ -0,0 +1,113 @@
+#![cfg(feature = "bytes")]
+use bytes::Bytes;
+use std::io::{self, IoSlice, Write};
+
+use oasis_borsh::chain::{to_chain, write_vectored, SEGMENT_SIZE, SHARE_THRESHOLD};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Envelope {
+    id: u64,
+    attachment: Bytes,
+    small: Bytes,
+    trailer: String,
+}
+
+fn envelope(len: usize) -> Envelope {
+    Envelope {
+        id: 1,
+        attachment: Bytes::from(vec![7u8; len]),
+        small: Bytes::from_static(b"abc"),
+        trailer: "end".to_string(),
+    }
+}
+
+#[test]
+fn test_chain_concatenates_to_vec() {
+    let value = envelope(3 * SEGMENT_SIZE);
+    let chain = to_chain(&value).unwrap();
+    let joined: Vec<u8> = chain
+        .iter()
+        .flat_map(|segment| segment.iter().copied())
+        .collect();
+    assert_eq!(joined, value.try_to_vec().unwrap());
+    assert_eq!(Envelope::try_from_slice(&joined).unwrap(), value);
+}
+
+#[test]
+fn test_large_fields_are_shared() {
+    let value = envelope(SHARE_THRESHOLD);
+    let chain = to_chain(&value).unwrap();
+    if cfg!(feature = "dyn-io") {
+        // Derived impls hide the writer of the chain behind `dyn Write`, so fields are copied.
+        assert_eq!(chain.len(), 1);
+    } else {
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[1].as_ptr(), value.attachment.as_ptr());
+    }
+
+    let value = envelope(SHARE_THRESHOLD - 1);
+    assert_eq!(to_chain(&value).unwrap().len(), 1);
+}
+
+#[test]
+fn test_small_writes_are_segmented() {
+    let value = vec![1u8; 2 * SEGMENT_SIZE + 10];
+    let chain = to_chain(&value).unwrap();
+    assert!(chain.iter().all(|segment| segment.len() <= SEGMENT_SIZE));
+    assert_eq!(
+        chain.iter().map(|s| s.len()).sum::<usize>(),
+        value.len() + 4
+    );
+}
+
+/// Accepts at most `limit` bytes per call, counting vectored calls.
+struct Gathering {
+    written: Vec<u8>,
+    limit: usize,
+    vectored: usize,
+}
+
+impl Write for Gathering {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.vectored += 1;
+        let mut written = 0;
+        for buf in bufs {
+            let len = buf.len().min(self.limit - written);
+            self.written.extend_from_slice(&buf[..len]);
+            written += len;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_write_vectored() {
+    let value = envelope(SHARE_THRESHOLD);
+    let mut writer = Gathering {
+        written: Vec::new(),
+        limit: usize::MAX,
+        vectored: 0,
+    };
+    write_vectored(&mut writer, &value).unwrap();
+    assert_eq!(writer.written, value.try_to_vec().unwrap());
+    assert_eq!(writer.vectored, 1);
+
+    // Partial writes resume where they stopped.
+    let mut writer = Gathering {
+        written: Vec::new(),
+        limit: 1000,
+        vectored: 0,
+    };
+    write_vectored(&mut writer, &value).unwrap();
+    assert_eq!(writer.written, value.try_to_vec().unwrap());
+    assert_eq!(writer.vectored, writer.written.len().div_ceil(1000));
+}
@@ -0,0 +1,48 @@
+use oasis_borsh::chunks::{from_chunks, ChunkReader};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Packet {
+    id: u64,
+    payload: Vec<u8>,
+    note: String,
+}
+
+fn packet() -> Packet {
+    Packet {
+        id: 0x0102_0304_0506_0708,
+        payload: vec![9; 10],
+        note: "fragmented".to_string(),
+    }
+}
+
+#[test]
+fn test_every_split() {
+    let bytes = packet().try_to_vec().unwrap();
+    for first in 0..=bytes.len() {
+        for second in first..=bytes.len() {
+            let chunks = [&bytes[..first], &bytes[first..second], &bytes[second..]];
+            assert_eq!(
+                from_chunks::<Packet, _>(chunks.iter().copied()).unwrap(),
+                packet()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_ring_buffer_halves() {
+    let bytes = packet().try_to_vec().unwrap();
+    let (head, tail) = bytes.split_at(5);
+    let mut reader = ChunkReader::new(vec![head, tail]);
+    assert_eq!(Packet::deserialize(&mut reader).unwrap(), packet());
+    assert!(reader.is_exhausted());
+}
+
+#[test]
+fn test_errors() {
+    let bytes = packet().try_to_vec().unwrap();
+    let (head, tail) = bytes.split_at(20);
+    assert!(from_chunks::<Packet, _>(vec![head]).is_err());
+    assert!(from_chunks::<Packet, _>(vec![head, tail, &[0u8][..]]).is_err());
+}
@@ -0,0 +1,104 @@
+use oasis_borsh::columnar::Columns;
+use oasis_borsh::{BorshColumnar, BorshDeserialize, BorshSerialize};
+
+#[derive(BorshColumnar, BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+struct Reading {
+    sensor: u16,
+    #[borsh_skip]
+    cached: Option<u64>,
+    #[borsh(len = "u8")]
+    label: String,
+    value: f32,
+}
+
+#[derive(BorshColumnar, PartialEq, Debug)]
+struct Pair<T>(T, u8);
+
+#[derive(BorshColumnar, PartialEq, Debug)]
+struct Reordered {
+    #[borsh(order = 1)]
+    a: u8,
+    #[borsh(order = 0)]
+    b: u8,
+}
+
+fn readings() -> Vec<Reading> {
+    (0..3)
+        .map(|i| Reading {
+            sensor: i,
+            cached: None,
+            label: format!("s{}", i),
+            value: f32::from(i) / 2.0,
+        })
+        .collect()
+}
+
+#[test]
+fn test_layout() {
+    let columns = Columns::from(readings());
+    let bytes = columns.try_to_vec().unwrap();
+    let mut expected = 3u32.try_to_vec().unwrap();
+    for reading in readings() {
+        reading.sensor.serialize(&mut expected).unwrap();
+    }
+    for reading in readings() {
+        expected.push(reading.label.len() as u8);
+        expected.extend_from_slice(reading.label.as_bytes());
+    }
+    for reading in readings() {
+        reading.value.serialize(&mut expected).unwrap();
+    }
+    assert_eq!(bytes, expected);
+    // The same rows as a `Vec`, one after the other, take as many bytes.
+    assert_eq!(readings().try_to_vec().unwrap().len(), bytes.len());
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut columns: Columns<Reading> = readings().into_iter().collect();
+    columns[0].cached = Some(1);
+    let bytes = columns.try_to_vec().unwrap();
+    let decoded = Columns::<Reading>::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded[0].cached, None);
+    assert_eq!(decoded.into_inner(), readings());
+
+    let empty = Columns::<Reading>::new();
+    let bytes = empty.try_to_vec().unwrap();
+    assert_eq!(bytes, [0, 0, 0, 0]);
+    assert!(Columns::<Reading>::try_from_slice(&bytes)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_generic_tuple_struct() {
+    let columns = Columns(vec![Pair(10u32, 1), Pair(20, 2)]);
+    let bytes = columns.try_to_vec().unwrap();
+    assert_eq!(bytes, [2, 0, 0, 0, 10, 0, 0, 0, 20, 0, 0, 0, 1, 2]);
+    assert_eq!(
+        Columns::<Pair<u32>>::try_from_slice(&bytes).unwrap(),
+        columns
+    );
+}
+
+#[test]
+fn test_wire_order() {
+    let columns = Columns(vec![Reordered { a: 1, b: 2 }, Reordered { a: 3, b: 4 }]);
+    let bytes = columns.try_to_vec().unwrap();
+    assert_eq!(bytes, [2, 0, 0, 0, 2, 4, 1, 3]);
+    assert_eq!(
+        Columns::<Reordered>::try_from_slice(&bytes).unwrap(),
+        columns
+    );
+}
+
+#[test]
+fn test_errors_name_the_column() {
+    let bytes = Columns::from(readings()).try_to_vec().unwrap();
+    let err = Columns::<Reading>::try_from_slice(&bytes[..12]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 0: error deserializing Reading.label[0]: unexpected end of input \
+         reading u8: wanted 1 bytes, 0 available"
+    );
+}
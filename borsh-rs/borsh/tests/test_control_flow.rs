@@ -0,0 +1,78 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Bound, ControlFlow, RangeFull};
+use std::task::Poll;
+
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Mirror {
+    Continue(u32),
+    Break(String),
+}
+
+#[test]
+fn test_control_flow_matches_mirror_enum() {
+    let values: [ControlFlow<String, u32>; 2] = [
+        ControlFlow::Continue(7),
+        ControlFlow::Break("done".to_string()),
+    ];
+    let mirrors = [Mirror::Continue(7), Mirror::Break("done".to_string())];
+    for (value, mirror) in values.iter().zip(mirrors.iter()) {
+        let bytes = value.try_to_vec().unwrap();
+        assert_eq!(bytes, mirror.try_to_vec().unwrap());
+        assert_eq!(
+            &ControlFlow::<String, u32>::try_from_slice(&bytes).unwrap(),
+            value
+        );
+    }
+    assert!(ControlFlow::<String, u32>::try_from_slice(&[2, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn test_poll() {
+    for value in [Poll::Ready(5u8), Poll::Pending] {
+        let bytes = value.try_to_vec().unwrap();
+        assert_eq!(Poll::<u8>::try_from_slice(&bytes).unwrap(), value);
+    }
+    assert_eq!(Poll::<u8>::Pending.try_to_vec().unwrap(), vec![1]);
+    assert_eq!(<Poll<u8>>::declaration(), "Poll<u8>");
+    assert_eq!(<ControlFlow<u8, ()>>::declaration(), "ControlFlow<u8, nil>");
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Query {
+    order: Ordering,
+    start: Bound<u32>,
+    end: Bound<u32>,
+    all: RangeFull,
+    marker: PhantomData<String>,
+}
+
+#[test]
+fn test_small_std_types() {
+    let orderings = [Ordering::Less, Ordering::Equal, Ordering::Greater];
+    for (idx, ordering) in orderings.iter().enumerate() {
+        assert_eq!(ordering.try_to_vec().unwrap(), vec![idx as u8]);
+        assert_eq!(Ordering::try_from_slice(&[idx as u8]).unwrap(), *ordering);
+    }
+    assert!(Ordering::try_from_slice(&[3]).is_err());
+
+    let query = Query {
+        order: Ordering::Greater,
+        start: Bound::Included(3),
+        end: Bound::Unbounded,
+        all: ..,
+        marker: PhantomData,
+    };
+    let bytes = query.try_to_vec().unwrap();
+    assert_eq!(bytes, vec![2, 0, 3, 0, 0, 0, 2]);
+    assert_eq!(Query::try_from_slice(&bytes).unwrap(), query);
+    assert_eq!(Bound::Excluded(1u8).try_to_vec().unwrap(), vec![1, 1]);
+    assert!(Bound::<u8>::try_from_slice(&[3]).is_err());
+
+    assert_eq!(<Bound<u8>>::declaration(), "Bound<u8>");
+    assert_eq!(Ordering::declaration(), "Ordering");
+    assert_eq!(RangeFull::declaration(), "nil");
+    assert_eq!(<PhantomData<String>>::declaration(), "nil");
+}
@@ -3,7 +3,7 @@ use oasis_borsh::BorshDeserialize;
 #[derive(BorshDeserialize, Debug)]
 enum A {
     X,
-    Y
+    Y,
 }
 
 #[derive(BorshDeserialize, Debug)]
@@ -15,25 +15,34 @@ struct B {
 #[test]
 fn test_missing_bytes() {
     let bytes = vec![1, 0];
-    assert_eq!(B::try_from_slice(&bytes).unwrap_err().to_string(), "failed to fill whole buffer");
+    assert_eq!(B::try_from_slice(&bytes).unwrap_err().to_string(), "at offset 0: error deserializing B.x: unexpected end of input reading u64: wanted 8 bytes, 2 available");
 }
 
 #[test]
 fn test_invalid_enum_variant() {
     let bytes = vec![123];
-    assert_eq!(A::try_from_slice(&bytes).unwrap_err().to_string(), "Unexpected variant index: 123");
+    assert_eq!(
+        A::try_from_slice(&bytes).unwrap_err().to_string(),
+        "at offset 0: Unexpected variant index: 123"
+    );
 }
 
 #[test]
 fn test_extra_bytes() {
     let bytes = vec![1, 0, 0, 0, 32, 32];
-    assert_eq!(<Vec<u8>>::try_from_slice(&bytes).unwrap_err().to_string(), "Not all bytes read");
+    assert_eq!(
+        <Vec<u8>>::try_from_slice(&bytes).unwrap_err().to_string(),
+        "Not all bytes read"
+    );
 }
 
 #[test]
 fn test_invalid_bool() {
     let bytes = vec![255];
-    assert_eq!(<bool>::try_from_slice(&bytes).unwrap(), false);
+    assert_eq!(
+        <bool>::try_from_slice(&bytes).unwrap_err().to_string(),
+        "at offset 0: Invalid bool value: 255"
+    );
 }
 
 #[test]
@@ -45,30 +54,39 @@ fn test_invalid_option() {
 #[test]
 fn test_invalid_length() {
     let bytes = vec![255u8; 4];
-    assert_eq!(<Vec<u64>>::try_from_slice(&bytes).unwrap_err().to_string(), "failed to fill whole buffer");
+    assert_eq!(<Vec<u64>>::try_from_slice(&bytes).unwrap_err().to_string(), "at offset 4: error deserializing [0]: unexpected end of input reading u64: wanted 8 bytes, 0 available");
 }
 
 #[test]
 fn test_invalid_length_string() {
     let bytes = vec![255u8; 4];
-    assert_eq!(String::try_from_slice(&bytes).unwrap_err().to_string(), "failed to fill whole buffer");
+    assert_eq!(
+        String::try_from_slice(&bytes).unwrap_err().to_string(),
+        "at offset 0: unexpected end of input reading string: wanted 4294967295 bytes, 0 available"
+    );
 }
 
 #[test]
 fn test_non_utf_string() {
     let bytes = vec![1, 0, 0, 0, 0xC0];
-    assert_eq!(String::try_from_slice(&bytes).unwrap_err().to_string(), "invalid utf-8 sequence of 1 bytes from index 0");
+    assert_eq!(
+        String::try_from_slice(&bytes).unwrap_err().to_string(),
+        "at offset 0: invalid utf-8 sequence of 1 bytes from index 0"
+    );
 }
 
 #[test]
 fn test_nan_float() {
     let bytes = vec![0, 0, 192, 127];
-    assert_eq!(f32::try_from_slice(&bytes).unwrap_err().to_string(), "For portability reasons we do not allow to deserialize NaNs.");
+    assert_eq!(
+        f32::try_from_slice(&bytes).unwrap_err().to_string(),
+        "at offset 0: For portability reasons we do not allow to deserialize NaNs."
+    );
 }
 
 #[test]
 fn test_evil_bytes() {
     // test takes a really long time if read() is used instead of read_exact()
     let bytes = vec![255, 255, 255, 255];
-    assert_eq!(<Vec<[u8;32]>>::try_from_slice(&bytes).unwrap_err().to_string(), "error deserializing element at index 0: failed to fill whole buffer");
+    assert_eq!(<Vec<[u8;32]>>::try_from_slice(&bytes).unwrap_err().to_string(), "at offset 4: error deserializing [0][0]: unexpected end of input reading u8: wanted 1 bytes, 0 available");
 }
@@ -0,0 +1,131 @@
+use std::io::{Cursor, ErrorKind, Read};
+
+use oasis_borsh::de::decode_all;
+use oasis_borsh::ser::encode_all;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Sample {
+    sensor: u16,
+    label: String,
+}
+
+fn samples() -> Vec<Sample> {
+    (0..5)
+        .map(|sensor| Sample {
+            sensor,
+            label: "x".repeat(sensor as usize),
+        })
+        .collect()
+}
+
+fn encode(samples: &[Sample]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for sample in samples {
+        sample.serialize(&mut bytes).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn test_decode_all() {
+    let bytes = encode(&samples());
+    let decoded = decode_all::<Sample, _>(Cursor::new(&bytes))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, samples());
+    assert_eq!(decode_all::<Sample, _>(&[][..]).count(), 0);
+
+    let mut values = decode_all::<Sample, _>(&bytes[..]);
+    values.next().unwrap().unwrap();
+    assert_eq!(values.position(), 6);
+    assert_eq!(values.into_inner(), &bytes[6..]);
+}
+
+#[test]
+fn test_truncated() {
+    let bytes = encode(&samples());
+    let mut values = decode_all::<Sample, _>(&bytes[..bytes.len() - 1]);
+    for _ in 0..4 {
+        values.next().unwrap().unwrap();
+    }
+    let start = values.position();
+    let err = values.next().unwrap().unwrap_err();
+    assert!(values.next().is_none());
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "at offset {}: error deserializing [4].label: unexpected end of input reading string: wanted 4 bytes, 3 available",
+            start
+        )
+    );
+    match Error::from(err) {
+        Error::At { offset, source } => {
+            assert_eq!(offset, start);
+            assert!(matches!(
+                source.root_cause(),
+                Error::Truncated { what: "string", .. }
+            ));
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_invalid_value() {
+    let mut bytes = encode(&samples()[..2]);
+    bytes.extend_from_slice(&[9, 0, 1, 0, 0, 0, 0xff]);
+    bytes.extend(encode(&samples()));
+    let results = decode_all::<Sample, _>(&bytes[..]).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    let err = results.into_iter().last().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err
+        .to_string()
+        .starts_with("at offset 13: error deserializing [2]"));
+}
+
+#[test]
+fn test_reader_errors() {
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk on fire"))
+        }
+    }
+
+    let err = decode_all::<Sample, _>(Broken).next().unwrap().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 0: error deserializing [0]: disk on fire"
+    );
+}
+
+#[test]
+fn test_encode_all_index() {
+    let samples = samples();
+    let mut bytes = vec![0xaa];
+    let mut index = vec![99];
+    let len = encode_all(&samples, &mut bytes, Some(&mut index)).unwrap();
+    assert_eq!(bytes[1..], encode(&samples)[..]);
+    assert_eq!(len, bytes.len() as u64 - 1);
+
+    // Offsets count from where the writer was, after what the index already held.
+    assert_eq!(index, [99, 0, 6, 13, 21, 30]);
+    for (i, sample) in samples.iter().enumerate().rev() {
+        let start = 1 + index[i + 1] as usize;
+        let (decoded, _) = Sample::try_from_slice_partial(&bytes[start..]).unwrap();
+        assert_eq!(&decoded, sample);
+    }
+
+    let decoded = decode_all::<Sample, _>(&bytes[1..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, samples);
+    assert_eq!(
+        encode_all(Vec::<Sample>::new(), &mut bytes, None).unwrap(),
+        0
+    );
+}
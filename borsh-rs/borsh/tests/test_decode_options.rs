@@ -0,0 +1,149 @@
+use std::panic::catch_unwind;
+use std::sync::Arc;
+
+use oasis_borsh::de::options::{DecodeOptions, NanPolicy, Profile, DEFAULT_MAX_PREALLOCATION};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+fn capacity(options: &DecodeOptions, bytes: &[u8]) -> usize {
+    options
+        .try_from_slice::<Vec<u64>>(bytes)
+        .unwrap()
+        .capacity()
+}
+
+#[test]
+fn test_max_preallocation() {
+    let bytes = vec![1u64; 3000].try_to_vec().unwrap();
+    assert_eq!(capacity(&DecodeOptions::trusted(), &bytes), 3000);
+    // 1024 elements of 8 bytes fit in 8 KiB, the rest is grown into.
+    let options = DecodeOptions::default().max_preallocation(8192);
+    let decoded: Vec<u64> = options.try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, vec![1u64; 3000]);
+    let decoded: Vec<u64> = DecodeOptions::default()
+        .max_preallocation(0)
+        .deserialize(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(decoded.len(), 3000);
+}
+
+#[test]
+fn test_untrusted_lengths_stay_bounded() {
+    // A vector that claims 4 billion elements but holds one.
+    let bytes = [0xff, 0xff, 0xff, 0xff, 1, 0, 0, 0, 0, 0, 0, 0];
+    assert!(<Vec<u64>>::try_from_slice(&bytes).is_err());
+    let small = [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+    assert!(
+        Vec::<u64>::try_from_slice(&small).unwrap().capacity() <= DEFAULT_MAX_PREALLOCATION / 8
+    );
+}
+
+#[test]
+fn test_options_are_scoped() {
+    let bytes = vec![0u64; 1000].try_to_vec().unwrap();
+    let trusted = DecodeOptions::trusted();
+    let limited = DecodeOptions::default().max_preallocation(80);
+    trusted.run(|| {
+        assert_eq!(<Vec<u64>>::try_from_slice(&bytes).unwrap().capacity(), 1000);
+        limited.run(|| {
+            assert_ne!(<Vec<u64>>::try_from_slice(&bytes).unwrap().capacity(), 1000);
+        });
+        assert_eq!(<Vec<u64>>::try_from_slice(&bytes).unwrap().capacity(), 1000);
+    });
+
+    let result = catch_unwind(|| trusted.run(|| panic!("decoder callback failed")));
+    assert!(result.is_err());
+    // 4096 bytes of the default hold 512 elements, short of the 1000 needed.
+    assert_ne!(<Vec<u64>>::try_from_slice(&bytes).unwrap().capacity(), 1000);
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Trade {
+    symbol: Arc<str>,
+    venue: Arc<str>,
+    size: u32,
+}
+
+#[test]
+fn test_intern_strings() {
+    let trades = (0..4)
+        .map(|size| Trade {
+            symbol: Arc::from(if size % 2 == 0 { "BTC" } else { "ETH" }),
+            venue: Arc::from("BTC"),
+            size,
+        })
+        .collect::<Vec<_>>();
+    let bytes = trades.try_to_vec().unwrap();
+
+    let decoded: Vec<Trade> = DecodeOptions::default()
+        .intern_strings(true)
+        .try_from_slice(&bytes)
+        .unwrap();
+    assert_eq!(decoded, trades);
+    assert!(Arc::ptr_eq(&decoded[0].symbol, &decoded[2].symbol));
+    assert!(Arc::ptr_eq(&decoded[0].symbol, &decoded[3].venue));
+    assert!(!Arc::ptr_eq(&decoded[0].symbol, &decoded[1].symbol));
+
+    // Off by default, and the reader path interns too.
+    let decoded = Vec::<Trade>::try_from_slice(&bytes).unwrap();
+    assert!(!Arc::ptr_eq(&decoded[0].symbol, &decoded[2].symbol));
+    let decoded: Vec<Trade> = DecodeOptions::default()
+        .intern_strings(true)
+        .deserialize(&mut &bytes[..])
+        .unwrap();
+    assert!(Arc::ptr_eq(&decoded[1].symbol, &decoded[3].symbol));
+}
+
+#[test]
+fn test_profiles() {
+    assert_eq!(
+        Profile::Trusted.options().max_preallocation,
+        DecodeOptions::trusted().max_preallocation
+    );
+    // `trusted` only lifts the preallocation limit.
+    assert_eq!(DecodeOptions::trusted().nan_policy, NanPolicy::Reject);
+    assert_eq!(
+        DecodeOptions::from(Profile::Consensus).max_preallocation,
+        DEFAULT_MAX_PREALLOCATION
+    );
+    assert!(!Profile::Consensus.options().intern_strings);
+
+    let bytes = vec![0u64; 1000].try_to_vec().unwrap();
+    let embedded = DecodeOptions::from(Profile::Embedded);
+    assert_ne!(capacity(&embedded, &bytes), 1000);
+    assert_eq!(capacity(&Profile::Trusted.options(), &bytes), 1000);
+
+    let trades = vec![Trade {
+        symbol: Arc::from("BTC"),
+        venue: Arc::from("BTC"),
+        size: 1,
+    }];
+    let decoded: Vec<Trade> = embedded
+        .try_from_slice(&trades.try_to_vec().unwrap())
+        .unwrap();
+    assert!(Arc::ptr_eq(&decoded[0].symbol, &decoded[0].venue));
+}
+
+#[test]
+fn test_strict_bools() {
+    let err = <Vec<bool>>::try_from_slice(&[3, 0, 0, 0, 1, 2, 0]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 5: error deserializing [1]: Invalid bool value: 2"
+    );
+    assert!(matches!(
+        oasis_borsh::Error::from(err).root_cause(),
+        oasis_borsh::Error::InvalidBool(2)
+    ));
+
+    let lenient = DecodeOptions::default().strict_bools(false);
+    let decoded: (bool, bool, bool) = lenient.try_from_slice(&[1, 2, 0]).unwrap();
+    assert_eq!(decoded, (true, false, false));
+    assert!(Profile::Lenient
+        .options()
+        .try_from_slice::<bool>(&[0xff])
+        .is_ok());
+    assert!(Profile::Consensus
+        .options()
+        .try_from_slice::<bool>(&[0xff])
+        .is_err());
+}
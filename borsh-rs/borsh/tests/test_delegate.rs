@@ -0,0 +1,78 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+/// A signature scheme, which is never encoded itself.
+#[derive(PartialEq, Debug)]
+struct Ed25519;
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_delegate]
+struct Signed<T, S> {
+    payload: T,
+    signature: [u8; 4],
+    #[borsh_skip]
+    scheme: PhantomData<S>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_delegate]
+struct Timestamped<T> {
+    at: u64,
+    #[borsh(len = "u8")]
+    values: Vec<T>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_delegate]
+enum Envelope<T, S> {
+    Plain(T),
+    Signed(Signed<T, S>),
+}
+
+fn roundtrip<T>(value: T)
+where
+    T: BorshSerialize + BorshDeserialize + PartialEq + Debug,
+{
+    let bytes = value.try_to_vec().unwrap();
+    assert_eq!(T::try_from_slice(&bytes).unwrap(), value);
+}
+
+/// Needs no bound on the scheme, which does not implement the borsh traits.
+fn sign<T: BorshSerialize>(payload: T) -> Signed<T, Ed25519> {
+    let signed = Signed {
+        payload,
+        signature: [0; 4],
+        scheme: PhantomData,
+    };
+    let len = signed.try_to_vec().unwrap().len();
+    Signed {
+        signature: [len as u8; 4],
+        ..signed
+    }
+}
+
+#[test]
+fn test_delegate() {
+    let signed = sign(Timestamped {
+        at: 5,
+        values: vec!["a".to_string()],
+    });
+    assert_eq!(signed.signature, [18; 4]);
+    roundtrip(signed);
+    roundtrip(Envelope::<_, Ed25519>::Plain(1u32));
+    roundtrip(Envelope::Signed(sign(vec![1u8, 2])));
+}
+
+#[test]
+fn test_same_encoding() {
+    // Bounds do not change the encoding: a delegating wrapper encodes like a tuple of its fields.
+    let timestamped = Timestamped {
+        at: 7,
+        values: vec![1u16, 2],
+    };
+    let mut expected = 7u64.try_to_vec().unwrap();
+    expected.extend_from_slice(&[2, 1, 0, 2, 0]);
+    assert_eq!(timestamped.try_to_vec().unwrap(), expected);
+}
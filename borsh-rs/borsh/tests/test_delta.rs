@@ -0,0 +1,74 @@
+use oasis_borsh::delta::{DeltaRleVec, DeltaVec};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[test]
+fn test_delta_roundtrip() {
+    let ids = DeltaVec(vec![0, 3, 3, 130, 1 << 40, u64::MAX]);
+    let encoded = ids.try_to_vec().unwrap();
+    assert_eq!(DeltaVec::try_from_slice(&encoded).unwrap(), ids);
+    assert_eq!(
+        DeltaVec(vec![5, 6, 300]).try_to_vec().unwrap(),
+        vec![3, 0, 0, 0, 5, 1, 0xa6, 0x02]
+    );
+}
+
+#[test]
+fn test_delta_is_compact() {
+    let ids: Vec<u64> = (1_000_000..1_001_000).collect();
+    let plain = ids.try_to_vec().unwrap();
+    let delta = DeltaVec(ids.clone()).try_to_vec().unwrap();
+    let rle = DeltaRleVec(ids).try_to_vec().unwrap();
+    assert!(delta.len() < plain.len() / 7);
+    assert_eq!(rle.len(), 4 + 3 + 1 + 1 + 2);
+}
+
+#[test]
+fn test_rle_roundtrip() {
+    let ids = DeltaRleVec(vec![10, 11, 12, 13, 20, 20, 30, 40, 50, 51]);
+    let encoded = ids.try_to_vec().unwrap();
+    assert_eq!(DeltaRleVec::try_from_slice(&encoded).unwrap(), ids);
+    assert_eq!(
+        DeltaRleVec::try_from_slice(&DeltaRleVec::new().try_to_vec().unwrap()).unwrap(),
+        DeltaRleVec::new()
+    );
+}
+
+#[test]
+fn test_unsorted() {
+    let err = DeltaVec(vec![2, 1]).try_to_vec().unwrap_err();
+    assert_eq!(err.to_string(), "Delta-encoded values must be sorted");
+    assert!(DeltaRleVec(vec![2, 1]).try_to_vec().is_err());
+}
+
+#[test]
+fn test_non_canonical() {
+    // Overlong varint for 5.
+    assert_eq!(
+        DeltaVec::try_from_slice(&[1, 0, 0, 0, 0x85, 0x00])
+            .unwrap_err()
+            .to_string(),
+        "at offset 0: Non-canonical varint encoding"
+    );
+    // Two adjacent runs with the same delta must be merged.
+    assert_eq!(
+        DeltaRleVec::try_from_slice(&[2, 0, 0, 0, 1, 1, 1, 1])
+            .unwrap_err()
+            .to_string(),
+        "at offset 0: Invalid delta run"
+    );
+    // Runs may not exceed the element count.
+    assert!(DeltaRleVec::try_from_slice(&[1, 0, 0, 0, 1, 2]).is_err());
+    // Overflow while accumulating.
+    assert!(DeltaVec::try_from_slice(&[
+        2, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 1
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_deref() {
+    let mut ids = DeltaVec::from(vec![1, 2]);
+    ids.push(3);
+    assert_eq!(ids.len(), 3);
+    assert_eq!(Vec::from(ids), vec![1, 2, 3]);
+}
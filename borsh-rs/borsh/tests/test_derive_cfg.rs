@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+/// Whether `T` implements `BorshSerialize`, decided by which `IMPLS` resolves.
+struct Probe<T>(PhantomData<T>);
+
+trait NotImplemented {
+    const IMPLS: bool = false;
+}
+
+impl<T> NotImplemented for Probe<T> {}
+
+impl<T: BorshSerialize> Probe<T> {
+    const IMPLS: bool = true;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+#[borsh(cfg(feature = "std"))]
+struct Enabled {
+    a: u32,
+    b: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+#[borsh(cfg(feature = "std"), cfg(not(any())))]
+enum EnabledEnum<T> {
+    A(T),
+    B { x: u8 },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema)]
+#[borsh(cfg(any()))]
+#[allow(dead_code)]
+struct Disabled {
+    // Only implements the traits if the impls are generated.
+    inner: NotBorsh,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+#[borsh(cfg(feature = "std"), cfg(not(feature = "std")))]
+#[allow(dead_code)]
+enum DisabledEnum {
+    A(NotBorsh),
+}
+
+#[allow(dead_code)]
+struct NotBorsh;
+
+const _: () = assert!(Probe::<Enabled>::IMPLS);
+const _: () = assert!(Probe::<EnabledEnum<u8>>::IMPLS);
+const _: () = assert!(!Probe::<Disabled>::IMPLS);
+const _: () = assert!(!Probe::<DisabledEnum>::IMPLS);
+
+#[test]
+fn test_enabled() {
+    let value = Enabled {
+        a: 1,
+        b: "x".to_string(),
+    };
+    let bytes = value.try_to_vec().unwrap();
+    assert_eq!(Enabled::try_from_slice(&bytes).unwrap(), value);
+    assert_eq!(Enabled::declaration(), "Enabled");
+
+    let value = EnabledEnum::A(7u16);
+    let bytes = value.try_to_vec().unwrap();
+    assert_eq!(EnabledEnum::try_from_slice(&bytes).unwrap(), value);
+}
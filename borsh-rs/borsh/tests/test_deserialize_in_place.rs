@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Default)]
+struct Message {
+    id: u64,
+    payload: Vec<u8>,
+    tags: Vec<String>,
+    attributes: HashMap<String, String>,
+    reply_to: Option<String>,
+    #[borsh_skip]
+    cached: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Default)]
+#[borsh_init(init)]
+struct Counted(Vec<u32>, #[borsh_skip] usize);
+
+impl Counted {
+    fn init(&mut self) {
+        self.1 = self.0.len();
+    }
+}
+
+fn message(id: u64, payload: &[u8], tags: &[&str]) -> Message {
+    Message {
+        id,
+        payload: payload.to_vec(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        attributes: vec![("key".to_string(), id.to_string())]
+            .into_iter()
+            .collect(),
+        reply_to: Some(format!("sender-{}", id)),
+        cached: 0,
+    }
+}
+
+#[test]
+fn test_overwrites_value() {
+    let mut place = message(1, &[1, 2, 3, 4], &["a", "b", "c"]);
+    place.cached = 7;
+    for expected in [
+        message(2, &[5], &["longer tag", "d"]),
+        message(3, &[6, 7, 8, 9, 10], &["e", "f", "g", "h"]),
+        Message::default(),
+    ] {
+        let bytes = expected.try_to_vec().unwrap();
+        Message::deserialize_in_place(&mut &bytes[..], &mut place).unwrap();
+        assert_eq!(place, expected);
+    }
+}
+
+#[test]
+fn test_reuses_allocations() {
+    let mut place = message(1, &[0; 64], &["a tag of some length"]);
+    let payload = place.payload.as_ptr();
+    let tag = place.tags[0].as_ptr();
+    let bytes = message(2, &[1; 32], &["short"]).try_to_vec().unwrap();
+    Message::deserialize_in_place(&mut &bytes[..], &mut place).unwrap();
+    assert_eq!(place.payload, vec![1; 32]);
+    assert_eq!(place.payload.as_ptr(), payload);
+    assert_eq!(place.tags[0].as_ptr(), tag);
+}
+
+#[test]
+fn test_std_types_in_place() {
+    let mut map = BTreeMap::new();
+    map.insert(1u8, "x".to_string());
+    let bytes = vec![(2u8, "y".to_string())]
+        .into_iter()
+        .collect::<BTreeMap<_, _>>()
+        .try_to_vec()
+        .unwrap();
+    BTreeMap::deserialize_in_place(&mut &bytes[..], &mut map).unwrap();
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        vec![(2, "y".to_string())]
+    );
+
+    let mut pair = ([0u16; 3], Some(Box::new(1u8)));
+    let bytes = ([1u16, 2, 3], None::<Box<u8>>).try_to_vec().unwrap();
+    BorshDeserialize::deserialize_in_place(&mut &bytes[..], &mut pair).unwrap();
+    assert_eq!(pair, ([1, 2, 3], None));
+}
+
+#[test]
+fn test_init_runs_after_in_place() {
+    let mut place = Counted(vec![1, 2, 3], 3);
+    let bytes = Counted(vec![4], 0).try_to_vec().unwrap();
+    Counted::deserialize_in_place(&mut &bytes[..], &mut place).unwrap();
+    assert_eq!(place, Counted(vec![4], 1));
+}
+
+#[test]
+fn test_errors_name_the_field() {
+    let mut place = message(1, &[], &["a"]);
+    let mut bytes = message(2, &[], &["b"]).try_to_vec().unwrap();
+    // Make the tag invalid UTF-8.
+    let position = bytes.iter().position(|&b| b == b'b').unwrap();
+    bytes[position] = 0xff;
+    let err = Message::deserialize_in_place(&mut &bytes[..], &mut place).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("error deserializing Message.tags[0]: "));
+}
@@ -0,0 +1,78 @@
+use oasis_borsh::de::deserialize_prefix;
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+enum Kind {
+    Query,
+    Reply,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_header(MessageHeader)]
+struct Message {
+    #[borsh(header)]
+    version: u8,
+    #[borsh(header, len = "u8")]
+    route: String,
+    #[borsh(header)]
+    kind: Kind,
+    #[borsh_skip]
+    received_at: u64,
+    body: Vec<u64>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_header(PairHeader)]
+struct Pair(#[borsh(header)] u16, Vec<u8>);
+
+#[test]
+fn test_named_header() {
+    let message = Message {
+        version: 2,
+        route: "a/b".to_string(),
+        kind: Kind::Reply,
+        received_at: 10,
+        body: vec![1, 2, 3],
+    };
+    let bytes = message.try_to_vec().unwrap();
+    let header = deserialize_prefix::<MessageHeader>(&bytes).unwrap();
+    assert_eq!(header.version, 2);
+    assert_eq!(header.route, "a/b");
+    assert_eq!(header.kind, Kind::Reply);
+
+    // The body is not looked at, so a truncated one does not matter.
+    let header = deserialize_prefix::<MessageHeader>(&bytes[..6]).unwrap();
+    assert_eq!(header.kind, Kind::Reply);
+    assert!(deserialize_prefix::<MessageHeader>(&bytes[..5]).is_err());
+}
+
+#[test]
+fn test_tuple_header() {
+    let bytes = Pair(7, vec![1]).try_to_vec().unwrap();
+    assert_eq!(deserialize_prefix::<PairHeader>(&bytes).unwrap().0, 7);
+}
+
+#[test]
+fn test_prefix_of_plain_types() {
+    let bytes = (5u32, "rest".to_string()).try_to_vec().unwrap();
+    assert_eq!(deserialize_prefix::<u32>(&bytes).unwrap(), 5);
+    assert!(u32::try_from_slice(&bytes).is_err());
+}
+
+#[test]
+fn test_partial() {
+    let mut bytes = Pair(7, vec![1, 2]).try_to_vec().unwrap();
+    let first = bytes.len();
+    bytes.extend(Pair(8, vec![]).try_to_vec().unwrap());
+    bytes.extend(3u8.try_to_vec().unwrap());
+
+    let (pair, used) = Pair::try_from_slice_partial(&bytes).unwrap();
+    assert_eq!((pair, used), (Pair(7, vec![1, 2]), first));
+    let (pair, second) = Pair::try_from_slice_partial(&bytes[used..]).unwrap();
+    assert_eq!((pair, second), (Pair(8, vec![]), 6));
+    assert_eq!(
+        u8::try_from_slice_partial(&bytes[used + second..]).unwrap(),
+        (3, 1)
+    );
+    assert!(Pair::try_from_slice_partial(&bytes[..first - 1]).is_err());
+}
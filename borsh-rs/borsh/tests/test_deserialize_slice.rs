@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Cursor;
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_init(init)]
+struct Block {
+    height: u64,
+    #[borsh(len = "u8")]
+    label: String,
+    transactions: Vec<Transaction>,
+    balances: BTreeMap<u128, i64>,
+    #[borsh_skip]
+    count: usize,
+}
+
+impl Block {
+    fn init(&mut self) {
+        self.count = self.transactions.len();
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Transaction {
+    Transfer { to: [u8; 32], amount: u128 },
+    Memo(String, Option<f32>),
+    Noop,
+}
+
+/// Only implements `deserialize`, so `deserialize_slice` falls back to it.
+#[derive(PartialEq, Debug)]
+struct Legacy(u16);
+
+impl BorshDeserialize for Legacy {
+    fn deserialize<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        Ok(Legacy(u16::deserialize(reader)?))
+    }
+}
+
+fn block() -> Block {
+    let mut balances = BTreeMap::new();
+    balances.insert(1, -5);
+    balances.insert(u128::MAX, 5);
+    Block {
+        height: 9,
+        label: "genesis".to_string(),
+        transactions: vec![
+            Transaction::Transfer {
+                to: [7; 32],
+                amount: 1 << 100,
+            },
+            Transaction::Memo("hi".to_string(), Some(0.5)),
+            Transaction::Noop,
+        ],
+        balances,
+        count: 3,
+    }
+}
+
+/// Decode `bytes` through a reader and through the slice, which must agree on values and errors.
+fn assert_same<T: BorshDeserialize + PartialEq + std::fmt::Debug>(bytes: &[u8]) {
+    let mut cursor = Cursor::new(bytes);
+    let from_reader = T::deserialize(&mut cursor);
+    let mut buf = bytes;
+    let from_slice = T::deserialize_slice(&mut buf);
+    match (from_reader, from_slice) {
+        (Ok(a), Ok(b)) => {
+            assert_eq!(a, b);
+            assert_eq!(cursor.position() as usize, bytes.len() - buf.len());
+        }
+        (Err(a), Err(b)) => {
+            assert_eq!(a.kind(), b.kind());
+            assert_eq!(a.to_string(), b.to_string());
+        }
+        (a, b) => panic!("reader gave {:?}, slice gave {:?}", a, b),
+    }
+}
+
+#[test]
+fn test_advances_past_value() {
+    let mut bytes = block().try_to_vec().unwrap();
+    let len = bytes.len();
+    bytes.extend_from_slice(&[1, 2]);
+    let mut buf = &bytes[..];
+    assert_eq!(Block::deserialize_slice(&mut buf).unwrap(), block());
+    assert_eq!(buf, &[1, 2]);
+    assert_eq!(Block::try_from_slice(&bytes[..len]).unwrap(), block());
+    assert_eq!(
+        Block::try_from_slice(&bytes).unwrap_err().to_string(),
+        "Not all bytes read"
+    );
+}
+
+#[test]
+fn test_same_as_reader() {
+    let bytes = block().try_to_vec().unwrap();
+    // Every truncation, so that each field runs out of input in turn.
+    for len in 0..=bytes.len() {
+        assert_same::<Block>(&bytes[..len]);
+    }
+    assert_same::<Transaction>(&[3]);
+    assert_same::<Transaction>(&[1, 0, 0, 0, 0, 1, 0, 0, 0xc0, 0x7f]);
+    assert_same::<String>(&[2, 0, 0, 0, 0xff, 0xfe]);
+    assert_same::<String>(&[0xff, 0xff, 0xff, 0xff, b'a']);
+    assert_same::<Box<[u8]>>(&[2, 0, 0, 0, 1, 2]);
+    assert_same::<Box<[u8]>>(&[3, 0, 0, 0, 1, 2]);
+    assert_same::<Vec<()>>(&[5, 0, 0, 0]);
+    assert_same::<[u16; 3]>(&[1, 0, 2, 0, 3]);
+    assert_same::<(bool, i8, Option<u32>)>(&[1, 0xff, 1, 4, 0, 0, 0]);
+    assert_same::<HashSet<u64>>(&vec![3u64, 1].try_to_vec().unwrap());
+    assert_same::<std::net::Ipv6Addr>(&[1; 16]);
+    assert_same::<Legacy>(&[1, 2]);
+}
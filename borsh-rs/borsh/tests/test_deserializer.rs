@@ -0,0 +1,99 @@
+use std::io;
+
+use oasis_borsh::de::Deserializer;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Header {
+    version: u8,
+    count: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Record {
+    Put(String, u64),
+    Delete(String),
+}
+
+fn message() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    Header {
+        version: 1,
+        count: 2,
+    }
+    .serialize(&mut bytes)
+    .unwrap();
+    Record::Put("a".to_string(), 7)
+        .serialize(&mut bytes)
+        .unwrap();
+    Record::Delete("b".to_string())
+        .serialize(&mut bytes)
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn test_heterogeneous_values() {
+    let bytes = message();
+    let mut de = Deserializer::new(&bytes[..]);
+    let header = de.next::<Header>().unwrap();
+    assert_eq!(header.count, 2);
+    assert_eq!(de.offset(), 5);
+    let records = (0..header.count)
+        .map(|_| de.next::<Record>())
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        records,
+        [
+            Record::Put("a".to_string(), 7),
+            Record::Delete("b".to_string())
+        ]
+    );
+    assert!(de.remaining().is_empty());
+    assert_eq!(de.offset(), bytes.len() as u64);
+    de.end().unwrap();
+}
+
+#[test]
+fn test_reports_what_is_left() {
+    let mut bytes = message();
+    bytes.extend_from_slice(b"tail");
+    let mut de = Deserializer::new(&bytes[..]);
+    de.next::<Header>().unwrap();
+    de.next::<Record>().unwrap();
+    de.next::<Record>().unwrap();
+    assert_eq!(de.remaining(), b"tail");
+    assert!(!de.is_empty().unwrap());
+    let err = de.end().unwrap_err();
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+}
+
+#[test]
+fn test_reader_input() {
+    let bytes = message();
+    let mut de = Deserializer::new(io::BufReader::with_capacity(3, &bytes[..]));
+    assert_eq!(de.next::<Header>().unwrap().version, 1);
+    assert!(!de.is_empty().unwrap());
+    assert_eq!(
+        de.next::<Record>().unwrap(),
+        Record::Put("a".to_string(), 7)
+    );
+    // The header's count is ignored here: a third record is missing.
+    de.next::<Record>().unwrap();
+    let err = de.next::<Record>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "at offset {}: unexpected end of input reading enum tag: wanted 1 bytes, 0 available",
+            bytes.len()
+        )
+    );
+    assert!(matches!(
+        Error::from(err).root_cause(),
+        Error::Truncated {
+            what: "enum tag",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,85 @@
+use oasis_borsh::diff::{self, Delta};
+use oasis_borsh::{BorshDeserialize, BorshDiff, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshDiff, Clone, PartialEq, Debug)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshDiff, Clone, PartialEq, Debug)]
+enum Status {
+    Idle,
+    Moving { speed: u16 },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshDiff, Clone, PartialEq, Debug)]
+struct Player {
+    name: String,
+    position: Position,
+    status: Status,
+    inventory: Vec<u32>,
+    #[borsh_skip]
+    cached: u64,
+}
+
+fn player() -> Player {
+    Player {
+        name: "alice".to_string(),
+        position: Position { x: 1, y: 2 },
+        status: Status::Idle,
+        inventory: vec![1, 2, 3],
+        cached: 0,
+    }
+}
+
+#[test]
+fn test_identical_values() {
+    let delta = diff::diff(&player(), &player()).unwrap();
+    assert!(delta.is_empty());
+    let mut target = player();
+    diff::apply(&mut target, &delta).unwrap();
+    assert_eq!(target, player());
+}
+
+#[test]
+fn test_nested_field_change() {
+    let old = player();
+    let mut new = player();
+    new.position.y = 5;
+    let delta = diff::diff(&old, &new).unwrap();
+    // Player bitmap, Position bitmap, then the new `y`.
+    assert_eq!(delta, Delta(vec![0b0010, 0b10, 5, 0, 0, 0]));
+
+    let mut target = old;
+    diff::apply(&mut target, &delta).unwrap();
+    assert_eq!(target, new);
+}
+
+#[test]
+fn test_multiple_changes() {
+    let old = player();
+    let mut new = player();
+    new.status = Status::Moving { speed: 3 };
+    new.inventory.push(4);
+    new.cached = 42;
+    let delta = diff::diff(&old, &new).unwrap();
+    assert!(delta.0.len() < new.try_to_vec().unwrap().len());
+
+    let mut target = old;
+    diff::apply(&mut target, &delta).unwrap();
+    new.cached = 0;
+    assert_eq!(target, new);
+
+    let encoded = delta.try_to_vec().unwrap();
+    assert_eq!(Delta::try_from_slice(&encoded).unwrap(), delta);
+}
+
+#[test]
+fn test_invalid_delta() {
+    let mut target = player();
+    let err = diff::apply(&mut target, &Delta(vec![0b10000])).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid delta field bitmap");
+    let err = diff::apply(&mut target, &Delta(vec![0b0001, 0, 0, 0, 0, 9])).unwrap_err();
+    assert_eq!(err.to_string(), "Not all bytes read");
+}
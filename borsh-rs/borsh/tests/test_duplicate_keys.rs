@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use oasis_borsh::de::options::{DecodeOptions, Profile};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Ledger {
+    #[borsh(len = "u8")]
+    balances: BTreeMap<u8, u64>,
+    #[borsh(len = "u8")]
+    frozen: BTreeSet<u8>,
+}
+
+fn strict() -> DecodeOptions {
+    DecodeOptions::default().reject_duplicate_keys(true)
+}
+
+fn is_duplicate_key(err: std::io::Error) -> bool {
+    matches!(Error::from(err).root_cause(), Error::DuplicateKey)
+}
+
+/// A map of two entries with the same key, as a peer could send it.
+fn repeated_entry() -> Vec<u8> {
+    let entries = vec![(1u8, 10u64), (1, 20)];
+    entries.try_to_vec().unwrap()
+}
+
+#[test]
+fn test_duplicates_are_kept_by_default() {
+    let bytes = repeated_entry();
+    let map = BTreeMap::<u8, u64>::try_from_slice(&bytes).unwrap();
+    assert_eq!(map.into_iter().collect::<Vec<_>>(), [(1, 20)]);
+    assert_eq!(HashMap::<u8, u64>::try_from_slice(&bytes).unwrap()[&1], 20);
+    let bytes = vec![3u8, 3].try_to_vec().unwrap();
+    assert_eq!(BTreeSet::<u8>::try_from_slice(&bytes).unwrap().len(), 1);
+}
+
+#[test]
+fn test_maps() {
+    let bytes = repeated_entry();
+    let err = strict()
+        .try_from_slice::<BTreeMap<u8, u64>>(&bytes)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "at offset 13: duplicate key in map or set");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(is_duplicate_key(
+        strict()
+            .try_from_slice::<HashMap<u8, u64>>(&bytes)
+            .unwrap_err()
+    ));
+
+    let mut place = HashMap::<u8, u64>::new();
+    let err = strict()
+        .run(|| HashMap::deserialize_in_place(&mut &bytes[..], &mut place))
+        .unwrap_err();
+    assert!(is_duplicate_key(err));
+
+    let distinct = vec![(1u8, 10u64), (2, 20)].try_to_vec().unwrap();
+    assert_eq!(
+        strict()
+            .try_from_slice::<BTreeMap<u8, u64>>(&distinct)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_sets() {
+    let bytes = vec![3u8, 5, 3].try_to_vec().unwrap();
+    assert!(is_duplicate_key(
+        strict().try_from_slice::<BTreeSet<u8>>(&bytes).unwrap_err()
+    ));
+    assert!(is_duplicate_key(
+        strict().try_from_slice::<HashSet<u8>>(&bytes).unwrap_err()
+    ));
+
+    let mut place = BTreeSet::<u8>::new();
+    let err = strict()
+        .run(|| BTreeSet::deserialize_in_place(&mut &bytes[..], &mut place))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error deserializing [2]: duplicate key in map or set"
+    );
+}
+
+#[test]
+fn test_custom_length_prefixes() {
+    let bytes = [
+        2, 7, 0, 0, 0, 0, 0, 0, 0, 0, 7, 1, 0, 0, 0, 0, 0, 0, 0, 1, 4,
+    ];
+    let ledger = Ledger::try_from_slice(&bytes).unwrap();
+    assert_eq!(ledger.balances.len(), 1);
+    assert!(is_duplicate_key(
+        strict().try_from_slice::<Ledger>(&bytes).unwrap_err()
+    ));
+
+    let bytes = [0, 2, 4, 4];
+    assert!(is_duplicate_key(
+        strict().try_from_slice::<Ledger>(&bytes).unwrap_err()
+    ));
+}
+
+#[test]
+fn test_consensus_profile() {
+    assert!(Profile::Consensus.options().reject_duplicate_keys);
+    assert!(!Profile::Lenient.options().reject_duplicate_keys);
+    let bytes = repeated_entry();
+    assert!(Profile::Consensus
+        .options()
+        .try_from_slice::<BTreeMap<u8, u64>>(&bytes)
+        .is_err());
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "dyn-io")]
+use std::io::{Cursor, Read, Write};
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Account<T> {
+    owner: String,
+    balance: u64,
+    #[borsh_skip]
+    cache: Option<T>,
+    history: Vec<Event>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Event {
+    Deposit(u64),
+    Withdraw { amount: u64, memo: String },
+}
+
+fn account() -> Account<u8> {
+    Account {
+        owner: "alice".to_string(),
+        balance: 10,
+        cache: None,
+        history: vec![
+            Event::Deposit(15),
+            Event::Withdraw {
+                amount: 5,
+                memo: "rent".to_string(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_forwards_to_dyn_helpers() {
+    let value = account();
+    let mut writer: &mut dyn Write = &mut Vec::new();
+    value.__borsh_serialize_dyn(&mut writer).unwrap();
+
+    let bytes = value.try_to_vec().unwrap();
+    let mut reader: &mut dyn Read = &mut &bytes[..];
+    let decoded = Account::<u8>::__borsh_deserialize_dyn(&mut reader).unwrap();
+    assert_eq!(decoded, value);
+
+    let mut reader: &mut dyn Read = &mut &[0, 7, 0, 0, 0, 0, 0, 0, 0][..];
+    assert_eq!(
+        Event::__borsh_deserialize_dyn(&mut reader).unwrap(),
+        Event::Deposit(7)
+    );
+}
+
+#[test]
+fn test_round_trip_through_readers_and_writers() {
+    let value = account();
+    let mut cursor = Cursor::new(Vec::new());
+    value.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert_eq!(bytes, value.try_to_vec().unwrap());
+    assert_eq!(value.serialized_size(), bytes.len());
+
+    let decoded = Account::<u8>::deserialize(&mut Cursor::new(&bytes)).unwrap();
+    assert_eq!(decoded, value);
+    let decoded = Account::<u8>::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, value);
+
+    let mut place = Account {
+        owner: String::new(),
+        balance: 0,
+        cache: Some(1),
+        history: Vec::new(),
+    };
+    Account::deserialize_in_place(&mut &bytes[..], &mut place).unwrap();
+    assert_eq!(place, value);
+
+    let err = Account::<u8>::try_from_slice(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+use std::collections::BTreeMap;
+
+use oasis_borsh::BorshSchema;
+
+#[derive(BorshSchema)]
+#[borsh(embed_schema)]
+struct Account {
+    owner: [u8; 32],
+    #[borsh_skip]
+    cached: u64,
+    #[borsh(len = "u16")]
+    memo: Vec<u8>,
+    balances: BTreeMap<String, Option<u128>>,
+}
+
+#[derive(BorshSchema)]
+#[borsh(embed_schema)]
+enum Instruction<T> {
+    Noop,
+    Transfer(T, std::string::String),
+    Close { account: Box<Account> },
+}
+
+#[derive(BorshSchema)]
+#[borsh(embed_schema)]
+struct Reordered(#[borsh(order = 1)] u8, #[borsh(order = 0)] (u16, Vec<u32>));
+
+#[derive(BorshSchema)]
+#[borsh(embed_schema, cfg(feature = "std"))]
+struct Unit;
+
+#[derive(BorshSchema)]
+#[borsh(embed_schema)]
+enum Never {}
+
+#[test]
+fn test_structs() {
+    assert_eq!(
+        Account::BORSH_SCHEMA,
+        "struct Account { owner: [u8; 32], memo: Prefixed<Vec<u8>, u16>, balances: BTreeMap<String, Option<u128>> }"
+    );
+    assert_eq!(
+        Reordered::BORSH_SCHEMA,
+        "struct Reordered((u16, Vec<u32>), u8)"
+    );
+    assert_eq!(Unit::BORSH_SCHEMA, "struct Unit");
+}
+
+#[test]
+fn test_enums() {
+    assert_eq!(
+        Instruction::<u8>::BORSH_SCHEMA,
+        "enum Instruction<T> { Noop, Transfer(T, std::string::String), Close { account: Box<Account> } }"
+    );
+    assert_eq!(Never::BORSH_SCHEMA, "enum Never {}");
+}
+
+#[test]
+fn test_schema_is_unchanged() {
+    let container = Account::schema_container();
+    assert_eq!(container.declaration, "Account");
+}
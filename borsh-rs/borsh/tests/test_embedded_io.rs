@@ -0,0 +1,78 @@
+#![cfg(feature = "embedded-io")]
+use oasis_borsh::embedded;
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct A {
+    x: u64,
+    y: String,
+}
+
+#[test]
+fn test_blocking_roundtrip() {
+    let a = A {
+        x: 1,
+        y: "uart".to_string(),
+    };
+    let mut out = Vec::new();
+    embedded::to_writer(&a, &mut out).unwrap();
+    assert_eq!(out, a.try_to_vec().unwrap());
+
+    out.push(0xff);
+    let mut input: &[u8] = &out;
+    let decoded: A = embedded::from_reader(&mut input).unwrap();
+    assert_eq!(decoded, a);
+    assert_eq!(input, &[0xff]);
+}
+
+#[test]
+fn test_blocking_eof() {
+    let mut input: &[u8] = &[1, 0, 0];
+    assert_eq!(
+        embedded::from_reader::<u64, _>(&mut input)
+            .unwrap_err()
+            .kind(),
+        std::io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[cfg(feature = "embedded-io-async")]
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    let mut fut = Box::pin(fut);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+#[test]
+fn test_async_frame_roundtrip() {
+    let a = A {
+        x: 7,
+        y: "spi".to_string(),
+    };
+    let mut out = Vec::new();
+    block_on(embedded::write_frame_async(&a, &mut out)).unwrap();
+    assert_eq!(out, a.try_to_vec().unwrap().try_to_vec().unwrap());
+
+    let mut input: &[u8] = &out;
+    let decoded: A = block_on(embedded::read_frame_async(&mut input)).unwrap();
+    assert_eq!(decoded, a);
+    assert!(input.is_empty());
+
+    // A blocking peer reads the frame as the encoded payload.
+    let mut input: &[u8] = &out;
+    let payload: Vec<u8> = embedded::from_reader(&mut input).unwrap();
+    assert_eq!(A::try_from_slice(&payload).unwrap(), a);
+}
+
+#[cfg(feature = "embedded-io-async")]
+#[test]
+fn test_async_truncated_frame() {
+    let mut input: &[u8] = &[255, 255, 255, 255, 1, 2, 3];
+    let err = block_on(embedded::read_frame_async::<A, _>(&mut input)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,127 @@
+use std::io::{Error, ErrorKind};
+
+use oasis_borsh::encrypted::{
+    from_slice_encrypted, to_vec_encrypted, Aead, EncryptedReader, EncryptedWriter,
+};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+/// A stand-in cipher with the shape of a real AEAD. It offers no security whatsoever.
+struct ToyAead(u8);
+
+impl ToyAead {
+    fn keystream(&self, nonce: &[u8], i: usize) -> u8 {
+        self.0 ^ nonce[i % nonce.len()] ^ i as u8
+    }
+
+    fn tag(&self, associated_data: &[u8], ciphertext: &[u8]) -> [u8; 2] {
+        let sum = associated_data
+            .iter()
+            .chain(ciphertext)
+            .fold(u16::from(self.0), |acc, b| {
+                acc.rotate_left(3) ^ u16::from(*b)
+            });
+        sum.to_le_bytes()
+    }
+}
+
+impl Aead for ToyAead {
+    const NONCE_LEN: usize = 4;
+    const TAG_LEN: usize = 2;
+
+    fn seal(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte ^= self.keystream(nonce, i);
+        }
+        let tag = self.tag(associated_data, buffer);
+        buffer.extend_from_slice(&tag);
+        Ok(())
+    }
+
+    fn open(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let (ciphertext, tag) = buffer.split_at(buffer.len() - 2);
+        if self.tag(associated_data, ciphertext) != tag {
+            return Err(Error::new(ErrorKind::InvalidData, "Tag mismatch"));
+        }
+        buffer.truncate(buffer.len() - 2);
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte ^= self.keystream(nonce, i);
+        }
+        Ok(())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Credentials {
+    user: String,
+    token: [u8; 8],
+}
+
+fn credentials() -> Credentials {
+    Credentials {
+        user: "alice".to_string(),
+        token: [9; 8],
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let aead = ToyAead(0x5a);
+    let frame = to_vec_encrypted(&credentials(), &aead, &[1, 2, 3, 4]).unwrap();
+    let plaintext = credentials().try_to_vec().unwrap();
+    assert_eq!(frame.len(), 4 + 4 + plaintext.len() + 2);
+    assert_eq!(&frame[..4], &[1, 2, 3, 4]);
+    assert_eq!(&frame[4..8], &(plaintext.len() as u32 + 2).to_le_bytes());
+    assert_ne!(&frame[8..8 + plaintext.len()], &plaintext[..]);
+    assert_eq!(
+        from_slice_encrypted::<Credentials, _>(&frame, &aead).unwrap(),
+        credentials()
+    );
+}
+
+#[test]
+fn test_rejects_tampering() {
+    let aead = ToyAead(0x5a);
+    let frame = to_vec_encrypted(&credentials(), &aead, &[1, 2, 3, 4]).unwrap();
+    for i in 0..frame.len() {
+        let mut tampered = frame.clone();
+        tampered[i] ^= 1;
+        assert!(from_slice_encrypted::<Credentials, _>(&tampered, &aead).is_err());
+    }
+    assert!(from_slice_encrypted::<Credentials, _>(&frame[..frame.len() - 1], &aead).is_err());
+
+    let mut trailing = frame;
+    trailing.push(0);
+    assert!(from_slice_encrypted::<Credentials, _>(&trailing, &aead).is_err());
+
+    let err = to_vec_encrypted(&credentials(), &aead, &[1, 2, 3]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_stream_of_frames() {
+    let aead = ToyAead(7);
+    let mut out = Vec::new();
+    for (i, value) in [1u64, 2, 3].iter().enumerate() {
+        let mut writer = EncryptedWriter::new(&mut out, &aead, &[i as u8; 4]).unwrap();
+        value.serialize(&mut writer).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let mut input = &out[..];
+    for expected in &[1u64, 2, 3] {
+        let mut reader = EncryptedReader::new(&mut input, &aead).unwrap();
+        assert_eq!(u64::deserialize(&mut reader).unwrap(), *expected);
+        assert!(reader.is_exhausted());
+    }
+    assert!(input.is_empty());
+}
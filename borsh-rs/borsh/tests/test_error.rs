@@ -0,0 +1,180 @@
+use std::io;
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+enum Message {
+    Ping,
+    Text(String),
+}
+
+/// The cause of failing to decode `bytes`, without the offset at which decoding stopped.
+fn decode<T: BorshDeserialize + std::fmt::Debug>(bytes: &[u8]) -> Error {
+    match Error::from(T::try_from_slice(bytes).unwrap_err()) {
+        Error::At { source, .. } => *source,
+        err => err,
+    }
+}
+
+#[test]
+fn test_classified() {
+    assert!(matches!(
+        decode::<u64>(&[1, 2, 3]),
+        Error::Truncated {
+            what: "u64",
+            wanted: 8,
+            available: 3
+        }
+    ));
+    assert!(matches!(
+        decode::<String>(&[1, 0, 0, 0, 0xff]),
+        Error::InvalidUtf8(_)
+    ));
+    assert!(matches!(
+        decode::<std::ops::ControlFlow<u8, u8>>(&[2]),
+        Error::InvalidTag(2)
+    ));
+    assert!(matches!(decode::<Message>(&[7]), Error::InvalidTag(7)));
+    assert!(matches!(decode::<Message>(&[0, 0]), Error::TrailingBytes));
+    let err = decode::<Message>(&[1, 5, 0, 0, 0, b'a']);
+    assert!(matches!(
+        err.root_cause(),
+        Error::Truncated {
+            what: "string",
+            wanted: 5,
+            available: 1
+        }
+    ));
+}
+
+#[test]
+fn test_truncated() {
+    let counts = |err: io::Error| match Error::from(err).root_cause() {
+        Error::Truncated {
+            what,
+            wanted,
+            available,
+        } => (*what, *wanted, *available),
+        err => panic!("unexpected error: {}", err),
+    };
+    // Through the slice and the reader paths.
+    let bytes = [3, 0, 0, 0, b'a', b'b'];
+    let from_slice = String::try_from_slice(&bytes).unwrap_err();
+    let from_reader = String::deserialize(&mut io::BufReader::new(&bytes[..])).unwrap_err();
+    assert_eq!(
+        from_slice.to_string(),
+        "at offset 0: unexpected end of input reading string: wanted 3 bytes, 2 available"
+    );
+    assert_eq!(counts(from_slice), ("string", 3, 2));
+    assert_eq!(counts(from_reader), ("string", 3, 2));
+
+    assert_eq!(
+        counts(decode_err::<Vec<u8>>(&[1, 0])),
+        ("length prefix", 4, 2)
+    );
+    assert_eq!(counts(decode_err::<Message>(&[])), ("enum tag", 1, 0));
+    assert_eq!(counts(decode_err::<Option<u8>>(&[])), ("enum tag", 1, 0));
+    assert_eq!(counts(decode_err::<f32>(&[0; 3])), ("f32", 4, 3));
+}
+
+fn decode_err<T: BorshDeserialize + std::fmt::Debug>(bytes: &[u8]) -> io::Error {
+    T::deserialize(&mut &bytes[..]).unwrap_err()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct Transaction {
+    nonce: u64,
+    signature: [u8; 4],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct Block {
+    height: u64,
+    transactions: Vec<Transaction>,
+    note: Message,
+}
+
+#[test]
+fn test_field_path() {
+    let block = Block {
+        height: 1,
+        transactions: (0..4)
+            .map(|nonce| Transaction {
+                nonce,
+                signature: [0; 4],
+            })
+            .collect(),
+        note: Message::Text("hi".to_string()),
+    };
+    let bytes = block.try_to_vec().unwrap();
+
+    // Cut into the signature of the last transaction.
+    let cut = 8 + 4 + 3 * 12 + 8 + 2;
+    let err = Block::try_from_slice(&bytes[..cut]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.to_string(),
+        "at offset 58: error deserializing Block.transactions[3].signature[2]: unexpected end of input reading u8: wanted 1 bytes, 0 available"
+    );
+    let source = match Error::from(err) {
+        Error::At { offset, source } => {
+            assert_eq!(offset, 58);
+            source
+        }
+        err => panic!("unexpected error: {}", err),
+    };
+    match *source {
+        Error::Context { ty, path, source } => {
+            assert_eq!(ty, "Block");
+            assert_eq!(path, ".transactions[3].signature[2]");
+            assert!(matches!(*source, Error::Truncated { what: "u8", .. }));
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+
+    // Break the UTF-8 of the note, through the reader path this time.
+    let mut corrupt = bytes.clone();
+    *corrupt.last_mut().unwrap() = 0xff;
+    let err = Block::deserialize(&mut &corrupt[..]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error deserializing Block.note::Text.0: invalid utf-8 sequence of 1 bytes from index 1"
+    );
+}
+
+#[test]
+fn test_reader_errors() {
+    struct Failing;
+
+    impl io::Read for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+        }
+    }
+
+    match Error::from(u8::deserialize(&mut Failing).unwrap_err()) {
+        Error::Io(err) => {
+            assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+            assert_eq!(err.to_string(), "reset");
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+    let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+    assert!(matches!(Error::from(eof), Error::UnexpectedEof));
+}
+
+#[test]
+fn test_io_compatibility() {
+    let err = Message::try_from_slice(&[7]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "at offset 0: Unexpected variant index: 7");
+
+    let err = io::Error::from(Error::TrailingBytes);
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "Not all bytes read");
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+
+    let err = io::Error::from(Error::Custom("bad checksum".to_string()));
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "bad checksum");
+}
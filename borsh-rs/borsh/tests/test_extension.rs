@@ -0,0 +1,104 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::extension::Extension;
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_extension(unknown)]
+struct ConfigV1 {
+    name: String,
+    unknown: Extension,
+    port: u16,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_extension(unknown)]
+struct ConfigV2 {
+    name: String,
+    unknown: Extension,
+    port: u16,
+    #[borsh(extension)]
+    retries: u8,
+    #[borsh(extension)]
+    tags: Vec<String>,
+}
+
+fn v2() -> ConfigV2 {
+    ConfigV2 {
+        name: "node".to_string(),
+        unknown: Extension::new(),
+        port: 8080,
+        retries: 3,
+        tags: vec!["a".to_string()],
+    }
+}
+
+#[test]
+fn test_layout() {
+    let bytes = v2().try_to_vec().unwrap();
+    let mut expected = ("node", 8080u16).try_to_vec().unwrap();
+    let region = (3u8, vec!["a"]).try_to_vec().unwrap();
+    expected.extend((region.len() as u32).to_le_bytes());
+    expected.extend(region);
+    assert_eq!(bytes, expected);
+    assert_eq!(v2().serialized_size(), bytes.len());
+}
+
+#[test]
+fn test_old_decoder_preserves_new_fields() {
+    let bytes = v2().try_to_vec().unwrap();
+    let v1 = ConfigV1::try_from_slice(&bytes).unwrap();
+    assert_eq!(v1.name, "node");
+    assert_eq!(v1.port, 8080);
+    assert!(!v1.unknown.is_empty());
+    assert_eq!(v1.try_to_vec().unwrap(), bytes);
+    assert_eq!(v1.serialized_size(), bytes.len());
+}
+
+#[test]
+fn test_new_decoder_defaults_missing_fields() {
+    let v1 = ConfigV1 {
+        name: "node".to_string(),
+        unknown: Extension::new(),
+        port: 1,
+    };
+    let bytes = v1.try_to_vec().unwrap();
+    let v2 = ConfigV2::try_from_slice(&bytes).unwrap();
+    assert_eq!(v2.retries, 0);
+    assert!(v2.tags.is_empty());
+    // The defaults are written out when the value is encoded again.
+    let again = ConfigV1::try_from_slice(&v2.try_to_vec().unwrap()).unwrap();
+    assert_eq!(again.unknown.unknown(), [0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_reader_matches_slice() {
+    let bytes = v2().try_to_vec().unwrap();
+    let mut reader = &bytes[..];
+    let decoded =
+        <ConfigV1 as BorshDeserialize>::deserialize(&mut std::io::Read::by_ref(&mut reader))
+            .unwrap();
+    assert!(reader.is_empty());
+    assert_eq!(decoded, ConfigV1::try_from_slice(&bytes).unwrap());
+}
+
+#[test]
+fn test_truncated_region() {
+    let mut bytes = v2().try_to_vec().unwrap();
+    bytes.pop();
+    let err = ConfigV2::try_from_slice(&bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    // A region that ends inside an extension field is not mistaken for an older version.
+    let mut bytes = ("node", 8080u16).try_to_vec().unwrap();
+    bytes.extend([2, 0, 0, 0, 3, 1]);
+    assert!(ConfigV2::try_from_slice(&bytes).is_err());
+}
+
+#[test]
+fn test_clear_drops_unknown_bytes() {
+    let mut v1 = ConfigV1::try_from_slice(&v2().try_to_vec().unwrap()).unwrap();
+    v1.unknown.clear();
+    let v2 = ConfigV2::try_from_slice(&v1.try_to_vec().unwrap()).unwrap();
+    assert_eq!(v2.retries, 0);
+}
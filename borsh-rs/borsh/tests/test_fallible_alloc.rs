@@ -0,0 +1,85 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{HashMap, HashSet};
+use std::io::ErrorKind;
+
+use oasis_borsh::de::options::DecodeOptions;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+/// Fails allocations above 1 GiB, like a server with little memory left would.
+struct Limited;
+
+const LIMIT: usize = 1 << 30;
+
+unsafe impl GlobalAlloc for Limited {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() > LIMIT {
+            return std::ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > LIMIT {
+            return std::ptr::null_mut();
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Limited = Limited;
+
+#[test]
+fn test_allocation_failure_is_an_error() {
+    // Trusted input reserves the whole announced length: 32 GiB here.
+    let bytes = u32::MAX.to_le_bytes();
+    let err = DecodeOptions::trusted()
+        .try_from_slice::<Vec<u64>>(&bytes)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+    match Error::from(err).root_cause() {
+        Error::AllocationFailed { bytes } => assert_eq!(*bytes, u32::MAX as usize * 8),
+        err => panic!("unexpected error: {}", err),
+    }
+
+    let err = DecodeOptions::trusted()
+        .deserialize::<String, _>(&mut &bytes[..])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("memory allocation of {} bytes failed", u32::MAX)
+    );
+}
+
+#[test]
+fn test_collections_still_decode() {
+    let values = (0..1000u32).collect::<Vec<_>>();
+    let bytes = values.try_to_vec().unwrap();
+    assert_eq!(Vec::<u32>::try_from_slice(&bytes).unwrap(), values);
+    let decoded: Vec<u32> = DecodeOptions::default()
+        .max_preallocation(0)
+        .deserialize(&mut &bytes[..])
+        .unwrap();
+    assert_eq!(decoded, values);
+
+    let set = HashSet::<u32>::try_from_slice(&bytes).unwrap();
+    assert_eq!(set.len(), 1000);
+    let map = values
+        .iter()
+        .map(|&value| (value, value.to_string()))
+        .collect::<HashMap<_, _>>();
+    let decoded = HashMap::<u32, String>::try_from_slice(&map.try_to_vec().unwrap()).unwrap();
+    assert_eq!(decoded, map);
+    let mut place = HashMap::new();
+    HashMap::deserialize_in_place(&mut &map.try_to_vec().unwrap()[..], &mut place).unwrap();
+    assert_eq!(place, map);
+
+    let text = "x".repeat(10_000);
+    let bytes = text.try_to_vec().unwrap();
+    assert_eq!(String::try_from_slice(&bytes).unwrap(), text);
+    assert_eq!(String::deserialize(&mut &bytes[..]).unwrap(), text);
+}
@@ -0,0 +1,102 @@
+use oasis_borsh::diff::{apply, diff};
+use oasis_borsh::{BorshDeserialize, BorshDiff, BorshSchema, BorshSerialize};
+
+/// The original layout.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct V1 {
+    id: u32,
+    name: String,
+    flag: bool,
+}
+
+/// The same wire layout, with the fields reorganized in source.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, BorshDiff, PartialEq, Debug, Clone)]
+struct V2 {
+    #[borsh(order = 2)]
+    flag: bool,
+    #[borsh_skip]
+    cache: u64,
+    #[borsh(order = 0)]
+    id: u32,
+    #[borsh(order = 1)]
+    name: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Tuple(#[borsh(order = 1)] u8, #[borsh(order = 0)] u16);
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Message {
+    Pair(#[borsh(order = 1)] u8, #[borsh(order = 0)] u16),
+    Named {
+        #[borsh(order = 1)]
+        a: u8,
+        #[borsh(order = 0)]
+        b: u16,
+        #[borsh_skip]
+        c: u8,
+    },
+}
+
+#[test]
+fn test_struct_order() {
+    let v1 = V1 {
+        id: 7,
+        name: "x".to_string(),
+        flag: true,
+    };
+    let v2 = V2 {
+        flag: true,
+        cache: 0,
+        id: 7,
+        name: "x".to_string(),
+    };
+    let bytes = v1.try_to_vec().unwrap();
+    assert_eq!(v2.try_to_vec().unwrap(), bytes);
+    assert_eq!(V2::try_from_slice(&bytes).unwrap(), v2);
+}
+
+#[test]
+fn test_schema_follows_wire_order() {
+    let v1 = V1::schema_container().definitions["V1"].clone();
+    let v2 = V2::schema_container().definitions["V2"].clone();
+    assert_eq!(v1, v2);
+}
+
+#[test]
+fn test_tuple_and_enum_order() {
+    let bytes = Tuple(1, 2).try_to_vec().unwrap();
+    assert_eq!(bytes, vec![2, 0, 1]);
+    assert_eq!(Tuple::try_from_slice(&bytes).unwrap(), Tuple(1, 2));
+
+    let pair = Message::Pair(1, 2);
+    let bytes = pair.try_to_vec().unwrap();
+    assert_eq!(bytes, vec![0, 2, 0, 1]);
+    assert_eq!(Message::try_from_slice(&bytes).unwrap(), pair);
+
+    let named = Message::Named { a: 1, b: 2, c: 9 };
+    let bytes = named.try_to_vec().unwrap();
+    assert_eq!(bytes, vec![1, 2, 0, 1]);
+    assert_eq!(
+        Message::try_from_slice(&bytes).unwrap(),
+        Message::Named { a: 1, b: 2, c: 0 }
+    );
+}
+
+#[test]
+fn test_diff_bits_follow_wire_order() {
+    let old = V2 {
+        flag: false,
+        cache: 0,
+        id: 1,
+        name: "a".to_string(),
+    };
+    let mut new = old.clone();
+    new.id = 2;
+    let delta = diff(&old, &new).unwrap();
+    // `id` is the first field on the wire.
+    assert_eq!(delta.0[0], 0b001);
+    let mut patched = old.clone();
+    apply(&mut patched, &delta).unwrap();
+    assert_eq!(patched, new);
+}
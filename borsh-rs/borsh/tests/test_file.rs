@@ -0,0 +1,84 @@
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+
+use oasis_borsh::file::FileOptions;
+use oasis_borsh::{from_file, to_file, BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct State {
+    height: u64,
+    peers: Vec<String>,
+}
+
+/// A fresh directory, so that leftover temporary files can be spotted.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("borsh-file-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entries(dir: &PathBuf) -> Vec<String> {
+    let mut names = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_round_trip() {
+    let dir = temp_dir("round-trip");
+    let path = dir.join("state.bin");
+    for height in 0..3 {
+        let state = State {
+            height,
+            peers: vec!["a".to_string(); height as usize],
+        };
+        to_file(&path, &state).unwrap();
+        assert_eq!(from_file::<State, _>(&path).unwrap(), state);
+    }
+    let state = State {
+        height: 9,
+        peers: Vec::new(),
+    };
+    FileOptions::new().sync(true).write(&path, &state).unwrap();
+    assert_eq!(from_file::<State, _>(&path).unwrap(), state);
+    assert_eq!(entries(&dir), ["state.bin"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+struct Failing;
+
+impl BorshSerialize for Failing {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[1, 2, 3])?;
+        Err(io::Error::other("cannot serialize"))
+    }
+}
+
+#[test]
+fn test_failed_write_keeps_old_contents() {
+    let dir = temp_dir("failed");
+    let path = dir.join("state.bin");
+    to_file(&path, &7u64).unwrap();
+    let err = to_file(&path, &Failing).unwrap_err();
+    assert_eq!(err.to_string(), "cannot serialize");
+    assert_eq!(from_file::<u64, _>(&path).unwrap(), 7);
+    assert_eq!(entries(&dir), ["state.bin"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_from_file_errors() {
+    let dir = temp_dir("errors");
+    let path = dir.join("state.bin");
+    let err = from_file::<u64, _>(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    to_file(&path, &7u64).unwrap();
+    let err = from_file::<u32, _>(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
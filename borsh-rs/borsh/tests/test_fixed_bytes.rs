@@ -0,0 +1,57 @@
+use std::convert::TryFrom;
+
+use oasis_borsh::fixed_bytes::{FixedBytes, ParseFixedBytesError};
+use oasis_borsh::schema::BorshSchema;
+use oasis_borsh::tagged::Tagged;
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+type Hash = FixedBytes<32>;
+
+#[test]
+fn test_hex_round_trip() {
+    let hash = Hash::new([0xab; 32]);
+    let hex = hash.to_string();
+    assert_eq!(hex, "ab".repeat(32));
+    assert_eq!(hex.parse::<Hash>().unwrap(), hash);
+    assert_eq!(
+        format!("0x{}", hex.to_uppercase()).parse::<Hash>().unwrap(),
+        hash
+    );
+    assert_eq!(format!("{:?}", FixedBytes([1u8, 2])), "FixedBytes(0102)");
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        "abc".parse::<FixedBytes<2>>(),
+        Err(ParseFixedBytesError::InvalidLength(3))
+    );
+    assert_eq!(
+        "0x01zz".parse::<FixedBytes<2>>(),
+        Err(ParseFixedBytesError::InvalidDigit(2))
+    );
+    // Lengths count bytes, so the two bytes of "é" are checked as digits.
+    assert_eq!(
+        "é".parse::<FixedBytes<1>>(),
+        Err(ParseFixedBytesError::InvalidDigit(0))
+    );
+}
+
+#[test]
+fn test_encoded_like_an_array() {
+    let bytes = [7u8; 20];
+    let fixed = FixedBytes::from(bytes);
+    assert_eq!(fixed.try_to_vec().unwrap(), bytes.try_to_vec().unwrap());
+    assert_eq!(FixedBytes::<20>::try_from_slice(&bytes).unwrap(), fixed);
+    assert_eq!(FixedBytes::<20>::declaration(), <[u8; 20]>::declaration());
+}
+
+#[test]
+fn test_conversions() {
+    enum Block {}
+    let tagged: Tagged<Block, 4> = FixedBytes([1, 2, 3, 4]).into();
+    assert_eq!(FixedBytes::from(tagged), FixedBytes([1, 2, 3, 4]));
+    assert_eq!(<[u8; 4]>::from(FixedBytes([1, 2, 3, 4])), [1, 2, 3, 4]);
+    assert!(FixedBytes::<4>::try_from(&[1u8, 2, 3][..]).is_err());
+    assert_eq!(FixedBytes::<64>::default().as_bytes(), &[0; 64]);
+}
@@ -0,0 +1,95 @@
+use oasis_borsh::fixed_bytes::FixedBytes;
+use oasis_borsh::{BorshFixedSize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshFixedSize)]
+struct Header {
+    version: u8,
+    flags: u16,
+    id: [u8; 32],
+    #[borsh_skip]
+    #[allow(dead_code)]
+    cached: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshFixedSize)]
+struct Pair<T>(T, T);
+
+#[derive(BorshSerialize, BorshFixedSize)]
+struct Unit;
+
+#[derive(BorshSerialize, BorshFixedSize)]
+enum Shape {
+    Point { x: i32, y: i32 },
+    Span(u64),
+    Pair(Pair<u32>),
+}
+
+#[derive(BorshSerialize, BorshFixedSize)]
+enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+#[derive(BorshSerialize, BorshFixedSize)]
+struct Nested {
+    header: Header,
+    shape: Shape,
+    hash: FixedBytes<20>,
+    pairs: [(u8, bool); 3],
+    more: Box<Pair<i16>>,
+}
+
+fn check<T: BorshSerialize + BorshFixedSize>(value: T) {
+    assert_eq!(value.try_to_vec().unwrap().len(), T::SIZE);
+}
+
+#[test]
+fn test_structs() {
+    const _: () = assert!(Header::SIZE == 35);
+    check(Header {
+        version: 1,
+        flags: 2,
+        id: [3; 32],
+        cached: vec![4; 10],
+    });
+    check(Pair(1u64, 2));
+    check(Unit);
+    assert_eq!(Unit::SIZE, 0);
+}
+
+#[test]
+fn test_enums() {
+    assert_eq!(Shape::SIZE, 9);
+    check(Shape::Point { x: 1, y: 2 });
+    check(Shape::Span(3));
+    check(Shape::Pair(Pair(4, 5)));
+    assert_eq!(<Either<u32, [u8; 4]>>::SIZE, 5);
+    check(Either::<u32, [u8; 4]>::Left(1));
+    check(Either::<u32, [u8; 4]>::Right([1; 4]));
+}
+
+#[test]
+fn test_nested() {
+    assert_eq!(Nested::SIZE, 35 + 9 + 20 + 6 + 4);
+    check(Nested {
+        header: Header {
+            version: 1,
+            flags: 2,
+            id: [3; 32],
+            cached: Vec::new(),
+        },
+        shape: Shape::Span(7),
+        hash: FixedBytes::from([5; 20]),
+        pairs: [(1, true); 3],
+        more: Box::new(Pair(-1, 1)),
+    });
+}
+
+#[test]
+fn test_std_types() {
+    assert_eq!(<(u8, u16, u32)>::SIZE, 7);
+    assert_eq!(<[[u16; 3]; 2]>::SIZE, 12);
+    check(std::net::Ipv6Addr::LOCALHOST);
+    check("127.0.0.1:80".parse::<std::net::SocketAddrV4>().unwrap());
+    check(std::cmp::Ordering::Less);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "fixtures")]
+use std::io::ErrorKind;
+
+use oasis_borsh::schema::fixture::{self, Fixture};
+use oasis_borsh::{BorshDeserialize, BorshSchema};
+
+#[derive(BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Account {
+    name: String,
+    balance: u128,
+    tags: Vec<u8>,
+    owner: Option<[u8; 2]>,
+    kind: Kind,
+}
+
+#[derive(BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Kind {
+    Basic,
+    Staked(u64),
+}
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn account() -> Fixture {
+    Fixture::load(format!("{}/account.json", FIXTURES)).unwrap()
+}
+
+#[test]
+fn test_check_dir() {
+    assert_eq!(fixture::check_dir(FIXTURES).unwrap(), 2);
+}
+
+#[test]
+fn test_fixture_describes_rust_type() {
+    let fixture = account();
+    assert_eq!(fixture.schema, Account::schema_container());
+    let expected = Account {
+        name: "bob".to_string(),
+        balance: 1000,
+        tags: vec![1, 2],
+        owner: Some([9, 10]),
+        kind: Kind::Staked(5),
+    };
+    assert_eq!(Account::try_from_slice(&fixture.bytes).unwrap(), expected);
+}
+
+#[test]
+fn test_mismatch_names_path() {
+    let mut fixture = account();
+    fixture.expected["kind"]["Staked"][0] = 6.into();
+    let err = fixture.check().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "Account.kind.Staked[0]: expected 6, decoded \"5\""
+    );
+}
+
+#[test]
+fn test_trailing_bytes_fail() {
+    let mut fixture = account();
+    fixture.bytes.push(0);
+    assert_eq!(fixture.check().unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_invalid_hex() {
+    let err = Fixture::from_json(
+        r#"{"schema": {"declaration": "u8", "definitions": {}},
+        "bytes": "0g", "expected": 0}"#,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,59 @@
+use std::collections::{BTreeMap, HashSet};
+
+use oasis_borsh::schema::fuzz;
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Event {
+    id: u64,
+    kind: Kind,
+    tags: BTreeMap<String, u8>,
+    children: Vec<Event>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Kind {
+    Created,
+    Moved(i16, i16),
+    Deleted { reason: String },
+}
+
+#[test]
+fn test_dictionary() {
+    let dict = fuzz::dictionary(&Event::schema_container());
+    let get = |name: &str| dict.iter().find(|(n, _)| n == name).map(|(_, b)| b.clone());
+    assert_eq!(get("tag_Kind_Deleted"), Some(vec![2]));
+    assert_eq!(get("u64_max"), Some(vec![0xff; 8]));
+    assert_eq!(get("len_256"), Some(vec![0, 1, 0, 0]));
+    // Duplicate byte strings are listed once.
+    assert_eq!(dict.iter().filter(|(_, b)| b == &vec![0u8]).count(), 1);
+
+    let mut out = Vec::new();
+    fuzz::write_dictionary(&Event::schema_container(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("tag_Kind_Deleted=\"\\x02\"\n"));
+}
+
+#[test]
+fn test_seed_corpus() {
+    let corpus = fuzz::seed_corpus(&Event::schema_container(), 6).unwrap();
+    assert_eq!(corpus.len(), 6);
+    let mut variants = HashSet::new();
+    for seed in &corpus {
+        let event = Event::try_from_slice(seed).unwrap();
+        variants.insert(std::mem::discriminant(&event.kind));
+    }
+    assert_eq!(variants.len(), 3);
+    assert_eq!(
+        corpus,
+        fuzz::seed_corpus(&Event::schema_container(), 6).unwrap()
+    );
+}
+
+#[derive(BorshSerialize, BorshSchema)]
+struct Endless(Box<Endless>);
+
+#[test]
+fn test_seed_corpus_infinite_type() {
+    assert!(fuzz::seed_corpus(&Endless::schema_container(), 1).is_err());
+}
@@ -18,7 +18,7 @@ fn test_generic_struct() {
     let a = A::<String, u64, String> {
         x: vec!["foo".to_string(), "bar".to_string()],
         y: "world".to_string(),
-        b: B::X {f: vec![1, 2]}
+        b: B::X { f: vec![1, 2] },
     };
     let data = a.try_to_vec().unwrap();
     let actual_a = A::<String, u64, String>::try_from_slice(&data).unwrap();
@@ -0,0 +1,104 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::handshake::{Negotiation, Registry};
+use oasis_borsh::BorshSchema;
+
+mod v1 {
+    use oasis_borsh::BorshSchema;
+
+    #[derive(BorshSchema)]
+    #[allow(dead_code)]
+    pub struct Transfer {
+        pub amount: u64,
+    }
+}
+
+mod v2 {
+    use oasis_borsh::BorshSchema;
+
+    #[derive(BorshSchema)]
+    #[allow(dead_code)]
+    pub struct Transfer {
+        pub amount: u128,
+    }
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Ping {
+    nonce: u64,
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Status {
+    height: u64,
+}
+
+/// Run the handshake of `a` and `b` against each other, returning what each side concluded.
+fn handshake(a: &Registry, b: &Registry) -> (Negotiation, Negotiation) {
+    let mut from_a = Vec::new();
+    let mut from_b = Vec::new();
+    a.write_hello(&mut from_a).unwrap();
+    b.write_hello(&mut from_b).unwrap();
+    let mut ignored = Vec::new();
+    (
+        a.handshake(&mut &from_b[..], &mut ignored).unwrap(),
+        b.handshake(&mut &from_a[..], &mut ignored).unwrap(),
+    )
+}
+
+#[test]
+fn test_compatible() {
+    let registry = Registry::new().with::<Ping>().with::<v1::Transfer>();
+    let (a, b) = handshake(&registry, &registry);
+    assert_eq!(a, b);
+    assert_eq!(a.compatible, ["Ping", "Transfer"]);
+    a.require_all().unwrap();
+    a.require::<Ping>().unwrap();
+    a.require::<v1::Transfer>().unwrap();
+}
+
+#[test]
+fn test_incompatible() {
+    let a = Registry::new().with::<Ping>().with::<v1::Transfer>();
+    let b = Registry::new().with::<v2::Transfer>().with::<Status>();
+    let (from_a, from_b) = handshake(&a, &b);
+    assert_eq!(
+        from_a,
+        Negotiation {
+            compatible: vec![],
+            mismatched: vec!["Transfer".to_string()],
+            local_only: vec!["Ping".to_string()],
+            remote_only: vec!["Status".to_string()],
+        }
+    );
+    assert_eq!(from_b.local_only, ["Status"]);
+    assert_eq!(from_b.remote_only, ["Ping"]);
+
+    let err = from_a.require::<v1::Transfer>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Handshake failed: Transfer has a different schema on the peer"
+    );
+    let err = from_a.require::<Ping>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Handshake failed: Ping is not registered on both sides"
+    );
+    assert_eq!(
+        from_a.require_all().unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn test_invalid_hello() {
+    let registry = Registry::new().with::<Ping>();
+    let err = registry.read_hello(&mut &b"HTTP/1.1"[..]).unwrap_err();
+    assert_eq!(err.to_string(), "Missing handshake hello");
+    let err = registry.read_hello(&mut &b"BRHS\x02"[..]).unwrap_err();
+    assert_eq!(err.to_string(), "Unsupported handshake version: 2");
+    let err = registry.read_hello(&mut &b"BRHS\x01\x01"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,60 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hasher;
+
+use oasis_borsh::hash::hash;
+use oasis_borsh::tagged::Tagged;
+use oasis_borsh::{BorshHash, BorshSerialize};
+
+#[derive(BorshSerialize)]
+struct Record {
+    id: u64,
+    tags: HashMap<String, u32>,
+}
+
+fn record(order: &[(&str, u32)]) -> Record {
+    let mut tags = HashMap::new();
+    for (tag, count) in order {
+        tags.insert(tag.to_string(), *count);
+    }
+    Record { id: 3, tags }
+}
+
+#[test]
+fn test_hash_of_encoding() {
+    let record = record(&[("a", 1), ("b", 2), ("c", 3)]);
+    let mut expected = DefaultHasher::new();
+    expected.write(&record.try_to_vec().unwrap());
+    assert_eq!(
+        hash::<DefaultHasher, _>(&record).unwrap(),
+        expected.finish()
+    );
+
+    // Map iteration order does not leak into the hash.
+    let reordered = self::record(&[("c", 3), ("a", 1), ("b", 2)]);
+    assert_eq!(
+        hash::<DefaultHasher, _>(&record).unwrap(),
+        hash::<DefaultHasher, _>(&reordered).unwrap()
+    );
+    let sorted: BTreeMap<_, _> = record.tags.clone().into_iter().collect();
+    assert_eq!(
+        hash::<DefaultHasher, _>(&(3u64, &record.tags)).unwrap(),
+        hash::<DefaultHasher, _>(&(3u64, sorted)).unwrap()
+    );
+}
+
+#[test]
+fn test_independent_of_write_sizes() {
+    // `Tagged` writes its bytes at once, arrays write them one at a time.
+    enum Key {}
+    let bytes = [7u8; 100];
+    let tagged = Tagged::<Key, 100>::new(bytes);
+    assert_eq!(
+        hash::<DefaultHasher, _>(&tagged).unwrap(),
+        hash::<DefaultHasher, _>(&bytes).unwrap()
+    );
+
+    let mut hasher = DefaultHasher::new();
+    bytes.borsh_hash(&mut hasher).unwrap();
+    assert_eq!(hasher.finish(), hash::<DefaultHasher, _>(&bytes).unwrap());
+}
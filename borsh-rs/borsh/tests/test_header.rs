@@ -0,0 +1,58 @@
+use oasis_borsh::header::{self, HEADER_LEN};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct V1 {
+    id: u64,
+    name: String,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct V2 {
+    id: u64,
+    tag: String,
+}
+
+#[test]
+fn test_header_roundtrip() {
+    let value = V1 {
+        id: 1,
+        name: "alice".to_string(),
+    };
+    let encoded = header::to_vec_with_header(&value).unwrap();
+    assert_eq!(&encoded[..4], b"BRSH");
+    assert_eq!(&encoded[HEADER_LEN..], &value.try_to_vec().unwrap()[..]);
+    assert_eq!(
+        header::from_slice_with_header::<V1>(&encoded).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn test_fingerprint_depends_on_field_names() {
+    assert_ne!(header::fingerprint::<V1>(), header::fingerprint::<V2>());
+    assert_eq!(header::fingerprint::<V1>(), header::fingerprint::<V1>());
+}
+
+#[test]
+fn test_schema_mismatch() {
+    let encoded = header::to_vec_with_header(&V1 {
+        id: 1,
+        name: "alice".to_string(),
+    })
+    .unwrap();
+    let err = header::from_slice_with_header::<V2>(&encoded).unwrap_err();
+    assert!(err.to_string().starts_with("Schema mismatch"), "{}", err);
+}
+
+#[test]
+fn test_missing_header() {
+    let encoded = V1 {
+        id: 1,
+        name: "alice".to_string(),
+    }
+    .try_to_vec()
+    .unwrap();
+    let err = header::from_slice_with_header::<V1>(&encoded).unwrap_err();
+    assert_eq!(err.to_string(), "Missing borsh header");
+}
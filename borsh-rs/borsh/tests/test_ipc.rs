@@ -0,0 +1,124 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::ipc::{IpcReceiver, IpcSender};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Message {
+    id: u32,
+    body: String,
+}
+
+fn message(id: u32) -> Message {
+    Message {
+        id,
+        body: format!("message {}", id),
+    }
+}
+
+fn frames(checksummed: bool) -> Vec<u8> {
+    let mut sender = IpcSender::new(Vec::new()).checksummed(checksummed);
+    for id in 0..3 {
+        sender.send(&message(id)).unwrap();
+    }
+    sender.into_inner()
+}
+
+#[test]
+fn test_roundtrip() {
+    for &checksummed in &[false, true] {
+        let bytes = frames(checksummed);
+        let received = IpcReceiver::<Message, _>::new(&bytes[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(received, (0..3).map(message).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_layout() {
+    let mut sender = IpcSender::new(Vec::new());
+    sender.send(&7u16).unwrap();
+    assert_eq!(sender.get_ref()[..], [2, 0, 0, 0, 0, 7, 0]);
+
+    let mut sender = IpcSender::new(Vec::new()).checksummed(true);
+    sender.send(&7u16).unwrap();
+    let bytes = sender.into_inner();
+    assert_eq!(bytes[..5], [2, 0, 0, 0, 1]);
+    assert_eq!(bytes.len(), 11);
+    assert_eq!(bytes[9..], [7, 0]);
+}
+
+#[test]
+fn test_truncated_frame() {
+    let bytes = frames(false);
+    for cut in 1..bytes.len() {
+        let mut receiver = IpcReceiver::<Message, _>::new(&bytes[..cut]);
+        let err = loop {
+            match receiver.recv() {
+                Ok(Some(_)) => {}
+                Ok(None) => break None,
+                Err(err) => break Some(err),
+            }
+        };
+        let first = message(0).try_to_vec().unwrap().len() + 5;
+        if cut % first == 0 {
+            assert!(err.is_none(), "cut at {}", cut);
+        } else {
+            assert_eq!(
+                err.unwrap().kind(),
+                ErrorKind::UnexpectedEof,
+                "cut at {}",
+                cut
+            );
+        }
+    }
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let mut bytes = frames(true);
+    // Flip a byte in the payload of the first frame.
+    bytes[12] ^= 1;
+    let mut receiver = IpcReceiver::<Message, _>::new(&bytes[..]);
+    let err = receiver.recv().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "Frame checksum mismatch");
+    // The next frame is still found.
+    assert_eq!(receiver.recv().unwrap(), Some(message(1)));
+}
+
+#[test]
+fn test_receiver_options() {
+    let bytes = frames(false);
+    let err = IpcReceiver::<Message, _>::new(&bytes[..])
+        .require_checksums(true)
+        .recv()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Frame carries no checksum");
+
+    let err = IpcReceiver::<Message, _>::new(&bytes[..])
+        .max_len(4)
+        .recv()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Frame of 17 bytes exceeds the limit of 4");
+
+    let mut bytes = frames(false);
+    bytes[4] = 0x80;
+    let err = IpcReceiver::<Message, _>::new(&bytes[..])
+        .recv()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unknown frame flags 0x80");
+}
+
+#[test]
+fn test_undecodable_frame() {
+    let mut sender = IpcSender::new(Vec::new());
+    sender.send(&1u8).unwrap();
+    sender.send(&2u8).unwrap();
+    let bytes = sender.into_inner();
+    let mut receiver = IpcReceiver::<u16, _>::new(&bytes[..]);
+    let err = receiver.recv().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("Undecodable frame ("));
+}
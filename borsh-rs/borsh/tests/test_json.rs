@@ -0,0 +1,59 @@
+#![cfg(feature = "serde_json")]
+use serde_json::{json, Value};
+
+use oasis_borsh::schema::value;
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Envelope {
+    id: u64,
+    body: Value,
+}
+
+#[test]
+fn test_round_trip() {
+    let envelope = Envelope {
+        id: 7,
+        body: json!({
+            "name": "gateway",
+            "ports": [80, 443],
+            "ratio": 0.5,
+            "offset": -3,
+            "tls": true,
+            "proxy": null,
+        }),
+    };
+    let bytes = envelope.try_to_vec().unwrap();
+    assert_eq!(envelope.serialized_size(), bytes.len());
+    assert_eq!(Envelope::try_from_slice(&bytes).unwrap(), envelope);
+    assert_eq!(Envelope::deserialize(&mut &bytes[..]).unwrap(), envelope);
+    value::validate(&Value::schema_container(), &bytes[8..]).unwrap();
+}
+
+#[test]
+fn test_encoding() {
+    assert_eq!(Value::Null.try_to_vec().unwrap(), vec![0]);
+    assert_eq!(json!(true).try_to_vec().unwrap(), vec![1, 1]);
+    assert_eq!(
+        json!(-1).try_to_vec().unwrap(),
+        vec![2, 1, 255, 255, 255, 255, 255, 255, 255, 255]
+    );
+    assert_eq!(json!("a").try_to_vec().unwrap(), vec![3, 1, 0, 0, 0, b'a']);
+    assert_eq!(json!([null]).try_to_vec().unwrap(), vec![4, 1, 0, 0, 0, 0]);
+    assert_eq!(
+        json!({"b": null, "a": null}).try_to_vec().unwrap(),
+        vec![5, 2, 0, 0, 0, 1, 0, 0, 0, b'a', 0, 1, 0, 0, 0, b'b', 0]
+    );
+}
+
+#[test]
+fn test_rejects_invalid() {
+    // Unknown tag.
+    assert!(Value::try_from_slice(&[6]).is_err());
+    // 1 encoded as a negative integer.
+    assert!(Value::try_from_slice(&[2, 1, 1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    // Infinity.
+    let mut bytes = vec![2, 2];
+    bytes.extend_from_slice(&f64::INFINITY.to_le_bytes());
+    assert!(Value::try_from_slice(&bytes).is_err());
+}
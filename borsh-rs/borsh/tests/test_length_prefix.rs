@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashSet};
+
+use oasis_borsh::schema::value::{self, Value};
+use oasis_borsh::schema::Definition;
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Packet {
+    #[borsh(len = "u8")]
+    name: String,
+    #[borsh(len = "u16")]
+    payload: Vec<u8>,
+    #[borsh(len = "u64")]
+    tags: BTreeMap<u8, bool>,
+    rest: Vec<u8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Frame {
+    Ping,
+    Data(#[borsh(len = "u8")] Vec<u16>),
+    Set {
+        #[borsh(len = "u16")]
+        ids: HashSet<u32>,
+    },
+}
+
+fn packet() -> Packet {
+    let mut tags = BTreeMap::new();
+    tags.insert(3, true);
+    Packet {
+        name: "ab".to_string(),
+        payload: vec![1, 2, 3],
+        tags,
+        rest: vec![9],
+    }
+}
+
+#[test]
+fn test_struct_prefix_widths() {
+    let packet = packet();
+    let bytes = packet.try_to_vec().unwrap();
+    let mut expected = vec![2, b'a', b'b', 3, 0, 1, 2, 3];
+    expected.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 3, 1]);
+    expected.extend_from_slice(&[1, 0, 0, 0, 9]);
+    assert_eq!(bytes, expected);
+    assert_eq!(Packet::try_from_slice(&bytes).unwrap(), packet);
+}
+
+#[test]
+fn test_enum_prefix_widths() {
+    let frame = Frame::Data(vec![1, 2]);
+    let bytes = frame.try_to_vec().unwrap();
+    assert_eq!(bytes, vec![1, 2, 1, 0, 2, 0]);
+    assert_eq!(Frame::try_from_slice(&bytes).unwrap(), frame);
+
+    let frame = Frame::Set {
+        ids: vec![5].into_iter().collect(),
+    };
+    let bytes = frame.try_to_vec().unwrap();
+    assert_eq!(bytes, vec![2, 1, 0, 5, 0, 0, 0]);
+    assert_eq!(Frame::try_from_slice(&bytes).unwrap(), frame);
+}
+
+#[test]
+fn test_length_overflow() {
+    let frame = Frame::Data(vec![0; 256]);
+    assert!(frame.try_to_vec().is_err());
+}
+
+#[test]
+fn test_truncated_prefix() {
+    assert!(Frame::try_from_slice(&[1, 3, 1, 0]).is_err());
+}
+
+#[test]
+fn test_schema() {
+    let container = Packet::schema_container();
+    assert_eq!(
+        container.definitions.get("Prefixed<Vec<u8>, u16>"),
+        Some(&Definition::Prefixed {
+            length: "u16".to_string(),
+            sequence: "Vec<u8>".to_string(),
+        })
+    );
+    assert_eq!(
+        container.definitions.get("Prefixed<string, u8>"),
+        Some(&Definition::Prefixed {
+            length: "u8".to_string(),
+            sequence: "string".to_string(),
+        })
+    );
+
+    let bytes = packet().try_to_vec().unwrap();
+    let decoded = value::decode(&container, &bytes).unwrap();
+    match &decoded {
+        Value::Struct(fields) => {
+            assert_eq!(fields[0], Value::String("ab".to_string()));
+            assert_eq!(
+                fields[1],
+                Value::Sequence(vec![Value::U8(1), Value::U8(2), Value::U8(3)])
+            );
+        }
+        _ => panic!("expected a struct, got {:?}", decoded),
+    }
+    assert_eq!(value::encode(&container, &decoded).unwrap(), bytes);
+    assert!(value::validate(&container, &bytes).is_ok());
+}
@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use oasis_borsh::de::options::{DecodeOptions, Limits, Profile};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Message {
+    name: String,
+    #[borsh(len = "u64")]
+    tags: BTreeMap<u8, bool>,
+    body: Vec<u16>,
+}
+
+fn too_long(err: std::io::Error) -> (&'static str, u64, u32) {
+    match Error::from(err).root_cause() {
+        Error::TooLong { what, len, limit } => (*what, *len, *limit),
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_lengths_at_the_limit() {
+    let limits = Limits::NONE
+        .max_sequence_len(3)
+        .max_string_len(5)
+        .max_map_entries(2);
+    let bytes = "hello".try_to_vec().unwrap();
+    assert_eq!(
+        String::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        "hello"
+    );
+    let bytes = vec![1u8, 2, 3].try_to_vec().unwrap();
+    assert_eq!(
+        Vec::<u8>::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        [1, 2, 3]
+    );
+    let map: HashMap<u8, u8> = vec![(1, 2), (3, 4)].into_iter().collect();
+    let bytes = map.try_to_vec().unwrap();
+    assert_eq!(
+        HashMap::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn test_lengths_over_the_limit() {
+    let limits = Limits::NONE
+        .max_sequence_len(3)
+        .max_string_len(5)
+        .max_map_entries(2);
+    let bytes = "hello!".try_to_vec().unwrap();
+    let err = String::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("string", 6, 5));
+    let bytes = vec![1u8, 2, 3, 4].try_to_vec().unwrap();
+    let err = Vec::<u8>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("sequence", 4, 3));
+    let set: HashSet<u8> = (0..4).collect();
+    let bytes = set.try_to_vec().unwrap();
+    let err = HashSet::<u8>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("sequence", 4, 3));
+    let map: BTreeMap<u8, u8> = (0..3).map(|i| (i, i)).collect();
+    let bytes = map.try_to_vec().unwrap();
+    let err = BTreeMap::<u8, u8>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("map", 3, 2));
+}
+
+#[test]
+fn test_checked_before_the_elements() {
+    // Only the length prefix is there: the limit is hit before the input runs out.
+    let limits = Limits::NONE.max_sequence_len(1000);
+    let err = Vec::<u64>::try_from_slice_with_limits(&[0xff; 4], limits).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 0: sequence of length 4294967295 exceeds the limit of 1000"
+    );
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_custom_length_prefixes() {
+    let mut tags = BTreeMap::new();
+    tags.insert(1, true);
+    tags.insert(2, false);
+    let message = Message {
+        name: "m".to_string(),
+        tags,
+        body: vec![7; 4],
+    };
+    let bytes = message.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_map_entries(2).max_sequence_len(4);
+    assert_eq!(
+        Message::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        message
+    );
+    // Map entries are not also counted against the sequence limit.
+    let limits = Limits::NONE.max_sequence_len(1).max_map_entries(2);
+    let err = Message::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("sequence", 4, 1));
+    let limits = Limits::NONE.max_map_entries(1);
+    let err = Message::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(too_long(err), ("map", 2, 1));
+}
+
+#[test]
+fn test_limits_are_restored() {
+    let bytes = vec![0u8; 10].try_to_vec().unwrap();
+    let limits = Limits::NONE.max_sequence_len(1);
+    assert!(Vec::<u8>::try_from_slice_with_limits(&bytes, limits).is_err());
+    assert_eq!(Vec::<u8>::try_from_slice(&bytes).unwrap().len(), 10);
+}
+
+#[test]
+fn test_options_and_profiles() {
+    let bytes = vec![0u8; 100].try_to_vec().unwrap();
+    let options = DecodeOptions::default().limits(Limits::NONE.max_sequence_len(10));
+    let err = options.try_from_slice::<Vec<u8>>(&bytes).unwrap_err();
+    assert_eq!(too_long(err), ("sequence", 100, 10));
+    assert!(DecodeOptions::default()
+        .try_from_slice::<Vec<u8>>(&bytes)
+        .is_ok());
+
+    let huge = (1u32 << 17).try_to_vec().unwrap();
+    let err = DecodeOptions::from(Profile::Embedded)
+        .try_from_slice::<Vec<u8>>(&huge)
+        .unwrap_err();
+    assert_eq!(too_long(err), ("sequence", 1 << 17, 1 << 16));
+    let err = DecodeOptions::from(Profile::Trusted)
+        .try_from_slice::<Vec<u8>>(&huge)
+        .unwrap_err();
+    assert!(matches!(
+        Error::from(err).root_cause(),
+        Error::Truncated { .. } | Error::UnexpectedEof
+    ));
+}
+
+fn over_budget(err: std::io::Error) -> usize {
+    match Error::from(err).root_cause() {
+        Error::OverBudget { budget } => *budget,
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_allocation_budget() {
+    // Each vector is short, but together they take 100 * 100 bytes, plus the outer vector.
+    let nested = vec![vec![7u8; 100]; 100];
+    let bytes = nested.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_sequence_len(100).max_allocation(5000);
+    let err = Vec::<Vec<u8>>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(over_budget(err), 5000);
+
+    let limits = limits.max_allocation(20_000);
+    assert_eq!(
+        Vec::<Vec<u8>>::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        nested
+    );
+    // Each call starts with the whole budget.
+    assert!(Vec::<Vec<u8>>::try_from_slice_with_limits(&bytes, limits).is_ok());
+}
+
+#[test]
+fn test_allocation_budget_covers_maps() {
+    let map: BTreeMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let bytes = map.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_allocation(100 * 16 - 1);
+    let err = BTreeMap::<u64, u64>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 1604: decoding needs more than the memory budget of 1599 bytes"
+    );
+    let limits = Limits::NONE.max_allocation(100 * 16);
+    assert_eq!(
+        BTreeMap::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        map
+    );
+
+    let map: HashMap<u64, u64> = map.into_iter().collect();
+    let bytes = map.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_allocation(1000);
+    let err = HashMap::<u64, u64>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(over_budget(err), 1000);
+}
+
+#[test]
+fn test_allocation_budget_is_shared_within_a_run() {
+    let bytes = vec![0u8; 600].try_to_vec().unwrap();
+    let options = DecodeOptions::default().limits(Limits::NONE.max_allocation(1000));
+    let (first, second) = options.run(|| {
+        (
+            Vec::<u8>::try_from_slice(&bytes),
+            Vec::<u8>::try_from_slice(&bytes),
+        )
+    });
+    assert_eq!(first.unwrap().len(), 600);
+    assert_eq!(over_budget(second.unwrap_err()), 1000);
+    assert!(options.try_from_slice::<Vec<u8>>(&bytes).is_ok());
+}
+
+fn too_many_elements(err: std::io::Error) -> u64 {
+    match Error::from(err).root_cause() {
+        Error::TooManyElements { budget } => *budget,
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_element_budget() {
+    // Each vector is within the length limit, but together they hold 100 * 100 elements, plus the
+    // 100 of the outer vector.
+    let nested = vec![vec![(); 100]; 100];
+    let bytes = nested.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_sequence_len(100).max_elements(10_099);
+    let err = Vec::<Vec<()>>::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 404: error deserializing [99]: decoding needs more than the budget of \
+         10099 elements"
+    );
+    let limits = limits.max_elements(10_100);
+    assert_eq!(
+        Vec::<Vec<()>>::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        nested
+    );
+}
+
+#[test]
+fn test_element_budget_covers_maps_not_strings() {
+    let map: BTreeMap<u8, String> = (0..10).map(|i| (i, "x".repeat(100))).collect();
+    let bytes = map.try_to_vec().unwrap();
+    let limits = Limits::NONE.max_elements(10);
+    assert_eq!(
+        BTreeMap::try_from_slice_with_limits(&bytes, limits).unwrap(),
+        map
+    );
+    let err = BTreeMap::<u8, String>::try_from_slice_with_limits(&bytes, limits.max_elements(9))
+        .unwrap_err();
+    assert_eq!(too_many_elements(err), 9);
+
+    // Five vectors of 64 Ki `()` take no memory, and each is within the length limit, but the
+    // fourth goes over the budget before it is decoded.
+    let mut bytes = 5u32.try_to_vec().unwrap();
+    for _ in 0..5 {
+        bytes.extend_from_slice(&(1u32 << 16).to_le_bytes());
+    }
+    let err = DecodeOptions::from(Profile::Embedded)
+        .try_from_slice::<Vec<Vec<()>>>(&bytes)
+        .unwrap_err();
+    assert_eq!(too_many_elements(err), 1 << 18);
+}
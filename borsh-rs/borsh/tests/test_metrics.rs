@@ -0,0 +1,93 @@
+#![cfg(feature = "metrics")]
+use std::any::type_name;
+
+use oasis_borsh::de::metrics::{with_metrics, FieldStats};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Point(u32, u32);
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Shape {
+    Dot(Point),
+    Path { points: Vec<Point>, label: String },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Drawing {
+    name: String,
+    shapes: Vec<Shape>,
+}
+
+fn drawing() -> Drawing {
+    Drawing {
+        name: "sketch".to_string(),
+        shapes: vec![
+            Shape::Dot(Point(1, 2)),
+            Shape::Path {
+                points: vec![Point(0, 0), Point(3, 4)],
+                label: String::new(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_slice_stats() {
+    let bytes = drawing().try_to_vec().unwrap();
+    let (decoded, stats) = with_metrics(|| Drawing::try_from_slice(&bytes));
+    assert_eq!(decoded.unwrap(), drawing());
+
+    // `name`, `shapes`, `points` and `label`.
+    assert_eq!(stats.collections, 4);
+    assert_eq!(stats.elements, 6 + 2 + 2);
+    // Drawing > Shape > Point.
+    assert_eq!(stats.max_depth, 3);
+
+    let field = |ty, name| stats.fields[&(ty, name)];
+    assert_eq!(
+        field(type_name::<Drawing>(), "name"),
+        FieldStats {
+            count: 1,
+            bytes: 10
+        }
+    );
+    assert_eq!(
+        field(type_name::<Drawing>(), "shapes").bytes,
+        bytes.len() as u64 - 10
+    );
+    assert_eq!(
+        field(type_name::<Point>(), "0"),
+        FieldStats {
+            count: 3,
+            bytes: 12
+        }
+    );
+    assert_eq!(field(type_name::<Shape>(), "Dot.0").bytes, 8);
+    assert_eq!(field(type_name::<Shape>(), "Path.points").bytes, 20);
+    assert_eq!(field(type_name::<Shape>(), "Path.label").bytes, 4);
+}
+
+#[test]
+fn test_reader_stats() {
+    let bytes = drawing().try_to_vec().unwrap();
+    let (decoded, stats) = with_metrics(|| Drawing::deserialize(&mut &bytes[..]));
+    assert_eq!(decoded.unwrap(), drawing());
+    assert_eq!(stats.collections, 4);
+    assert_eq!(stats.max_depth, 3);
+    assert!(stats.fields.is_empty());
+}
+
+#[test]
+fn test_scoped() {
+    let bytes = vec![1u8; 3].try_to_vec().unwrap();
+    let _ = Vec::<u8>::try_from_slice(&bytes).unwrap();
+    let (_, outer) = with_metrics(|| {
+        let (_, inner) = with_metrics(|| Vec::<u8>::try_from_slice(&bytes).unwrap());
+        assert_eq!(inner.elements, 3);
+        Point::try_from_slice(&[0; 8]).unwrap()
+    });
+    assert_eq!(outer.collections, 0);
+    assert_eq!(outer.max_depth, 1);
+    assert_eq!(outer.fields.len(), 2);
+}
@@ -0,0 +1,84 @@
+use std::panic::catch_unwind;
+
+use oasis_borsh::de::options::{DecodeOptions, NanPolicy, Profile};
+use oasis_borsh::spec::{CANONICAL_NAN_F32, CANONICAL_NAN_F64};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+struct Sample {
+    time: u64,
+    value: f32,
+}
+
+/// A negative signalling NaN, as far from the canonical one as NaNs get.
+fn odd_nan() -> f64 {
+    f64::from_bits(0xfff0_0000_0000_0001)
+}
+
+#[test]
+fn test_reject_by_default() {
+    let bytes = odd_nan().to_bits().to_le_bytes();
+    let err = f64::try_from_slice(&bytes).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(catch_unwind(|| odd_nan().try_to_vec()).is_err());
+    assert!(DecodeOptions::from(Profile::Consensus)
+        .try_from_slice::<f64>(&bytes)
+        .is_err());
+}
+
+#[test]
+fn test_accept() {
+    let bytes = NanPolicy::Accept.run(|| odd_nan().try_to_vec()).unwrap();
+    assert_eq!(bytes, odd_nan().to_bits().to_le_bytes());
+    let decoded = NanPolicy::Accept
+        .run(|| f64::try_from_slice(&bytes))
+        .unwrap();
+    assert_eq!(decoded.to_bits(), odd_nan().to_bits());
+    let decoded = DecodeOptions::from(Profile::Trusted)
+        .try_from_slice::<f64>(&bytes)
+        .unwrap();
+    assert!(DecodeOptions::trusted()
+        .try_from_slice::<f64>(&bytes)
+        .is_err());
+    assert_eq!(decoded.to_bits(), odd_nan().to_bits());
+}
+
+#[test]
+fn test_canonicalize() {
+    let canonicalize = DecodeOptions::default().nan_policy(NanPolicy::Canonicalize);
+    let bytes = canonicalize.run(|| odd_nan().try_to_vec()).unwrap();
+    assert_eq!(bytes, CANONICAL_NAN_F64.to_le_bytes());
+
+    let bytes = odd_nan().to_bits().to_le_bytes();
+    let decoded: f64 = canonicalize.try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.to_bits(), CANONICAL_NAN_F64);
+
+    let sample = Sample {
+        time: 7,
+        value: f32::from_bits(0xffc0_0001),
+    };
+    let bytes = NanPolicy::Accept.run(|| sample.try_to_vec()).unwrap();
+    let decoded: Sample = DecodeOptions::from(Profile::Lenient)
+        .try_from_slice(&bytes)
+        .unwrap();
+    assert_eq!(decoded.time, 7);
+    assert_eq!(decoded.value.to_bits(), CANONICAL_NAN_F32);
+    // Numbers are left alone.
+    assert_eq!(
+        canonicalize
+            .try_from_slice::<f32>(&[0, 0, 0xc0, 0x3f])
+            .unwrap(),
+        1.5
+    );
+}
+
+#[test]
+fn test_policy_is_scoped() {
+    let bytes = f32::NAN.to_bits().to_le_bytes();
+    NanPolicy::Accept.run(|| {
+        assert!(f32::try_from_slice(&bytes).is_ok());
+        NanPolicy::Reject.run(|| assert!(f32::try_from_slice(&bytes).is_err()));
+        assert!(f32::try_from_slice(&bytes).is_ok());
+    });
+    assert!(f32::try_from_slice(&bytes).is_err());
+}
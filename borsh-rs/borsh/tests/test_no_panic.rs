@@ -0,0 +1,141 @@
+//! Decoding arbitrary bytes must fail with an error, never panic: in a node that decodes input
+//! from peers, a panic takes the node down.
+//!
+//! Every decoder is run on all inputs of up to two bytes and on a deterministic set of longer
+//! inputs biased towards small length prefixes and boundary values, so that decoders get past
+//! their first few bytes.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::panic::catch_unwind;
+
+use oasis_borsh::de::options::Limits;
+use oasis_borsh::delta::DeltaVec;
+use oasis_borsh::header::from_slice_with_header;
+use oasis_borsh::schema::value;
+use oasis_borsh::stream::StreamedBytes;
+use oasis_borsh::{BorshDeserialize, BorshSchema};
+
+#[derive(BorshDeserialize, BorshSchema, Debug)]
+#[allow(dead_code)]
+struct Record {
+    id: u64,
+    kind: Kind,
+    #[borsh(len = "u8")]
+    name: String,
+    #[borsh_skip]
+    cache: u32,
+    tags: BTreeMap<String, Option<u8>>,
+    ratio: f32,
+    #[borsh(len = "u16")]
+    markers: Vec<()>,
+}
+
+#[derive(BorshDeserialize, BorshSchema, Debug)]
+#[allow(dead_code)]
+enum Kind {
+    Empty,
+    Pair(i16, bool),
+    Nested { children: Vec<Kind> },
+}
+
+#[derive(BorshDeserialize, PartialEq, Debug)]
+struct Unit;
+
+fn inputs() -> Vec<Vec<u8>> {
+    let mut inputs = vec![Vec::new()];
+    for a in 0..=255u8 {
+        inputs.push(vec![a]);
+        for b in 0..=255u8 {
+            inputs.push(vec![a, b]);
+        }
+    }
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..20_000 {
+        let len = (next() % 48) as usize;
+        let input = (0..len)
+            .map(|_| match next() % 8 {
+                0..=3 => 0,
+                4 => 1,
+                5 => 2,
+                6 => 0xff,
+                _ => next() as u8,
+            })
+            .collect();
+        inputs.push(input);
+    }
+    inputs
+}
+
+macro_rules! check_types {
+    ($inputs: expr; $($type: ty,)+) => {
+        for input in $inputs {
+            $(
+                let result = catch_unwind(|| {
+                    // Vectors of zero-sized values take no memory, but each element is decoded, so
+                    // a few bytes could keep a decoder busy for billions of them.
+                    let limits = Limits::NONE.max_elements(1 << 10);
+                    let _ = <$type>::try_from_slice_with_limits(input, limits);
+                });
+                assert!(
+                    result.is_ok(),
+                    "decoding {} panicked on {:?}",
+                    stringify!($type),
+                    input
+                );
+            )+
+        }
+    };
+}
+
+#[test]
+fn test_types_never_panic() {
+    check_types!(&inputs();
+        u8, u64, i128, f32, f64, bool, String, (),
+        Option<u64>, Option<String>, Result<u8, String>,
+        Vec<u8>, Vec<u64>, Vec<String>, Vec<Vec<u16>>, Vec<()>, Vec<Unit>, Vec<[u8; 0]>,
+        Box<[u8]>, [u32; 3], (u8, String, f64),
+        HashMap<u64, String>, HashSet<Vec<u8>>, BTreeMap<String, u32>, BTreeSet<u16>,
+        std::net::SocketAddr, std::net::Ipv6Addr,
+        std::ops::ControlFlow<u8, String>, std::task::Poll<u16>,
+        DeltaVec, StreamedBytes,
+        Record, Kind,
+    );
+}
+
+#[test]
+fn test_schema_decoding_never_panics() {
+    let containers = vec![
+        Record::schema_container(),
+        <Vec<Option<(u8, String)>>>::schema_container(),
+        <HashMap<u64, [u16; 2]>>::schema_container(),
+    ];
+    for input in &inputs() {
+        for container in &containers {
+            let result = catch_unwind(|| {
+                let _ = value::decode(container, input);
+                let _ = value::validate(container, input);
+            });
+            assert!(
+                result.is_ok(),
+                "decoding {} panicked on {:?}",
+                container.declaration,
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn test_headers_never_panic() {
+    for input in &inputs() {
+        let result = catch_unwind(|| {
+            let _ = from_slice_with_header::<Record>(input);
+        });
+        assert!(result.is_ok(), "decoding a header panicked on {:?}", input);
+    }
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "unicode-normalization")]
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+use oasis_borsh::de::options::{DecodeOptions, Normalization, Profile};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+/// "café" with the accent as a combining code point.
+const DECOMPOSED: &str = "cafe\u{301}";
+const COMPOSED: &str = "caf\u{e9}";
+
+#[test]
+fn test_keep_by_default() {
+    let bytes = DECOMPOSED.to_string().try_to_vec().unwrap();
+    assert_eq!(String::try_from_slice(&bytes).unwrap(), DECOMPOSED);
+    let options = DecodeOptions::default();
+    assert_eq!(options.normalization, Normalization::Keep);
+    assert_eq!(
+        options.try_from_slice::<String>(&bytes).unwrap(),
+        DECOMPOSED
+    );
+}
+
+#[test]
+fn test_nfc() {
+    let options = DecodeOptions::default().normalization(Normalization::Nfc);
+    let bytes = vec![DECOMPOSED.to_string(), COMPOSED.to_string()]
+        .try_to_vec()
+        .unwrap();
+    let decoded: Vec<String> = options.try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, vec![COMPOSED, COMPOSED]);
+    let decoded: Arc<str> = options.deserialize(&mut &bytes[4..]).unwrap();
+    assert_eq!(&*decoded, COMPOSED);
+    let decoded: Box<str> = options.try_from_slice(&bytes[4..14]).unwrap();
+    assert_eq!(&*decoded, COMPOSED);
+}
+
+#[test]
+fn test_require_nfc() {
+    let options = DecodeOptions::default().normalization(Normalization::RequireNfc);
+    let bytes = COMPOSED.to_string().try_to_vec().unwrap();
+    assert_eq!(options.try_from_slice::<String>(&bytes).unwrap(), COMPOSED);
+    let bytes = DECOMPOSED.to_string().try_to_vec().unwrap();
+    let err = options.try_from_slice::<String>(&bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "at offset 0: String is not in Unicode normalization form C"
+    );
+    // The setting only applies inside the call.
+    assert_eq!(String::try_from_slice(&bytes).unwrap(), DECOMPOSED);
+}
+
+#[test]
+fn test_profiles() {
+    let bytes = DECOMPOSED.to_string().try_to_vec().unwrap();
+    let err = DecodeOptions::from(Profile::Consensus)
+        .try_from_slice::<String>(&bytes)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    let decoded: String = DecodeOptions::from(Profile::Lenient)
+        .try_from_slice(&bytes)
+        .unwrap();
+    assert_eq!(decoded, COMPOSED);
+    let decoded: String = Profile::Trusted.options().try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, DECOMPOSED);
+}
@@ -0,0 +1,54 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use oasis_borsh::BorshSerialize;
+
+#[derive(BorshSerialize)]
+struct Owned {
+    name: String,
+    scores: Vec<u32>,
+    parent: Option<Box<u8>>,
+}
+
+#[derive(BorshSerialize)]
+struct Shared<'a> {
+    name: Arc<str>,
+    scores: Rc<[u32]>,
+    parent: Option<&'a mut u8>,
+}
+
+#[test]
+fn test_pointers_encode_like_pointee() {
+    let mut parent = 4;
+    let owned = Owned {
+        name: "node".to_string(),
+        scores: vec![1, 2],
+        parent: Some(Box::new(parent)),
+    };
+    let shared = Shared {
+        name: Arc::from("node"),
+        scores: Rc::from(vec![1, 2]),
+        parent: Some(&mut parent),
+    };
+    assert_eq!(shared.try_to_vec().unwrap(), owned.try_to_vec().unwrap());
+    assert_eq!(shared.serialized_size(), owned.serialized_size());
+    assert_eq!(shared.size_hint(), owned.size_hint());
+}
+
+#[test]
+fn test_containers_of_references() {
+    let values = ["a".to_string(), "bc".to_string()];
+    let refs = values.iter().collect::<Vec<&String>>();
+    assert_eq!(
+        refs.try_to_vec().unwrap(),
+        values.to_vec().try_to_vec().unwrap()
+    );
+
+    let rcs = vec![Rc::new(7u64), Rc::new(8)];
+    assert_eq!(
+        rcs.try_to_vec().unwrap(),
+        vec![7u64, 8].try_to_vec().unwrap()
+    );
+    let arc = Arc::new((1u8, "x".to_string()));
+    assert_eq!(arc.try_to_vec().unwrap(), (1u8, "x").try_to_vec().unwrap());
+}
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+
+use oasis_borsh::schema::proto::to_proto;
+use oasis_borsh::BorshSchema;
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Account {
+    owner: String,
+    balance: u128,
+    nonce: u16,
+    frozen: Option<bool>,
+    limits: BTreeMap<String, u64>,
+    key: [u8; 32],
+    status: Status,
+    history: Vec<Status>,
+    pairs: HashMap<(u8, u8), Vec<u64>>,
+    notes: Vec<Option<String>>,
+    #[borsh(len = "u8")]
+    tags: Vec<String>,
+    next: Option<Box<Account>>,
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+enum Status {
+    Active,
+    Frozen(u64, String),
+    Closed { at: u64 },
+}
+
+#[test]
+fn test_struct() {
+    let proto = to_proto(&Account::schema_container(), Some("bank.v1")).unwrap();
+    let expected = r#"// Generated from the borsh schema of Account.
+syntax = "proto3";
+
+package bank.v1;
+
+message Account {
+  string owner = 1;
+  bytes balance = 2;
+  uint32 nonce = 3;
+  optional bool frozen = 4;
+  map<string, uint64> limits = 5;
+  bytes key = 6;
+  Status status = 7;
+  repeated Status history = 8;
+  repeated Tuple_Tuple_u8_u8_Vec_u64 pairs = 9;
+  repeated Option_string notes = 10;
+  repeated string tags = 11;
+  optional Account next = 12;
+}
+
+message Option_string {
+  optional string value = 1;
+}
+
+message Status {
+  oneof variant {
+    StatusActive active = 1;
+    StatusFrozen frozen = 2;
+    StatusClosed closed = 3;
+  }
+}
+
+message StatusActive {}
+
+message StatusClosed {
+  uint64 at = 1;
+}
+
+message StatusFrozen {
+  uint64 field_0 = 1;
+  string field_1 = 2;
+}
+
+message Tuple_Tuple_u8_u8_Vec_u64 {
+  Tuple_u8_u8 field_0 = 1;
+  repeated uint64 field_1 = 2;
+}
+
+message Tuple_u8_u8 {
+  uint32 field_0 = 1;
+  uint32 field_1 = 2;
+}
+"#;
+    assert_eq!(proto, expected);
+}
+
+#[test]
+fn test_root_wrapper() {
+    let proto = to_proto(&<Vec<Option<u8>>>::schema_container(), None).unwrap();
+    let expected = r#"// Generated from the borsh schema of Vec<Option<u8>>.
+syntax = "proto3";
+
+message Option_u8 {
+  optional uint32 value = 1;
+}
+
+message Vec_Option_u8 {
+  repeated Option_u8 value = 1;
+}
+"#;
+    assert_eq!(proto, expected);
+}
+
+#[test]
+fn test_enum_variants() {
+    let proto = to_proto(&<Result<(), Vec<Vec<u8>>>>::schema_container(), None).unwrap();
+    let expected = r#"// Generated from the borsh schema of Result<nil, Vec<Vec<u8>>>.
+syntax = "proto3";
+
+message Nil {}
+
+message Result_nil_Vec_Vec_u8 {
+  oneof variant {
+    Nil ok = 1;
+    Vec_Vec_u8 err = 2;
+  }
+}
+
+message Vec_Vec_u8 {
+  repeated bytes value = 1;
+}
+"#;
+    assert_eq!(proto, expected);
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Nil {
+    x: u8,
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Clash {
+    a: Nil,
+    b: Result<(), u8>,
+}
+
+#[test]
+fn test_name_clash() {
+    let err = to_proto(&Clash::schema_container(), None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Type nil cannot be converted to protobuf: its message name Nil is taken by Nil"
+    );
+}
@@ -0,0 +1,68 @@
+use oasis_borsh::raw::RawBorsh;
+use oasis_borsh::schema::BorshSchemaContainer;
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Body {
+    Transfer { to: String, amount: u64 },
+    Burn(u64),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Envelope {
+    nonce: u64,
+    body: RawBorsh,
+    signature: [u8; 4],
+}
+
+#[test]
+fn test_forwarded_verbatim() {
+    let body = Body::Transfer {
+        to: "alice".to_string(),
+        amount: 10,
+    };
+    let envelope = Envelope {
+        nonce: 3,
+        body: RawBorsh::from_value(&body).unwrap(),
+        signature: [9; 4],
+    };
+    let bytes = envelope.try_to_vec().unwrap();
+    assert_eq!(
+        &bytes[8..12],
+        &(body.try_to_vec().unwrap().len() as u32).to_le_bytes()
+    );
+
+    let decoded = Envelope::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, envelope);
+    assert_eq!(decoded.body.decode::<Body>().unwrap(), body);
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+    let decoded = Envelope::deserialize(&mut &bytes[..]).unwrap();
+    assert_eq!(decoded.body.as_bytes(), &body.try_to_vec().unwrap()[..]);
+}
+
+#[test]
+fn test_unknown_bodies() {
+    // Bytes no type here decodes are still forwarded.
+    let raw = RawBorsh::new(vec![0xff, 0xfe]);
+    assert_eq!(raw.try_to_vec().unwrap(), [2, 0, 0, 0, 0xff, 0xfe]);
+    assert_eq!(raw.serialized_size(), 6);
+    assert!(raw.decode::<Body>().is_err());
+    assert_eq!(
+        RawBorsh::try_from_slice(&[2, 0, 0, 0, 0xff, 0xfe]).unwrap(),
+        raw
+    );
+    assert!(RawBorsh::try_from_slice(&[3, 0, 0, 0, 0xff, 0xfe]).is_err());
+    assert_eq!(raw.len(), 2);
+    assert_eq!(Vec::from(raw), [0xff, 0xfe]);
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(
+        RawBorsh::schema_container(),
+        BorshSchemaContainer {
+            declaration: Vec::<u8>::declaration(),
+            definitions: Vec::<u8>::schema_container().definitions,
+        }
+    );
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use oasis_borsh::{from_reader, to_writer, BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Snapshot {
+    height: u64,
+    balances: BTreeMap<String, u128>,
+}
+
+fn snapshot() -> Snapshot {
+    Snapshot {
+        height: 7,
+        balances: (0..100)
+            .map(|i| (format!("account{}", i), i * 1000))
+            .collect(),
+    }
+}
+
+/// Counts the writes reaching it, to check that they are buffered.
+struct CountingWriter {
+    bytes: Vec<u8>,
+    writes: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        self.bytes.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let value = snapshot();
+    let mut writer = CountingWriter {
+        bytes: Vec::new(),
+        writes: 0,
+    };
+    to_writer(&mut writer, &value).unwrap();
+    assert_eq!(writer.bytes, value.try_to_vec().unwrap());
+    assert_eq!(writer.writes, 1);
+
+    let decoded: Snapshot = from_reader(&writer.bytes[..]).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn test_trailing_bytes() {
+    let mut bytes = snapshot().try_to_vec().unwrap();
+    bytes.push(0);
+    let err = from_reader::<Snapshot, _>(&bytes[..]).unwrap_err();
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+
+    bytes.truncate(bytes.len() - 2);
+    let err = from_reader::<Snapshot, _>(&bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
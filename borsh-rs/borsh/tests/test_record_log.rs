@@ -0,0 +1,193 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use oasis_borsh::record_log::Recovered;
+use oasis_borsh::{BorshDeserialize, BorshSerialize, RecordLog};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Entry {
+    seq: u64,
+    key: String,
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("borsh-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn entry(seq: u64) -> Entry {
+    Entry {
+        seq,
+        key: format!("key-{}", seq),
+    }
+}
+
+#[test]
+fn test_append_and_replay() {
+    for &checksummed in &[false, true] {
+        let path = temp_path(&format!("replay-{}", checksummed));
+        {
+            let mut log = RecordLog::<Entry>::open(&path, checksummed).unwrap();
+            for seq in 0..3 {
+                log.append(&entry(seq)).unwrap();
+            }
+            log.sync().unwrap();
+        }
+        let mut log = RecordLog::<Entry>::open(&path, checksummed).unwrap();
+        log.append(&entry(3)).unwrap();
+        let records = log
+            .replay()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, (0..4).map(entry).collect::<Vec<_>>());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_mismatched_checksum_setting() {
+    let path = temp_path("mismatch");
+    RecordLog::<Entry>::open(&path, true).unwrap();
+    assert!(RecordLog::<Entry>::open(&path, false).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_truncate_torn_tail() {
+    let path = temp_path("torn");
+    let mut log = RecordLog::<Entry>::open(&path, false).unwrap();
+    log.append(&entry(0)).unwrap();
+    let torn_at = log.append(&entry(1)).unwrap();
+    let len = std::fs::metadata(&path).unwrap().len();
+    OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_len(len - 3)
+        .unwrap();
+
+    let err = log.replay().unwrap().nth(1).unwrap().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Truncated payload in record at offset {}", torn_at)
+    );
+    assert_eq!(log.truncate_at_corruption().unwrap(), 1);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), torn_at);
+
+    log.append(&entry(2)).unwrap();
+    let records = log
+        .replay()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records, vec![entry(0), entry(2)]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_truncate_checksum_mismatch() {
+    let path = temp_path("crc");
+    let mut log = RecordLog::<Entry>::open(&path, true).unwrap();
+    log.append(&entry(0)).unwrap();
+    log.append(&entry(1)).unwrap();
+    log.append(&entry(2)).unwrap();
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    // Flip the last byte of the final record's payload.
+    let len = file.metadata().unwrap().len();
+    file.set_len(len - 1).unwrap();
+    file.write_all(b"!").unwrap();
+
+    let replayed = log.replay().unwrap().collect::<Vec<_>>();
+    assert_eq!(replayed.len(), 3);
+    assert!(replayed[2]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .starts_with("Checksum mismatch"));
+    assert_eq!(log.truncate_at_corruption().unwrap(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Write `bytes` over the log at `offset`.
+fn overwrite(path: &PathBuf, offset: u64, bytes: &[u8]) {
+    let mut file = OpenOptions::new().write(true).open(path).unwrap();
+    file.seek(SeekFrom::Start(offset)).unwrap();
+    file.write_all(bytes).unwrap();
+}
+
+/// The records replayed with recovery, and the spans skipped as `(start, end)`.
+fn recover(log: &mut RecordLog<Entry>) -> (Vec<u64>, Vec<(u64, u64)>) {
+    let mut records = Vec::new();
+    let mut spans = Vec::new();
+    for recovered in log.replay_recovering().unwrap() {
+        match recovered.unwrap() {
+            Recovered::Record(entry) => records.push(entry.seq),
+            Recovered::Corrupt { start, end, .. } => spans.push((start, end)),
+        }
+    }
+    (records, spans)
+}
+
+#[test]
+fn test_recover_skips_corrupt_record() {
+    for &checksummed in &[false, true] {
+        let path = temp_path(&format!("recover-{}", checksummed));
+        let mut log = RecordLog::<Entry>::open(&path, checksummed).unwrap();
+        log.append(&entry(0)).unwrap();
+        let corrupt = log.append(&entry(1)).unwrap();
+        let next = log.append(&entry(2)).unwrap();
+        log.append(&entry(3)).unwrap();
+        // Make the length of the key overrun the payload.
+        let header_len = if checksummed { 8 } else { 4 };
+        overwrite(&path, corrupt + header_len + 8, &[0xff]);
+
+        assert_eq!(recover(&mut log), (vec![0, 2, 3], vec![(corrupt, next)]));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn test_recover_resyncs_after_corrupt_length() {
+    let path = temp_path("resync");
+    let mut log = RecordLog::<Entry>::open(&path, true).unwrap();
+    log.append(&entry(0)).unwrap();
+    let corrupt = log.append(&entry(1)).unwrap();
+    let next = log.append(&entry(2)).unwrap();
+    // A length that points inside the record, and one that points past the end of the log.
+    for len in &[3u32, u32::MAX] {
+        overwrite(&path, corrupt, &len.to_le_bytes());
+        assert_eq!(recover(&mut log), (vec![0, 2], vec![(corrupt, next)]));
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_recover_without_checksums_gives_up_on_length() {
+    let path = temp_path("recover-unchecked");
+    let mut log = RecordLog::<Entry>::open(&path, false).unwrap();
+    log.append(&entry(0)).unwrap();
+    let corrupt = log.append(&entry(1)).unwrap();
+    log.append(&entry(2)).unwrap();
+    overwrite(&path, corrupt, &u32::MAX.to_le_bytes());
+    let end = std::fs::metadata(&path).unwrap().len();
+
+    let mut replay = log.replay_recovering().unwrap();
+    assert!(matches!(replay.next(), Some(Ok(Recovered::Record(_)))));
+    match replay.next() {
+        Some(Ok(Recovered::Corrupt {
+            start,
+            end: span_end,
+            error,
+        })) => {
+            assert_eq!((start, span_end), (corrupt, end));
+            let message = format!("Truncated payload in record at offset {}", corrupt);
+            assert_eq!(error.to_string(), message);
+        }
+        other => panic!("expected a corrupt span, got {:?}", other),
+    }
+    assert!(replay.next().is_none());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,208 @@
+//! Self-referential types, which refer to themselves through `Option<Box<Self>>` or a collection.
+use std::collections::BTreeMap;
+
+use oasis_borsh::de::options::{DecodeOptions, Profile, DEFAULT_MAX_DEPTH};
+use oasis_borsh::schema::{fuzz, proto, value, Definition, Fields};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct List<T> {
+    value: T,
+    next: Option<Box<List<T>>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug, Default)]
+struct Trie {
+    terminal: bool,
+    children: BTreeMap<u8, Trie>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Expr {
+    Value(i64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+fn list(values: &[u32]) -> Option<Box<List<u32>>> {
+    values.split_first().map(|(value, rest)| {
+        Box::new(List {
+            value: *value,
+            next: list(rest),
+        })
+    })
+}
+
+fn trie(words: &[&str]) -> Trie {
+    let mut root = Trie::default();
+    for word in words {
+        let mut node = &mut root;
+        for byte in word.bytes() {
+            node = node.children.entry(byte).or_default();
+        }
+        node.terminal = true;
+    }
+    root
+}
+
+#[test]
+fn test_schema_terminates() {
+    let container = <List<u32>>::schema_container();
+    assert_eq!(container.declaration, "List<u32>");
+    assert_eq!(
+        container.definitions.get("List<u32>"),
+        Some(&Definition::Struct {
+            fields: Fields::NamedFields(vec![
+                ("value".to_string(), "u32".to_string()),
+                ("next".to_string(), "Option<List<u32>>".to_string()),
+            ])
+        })
+    );
+    assert_eq!(
+        container.definitions.get("Option<List<u32>>"),
+        Some(&Definition::Enum {
+            variants: vec![
+                ("None".to_string(), "nil".to_string()),
+                ("Some".to_string(), "List<u32>".to_string()),
+            ]
+        })
+    );
+    assert!(container.is_recursive());
+    assert!(Trie::schema_container().is_recursive());
+    assert!(Expr::schema_container().is_recursive());
+    assert!(!<Option<Box<u8>>>::schema_container().is_recursive());
+    assert!(!<(Vec<u8>, Vec<u8>)>::schema_container().is_recursive());
+}
+
+#[test]
+fn test_round_trip() {
+    let list = list(&[1, 2, 3]).unwrap();
+    let bytes = list.try_to_vec().unwrap();
+    assert_eq!(list.serialized_size(), bytes.len());
+    assert_eq!(<List<u32>>::try_from_slice(&bytes).unwrap(), *list);
+    value::validate(&<List<u32>>::schema_container(), &bytes).unwrap();
+
+    let trie = trie(&["a", "ab", "abc", "b"]);
+    let bytes = trie.try_to_vec().unwrap();
+    assert_eq!(trie.serialized_size(), bytes.len());
+    assert_eq!(Trie::try_from_slice(&bytes).unwrap(), trie);
+    value::validate(&Trie::schema_container(), &bytes).unwrap();
+
+    let expr = Expr::Add(
+        Box::new(Expr::Value(1)),
+        Box::new(Expr::Neg(Box::new(Expr::Value(2)))),
+    );
+    let bytes = expr.try_to_vec().unwrap();
+    assert_eq!(expr.serialized_size(), bytes.len());
+    assert_eq!(Expr::try_from_slice(&bytes).unwrap(), expr);
+    value::validate(&Expr::schema_container(), &bytes).unwrap();
+}
+
+#[test]
+fn test_schema_consumers() {
+    for container in &[
+        <List<u32>>::schema_container(),
+        Trie::schema_container(),
+        Expr::schema_container(),
+    ] {
+        for input in fuzz::seed_corpus(container, 8).unwrap() {
+            value::validate(container, &input).unwrap();
+        }
+        assert!(!fuzz::dictionary(container).is_empty());
+        assert!(proto::to_proto(container, None).is_ok());
+    }
+    let proto = proto::to_proto(&Trie::schema_container(), None).unwrap();
+    assert!(proto.contains("map<uint32, Trie> children = 2;"));
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Node {
+    name: Box<str>,
+    children: Box<[Node]>,
+}
+
+#[test]
+fn test_boxed_slices() {
+    let tree = Node {
+        name: "root".into(),
+        children: vec![
+            Node {
+                name: "a".into(),
+                children: Box::new([]),
+            },
+            Node {
+                name: "b".into(),
+                children: vec![Node {
+                    name: "c".into(),
+                    children: Box::new([]),
+                }]
+                .into_boxed_slice(),
+            },
+        ]
+        .into_boxed_slice(),
+    };
+    let bytes = tree.try_to_vec().unwrap();
+    assert_eq!(Node::try_from_slice(&bytes).unwrap(), tree);
+    assert_eq!(Node::deserialize(&mut bytes.as_slice()).unwrap(), tree);
+    assert_eq!(
+        (String::from("root"), 2u32).try_to_vec().unwrap(),
+        bytes[..12]
+    );
+    assert_eq!(<Box<str>>::declaration(), "string");
+}
+
+/// The encoding of a `List<u32>` of `len` nodes, built without recursing.
+fn deep_list(len: usize) -> Vec<u8> {
+    let mut bytes = [0, 0, 0, 0, 1].repeat(len);
+    let last = bytes.len() - 1;
+    bytes[last] = 0;
+    bytes
+}
+
+fn too_deep(err: std::io::Error) -> usize {
+    match Error::from(err).root_cause() {
+        Error::TooDeep { limit } => *limit,
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_depth_limit() {
+    let decoded = <List<u32>>::try_from_slice(&deep_list(DEFAULT_MAX_DEPTH)).unwrap();
+    assert_eq!(decoded.value, 0);
+    let err = <List<u32>>::try_from_slice(&deep_list(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+    assert_eq!(too_deep(err), DEFAULT_MAX_DEPTH);
+    // Far deeper than the stack would allow.
+    let err = <List<u32>>::try_from_slice(&deep_list(1_000_000)).unwrap_err();
+    assert_eq!(too_deep(err), DEFAULT_MAX_DEPTH);
+    let mut reader = &deep_list(1_000_000)[..];
+    let err = <List<u32>>::deserialize(&mut reader).unwrap_err();
+    assert_eq!(too_deep(err), DEFAULT_MAX_DEPTH);
+
+    // Each `Neg` is a level, and the `Value` inside is one more.
+    let mut bytes = vec![1; 100];
+    bytes.push(0);
+    bytes.extend_from_slice(&7i64.to_le_bytes());
+    let options = DecodeOptions::default().max_depth(100);
+    let err = options.try_from_slice::<Expr>(&bytes).unwrap_err();
+    assert_eq!(too_deep(err), 100);
+    assert!(options
+        .max_depth(101)
+        .try_from_slice::<Expr>(&bytes)
+        .is_ok());
+    let err = DecodeOptions::from(Profile::Embedded)
+        .try_from_slice::<Expr>(&bytes)
+        .unwrap_err();
+    assert_eq!(too_deep(err), 64);
+}
+
+#[test]
+fn test_depth_is_restored_after_errors() {
+    assert!(<List<u32>>::try_from_slice(&deep_list(DEFAULT_MAX_DEPTH + 1)).is_err());
+    // A failed decode leaves no levels behind.
+    assert!(<List<u32>>::try_from_slice(&deep_list(DEFAULT_MAX_DEPTH)).is_ok());
+    let mut truncated = deep_list(DEFAULT_MAX_DEPTH);
+    truncated.pop();
+    assert!(<List<u32>>::try_from_slice(&truncated).is_err());
+    assert!(<List<u32>>::try_from_slice(&deep_list(DEFAULT_MAX_DEPTH)).is_ok());
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use oasis_borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+macro_rules! map(
+    () => { HashMap::new() };
+    { $($key:expr => $value:expr),+ } => {
+        {
+            let mut m = HashMap::new();
+            $(
+                m.insert($key.to_string(), $value);
+            )+
+            m
+        }
+     };
+);
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct A {
+    x: u64,
+    y: String,
+    #[borsh_skip]
+    z: Vec<u8>,
+}
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+struct Pair(u8, Option<u32>);
+
+#[derive(BorshSchema)]
+#[allow(dead_code)]
+enum B<T> {
+    X,
+    Y(T),
+    Z { t: Vec<T> },
+}
+
+#[test]
+fn test_primitives() {
+    assert_eq!(
+        u64::schema_container(),
+        BorshSchemaContainer {
+            declaration: "u64".to_string(),
+            definitions: map!()
+        }
+    );
+    assert_eq!(String::declaration(), "string");
+    assert_eq!(<[u8; 32]>::declaration(), "Array<u8, 32>");
+    assert_eq!(<HashMap<u8, String>>::declaration(), "HashMap<u8, string>");
+}
+
+#[test]
+fn test_collections() {
+    let expected = map! {
+        "HashMap<u8, string>" => Definition::Map { entries: "Tuple<u8, string>".to_string() },
+        "Tuple<u8, string>" => Definition::Tuple { elements: vec!["u8".to_string(), "string".to_string()] }
+    };
+    assert_eq!(
+        <HashMap<u8, String>>::schema_container().definitions,
+        expected
+    );
+    let expected = map! {
+        "BTreeSet<u16>" => Definition::Set { elements: "u16".to_string() }
+    };
+    assert_eq!(
+        <std::collections::BTreeSet<u16>>::schema_container().definitions,
+        expected
+    );
+}
+
+#[test]
+fn test_named_struct() {
+    let expected = map! {
+        "A" => Definition::Struct {
+            fields: Fields::NamedFields(vec![
+                ("x".to_string(), "u64".to_string()),
+                ("y".to_string(), "string".to_string()),
+            ])
+        }
+    };
+    assert_eq!(
+        A::schema_container(),
+        BorshSchemaContainer {
+            declaration: "A".to_string(),
+            definitions: expected
+        }
+    );
+}
+
+#[test]
+fn test_unnamed_struct() {
+    let expected = map! {
+        "Pair" => Definition::Struct { fields: Fields::UnnamedFields(vec!["u8".to_string(), "Option<u32>".to_string()]) },
+        "Option<u32>" => Definition::Enum { variants: vec![("None".to_string(), "nil".to_string()), ("Some".to_string(), "u32".to_string())] }
+    };
+    assert_eq!(Pair::schema_container().definitions, expected);
+}
+
+#[test]
+fn test_generic_enum() {
+    let expected = map! {
+        "B<u16>" => Definition::Enum {
+            variants: vec![
+                ("X".to_string(), "BX<u16>".to_string()),
+                ("Y".to_string(), "BY<u16>".to_string()),
+                ("Z".to_string(), "BZ<u16>".to_string()),
+            ]
+        },
+        "BX<u16>" => Definition::Struct { fields: Fields::Empty },
+        "BY<u16>" => Definition::Struct { fields: Fields::UnnamedFields(vec!["u16".to_string()]) },
+        "BZ<u16>" => Definition::Struct { fields: Fields::NamedFields(vec![("t".to_string(), "Vec<u16>".to_string())]) },
+        "Vec<u16>" => Definition::Sequence { elements: "u16".to_string() }
+    };
+    let container = <B<u16>>::schema_container();
+    assert_eq!(container.declaration, "B<u16>");
+    assert_eq!(container.definitions, expected);
+}
+
+#[test]
+fn test_container_roundtrip() {
+    let container = <B<A>>::schema_container();
+    let encoded = container.try_to_vec().unwrap();
+    assert_eq!(
+        BorshSchemaContainer::try_from_slice(&encoded).unwrap(),
+        container
+    );
+}
@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+
+use oasis_borsh::schema::value::{self, Value};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+struct Event {
+    id: u64,
+    kind: Kind,
+    tags: BTreeMap<String, u8>,
+    digest: [u8; 4],
+    parent: Option<u32>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Debug)]
+enum Kind {
+    Created,
+    Moved(i16, i16),
+}
+
+fn event() -> Event {
+    let mut tags = BTreeMap::new();
+    tags.insert("a".to_string(), 1);
+    Event {
+        id: 7,
+        kind: Kind::Moved(-1, 2),
+        tags,
+        digest: [1, 2, 3, 4],
+        parent: None,
+    }
+}
+
+#[test]
+fn test_decode() {
+    let container = Event::schema_container();
+    let decoded = value::decode(&container, &event().try_to_vec().unwrap()).unwrap();
+    let expected = Value::Struct(vec![
+        Value::U64(7),
+        Value::Enum {
+            variant: 1,
+            value: Box::new(Value::Struct(vec![Value::I16(-1), Value::I16(2)])),
+        },
+        Value::Sequence(vec![Value::Tuple(vec![
+            Value::String("a".to_string()),
+            Value::U8(1),
+        ])]),
+        Value::Array(vec![Value::U8(1), Value::U8(2), Value::U8(3), Value::U8(4)]),
+        Value::Enum {
+            variant: 0,
+            value: Box::new(Value::Nil),
+        },
+    ]);
+    assert_eq!(decoded, expected);
+    assert_eq!(
+        value::encode(&container, &decoded).unwrap(),
+        event().try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_decode_errors() {
+    let container = Kind::schema_container();
+    assert_eq!(
+        value::decode(&container, &[2]).unwrap_err().to_string(),
+        "Unexpected variant index: 2"
+    );
+    assert_eq!(
+        value::decode(&container, &[0, 0]).unwrap_err().to_string(),
+        "Not all bytes read"
+    );
+    assert_eq!(
+        value::decode(&bool::schema_container(), &[2])
+            .unwrap_err()
+            .to_string(),
+        "Invalid bool value: 2"
+    );
+}
+
+#[test]
+fn test_encode_mismatch() {
+    let container = Kind::schema_container();
+    let err = value::encode(&container, &Value::U8(0)).unwrap_err();
+    assert_eq!(err.to_string(), "Value U8(0) does not match type Kind");
+}
+
+#[test]
+fn test_validate_reports_path_and_offset() {
+    let container = Event::schema_container();
+    let mut bytes = event().try_to_vec().unwrap();
+    assert!(value::validate(&container, &bytes).is_ok());
+    // Variant index of `kind`, right after the `u64` id.
+    bytes[8] = 9;
+    let invalid = value::validate(&container, &bytes).unwrap_err();
+    assert_eq!(invalid.offset, 8);
+    assert_eq!(invalid.path, "Event.kind");
+    let truncated = event().try_to_vec().unwrap();
+    let invalid = value::validate(&container, &truncated[..14]).unwrap_err();
+    assert_eq!(invalid.path, "Event.tags");
+    assert_eq!(invalid.offset, 13);
+}
+
+#[derive(BorshSerialize, BorshSchema, PartialEq, Eq, PartialOrd, Ord)]
+enum Slot {
+    Free,
+    Taken { owner: u128, since: i64 },
+}
+
+#[test]
+fn test_validate_key_order() {
+    // Numeric order, which differs from the byte-wise order of the little-endian encodings.
+    let balances: BTreeMap<u128, u8> = vec![(1, 0), (256, 1), (u128::MAX, 2)].into_iter().collect();
+    let container = <BTreeMap<u128, u8>>::schema_container();
+    assert!(value::validate(&container, &balances.try_to_vec().unwrap()).is_ok());
+
+    let signed: BTreeMap<i128, ()> = vec![(-1, ()), (0, ()), (1, ())].into_iter().collect();
+    let container = <BTreeMap<i128, ()>>::schema_container();
+    assert!(value::validate(&container, &signed.try_to_vec().unwrap()).is_ok());
+
+    let slots: BTreeMap<(u8, Slot), bool> = vec![
+        (
+            (
+                0,
+                Slot::Taken {
+                    owner: 2,
+                    since: -5,
+                },
+            ),
+            true,
+        ),
+        ((1, Slot::Free), false),
+        ((1, Slot::Taken { owner: 1, since: 0 }), true),
+        (
+            (
+                1,
+                Slot::Taken {
+                    owner: 256,
+                    since: -1,
+                },
+            ),
+            true,
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let container = <BTreeMap<(u8, Slot), bool>>::schema_container();
+    assert!(value::validate(&container, &slots.try_to_vec().unwrap()).is_ok());
+}
+
+#[test]
+fn test_validate_rejects_unordered_keys() {
+    let container = <BTreeMap<u128, u8>>::schema_container();
+    // Byte-wise ascending, numerically descending.
+    let unordered = vec![(256u128, 0u8), (1, 1)].try_to_vec().unwrap();
+    assert!(value::decode(&container, &unordered).is_ok());
+    let invalid = value::validate(&container, &unordered).unwrap_err();
+    assert_eq!(invalid.path, "BTreeMap<u128, u8>[1]");
+    assert_eq!(invalid.offset, 4 + 17);
+    assert_eq!(
+        invalid.error.to_string(),
+        "Keys of BTreeMap<u128, u8> are not in ascending order"
+    );
+
+    let duplicate = vec![(1u128, 0u8), (1, 1)].try_to_vec().unwrap();
+    let invalid = value::validate(&container, &duplicate).unwrap_err();
+    assert_eq!(
+        invalid.error.to_string(),
+        "Duplicate key in BTreeMap<u128, u8>"
+    );
+
+    let container = <std::collections::HashSet<(i128, String)>>::schema_container();
+    let unordered = vec![(0i128, "b".to_string()), (0, "a".to_string())]
+        .try_to_vec()
+        .unwrap();
+    let invalid = value::validate(&container, &unordered).unwrap_err();
+    assert_eq!(invalid.path, "HashSet<Tuple<i128, string>>[1]");
+
+    // Sequences other than maps and sets may be in any order.
+    let container = <Vec<u128>>::schema_container();
+    assert!(value::validate(&container, &vec![256u128, 1].try_to_vec().unwrap()).is_ok());
+}
+
+#[test]
+fn test_validate_order_follows_definition() {
+    // A map under a name that does not say so is still checked.
+    let mut container = <BTreeMap<u128, u8>>::schema_container();
+    let definition = container
+        .definitions
+        .remove(&container.declaration)
+        .unwrap();
+    container.declaration = "Balances".to_string();
+    container
+        .definitions
+        .insert(container.declaration.clone(), definition);
+    let unordered = vec![(256u128, 0u8), (1, 1)].try_to_vec().unwrap();
+    let invalid = value::validate(&container, &unordered).unwrap_err();
+    assert_eq!(invalid.path, "Balances[1]");
+
+    // A sequence named like a map is not.
+    let mut container = <Vec<(u128, u8)>>::schema_container();
+    let definition = container
+        .definitions
+        .remove(&container.declaration)
+        .unwrap();
+    container.declaration = "BTreeMap<u128, u8>".to_string();
+    container
+        .definitions
+        .insert(container.declaration.clone(), definition);
+    assert!(value::validate(&container, &unordered).is_ok());
+}
+
+#[test]
+fn test_display() {
+    let container = Event::schema_container();
+    let decoded = value::decode(&container, &event().try_to_vec().unwrap()).unwrap();
+    assert_eq!(
+        decoded.display(&container).to_string(),
+        r#"Event { id: 7, kind: Moved(-1, 2), tags: {"a": 1}, digest: 0x01020304, parent: None }"#
+    );
+    assert_eq!(
+        format!("{:#}", decoded.display(&container)),
+        r#"Event {
+    id: 7,
+    kind: Moved(
+        -1,
+        2,
+    ),
+    tags: {
+        "a": 1,
+    },
+    digest: 0x01020304,
+    parent: None,
+}"#
+    );
+
+    let container = <(Vec<Slot>, Vec<u8>, f32)>::schema_container();
+    let bytes = (
+        vec![
+            Slot::Free,
+            Slot::Taken {
+                owner: 1,
+                since: -2,
+            },
+        ],
+        Vec::<u8>::new(),
+        1f32,
+    )
+        .try_to_vec()
+        .unwrap();
+    let decoded = value::decode(&container, &bytes).unwrap();
+    assert_eq!(
+        decoded.display(&container).to_string(),
+        "([Free, Taken { owner: 1, since: -2 }], 0x, 1.0)"
+    );
+}
+
+#[test]
+fn test_display_mismatch() {
+    let container = Event::schema_container();
+    let value = Value::Struct(vec![Value::U64(7)]);
+    assert_eq!(value.display(&container).to_string(), "Struct([U64(7)])");
+    let container = <Option<u8>>::schema_container();
+    let value = Value::Enum {
+        variant: 1,
+        value: Box::new(Value::Tuple(vec![])),
+    };
+    assert_eq!(value.display(&container).to_string(), "Some(Tuple([]))");
+}
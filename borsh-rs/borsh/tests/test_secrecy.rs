@@ -0,0 +1,32 @@
+#![cfg(feature = "secrecy")]
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use secrecy::{ExposeSecret, SecretString, SecretVec};
+
+#[derive(BorshDeserialize, BorshSchema)]
+struct Credentials {
+    user: String,
+    password: SecretString,
+    key: SecretVec<u8>,
+}
+
+#[test]
+fn test_decode_secrets() {
+    let bytes = ("alice".to_string(), "hunter2".to_string(), vec![1u8, 2, 3])
+        .try_to_vec()
+        .unwrap();
+    let credentials = Credentials::try_from_slice(&bytes).unwrap();
+    assert_eq!(credentials.user, "alice");
+    assert_eq!(credentials.password.expose_secret(), "hunter2");
+    assert_eq!(credentials.key.expose_secret(), &vec![1, 2, 3]);
+    assert_eq!(<SecretString>::declaration(), "string");
+}
+
+#[cfg(feature = "secrecy-serialize")]
+#[test]
+fn test_serialize_secrets() {
+    let secret = SecretString::new("hunter2".to_string());
+    assert_eq!(
+        secret.try_to_vec().unwrap(),
+        "hunter2".to_string().try_to_vec().unwrap()
+    );
+}
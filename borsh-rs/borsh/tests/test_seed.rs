@@ -0,0 +1,143 @@
+use std::io::{Error, ErrorKind, Read};
+
+use oasis_borsh::{BorshDeserialize, BorshDeserializeSeed, BorshSerialize};
+
+/// Accounts interned by id: the wire carries ids, decoded values hold indices into `accounts`.
+#[derive(Default)]
+struct Registry {
+    accounts: Vec<String>,
+}
+
+#[derive(PartialEq, Debug)]
+struct AccountRef(usize);
+
+impl BorshDeserializeSeed<Registry> for AccountRef {
+    fn deserialize_seed<R: Read>(registry: &mut Registry, reader: &mut R) -> Result<Self, Error> {
+        let name = String::deserialize(reader)?;
+        if name.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "Empty account name"));
+        }
+        let index = match registry.accounts.iter().position(|known| *known == name) {
+            Some(index) => index,
+            None => {
+                registry.accounts.push(name);
+                registry.accounts.len() - 1
+            }
+        };
+        Ok(AccountRef(index))
+    }
+}
+
+#[derive(BorshDeserializeSeed, PartialEq, Debug)]
+#[borsh_seed(Registry)]
+struct Transfer {
+    #[borsh(seed)]
+    from: AccountRef,
+    #[borsh(seed)]
+    to: AccountRef,
+    amount: u64,
+    #[borsh_skip]
+    note: String,
+}
+
+#[derive(BorshDeserializeSeed, PartialEq, Debug)]
+enum Instruction {
+    Transfer(#[borsh(seed)] Transfer),
+    #[borsh(alias_tag = 7)]
+    Close {
+        #[borsh(seed)]
+        accounts: Vec<AccountRef>,
+    },
+    Noop,
+}
+
+/// Generic over the seed, since it only passes it on.
+#[derive(BorshDeserializeSeed, PartialEq, Debug)]
+#[borsh_init(count)]
+struct Batch {
+    #[borsh(seed)]
+    instructions: Vec<Instruction>,
+    #[borsh_skip]
+    len: usize,
+}
+
+impl Batch {
+    fn count(&mut self) {
+        self.len = self.instructions.len();
+    }
+}
+
+#[derive(BorshSerialize)]
+enum WireInstruction {
+    Transfer(String, String, u64),
+    Close(Vec<String>),
+    Noop,
+}
+
+fn transfer(from: usize, to: usize, amount: u64) -> Instruction {
+    Instruction::Transfer(Transfer {
+        from: AccountRef(from),
+        to: AccountRef(to),
+        amount,
+        note: String::new(),
+    })
+}
+
+#[test]
+fn test_seed_is_threaded_through_fields() {
+    let bytes = vec![
+        WireInstruction::Transfer("alice".to_string(), "bob".to_string(), 5),
+        WireInstruction::Noop,
+        WireInstruction::Transfer("bob".to_string(), "carol".to_string(), 2),
+        WireInstruction::Close(vec!["carol".to_string(), "alice".to_string()]),
+    ]
+    .try_to_vec()
+    .unwrap();
+    let mut registry = Registry::default();
+    let batch = Batch::deserialize_seed(&mut registry, &mut &bytes[..]).unwrap();
+    assert_eq!(registry.accounts, vec!["alice", "bob", "carol"]);
+    assert_eq!(
+        batch,
+        Batch {
+            instructions: vec![
+                transfer(0, 1, 5),
+                Instruction::Noop,
+                transfer(1, 2, 2),
+                Instruction::Close {
+                    accounts: vec![AccountRef(2), AccountRef(0)],
+                },
+            ],
+            len: 4,
+        }
+    );
+}
+
+#[test]
+fn test_seeded_enum_tags() {
+    let mut bytes = WireInstruction::Close(vec!["dave".to_string()])
+        .try_to_vec()
+        .unwrap();
+    bytes[0] = 7;
+    let mut registry = Registry::default();
+    let decoded = Instruction::deserialize_seed(&mut registry, &mut &bytes[..]).unwrap();
+    assert_eq!(
+        decoded,
+        Instruction::Close {
+            accounts: vec![AccountRef(0)]
+        }
+    );
+    let err = Instruction::deserialize_seed(&mut registry, &mut &[3u8][..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_seeded_errors_name_the_field() {
+    let bytes = WireInstruction::Transfer("alice".to_string(), String::new(), 1)
+        .try_to_vec()
+        .unwrap();
+    let err = Instruction::deserialize_seed(&mut Registry::default(), &mut &bytes[..]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error deserializing Instruction::Transfer.0.to: Empty account name"
+    );
+}
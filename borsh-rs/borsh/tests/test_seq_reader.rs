@@ -0,0 +1,58 @@
+use std::io::ErrorKind;
+
+use oasis_borsh::de::SeqReader;
+use oasis_borsh::{BorshSerialize, Error};
+
+#[test]
+fn test_yields_elements_in_order() {
+    let values = vec!["a".to_string(), "bc".to_string(), String::new()];
+    let mut bytes = values.try_to_vec().unwrap();
+    bytes.push(9);
+    let mut seq = SeqReader::<String, _>::new(&bytes[..]).unwrap();
+    assert_eq!(seq.len(), 3);
+    assert_eq!(seq.size_hint(), (0, Some(3)));
+    assert_eq!(seq.next().unwrap().unwrap(), "a");
+    assert_eq!(seq.remaining(), 2);
+    let rest = seq.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(rest, ["bc", ""]);
+    assert!(seq.next().is_none());
+    assert_eq!(seq.into_inner(), [9]);
+}
+
+#[test]
+fn test_empty() {
+    let bytes = Vec::<u8>::new().try_to_vec().unwrap();
+    let mut seq = SeqReader::<u8, _>::new(&bytes[..]).unwrap();
+    assert!(seq.is_empty());
+    assert!(seq.next().is_none());
+}
+
+#[test]
+fn test_error_ends_iteration() {
+    let mut bytes = vec![1u32, 2, 3].try_to_vec().unwrap();
+    bytes.truncate(10);
+    let results = SeqReader::<u32, _>::new(&bytes[..])
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert_eq!(*results[0].as_ref().unwrap(), 1);
+    let err = results[1].as_ref().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.to_string(),
+        "error deserializing [1]: unexpected end of input reading u32: wanted 4 bytes, 2 available"
+    );
+}
+
+#[test]
+fn test_missing_length() {
+    let err = SeqReader::<u8, _>::new(&[1u8, 0][..]).err().unwrap();
+    assert!(matches!(
+        Error::from(err),
+        Error::Truncated {
+            what: "length prefix",
+            wanted: 4,
+            available: 2
+        }
+    ));
+}
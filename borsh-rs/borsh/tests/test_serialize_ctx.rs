@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+use std::io::{Error, Write};
+
+use oasis_borsh::{BorshSerialize, BorshSerializeCtx};
+
+/// Counts how often each label is written; labels are encoded as plain strings.
+#[derive(Default)]
+struct LabelStats(BTreeMap<String, usize>);
+
+#[derive(BorshSerialize, Clone)]
+struct Label(String);
+
+impl BorshSerializeCtx<LabelStats> for Label {
+    fn serialize_with_ctx<W: Write>(
+        &self,
+        writer: &mut W,
+        stats: &mut LabelStats,
+    ) -> Result<(), Error> {
+        *stats.0.entry(self.0.clone()).or_default() += 1;
+        self.serialize(writer)
+    }
+}
+
+#[derive(BorshSerialize, BorshSerializeCtx)]
+#[borsh_ctx(LabelStats)]
+struct Metric {
+    #[borsh(order = 1)]
+    value: u64,
+    #[borsh(ctx, order = 0)]
+    label: Label,
+    #[borsh_skip]
+    #[allow(dead_code)]
+    cached: Option<String>,
+}
+
+/// Generic over the context, since it only passes it on.
+#[derive(BorshSerialize, BorshSerializeCtx)]
+enum Sample {
+    Empty,
+    Single(#[borsh(ctx)] Metric),
+    Labelled {
+        #[borsh(ctx)]
+        labels: Vec<Label>,
+        #[borsh_skip]
+        #[allow(dead_code)]
+        seen: bool,
+        #[borsh(ctx)]
+        extra: Option<Box<Metric>>,
+    },
+}
+
+fn metric(label: &str, value: u64) -> Metric {
+    Metric {
+        value,
+        label: Label(label.to_string()),
+        cached: None,
+    }
+}
+
+#[test]
+fn test_ctx_records_while_serializing() {
+    let samples = vec![
+        Sample::Single(metric("cpu", 3)),
+        Sample::Empty,
+        Sample::Labelled {
+            labels: vec![Label("cpu".to_string()), Label("mem".to_string())],
+            seen: true,
+            extra: Some(Box::new(metric("mem", 4))),
+        },
+    ];
+    let mut stats = LabelStats::default();
+    let bytes = samples.try_to_vec_with_ctx(&mut stats).unwrap();
+    // A context that only observes leaves the encoding as it is.
+    assert_eq!(bytes, samples.try_to_vec().unwrap());
+    let counts = stats.0.into_iter().collect::<Vec<_>>();
+    assert_eq!(counts, vec![("cpu".to_string(), 2), ("mem".to_string(), 2)]);
+}
+
+#[test]
+fn test_ctx_follows_field_order() {
+    let mut stats = LabelStats::default();
+    let mut bytes = Vec::new();
+    metric("io", 9)
+        .serialize_with_ctx(&mut bytes, &mut stats)
+        .unwrap();
+    assert_eq!(bytes, ("io".to_string(), 9u64).try_to_vec().unwrap());
+}
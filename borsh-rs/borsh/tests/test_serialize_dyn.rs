@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize, BorshSerializeDyn};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Transfer {
+    to: String,
+    amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Message {
+    Ping,
+    Transfer(Transfer),
+}
+
+#[test]
+fn test_heterogeneous() {
+    let transfer = Transfer {
+        to: "alice".to_string(),
+        amount: 5,
+    };
+    let expected = (7u32, transfer, Message::Ping, vec![1u8, 2])
+        .try_to_vec()
+        .unwrap();
+
+    let transfer = Transfer {
+        to: "alice".to_string(),
+        amount: 5,
+    };
+    let values: Vec<Box<dyn BorshSerializeDyn>> = vec![
+        Box::new(7u32),
+        Box::new(transfer),
+        Box::new(Message::Ping),
+        Box::new(vec![1u8, 2]),
+    ];
+    let mut buf = Vec::new();
+    for value in &values {
+        value.serialize_dyn(&mut buf).unwrap();
+    }
+    assert_eq!(buf, expected);
+
+    let sizes: usize = values.iter().map(|value| value.serialized_size_dyn()).sum();
+    assert_eq!(sizes, expected.len());
+    assert_eq!(values[0].size_hint_dyn(), 4);
+}
+
+#[test]
+fn test_trait_objects_serialize() {
+    let value: &(dyn BorshSerializeDyn + Send + Sync) = &Message::Ping;
+    assert_eq!(value.try_to_vec().unwrap(), [0]);
+    assert_eq!(value.serialized_size(), 1);
+
+    let mut map: BTreeMap<u8, Arc<dyn BorshSerializeDyn + Send + Sync>> = BTreeMap::new();
+    map.insert(1, Arc::new("x".to_string()));
+    map.insert(2, Arc::new(Some(3u16)));
+    let bytes = map.try_to_vec().unwrap();
+    assert_eq!(bytes, [2, 0, 0, 0, 1, 1, 0, 0, 0, b'x', 2, 1, 3, 0]);
+
+    let boxed: Box<dyn BorshSerializeDyn + Send> = Box::new(Transfer {
+        to: "bob".to_string(),
+        amount: 9,
+    });
+    let decoded = Transfer::try_from_slice(&boxed.try_to_vec().unwrap()).unwrap();
+    assert_eq!(decoded.to, "bob");
+}
+
+#[test]
+fn test_writer_errors() {
+    let value: Box<dyn BorshSerializeDyn> = Box::new(1u64);
+    let mut buf = [0u8; 4];
+    let err = value.serialize_dyn(&mut &mut buf[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+}
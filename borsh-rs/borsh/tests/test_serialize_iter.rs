@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+
+use oasis_borsh::ser::{
+    serialize_exact_iter, serialize_exact_map_iter, serialize_iter, serialize_map_iter,
+};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[test]
+fn test_matches_vec() {
+    let rows = vec!["a".to_string(), "bc".to_string()];
+    let mut bytes = Vec::new();
+    serialize_iter(rows.len(), rows.iter(), &mut bytes).unwrap();
+    assert_eq!(bytes, rows.try_to_vec().unwrap());
+    assert_eq!(Vec::<String>::try_from_slice(&bytes).unwrap(), rows);
+}
+
+#[test]
+fn test_exact_iter() {
+    let map: BTreeMap<u8, u32> = (0..4).map(|k| (k, u32::from(k) * 7)).collect();
+    let mut bytes = Vec::new();
+    serialize_exact_iter(map.iter(), &mut bytes).unwrap();
+    assert_eq!(bytes, map.try_to_vec().unwrap());
+}
+
+#[test]
+fn test_empty() {
+    let mut bytes = Vec::new();
+    serialize_exact_iter(std::iter::empty::<u64>(), &mut bytes).unwrap();
+    assert_eq!(bytes, [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_wrong_len() {
+    let mut bytes = Vec::new();
+    let err = serialize_iter(3, 0..2u8, &mut bytes).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = serialize_iter(1, 0..2u8, &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_map_matches_btree_map() {
+    let map: BTreeMap<String, Vec<u16>> = (0..5u16)
+        .map(|n| (format!("key{}", n), vec![n; n as usize]))
+        .collect();
+    let mut bytes = Vec::new();
+    serialize_map_iter(map.len(), map.iter(), &mut bytes).unwrap();
+    assert_eq!(bytes, map.try_to_vec().unwrap());
+    assert_eq!(
+        BTreeMap::<String, Vec<u16>>::try_from_slice(&bytes).unwrap(),
+        map
+    );
+
+    let mut bytes = Vec::new();
+    serialize_exact_map_iter((1..4u32).map(|k| (k, k % 2 == 0)), &mut bytes).unwrap();
+    let map: BTreeMap<u32, bool> = (1..4).map(|k| (k, k % 2 == 0)).collect();
+    assert_eq!(bytes, map.try_to_vec().unwrap());
+}
+
+#[test]
+fn test_map_errors() {
+    let err = serialize_map_iter(2, vec![(2u8, ()), (1, ())], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = serialize_map_iter(2, vec![(1u8, 10u8), (1, 20)], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = serialize_map_iter(3, vec![(1u8, ())], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = serialize_map_iter(0, vec![(1u8, ())], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
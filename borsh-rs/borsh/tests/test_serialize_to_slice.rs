@@ -0,0 +1,84 @@
+use oasis_borsh::maybestd::io::ErrorKind;
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Command {
+    Reset,
+    Move { x: i16, y: i16, label: String },
+}
+
+#[test]
+fn test_serialize_to_slice() {
+    let command = Command::Move {
+        x: -3,
+        y: 4,
+        label: "home".to_string(),
+    };
+    let mut buf = [0xffu8; 32];
+    let len = command.serialize_to_slice(&mut buf).unwrap();
+    assert_eq!(len, command.serialized_size());
+    assert_eq!(&buf[..len], &command.try_to_vec().unwrap()[..]);
+    assert!(buf[len..].iter().all(|&byte| byte == 0xff));
+    assert_eq!(Command::try_from_slice(&buf[..len]).unwrap(), command);
+
+    assert_eq!(Command::Reset.serialize_to_slice(&mut buf).unwrap(), 1);
+    assert_eq!(().serialize_to_slice(&mut []).unwrap(), 0);
+}
+
+#[test]
+fn test_buffer_too_small() {
+    let command = Command::Move {
+        x: 1,
+        y: 2,
+        label: "far".to_string(),
+    };
+    let size = command.serialized_size();
+    let mut buf = vec![0u8; size];
+    assert_eq!(command.serialize_to_slice(&mut buf).unwrap(), size);
+    let err = command
+        .serialize_to_slice(&mut buf[..size - 1])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+}
+
+#[test]
+fn test_serialize_into() {
+    let mut buf = Vec::new();
+    let commands = [
+        Command::Reset,
+        Command::Move {
+            x: 0,
+            y: 9,
+            label: "up".to_string(),
+        },
+    ];
+    for command in &commands {
+        command.serialize_into(&mut buf).unwrap();
+    }
+    let mut expected = commands[0].try_to_vec().unwrap();
+    expected.extend(commands[1].try_to_vec().unwrap());
+    assert_eq!(buf, expected);
+
+    // Cleared, the buffer is reused without growing.
+    let capacity = buf.capacity();
+    buf.clear();
+    commands[1].serialize_into(&mut buf).unwrap();
+    assert_eq!(buf, commands[1].try_to_vec().unwrap());
+    assert_eq!(buf.capacity(), capacity);
+}
+
+#[test]
+fn test_serialize_into_failure() {
+    struct Failing;
+
+    impl BorshSerialize for Failing {
+        fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+            writer.write_all(&[1, 2, 3])?;
+            Err(std::io::Error::other("failed"))
+        }
+    }
+
+    let mut buf = vec![9];
+    assert!((5u8, Failing).serialize_into(&mut buf).is_err());
+    assert_eq!(buf, vec![9]);
+}
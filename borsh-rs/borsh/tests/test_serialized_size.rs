@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use oasis_borsh::ser::SizeWriter;
+use oasis_borsh::{serialized_len, BorshSerialize};
+
+#[derive(BorshSerialize)]
+#[allow(dead_code)]
+struct Account {
+    id: u64,
+    owner: String,
+    #[borsh_skip]
+    cache: Vec<u8>,
+    #[borsh(len = "u8")]
+    tags: Vec<String>,
+    limits: HashMap<String, u32>,
+    parent: Option<Box<Account>>,
+    kind: Kind,
+}
+
+#[derive(BorshSerialize)]
+enum Kind {
+    Empty,
+    Pair(u8, #[borsh_skip] u64, i128),
+    Named {
+        #[borsh(order = 1)]
+        x: String,
+        #[borsh(order = 0)]
+        y: [u16; 3],
+    },
+}
+
+#[derive(BorshSerialize)]
+struct Wrapper<T>(T, u16);
+
+#[derive(BorshSerialize)]
+#[borsh_memcpy]
+#[repr(C)]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+/// Only implements `serialize`, so its size is counted by serializing it.
+struct Opaque(Vec<u8>);
+
+impl BorshSerialize for Opaque {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+fn check<T: BorshSerialize>(value: &T) {
+    assert_eq!(value.serialized_size(), value.try_to_vec().unwrap().len());
+}
+
+#[test]
+fn test_std_types() {
+    check(&());
+    check(&7u8);
+    check(&-7i128);
+    check(&1.5f32);
+    check(&true);
+    check(&"hello".to_string());
+    check(&"hello");
+    check(&Some(5u64));
+    check(&None::<u64>);
+    check(&Ok::<u8, String>(1));
+    check(&Err::<u8, String>("no".to_string()));
+    check(&vec![1u16, 2, 3]);
+    check(&vec!["a".to_string(), "bc".to_string()]);
+    check(&vec![1u8, 2].into_boxed_slice());
+    check(&[7u32; 4]);
+    check(&(1u8, "x".to_string(), 2.0f64));
+    check(&[1u64, 2].iter().cloned().collect::<HashSet<_>>());
+    check(
+        &[(1u64, "a".to_string())]
+            .iter()
+            .cloned()
+            .collect::<BTreeMap<_, _>>(),
+    );
+    check(&"127.0.0.1:80".parse::<std::net::SocketAddr>().unwrap());
+    check(&"[::1]:80".parse::<std::net::SocketAddr>().unwrap());
+    check(&std::task::Poll::Ready(3u32));
+    check(&std::ops::ControlFlow::<u8, String>::Break(3));
+}
+
+#[test]
+fn test_derived() {
+    let mut limits = HashMap::new();
+    limits.insert("daily".to_string(), 100);
+    let parent = Account {
+        id: 1,
+        owner: "root".to_string(),
+        cache: vec![1, 2, 3],
+        tags: vec![],
+        limits: HashMap::new(),
+        parent: None,
+        kind: Kind::Empty,
+    };
+    let account = Account {
+        id: 2,
+        owner: "alice".to_string(),
+        cache: vec![4; 100],
+        tags: vec!["a".to_string(), "bcd".to_string()],
+        limits,
+        parent: Some(Box::new(parent)),
+        kind: Kind::Named {
+            x: "x".to_string(),
+            y: [1, 2, 3],
+        },
+    };
+    check(&account);
+    check(&Kind::Pair(1, 2, 3));
+    check(&Wrapper(vec![Kind::Empty], 5));
+    check(&Point { x: 1, y: 2 });
+}
+
+#[test]
+fn test_counted() {
+    check(&Opaque(vec![0; 13]));
+    check(&vec![Opaque(vec![1]), Opaque(vec![2, 3])]);
+}
+
+#[test]
+fn test_size_writer() {
+    let values = (Kind::Pair(1, 2, 3), "header".to_string(), vec![7u64; 9]);
+    let len = values.try_to_vec().unwrap().len();
+    assert_eq!(serialized_len(&values).unwrap(), len);
+
+    let mut writer = SizeWriter::new();
+    values.serialize(&mut writer).unwrap();
+    values.0.serialize(&mut writer).unwrap();
+    assert_eq!(writer.written(), len + values.0.try_to_vec().unwrap().len());
+    assert_eq!(serialized_len(&[0u8; 0][..]).unwrap(), 4);
+}
@@ -1,6 +1,5 @@
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
 use std::collections::{HashMap, HashSet};
-use oasis_borsh::{BorshSerialize, BorshDeserialize};
-
 
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
 #[borsh_init(init)]
@@ -57,7 +56,11 @@ fn test_simple_struct() {
     set.insert(std::u64::MAX);
     let a = A {
         x: 1,
-        b: B { x: 2, y: 3, c: C::C5(D { x: 1 }) },
+        b: B {
+            x: 2,
+            y: 3,
+            c: C::C5(D { x: 1 }),
+        },
         y: 4.0,
         z: "123".to_string(),
         t: ("Hello".to_string(), 10),
@@ -73,7 +76,11 @@ fn test_simple_struct() {
     let decoded_a = A::try_from_slice(&encoded_a).unwrap();
     let expected_a = A {
         x: 1,
-        b: B { x: 2, y: 3, c: C::C5(D { x: 1 }) },
+        b: B {
+            x: 2,
+            y: 3,
+            c: C::C5(D { x: 1 }),
+        },
         y: 4.0,
         z: a.z,
         t: ("Hello".to_string(), 10),
@@ -88,3 +95,67 @@ fn test_simple_struct() {
 
     assert_eq!(expected_a, decoded_a);
 }
+
+#[derive(Default, PartialEq, Debug)]
+struct Cache(u32);
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Wrapper(u64, #[borsh_skip] Cache, String);
+
+#[test]
+fn test_tuple_struct_skip() {
+    let wrapper = Wrapper(7, Cache(3), "x".to_string());
+    let encoded = wrapper.try_to_vec().unwrap();
+    assert_eq!(encoded, (7u64, "x".to_string()).try_to_vec().unwrap());
+    assert_eq!(
+        Wrapper::try_from_slice(&encoded).unwrap(),
+        Wrapper(7, Cache::default(), "x".to_string())
+    );
+}
+
+fn unknown() -> String {
+    "unknown".to_string()
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Event {
+    Tuple(
+        u8,
+        #[borsh_skip] Cache,
+        #[borsh_skip(default = "unknown")] String,
+    ),
+    Named {
+        x: u8,
+        #[borsh_skip]
+        cache: Cache,
+        #[borsh_skip(default = "unknown")]
+        source: String,
+    },
+}
+
+#[test]
+fn test_enum_variant_skip() {
+    let tuple = Event::Tuple(1, Cache(5), "local".to_string());
+    let encoded = tuple.try_to_vec().unwrap();
+    assert_eq!(encoded, vec![0, 1]);
+    assert_eq!(
+        Event::try_from_slice(&encoded).unwrap(),
+        Event::Tuple(1, Cache(0), "unknown".to_string())
+    );
+
+    let named = Event::Named {
+        x: 2,
+        cache: Cache(5),
+        source: "local".to_string(),
+    };
+    let encoded = named.try_to_vec().unwrap();
+    assert_eq!(encoded, vec![1, 2]);
+    assert_eq!(
+        Event::try_from_slice(&encoded).unwrap(),
+        Event::Named {
+            x: 2,
+            cache: Cache(0),
+            source: "unknown".to_string()
+        }
+    );
+}
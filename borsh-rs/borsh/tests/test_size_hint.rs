@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, HashSet};
+
+use oasis_borsh::tagged::Tagged;
+use oasis_borsh::BorshSerialize;
+
+#[derive(BorshSerialize)]
+struct Fixed {
+    id: u64,
+    flags: [bool; 3],
+    key: Tagged<Fixed, 32>,
+    pair: (u8, i128),
+    #[borsh_skip]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+}
+
+#[derive(BorshSerialize)]
+enum Update {
+    Clear,
+    Set(Fixed),
+    Rename {
+        #[borsh(len = "u8")]
+        name: String,
+    },
+}
+
+#[derive(BorshSerialize)]
+struct State {
+    height: u64,
+    accounts: Vec<Fixed>,
+    names: BTreeMap<u32, String>,
+    updates: Vec<Update>,
+}
+
+/// Only implements `serialize`, so nothing is known about its size.
+struct Opaque;
+
+impl BorshSerialize for Opaque {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&[1, 2, 3])
+    }
+}
+
+fn fixed(id: u64) -> Fixed {
+    Fixed {
+        id,
+        flags: [true; 3],
+        key: Tagged::new([id as u8; 32]),
+        pair: (1, -1),
+        cache: vec![0; 10],
+    }
+}
+
+fn assert_exact<T: BorshSerialize>(value: &T) {
+    assert_eq!(value.size_hint(), value.try_to_vec().unwrap().len());
+}
+
+#[test]
+fn test_exact() {
+    assert_exact(&0u32);
+    assert_exact(&1.5f64);
+    assert_exact(&[7u16; 5]);
+    assert_exact(&(1u8, 2u64, true));
+    assert_exact(&Some(3i8));
+    assert_exact(&"hello".to_string());
+    assert_exact(&vec![1u64, 2, 3]);
+    assert_exact(&vec![0u8; 100]);
+    assert_exact(&[1u32, 2].iter().cloned().collect::<HashSet<_>>());
+    assert_exact(&fixed(1));
+    assert_exact(&Update::Set(fixed(2)));
+    assert_exact(&Update::Rename {
+        name: "x".to_string(),
+    });
+    assert_exact(&vec![fixed(1), fixed(2)]);
+}
+
+#[test]
+fn test_estimate() {
+    // Collections extrapolate from their first element.
+    let names = vec!["a".to_string(), "bcdef".to_string()];
+    assert_eq!(names.size_hint(), 4 + 2 * (4 + 1));
+    let updates = vec![Update::Clear, Update::Set(fixed(1))];
+    assert_eq!(updates.size_hint(), 4 + 2);
+    assert!(updates.size_hint() < updates.try_to_vec().unwrap().len());
+    // Types without a hint count as empty.
+    assert_eq!(Opaque.size_hint(), 0);
+    assert_eq!(vec![Opaque, Opaque].size_hint(), 4);
+}
+
+#[test]
+fn test_try_to_vec_preallocates() {
+    let mut names = BTreeMap::new();
+    names.insert(1, "one".to_string());
+    let state = State {
+        height: 10,
+        accounts: (0..1000).map(fixed).collect(),
+        names,
+        updates: vec![],
+    };
+    let hint = state.size_hint();
+    let bytes = state.try_to_vec().unwrap();
+    assert_eq!(hint, bytes.len());
+    assert_eq!(bytes.capacity(), hint);
+}
+
+#[test]
+fn test_overshooting_hint_is_capped() {
+    // The hint extrapolates the first, large element to all the empty ones.
+    let mut chunks = vec![vec![7u8; 1 << 16]];
+    chunks.resize(1 << 10, Vec::new());
+    assert_eq!(chunks.size_hint(), 4 + (1 << 10) * (4 + (1 << 16)));
+    let bytes = chunks.try_to_vec().unwrap();
+    assert_eq!(bytes.len(), 4 + (1 << 10) * 4 + (1 << 16));
+    assert_eq!(bytes.capacity(), 1 << 20);
+    let mut buf = Vec::new();
+    chunks.serialize_into(&mut buf).unwrap();
+    assert_eq!(buf, bytes);
+    assert_eq!(buf.capacity(), 1 << 20);
+}
+
+/// Hints at half of `usize::MAX`, which 32-bit targets reach with far smaller collections.
+struct Huge;
+
+impl BorshSerialize for Huge {
+    fn serialize<W: std::io::Write>(&self, _writer: &mut W) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        usize::MAX / 2
+    }
+}
+
+#[derive(BorshSerialize)]
+struct Pair {
+    first: Huge,
+    second: Huge,
+    tag: u8,
+}
+
+#[test]
+fn test_hint_saturates() {
+    assert_eq!(vec![Huge, Huge, Huge].size_hint(), usize::MAX);
+    assert_eq!([Huge, Huge, Huge].size_hint(), usize::MAX);
+    assert_eq!(Some(vec![Huge, Huge]).size_hint(), usize::MAX);
+    assert_eq!((Huge, Huge, 1u8).size_hint(), usize::MAX);
+    let pair = Pair {
+        first: Huge,
+        second: Huge,
+        tag: 1,
+    };
+    assert_eq!(pair.size_hint(), usize::MAX);
+    assert_eq!(pair.try_to_vec().unwrap(), [1]);
+}
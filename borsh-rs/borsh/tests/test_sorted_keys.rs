@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use oasis_borsh::de::options::{DecodeOptions, Profile};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Registry {
+    #[borsh(len = "u8")]
+    owners: BTreeMap<u8, u8>,
+    #[borsh(len = "u8")]
+    admins: BTreeSet<u8>,
+}
+
+fn sorted() -> DecodeOptions {
+    DecodeOptions::default().require_sorted_keys(true)
+}
+
+fn is_unsorted(err: std::io::Error) -> bool {
+    matches!(Error::from(err).root_cause(), Error::UnsortedKeys)
+}
+
+#[test]
+fn test_unsorted_keys_are_accepted_by_default() {
+    let bytes = vec![(2u8, 20u8), (1, 10)].try_to_vec().unwrap();
+    let map = BTreeMap::<u8, u8>::try_from_slice(&bytes).unwrap();
+    assert_eq!(map.try_to_vec().unwrap(), [2, 0, 0, 0, 1, 10, 2, 20]);
+}
+
+#[test]
+fn test_maps() {
+    let bytes = vec![(2u8, 20u8), (1, 10)].try_to_vec().unwrap();
+    let err = sorted()
+        .try_from_slice::<BTreeMap<u8, u8>>(&bytes)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 6: keys of map or set are not in ascending order"
+    );
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let mut place = BTreeMap::new();
+    let err = sorted()
+        .run(|| BTreeMap::<u8, u8>::deserialize_in_place(&mut &bytes[..], &mut place))
+        .unwrap_err();
+    assert!(is_unsorted(err));
+
+    // Equal keys are not ascending either.
+    let bytes = vec![(1u8, 10u8), (1, 10)].try_to_vec().unwrap();
+    assert!(is_unsorted(
+        sorted()
+            .try_from_slice::<BTreeMap<u8, u8>>(&bytes)
+            .unwrap_err()
+    ));
+
+    let map: BTreeMap<u8, u8> = (0..10).map(|i| (i, i)).collect();
+    let bytes = map.try_to_vec().unwrap();
+    assert_eq!(
+        sorted().try_from_slice::<BTreeMap<u8, u8>>(&bytes).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn test_sets() {
+    let bytes = vec![1u8, 3, 2].try_to_vec().unwrap();
+    assert!(is_unsorted(
+        sorted().try_from_slice::<BTreeSet<u8>>(&bytes).unwrap_err()
+    ));
+    let mut place = BTreeSet::new();
+    let err = sorted()
+        .run(|| BTreeSet::<u8>::deserialize_in_place(&mut &bytes[..], &mut place))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error deserializing [2]: keys of map or set are not in ascending order"
+    );
+    // Hash sets have no order to check.
+    assert_eq!(
+        sorted()
+            .try_from_slice::<HashSet<u8>>(&bytes)
+            .unwrap()
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_custom_length_prefixes() {
+    let registry = Registry {
+        owners: (0..3).map(|i| (i, i)).collect(),
+        admins: (0..3).collect(),
+    };
+    let bytes = registry.try_to_vec().unwrap();
+    assert_eq!(
+        sorted().try_from_slice::<Registry>(&bytes).unwrap(),
+        registry
+    );
+
+    let bytes = [2, 5, 0, 4, 0, 0];
+    assert!(is_unsorted(
+        sorted().try_from_slice::<Registry>(&bytes).unwrap_err()
+    ));
+    let bytes = [0, 2, 5, 4];
+    assert!(is_unsorted(
+        sorted().try_from_slice::<Registry>(&bytes).unwrap_err()
+    ));
+}
+
+#[test]
+fn test_consensus_profile() {
+    assert!(Profile::Consensus.options().require_sorted_keys);
+    assert!(!Profile::Trusted.options().require_sorted_keys);
+}
+
+#[test]
+fn test_wide_and_composite_keys() {
+    // Numeric order, which differs from the byte-wise order of the little-endian encodings.
+    let map: BTreeMap<u128, u8> = vec![(1, 0), (256, 1), (u128::MAX, 2)].into_iter().collect();
+    let bytes = map.try_to_vec().unwrap();
+    assert_eq!(
+        sorted()
+            .try_from_slice::<BTreeMap<u128, u8>>(&bytes)
+            .unwrap(),
+        map
+    );
+    let bytes = vec![(256u128, 0u8), (1, 1)].try_to_vec().unwrap();
+    assert!(is_unsorted(
+        sorted()
+            .try_from_slice::<BTreeMap<u128, u8>>(&bytes)
+            .unwrap_err()
+    ));
+
+    let set: BTreeSet<(i128, String)> = vec![(-1, "b".to_string()), (0, "a".to_string())]
+        .into_iter()
+        .collect();
+    let bytes = set
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>()
+        .try_to_vec()
+        .unwrap();
+    assert_eq!(
+        sorted()
+            .try_from_slice::<BTreeSet<(i128, String)>>(&bytes)
+            .unwrap(),
+        set
+    );
+    let bytes = vec![(0i128, "b".to_string()), (0, "a".to_string())]
+        .try_to_vec()
+        .unwrap();
+    assert!(is_unsorted(
+        sorted()
+            .try_from_slice::<BTreeSet<(i128, String)>>(&bytes)
+            .unwrap_err()
+    ));
+}
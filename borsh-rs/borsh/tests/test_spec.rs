@@ -0,0 +1,66 @@
+use oasis_borsh::spec::{self, Endianness, Spec};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[test]
+fn test_constants_match_the_encoding() {
+    assert_eq!(true.try_to_vec().unwrap(), [spec::BOOL_TRUE]);
+    assert_eq!(false.try_to_vec().unwrap(), [spec::BOOL_FALSE]);
+    assert_eq!(None::<u8>.try_to_vec().unwrap(), [spec::OPTION_NONE_TAG]);
+    assert_eq!(Some(7u8).try_to_vec().unwrap(), [spec::OPTION_SOME_TAG, 7]);
+    assert_eq!(
+        Ok::<u8, u8>(7).try_to_vec().unwrap(),
+        [spec::RESULT_OK_TAG, 7]
+    );
+    assert_eq!(
+        Err::<u8, u8>(7).try_to_vec().unwrap(),
+        [spec::RESULT_ERR_TAG, 7]
+    );
+    let bytes = "abc".try_to_vec().unwrap();
+    assert_eq!(bytes.len(), spec::LENGTH_PREFIX_SIZE + 3);
+    assert_eq!(0x0102_0304u32.try_to_vec().unwrap(), [4, 3, 2, 1]);
+    assert_eq!(spec::ENDIANNESS, Endianness::Little);
+}
+
+#[test]
+fn test_constants_match_the_decoding() {
+    assert!(bool::try_from_slice(&[spec::BOOL_TRUE]).unwrap());
+    assert!(!bool::try_from_slice(&[spec::BOOL_FALSE]).unwrap());
+    assert_eq!(
+        Option::<u8>::try_from_slice(&[spec::OPTION_SOME_TAG, 7]).unwrap(),
+        Some(7)
+    );
+    assert_eq!(
+        Result::<u8, u8>::try_from_slice(&[spec::RESULT_ERR_TAG, 7]).unwrap(),
+        Err(7)
+    );
+}
+
+#[test]
+fn test_lengths() {
+    let format = Spec::CURRENT;
+    assert_eq!(format, Spec::default());
+    let prefix = format.encode_length(3).unwrap();
+    assert_eq!(
+        prefix[..],
+        "abc".try_to_vec().unwrap()[..spec::LENGTH_PREFIX_SIZE]
+    );
+    assert_eq!(format.decode_length(prefix), 3);
+    assert_eq!(
+        format.decode_length(format.encode_length(u32::MAX as usize).unwrap()),
+        spec::MAX_LENGTH
+    );
+    assert_eq!(format.encode_length(u32::MAX as usize + 1), None);
+
+    let big = Spec {
+        endianness: Endianness::Big,
+        ..format
+    };
+    assert_eq!(big.encode_length(3).unwrap(), [0, 0, 0, 3]);
+    assert_eq!(big.decode_length([0, 0, 1, 0]), 256);
+}
+
+#[test]
+fn test_variant_count() {
+    assert_eq!(spec::ENUM_TAG_SIZE, 1);
+    assert_eq!(spec::MAX_VARIANTS, 256);
+}
@@ -0,0 +1,122 @@
+use std::io::Cursor;
+
+use oasis_borsh::de::options::Limits;
+use oasis_borsh::stream::{from_reader_streaming, StreamedBytes};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Upload {
+    name: String,
+    blob: StreamedBytes,
+    checksum: u32,
+}
+
+fn upload(len: usize) -> Upload {
+    upload_with((0..len).map(|i| i as u8).collect())
+}
+
+fn upload_with(data: Vec<u8>) -> Upload {
+    Upload {
+        name: "disk.img".to_string(),
+        blob: StreamedBytes::from(data),
+        checksum: 42,
+    }
+}
+
+#[test]
+fn test_buffered_without_sink() {
+    let bytes = upload(100).try_to_vec().unwrap();
+    assert_eq!(
+        bytes,
+        (
+            "disk.img".to_string(),
+            (0..100u8).collect::<Vec<u8>>(),
+            42u32
+        )
+            .try_to_vec()
+            .unwrap()
+    );
+    let decoded = Upload::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, upload(100));
+    assert!(!decoded.blob.is_streamed());
+}
+
+#[test]
+fn test_streamed_into_sink() {
+    let original = upload(100_000);
+    let bytes = original.try_to_vec().unwrap();
+    let mut sink = Vec::new();
+    let decoded: Upload = from_reader_streaming(&mut Cursor::new(&bytes), &mut sink).unwrap();
+    assert_eq!(sink, original.blob.as_bytes().unwrap());
+    assert!(decoded.blob.is_streamed());
+    assert_eq!(decoded.blob.len(), 100_000);
+    assert_eq!(decoded.blob.as_bytes(), None);
+    assert_eq!(decoded.checksum, 42);
+    assert!(decoded.try_to_vec().is_err());
+}
+
+#[test]
+fn test_truncated_stream() {
+    let bytes = upload(1000).try_to_vec().unwrap();
+    let mut sink = Vec::new();
+    let result: Result<Upload, _> =
+        from_reader_streaming(&mut Cursor::new(&bytes[..500]), &mut sink);
+    assert!(result.is_err());
+    assert!(Upload::try_from_slice(&bytes[..500]).is_err());
+}
+
+#[test]
+fn test_serialize_from_source() {
+    let data: Vec<u8> = (0..50_000).map(|i| (i % 251) as u8).collect();
+    let value = Upload {
+        name: "disk.img".to_string(),
+        blob: StreamedBytes::from_reader(Cursor::new(data.clone()), data.len() as u32),
+        checksum: 42,
+    };
+    let mut out = Vec::new();
+    value.serialize(&mut out).unwrap();
+    assert_eq!(out, upload_with(data).try_to_vec().unwrap());
+    // The source is consumed by the first serialization.
+    assert!(value.try_to_vec().is_err());
+}
+
+#[test]
+fn test_short_source() {
+    let blob = StreamedBytes::from_reader(Cursor::new(vec![1u8; 10]), 11);
+    assert_eq!(blob.len(), 11);
+    assert!(blob.try_to_vec().is_err());
+}
+
+#[test]
+fn test_size_before_serializing_from_source() {
+    let data = vec![7u8; 1000];
+    let value = Upload {
+        name: "disk.img".to_string(),
+        blob: StreamedBytes::from_reader(Cursor::new(data.clone()), data.len() as u32),
+        checksum: 42,
+    };
+    let expected = upload_with(data).try_to_vec().unwrap();
+    assert_eq!(value.serialized_size(), expected.len());
+    assert_eq!(value.blob.size_hint(), 4 + 1000);
+    // Sizing leaves the source for the actual serialization.
+    assert_eq!(value.try_to_vec().unwrap(), expected);
+}
+
+#[test]
+fn test_limits_apply_to_streamed_bytes() {
+    let bytes = upload(100).try_to_vec().unwrap();
+    let limits = Limits::NONE.max_sequence_len(99);
+    let err = Upload::try_from_slice_with_limits(&bytes, limits).unwrap_err();
+    match Error::from(err).root_cause() {
+        Error::TooLong { what, len, limit } => {
+            assert_eq!((*what, *len, *limit), ("sequence", 100, 99))
+        }
+        err => panic!("unexpected error: {}", err),
+    }
+    let limits = Limits::NONE.max_sequence_len(100);
+    let mut sink = Vec::new();
+    let decoded: Upload = limits
+        .run(|| from_reader_streaming(&mut Cursor::new(&bytes), &mut sink))
+        .unwrap();
+    assert_eq!(decoded.blob.len(), 100);
+}
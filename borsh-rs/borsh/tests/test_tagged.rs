@@ -0,0 +1,72 @@
+use std::collections::{BTreeSet, HashMap};
+
+use oasis_borsh::diff::{apply, diff};
+use oasis_borsh::tagged::Tagged;
+use oasis_borsh::{BorshDeserialize, BorshDiff, BorshSchema, BorshSerialize};
+
+enum Block {}
+struct Key;
+
+type BlockHash = Tagged<Block, 32>;
+type PublicKey = Tagged<Key, 4>;
+
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    BorshDiff,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Debug,
+    Default,
+)]
+struct Header {
+    parent: BlockHash,
+    proposer: PublicKey,
+    height: u64,
+}
+
+#[test]
+fn test_encoded_as_array() {
+    let key = PublicKey::new([1, 2, 3, 4]);
+    assert_eq!(
+        key.try_to_vec().unwrap(),
+        [1u8, 2, 3, 4].try_to_vec().unwrap()
+    );
+    assert_eq!(key.serialized_size(), 4);
+    assert_eq!(PublicKey::try_from_slice(&[1, 2, 3, 4]).unwrap(), key);
+    assert!(PublicKey::try_from_slice(&[1, 2, 3]).is_err());
+    assert_eq!(PublicKey::declaration(), <[u8; 4]>::declaration());
+    assert_eq!(format!("{:?}", key), "01020304");
+}
+
+#[test]
+fn test_derive_without_marker_bounds() {
+    let header = Header {
+        parent: BlockHash::new([7; 32]),
+        proposer: [9, 9, 9, 9].into(),
+        height: 3,
+    };
+    let encoded = header.try_to_vec().unwrap();
+    assert_eq!(encoded.len(), 32 + 4 + 8);
+    assert_eq!(Header::try_from_slice(&encoded).unwrap(), header);
+
+    let mut next = header.clone();
+    next.height = 4;
+    let delta = diff(&header, &next).unwrap();
+    let mut applied = header.clone();
+    apply(&mut applied, &delta).unwrap();
+    assert_eq!(applied, next);
+
+    let mut by_parent = HashMap::new();
+    by_parent.insert(header.parent, header.clone());
+    assert_eq!(by_parent[&BlockHash::new([7; 32])], header);
+    let ordered = vec![PublicKey::new([2; 4]), PublicKey::new([1; 4])]
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    assert_eq!(ordered.into_iter().next(), Some(PublicKey::new([1; 4])));
+}
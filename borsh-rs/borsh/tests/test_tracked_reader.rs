@@ -0,0 +1,122 @@
+use std::io::{self, BufRead, Read};
+
+use oasis_borsh::de::TrackedReader;
+use std::collections::BTreeMap;
+
+use oasis_borsh::de::options::DecodeOptions;
+use oasis_borsh::{from_reader, BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Entry {
+    key: String,
+    value: u32,
+}
+
+fn offset_of(err: io::Error) -> u64 {
+    match Error::from(err) {
+        Error::At { offset, .. } => offset,
+        err => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_counts_reads() {
+    let entries = vec![
+        Entry {
+            key: "a".to_string(),
+            value: 1,
+        },
+        Entry {
+            key: "bcd".to_string(),
+            value: 2,
+        },
+    ];
+    let mut bytes = Vec::new();
+    for entry in &entries {
+        entry.serialize(&mut bytes).unwrap();
+    }
+    let mut reader = TrackedReader::new(&bytes[..]);
+    assert_eq!(reader.decode::<Entry>().unwrap(), entries[0]);
+    assert_eq!(reader.offset(), 9);
+    assert_eq!(reader.decode::<Entry>().unwrap(), entries[1]);
+    assert_eq!(reader.offset(), bytes.len() as u64);
+    assert!(reader.into_inner().is_empty());
+}
+
+#[test]
+fn test_counts_consumed() {
+    let mut reader = TrackedReader::new(io::BufReader::new(&[1u8, 2, 3, 4][..]));
+    assert_eq!(reader.fill_buf().unwrap(), [1, 2, 3, 4]);
+    reader.consume(3);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, [4]);
+    assert_eq!(reader.offset(), 4);
+}
+
+#[test]
+fn test_error_offsets() {
+    // Cut into the string of the second entry.
+    let mut bytes = Entry {
+        key: "a".to_string(),
+        value: 1,
+    }
+    .try_to_vec()
+    .unwrap();
+    bytes.extend_from_slice(&[3, 0, 0, 0, b'b']);
+
+    let err = <(Entry, Entry)>::try_from_slice(&bytes).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at offset 9: error deserializing Entry.key: unexpected end of input reading string: wanted 3 bytes, 1 available"
+    );
+    assert!(matches!(
+        Error::from(err).root_cause(),
+        Error::Truncated { what: "string", .. }
+    ));
+    let err = <(Entry, Entry)>::try_from_slice_partial(&bytes).unwrap_err();
+    assert_eq!(offset_of(err), 9);
+    // A reader cannot go back to the start of the string.
+    let err = from_reader::<(Entry, Entry), _>(&bytes[..]).unwrap_err();
+    assert_eq!(offset_of(err), 14);
+}
+
+#[derive(BorshDeserialize, Debug)]
+#[allow(dead_code)]
+enum Op {
+    Put(Entry),
+    Delete(String),
+}
+
+#[test]
+fn test_offset_of_invalid_value() {
+    // Values are reported where they start, not after the bytes found invalid.
+    let err = bool::try_from_slice(&[2]).unwrap_err();
+    assert_eq!(err.to_string(), "at offset 0: Invalid bool value: 2");
+    assert_eq!(offset_of(bool::try_from_slice(&[]).unwrap_err()), 0);
+    let err = <(u8, String)>::try_from_slice(&[7, 1, 0, 0, 0, 0xff]).unwrap_err();
+    assert_eq!(offset_of(err), 1);
+    let err = <(u8, f32)>::try_from_slice(&[7, 0, 0, 0xc0, 0x7f]).unwrap_err();
+    assert_eq!(offset_of(err), 1);
+    let err = <(u16, Op)>::try_from_slice(&[0, 0, 2]).unwrap_err();
+    assert_eq!(offset_of(err), 2);
+    let err =
+        <Vec<Op>>::try_from_slice(&[2, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0xff]).unwrap_err();
+    assert_eq!(offset_of(err), 10);
+
+    // A map entry that breaks the order of the keys is reported where the entry starts.
+    let bytes = vec![(1u8, 1u8), (3, 3), (2, 2)].try_to_vec().unwrap();
+    let err = DecodeOptions::default()
+        .require_sorted_keys(true)
+        .try_from_slice::<BTreeMap<u8, u8>>(&bytes)
+        .unwrap_err();
+    assert_eq!(offset_of(err), 8);
+}
+
+#[test]
+fn test_trailing_bytes_have_no_offset() {
+    let err = u8::try_from_slice(&[1, 2]).unwrap_err();
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+    let err = from_reader::<u8, _>(&[1, 2][..]).unwrap_err();
+    assert!(matches!(Error::from(err), Error::TrailingBytes));
+}
@@ -0,0 +1,40 @@
+use oasis_borsh::diff::{apply, diff};
+use oasis_borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[test]
+fn test_single_element_tuple() {
+    let value = (String::from("only"),);
+    let bytes = value.try_to_vec().unwrap();
+    assert_eq!(bytes, "only".try_to_vec().unwrap());
+    assert_eq!(<(String,)>::try_from_slice(&bytes).unwrap(), value);
+    assert_eq!(value.serialized_size(), bytes.len());
+    assert_eq!(<(String,)>::declaration(), "Tuple<string>");
+}
+
+#[rustfmt::skip]
+type Wide = (
+    u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+    u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u16,
+);
+
+#[test]
+fn test_32_element_tuple() {
+    let value: Wide = (
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 0x1f1f,
+    );
+    let bytes = value.try_to_vec().unwrap();
+    assert_eq!(bytes.len(), 33);
+    assert_eq!(bytes[..31], (0..31).collect::<Vec<u8>>()[..]);
+    // std only compares tuples of up to 12 elements, so compare encodings.
+    let decoded = Wide::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+    assert!(Wide::declaration().ends_with("u8, u16>"));
+
+    let mut changed = value;
+    changed.31 = 7;
+    let delta = diff(&value, &changed).unwrap();
+    let mut patched = value;
+    apply(&mut patched, &delta).unwrap();
+    assert_eq!(patched.31, 7);
+}
@@ -0,0 +1,64 @@
+#![cfg(feature = "type-guard")]
+use oasis_borsh::type_guard::{fingerprint, from_slice_guarded, to_vec_guarded, GUARD_LEN};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Deposit {
+    account: u32,
+    amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Withdrawal {
+    account: u32,
+    amount: u64,
+}
+
+#[test]
+fn test_type_guard() {
+    let deposit = Deposit {
+        account: 1,
+        amount: 5,
+    };
+    let plain = deposit.try_to_vec().unwrap();
+    let guarded = to_vec_guarded(&deposit).unwrap();
+    assert_eq!(guarded[..GUARD_LEN], fingerprint::<Deposit>());
+    assert_eq!(guarded[GUARD_LEN..], plain[..]);
+    assert_eq!(from_slice_guarded::<Deposit>(&guarded).unwrap(), deposit);
+
+    let err = from_slice_guarded::<Withdrawal>(&guarded).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    match Error::from(err) {
+        Error::WrongType { expected } => assert!(expected.ends_with("Withdrawal")),
+        err => panic!("unexpected error: {}", err),
+    }
+    // Unguarded bytes are caught too, unless they happen to start with the fingerprint.
+    assert!(from_slice_guarded::<Deposit>(&plain).is_err());
+    // Offsets in errors count the fingerprint.
+    let err = from_slice_guarded::<Deposit>(&guarded[..GUARD_LEN + 6]).unwrap_err();
+    assert!(err.to_string().starts_with("at offset 12: "), "{}", err);
+    assert!(from_slice_guarded::<Vec<u8>>(&[1, 2]).is_err());
+    let mut trailing = guarded.clone();
+    trailing.push(0);
+    assert!(matches!(
+        Error::from(from_slice_guarded::<Deposit>(&trailing).unwrap_err()),
+        Error::TrailingBytes
+    ));
+    // Generic arguments are part of the type.
+    let numbers = to_vec_guarded(&vec![1u32, 2]).unwrap();
+    assert!(from_slice_guarded::<Vec<u32>>(&numbers).is_ok());
+    assert!(from_slice_guarded::<Vec<i32>>(&numbers).is_err());
+}
+
+#[test]
+fn test_plain_encodings_are_unaffected() {
+    let deposit = Deposit {
+        account: 1,
+        amount: 5,
+    };
+    let plain = deposit.try_to_vec().unwrap();
+    assert_eq!(plain.len(), 12);
+    assert!(Withdrawal::try_from_slice(&plain).is_ok());
+    let guarded = to_vec_guarded(&deposit).unwrap();
+    assert!(Deposit::try_from_slice(&guarded).is_err());
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "unchecked")]
+use std::io::{Read, Result};
+
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+struct Quote {
+    symbol: String,
+    bid: f64,
+    ask: f32,
+}
+
+#[test]
+fn test_round_trip() {
+    let quote = Quote {
+        symbol: "ABC".to_string(),
+        bid: 1.5,
+        ask: 1.75,
+    };
+    let bytes = quote.try_to_vec().unwrap();
+    assert_eq!(
+        unsafe { Quote::try_from_slice_unchecked(&bytes) }.unwrap(),
+        quote
+    );
+}
+
+#[test]
+fn test_checks_skipped() {
+    let mut bytes = ("ABC".to_string(), 1.5f64, 1.75f32).try_to_vec().unwrap();
+    bytes[7..15].copy_from_slice(&f64::NAN.to_le_bytes());
+    bytes.push(0);
+    assert!(Quote::try_from_slice(&bytes).is_err());
+
+    let quote = unsafe { Quote::try_from_slice_unchecked(&bytes) }.unwrap();
+    assert!(quote.bid.is_nan());
+
+    // Checks are back on afterwards.
+    assert!(f64::try_from_slice(&f64::NAN.to_le_bytes()).is_err());
+}
+
+#[test]
+fn test_truncated_input_still_fails() {
+    let bytes = "ABC".to_string().try_to_vec().unwrap();
+    assert!(unsafe { String::try_from_slice_unchecked(&bytes[..5]) }.is_err());
+}
+
+/// Decodes a float from a buffer of its own, as a handwritten impl might.
+#[derive(Debug)]
+struct Side(f64);
+
+impl BorshDeserialize for Side {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+        let buf = Vec::<u8>::deserialize(reader)?;
+        Ok(Side(f64::try_from_slice(&buf)?))
+    }
+}
+
+#[test]
+fn test_checks_skipped_for_nested_buffers() {
+    let bytes = f64::NAN.to_le_bytes().to_vec().try_to_vec().unwrap();
+    assert!(Side::try_from_slice(&bytes).is_err());
+    // The inner buffer is decoded while the outer call is unchecked, so it is not checked either.
+    let side = unsafe { Side::try_from_slice_unchecked(&bytes) }.unwrap();
+    assert!(side.0.is_nan());
+}
@@ -0,0 +1,91 @@
+use oasis_borsh::de::{deserialize_variant, BorshDeserializeVariant};
+use oasis_borsh::{BorshDeserialize, BorshSerialize, Error};
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+enum Instruction<T> {
+    Mint(T),
+    #[borsh(alias_tag = 3)]
+    Transfer {
+        to: String,
+        amount: T,
+    },
+    Burn,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_init(init)]
+enum Counted {
+    Empty,
+    Values(Vec<u8>, #[borsh_skip] usize),
+}
+
+impl Counted {
+    fn init(&mut self) {
+        if let Counted::Values(values, len) = self {
+            *len = values.len();
+        }
+    }
+}
+
+#[test]
+fn test_body_without_tag() {
+    let transfer = Instruction::Transfer {
+        to: "alice".to_string(),
+        amount: 5u64,
+    };
+    let bytes = transfer.try_to_vec().unwrap();
+    let (tag, body) = (bytes[0], &bytes[1..]);
+    let mut reader = body;
+    let decoded: Instruction<u64> = deserialize_variant(tag, &mut reader).unwrap();
+    assert_eq!(decoded, transfer);
+    assert!(reader.is_empty());
+
+    // Legacy tags resolve as they do in the encoding.
+    let decoded = Instruction::<u64>::deserialize_variant(3, &mut &body[..]).unwrap();
+    assert_eq!(decoded, transfer);
+    let decoded = Instruction::<u64>::deserialize_variant(2, &mut &[][..]).unwrap();
+    assert_eq!(decoded, Instruction::Burn);
+}
+
+#[test]
+fn test_unknown_tag() {
+    let err = deserialize_variant::<Instruction<u8>, _>(4, &mut &[1u8][..]).unwrap_err();
+    assert!(matches!(Error::from(err), Error::InvalidTag(4)));
+}
+
+#[test]
+fn test_truncated_body() {
+    let err = deserialize_variant::<Instruction<u64>, _>(0, &mut &[1u8, 2][..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_init_runs() {
+    let body = vec![1u8, 2, 3].try_to_vec().unwrap();
+    let decoded: Counted = deserialize_variant(1, &mut &body[..]).unwrap();
+    assert_eq!(decoded, Counted::Values(vec![1, 2, 3], 3));
+    let decoded: Counted = deserialize_variant(0, &mut &[][..]).unwrap();
+    assert_eq!(decoded, Counted::Empty);
+}
+
+#[test]
+fn test_option_and_result() {
+    let body = 7u32.try_to_vec().unwrap();
+    assert_eq!(
+        deserialize_variant::<Option<u32>, _>(1, &mut &body[..]).unwrap(),
+        Some(7)
+    );
+    assert_eq!(
+        deserialize_variant::<Option<u32>, _>(0, &mut &[][..]).unwrap(),
+        None
+    );
+    assert_eq!(
+        deserialize_variant::<Result<u32, String>, _>(0, &mut &body[..]).unwrap(),
+        Ok(7)
+    );
+    let body = "no".try_to_vec().unwrap();
+    assert_eq!(
+        deserialize_variant::<Result<u32, String>, _>(1, &mut &body[..]).unwrap(),
+        Err("no".to_string())
+    );
+}
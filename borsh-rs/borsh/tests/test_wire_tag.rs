@@ -0,0 +1,71 @@
+use std::io::{BufRead, BufReader, ErrorKind};
+
+use oasis_borsh::de::{peek_tag, peek_tag_buffered};
+use oasis_borsh::{BorshDeserialize, BorshSerialize};
+
+#[derive(BorshSerialize)]
+#[borsh_wire_tag]
+enum Message<T> {
+    Ping,
+    Data(T, #[borsh_skip] u8),
+    Close { code: u16 },
+}
+
+#[test]
+fn test_wire_tag_matches_encoding() {
+    let messages = [
+        Message::Ping,
+        Message::Data("x".to_string(), 3),
+        Message::Close { code: 1000 },
+    ];
+    for (idx, message) in messages.iter().enumerate() {
+        assert_eq!(message.wire_tag(), idx as u8);
+        assert_eq!(message.try_to_vec().unwrap()[0], message.wire_tag());
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[borsh_wire_tag]
+enum Request {
+    Get {
+        key: String,
+    },
+    #[borsh(alias_tag = 9)]
+    Put {
+        key: String,
+        value: Vec<u8>,
+    },
+}
+
+#[test]
+fn test_peek_wire_tag() {
+    let put = Request::Put {
+        key: "k".to_string(),
+        value: vec![1, 2],
+    };
+    let mut bytes = put.try_to_vec().unwrap();
+    assert_eq!(Request::peek_wire_tag(&bytes).unwrap(), put.wire_tag());
+    bytes[0] = 9;
+    assert_eq!(Request::peek_wire_tag(&bytes).unwrap(), 1);
+    assert_eq!(peek_tag(&bytes).unwrap(), 9);
+    assert_eq!(
+        Request::peek_wire_tag(&[2]).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(
+        Request::peek_wire_tag(&[]).unwrap_err().kind(),
+        ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn test_peek_tag_buffered_leaves_input() {
+    let get = Request::Get {
+        key: "k".to_string(),
+    };
+    let bytes = get.try_to_vec().unwrap();
+    let mut reader = BufReader::new(&bytes[..]);
+    assert_eq!(peek_tag_buffered(&mut reader).unwrap(), 0);
+    assert_eq!(reader.fill_buf().unwrap(), &bytes[..]);
+    assert_eq!(Request::deserialize(&mut reader).unwrap(), get);
+}
@@ -1,3 +1,6 @@
+use std::io::{Read, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use oasis_borsh::{BorshDeserialize, BorshSerialize};
 
 #[derive(BorshDeserialize, PartialEq, Debug)]
@@ -5,7 +8,41 @@ struct A;
 
 #[test]
 fn test_deserialize_vector_to_many_zero_size_struct() {
-    let v = [0u8, 0u8, 0u8, 64u8];
+    let v = [0u8, 0u8, 16u8, 0u8];
     let a = Vec::<A>::try_from_slice(&v).unwrap();
-    assert_eq!(A {}, a[usize::pow(2, 30) - 1])
+    assert_eq!(A {}, a[usize::pow(2, 20) - 1])
+}
+
+static DECODED: AtomicUsize = AtomicUsize::new(0);
+static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// A zero-sized value that reads a byte and counts how often it is decoded and dropped.
+struct Counted;
+
+impl BorshDeserialize for Counted {
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self> {
+        u8::deserialize(reader)?;
+        DECODED.fetch_add(1, Ordering::SeqCst);
+        Ok(Counted)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_zero_size_elements_are_each_decoded() {
+    let v = Vec::<Counted>::try_from_slice(&[3, 0, 0, 0, 7, 8, 9]).unwrap();
+    assert_eq!(v.len(), 3);
+    assert_eq!(DECODED.load(Ordering::SeqCst), 3);
+    drop(v);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+
+    // The third element is missing, and the two decoded ones are dropped once each.
+    assert!(Vec::<Counted>::try_from_slice(&[3, 0, 0, 0, 7, 8]).is_err());
+    assert_eq!(DECODED.load(Ordering::SeqCst), 5);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 5);
 }